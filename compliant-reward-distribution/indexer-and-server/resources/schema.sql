@@ -23,19 +23,37 @@ CREATE TABLE IF NOT EXISTS settings (
 CREATE TABLE IF NOT EXISTS accounts (
   -- The account address created on chain.
   account_address BYTEA NOT NULL PRIMARY KEY,
-  -- The timestamp of the block when the account was created on chain.
+  -- The timestamp of the block when the account was created on chain. For imported accounts, the time of the import,
+  -- so the claim expiry duration starts with the import.
   block_time TIMESTAMP WITH TIME ZONE NOT NULL,
   -- The transaction hash of the transaction that created the account on chain.
-  transaction_hash BYTEA NOT NULL,
+  -- Accounts imported by an admin have no transaction hash.
+  transaction_hash BYTEA,
   -- A boolean specifying if the account has already claimed its rewards (got a reward payout).
   -- Every account can only claim rewards once.
   claimed BOOL NOT NULL,
   -- A boolean specifying if this account address has submitted all tasks
   -- and the regulatory conditions have been proven via a ZK proof.
   -- A manual check of the completed tasks is required now before releasing the reward.
-  pending_approval BOOL NOT NULL
+  pending_approval BOOL NOT NULL,
+  -- The timestamp of the block in which the credentials (or credential keys) of the account were last updated.
+  credentials_updated_time TIMESTAMP WITH TIME ZONE,
+  -- A boolean specifying if the credentials of the account were updated after the ZK proof was submitted.
+  -- A key rotation or credential update may invalidate the compliance assumption of the ZK proof,
+  -- so the account has to be reviewed again (or submit a new ZK proof) before releasing the reward.
+  re_review_required BOOL NOT NULL DEFAULT false,
+  -- A boolean specifying if the account was pre-approved by an admin with the `importAccounts` endpoint
+  -- (e.g. from a partner allowlist) instead of being captured by the indexer and completing the tasks.
+  imported BOOL NOT NULL DEFAULT false
 );
 
+-- Add the credential tracking columns to tables created before they were introduced.
+ALTER TABLE accounts ADD COLUMN IF NOT EXISTS credentials_updated_time TIMESTAMP WITH TIME ZONE;
+ALTER TABLE accounts ADD COLUMN IF NOT EXISTS re_review_required BOOL NOT NULL DEFAULT false;
+-- Allow imported accounts in tables created before accounts could be imported.
+ALTER TABLE accounts ADD COLUMN IF NOT EXISTS imported BOOL NOT NULL DEFAULT false;
+ALTER TABLE accounts ALTER COLUMN transaction_hash DROP NOT NULL;
+
 -- Task 1:
 -- Table containing information regarding task 1 (tweet verification).
 CREATE TABLE IF NOT EXISTS tweets (
@@ -71,7 +89,55 @@ CREATE TABLE IF NOT EXISTS zkProofs (
   -- to update the ZK proof verification logic in the future and invalidate older proofs.
   zk_proof_verification_version INT8 NOT NULL,
    -- The timestamp when the ZK proof verification was submitted.
-  zk_proof_verification_submit_time TIMESTAMP WITH TIME ZONE NOT NULL
+  zk_proof_verification_submit_time TIMESTAMP WITH TIME ZONE NOT NULL,
+  -- The identifier of the accepted ZK statement that the ZK proof satisfied. Several ZK statements
+  -- can be accepted at the same time (e.g. during a rotation of the ZK statement).
+  zk_statement_id INT8
+);
+
+-- Add the zk_statement_id column to tables created before it was introduced.
+ALTER TABLE zkProofs ADD COLUMN IF NOT EXISTS zk_statement_id INT8;
+
+-- Table containing the pending approvals that an admin has claimed for review. A claim locks the account until
+-- `locked_until`, so that two admins do not review the same account at the same time. The lock can be released earlier
+-- by the admin that claimed it and is released when the account is marked as claimed.
+CREATE TABLE IF NOT EXISTS reviewLocks (
+  -- The account address that is reviewed.
+  account_address BYTEA NOT NULL PRIMARY KEY,
+  -- The admin account address that claimed the account for review.
+  reviewer BYTEA NOT NULL,
+  -- The timestamp when the lock expires and the account can be claimed by another admin.
+  locked_until TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+-- Table containing the latest on-chain eligibility check of each account. The check is only done if an eligibility rule
+-- (minimum balance or minimum age-weighted transaction count) is configured, and makes sybil farming more costly than
+-- only requiring a unique identity.
+CREATE TABLE IF NOT EXISTS eligibilityChecks (
+  -- The account address that was checked.
+  account_address BYTEA NOT NULL PRIMARY KEY,
+  -- The CCD balance of the account in microCCD at the time of the check.
+  balance INT8 NOT NULL,
+  -- The number of transactions sent by the account at the time of the check.
+  transaction_count INT8 NOT NULL,
+  -- The number of transactions sent by the account multiplied by the age of the account in days.
+  weighted_transaction_count INT8 NOT NULL,
+  -- A boolean specifying if the account fulfilled the eligibility rule.
+  eligible BOOL NOT NULL,
+  -- The timestamp when the check was done.
+  check_time TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+-- Table containing the claim attestations signed by the server after an admin marked an account as claimed. The
+-- attestations are portable proofs of participation in the campaign that can be retrieved by the users.
+CREATE TABLE IF NOT EXISTS claimAttestations (
+  -- The account address that claimed the reward.
+  account_address BYTEA NOT NULL PRIMARY KEY,
+  -- The attestation (account, campaign, amount, network, timestamp, and issuer key) serialized as JSON.
+  -- The signature is computed over exactly these bytes.
+  attestation TEXT NOT NULL,
+  -- The ed25519 signature of the server on the SHA256 hash of the `CONTEXT_STRING` followed by the `attestation`.
+  signature BYTEA NOT NULL
 );
 
 -- Improve performance on queries for given pending_approvals in the accounts table.
@@ -0,0 +1,288 @@
+//! Alerts on the velocity of the campaign. The server counts the accepted task
+//! submissions (tweets and ZK proofs) and the accounts marked as claimed. A
+//! background analyzer samples the counters periodically, computes the rates
+//! over a rolling window, and fires an alert when a rate exceeds its threshold
+//! (e.g. a bot attack) or when no tasks are submitted during the campaign
+//! hours (e.g. an outage of the front end). Alerts are logged and, if
+//! configured, posted as JSON to a webhook.
+use chrono::{DateTime, Timelike, Utc};
+use std::{
+    collections::{BTreeSet, VecDeque},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// The counters of the accepted task submissions and the claimed accounts
+/// since the server was started.
+#[derive(Debug, Clone, Default)]
+pub struct VelocityCounters {
+    /// The number of accepted tweet and ZK proof submissions.
+    submissions: Arc<AtomicU64>,
+    /// The number of accounts marked as claimed.
+    claims: Arc<AtomicU64>,
+}
+
+impl VelocityCounters {
+    /// Record an accepted tweet or ZK proof submission.
+    pub fn record_submission(&self) {
+        self.submissions.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record that `count` accounts were marked as claimed.
+    pub fn record_claims(&self, count: u64) {
+        self.claims.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// The current number of submissions and claims.
+    fn sample(&self) -> (u64, u64) {
+        (
+            self.submissions.load(Ordering::Relaxed),
+            self.claims.load(Ordering::Relaxed),
+        )
+    }
+}
+
+/// The hours of the day (UTC) during which submissions are expected, given as
+/// `<start>-<end>` (e.g. `8-20`). The end hour is exclusive and a range with
+/// an end before its start wraps around midnight (e.g. `22-6`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CampaignHours {
+    /// The first hour of the campaign hours.
+    pub start: u32,
+    /// The hour after the last hour of the campaign hours.
+    pub end: u32,
+}
+
+impl CampaignHours {
+    /// Whether the `time` is within the campaign hours.
+    pub fn contains(&self, time: DateTime<Utc>) -> bool {
+        let hour = time.hour();
+        if self.start <= self.end {
+            self.start <= hour && hour < self.end
+        } else {
+            self.start <= hour || hour < self.end
+        }
+    }
+}
+
+impl std::str::FromStr for CampaignHours {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (start, end) = value
+            .split_once('-')
+            .ok_or_else(|| anyhow::anyhow!("Expected the format `<start>-<end>`, e.g. `8-20`"))?;
+        let start: u32 = start.trim().parse()?;
+        let end: u32 = end.trim().parse()?;
+        anyhow::ensure!(
+            start < 24 && end <= 24 && start != end,
+            "The campaign hours have to be two different hours between 0 and 24"
+        );
+        Ok(Self { start, end })
+    }
+}
+
+/// The thresholds of the velocity alerts. An alert is disabled if its
+/// threshold is not set.
+#[derive(Debug, Clone, Default)]
+pub struct VelocityThresholds {
+    /// The maximum number of accepted submissions per minute.
+    pub max_submissions_per_minute: Option<f64>,
+    /// The maximum number of accounts marked as claimed per minute.
+    pub max_claims_per_minute: Option<f64>,
+    /// The hours during which at least one submission per rolling window is
+    /// expected.
+    pub campaign_hours: Option<CampaignHours>,
+}
+
+impl VelocityThresholds {
+    /// Whether at least one alert is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.max_submissions_per_minute.is_some()
+            || self.max_claims_per_minute.is_some()
+            || self.campaign_hours.is_some()
+    }
+}
+
+/// The kinds of velocity alerts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AlertKind {
+    /// The submission rate exceeds its threshold.
+    SubmissionSpike,
+    /// The claim rate exceeds its threshold.
+    ClaimSpike,
+    /// No submissions were accepted during the campaign hours.
+    SubmissionsStalled,
+}
+
+/// The alert posted to the webhook.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VelocityAlert {
+    /// The kind of the alert.
+    pub kind: AlertKind,
+    /// The rate per minute over the rolling window that fired the alert.
+    pub rate_per_minute: f64,
+    /// The threshold the rate was compared with. Not set for
+    /// `submissionsStalled` alerts.
+    pub threshold: Option<f64>,
+    /// The length of the rolling window in seconds.
+    pub window_secs: u64,
+    /// The time the alert was fired.
+    pub fired_at: DateTime<Utc>,
+}
+
+/// Computes the rolling rates of the [`VelocityCounters`] and fires the
+/// alerts.
+#[derive(Debug, Clone)]
+pub struct VelocityAnalyzer {
+    /// The counters of the server.
+    counters: VelocityCounters,
+    /// The thresholds of the alerts.
+    thresholds: VelocityThresholds,
+    /// The length of the rolling window the rates are computed over.
+    window: Duration,
+    /// The webhook the alerts are posted to. If not set, alerts are only
+    /// logged.
+    webhook: Option<reqwest::Url>,
+    /// The client posting the alerts to the webhook.
+    http_client: reqwest::Client,
+}
+
+impl VelocityAnalyzer {
+    /// Create an analyzer computing the rates of the `counters` over the
+    /// `window`.
+    pub fn new(
+        counters: VelocityCounters,
+        thresholds: VelocityThresholds,
+        window: Duration,
+        webhook: Option<reqwest::Url>,
+    ) -> Self {
+        Self {
+            counters,
+            thresholds,
+            window,
+            webhook,
+            http_client: reqwest::Client::new(),
+        }
+    }
+
+    /// Sample the counters every `interval` and fire the alerts whose
+    /// condition holds over the rolling window. An alert is fired once when
+    /// its condition starts to hold and is fired again only after it stopped
+    /// holding in between. No alerts are fired before a full window has been
+    /// observed.
+    pub async fn run(self, interval: Duration) {
+        let mut ticks = tokio::time::interval(interval);
+        ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        let mut samples: VecDeque<(Instant, (u64, u64))> = VecDeque::new();
+        let mut active = BTreeSet::new();
+
+        loop {
+            ticks.tick().await;
+
+            let now = Instant::now();
+            let sample = self.counters.sample();
+            samples.push_back((now, sample));
+            // Keep the latest sample that is at least a window old as the start
+            // of the window.
+            while samples
+                .get(1)
+                .map_or(false, |(time, _)| now.duration_since(*time) >= self.window)
+            {
+                samples.pop_front();
+            }
+
+            let Some(&(start_time, (start_submissions, start_claims))) = samples.front() else {
+                continue;
+            };
+            let elapsed = now.duration_since(start_time);
+            if elapsed < self.window {
+                continue;
+            }
+            let minutes = elapsed.as_secs_f64() / 60.0;
+            let submission_rate = (sample.0 - start_submissions) as f64 / minutes;
+            let claim_rate = (sample.1 - start_claims) as f64 / minutes;
+
+            let fired_at = Utc::now();
+            let checks = [
+                (
+                    AlertKind::SubmissionSpike,
+                    submission_rate,
+                    self.thresholds.max_submissions_per_minute,
+                    self.thresholds
+                        .max_submissions_per_minute
+                        .map_or(false, |max| submission_rate > max),
+                ),
+                (
+                    AlertKind::ClaimSpike,
+                    claim_rate,
+                    self.thresholds.max_claims_per_minute,
+                    self.thresholds
+                        .max_claims_per_minute
+                        .map_or(false, |max| claim_rate > max),
+                ),
+                (
+                    AlertKind::SubmissionsStalled,
+                    submission_rate,
+                    None,
+                    self.thresholds
+                        .campaign_hours
+                        .map_or(false, |hours| hours.contains(fired_at))
+                        && submission_rate == 0.0,
+                ),
+            ];
+
+            for (kind, rate_per_minute, threshold, holds) in checks {
+                if !holds {
+                    if active.remove(&kind) {
+                        tracing::info!("Velocity alert {kind:?} resolved.");
+                    }
+                    continue;
+                }
+                if !active.insert(kind) {
+                    continue;
+                }
+                self.fire(VelocityAlert {
+                    kind,
+                    rate_per_minute,
+                    threshold,
+                    window_secs: self.window.as_secs(),
+                    fired_at,
+                })
+                .await;
+            }
+        }
+    }
+
+    /// Log the `alert` and post it to the webhook.
+    async fn fire(&self, alert: VelocityAlert) {
+        tracing::warn!(
+            "Velocity alert {:?}: {:.2} per minute over the last {} seconds (threshold: {:?}).",
+            alert.kind,
+            alert.rate_per_minute,
+            alert.window_secs,
+            alert.threshold
+        );
+
+        let Some(webhook) = &self.webhook else {
+            return;
+        };
+        let response = self
+            .http_client
+            .post(webhook.clone())
+            .timeout(Duration::from_secs(10))
+            .json(&alert)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status);
+        if let Err(error) = response {
+            tracing::error!("Unable to post the velocity alert to the webhook: {error}");
+        }
+    }
+}
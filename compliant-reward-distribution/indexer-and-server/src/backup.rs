@@ -0,0 +1,305 @@
+//! Logical backups of the campaign tables. Each backup is a directory with one
+//! JSON lines file per table (one `row_to_json` object per row) and a
+//! `manifest.json` file recording the number of rows and the SHA256 hash of
+//! each file. A backup is verified by restoring it into temporary tables
+//! within a database transaction that is rolled back afterwards.
+use crate::{error::BackupError, DatabasePool};
+use chrono::{DateTime, Utc};
+use sha2::Digest;
+use std::{path::PathBuf, sync::Arc};
+use tokio::sync::Mutex;
+use tokio_postgres::IsolationLevel;
+
+/// The tables that are included in a backup. Losing any of these tables
+/// mid-campaign would be unrecoverable since the accounts cannot be
+/// re-indexed after the campaign started and the submitted tasks are only
+/// stored in the database. The signed claim attestations are included since
+/// they record when the accounts were marked as claimed.
+pub const BACKUP_TABLES: [&str; 5] = [
+    "settings",
+    "accounts",
+    "tweets",
+    "zkproofs",
+    "claimattestations",
+];
+
+/// The name of the manifest file in a backup directory.
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// The prefix of the name of every backup directory. The prefix is followed by
+/// the UTC creation time so that the names sort chronologically.
+const BACKUP_DIRECTORY_PREFIX: &str = "backup-";
+
+/// The manifest of a backup describing the exported tables.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupManifest {
+    /// The name of the backup directory.
+    pub name: String,
+    /// The time when the backup was created.
+    pub created_at: DateTime<Utc>,
+    /// The exported tables.
+    pub tables: Vec<BackupTable>,
+}
+
+/// A table exported as part of a backup.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupTable {
+    /// The name of the table.
+    pub name: String,
+    /// The number of rows exported.
+    pub rows: u64,
+    /// The hex encoded SHA256 hash of the exported file.
+    pub sha256: String,
+}
+
+/// Creates and verifies backups in the `directory`. Backups are serialized so
+/// that a backup triggered by an admin does not run concurrently with a
+/// scheduled backup.
+#[derive(Debug, Clone)]
+pub struct Backups {
+    /// The directory where the backups are stored.
+    directory: PathBuf,
+    /// The number of backups that are kept. Older backups are deleted after a
+    /// new backup was created.
+    keep: usize,
+    /// Lock held while a backup is created or verified.
+    lock: Arc<Mutex<()>>,
+}
+
+impl Backups {
+    /// Create a new [`Backups`] storing at most `keep` backups in the
+    /// `directory`. The directory is created if it does not exist.
+    pub fn new(directory: PathBuf, keep: usize) -> Result<Self, BackupError> {
+        std::fs::create_dir_all(&directory)?;
+        Ok(Self {
+            directory,
+            keep: keep.max(1),
+            lock: Arc::new(Mutex::new(())),
+        })
+    }
+
+    /// Create a new backup of the [`BACKUP_TABLES`] and delete the backups
+    /// exceeding the number of backups to keep. If `verify` is true, the
+    /// restorability of the new backup is verified afterwards.
+    pub async fn create(
+        &self,
+        db_pool: &DatabasePool,
+        verify: bool,
+    ) -> Result<BackupManifest, BackupError> {
+        let _guard = self.lock.lock().await;
+
+        let manifest = self.export(db_pool).await?;
+        if verify {
+            self.verify(db_pool, &manifest.name).await?;
+        }
+        self.prune()?;
+
+        Ok(manifest)
+    }
+
+    /// Verify the restorability of the latest backup.
+    pub async fn verify_latest(
+        &self,
+        db_pool: &DatabasePool,
+    ) -> Result<BackupManifest, BackupError> {
+        let _guard = self.lock.lock().await;
+
+        let name = self.backup_names()?.pop().ok_or(BackupError::NoBackup)?;
+        self.verify(db_pool, &name).await
+    }
+
+    /// Export the [`BACKUP_TABLES`] into a new backup directory. All tables are
+    /// read within one transaction so that the backup is a consistent
+    /// snapshot. The files are written to a temporary directory first which
+    /// is renamed once the manifest has been written.
+    async fn export(&self, db_pool: &DatabasePool) -> Result<BackupManifest, BackupError> {
+        let created_at = Utc::now();
+        let name = format!(
+            "{BACKUP_DIRECTORY_PREFIX}{}",
+            created_at.format("%Y%m%dT%H%M%S%.3fZ")
+        );
+        let partial_directory = self.directory.join(format!("{name}.partial"));
+        std::fs::create_dir_all(&partial_directory)?;
+
+        let mut db = db_pool.get().await?;
+        let db_transaction = db
+            .client
+            .build_transaction()
+            .isolation_level(IsolationLevel::RepeatableRead)
+            .read_only(true)
+            .start()
+            .await?;
+        // Backups are not bound by the request deadline of the server.
+        db_transaction
+            .batch_execute("SET LOCAL statement_timeout = 0")
+            .await?;
+
+        let mut tables = Vec::with_capacity(BACKUP_TABLES.len());
+        for table in BACKUP_TABLES {
+            let rows = db_transaction
+                .query(&format!("SELECT row_to_json(t)::TEXT FROM {table} t"), &[])
+                .await?;
+
+            let mut content = String::new();
+            for row in &rows {
+                let line: &str = row.try_get(0)?;
+                content.push_str(line);
+                content.push('\n');
+            }
+            std::fs::write(partial_directory.join(table_file(table)), &content)?;
+
+            tables.push(BackupTable {
+                name: table.to_string(),
+                rows: rows.len() as u64,
+                sha256: format!("{:x}", sha2::Sha256::digest(content.as_bytes())),
+            });
+        }
+        db_transaction.commit().await?;
+
+        let manifest = BackupManifest {
+            name: name.clone(),
+            created_at,
+            tables,
+        };
+        std::fs::write(
+            partial_directory.join(MANIFEST_FILE),
+            serde_json::to_vec_pretty(&manifest)?,
+        )?;
+        std::fs::rename(&partial_directory, self.directory.join(&name))?;
+
+        tracing::info!("Created backup {name}.");
+        Ok(manifest)
+    }
+
+    /// Verify that the backup with the given `name` can be restored. The hash
+    /// of every file is compared with the manifest and the rows are inserted
+    /// into temporary copies of the tables (including their constraints). The
+    /// database transaction is rolled back afterwards so the tables of the
+    /// campaign are not modified.
+    async fn verify(
+        &self,
+        db_pool: &DatabasePool,
+        name: &str,
+    ) -> Result<BackupManifest, BackupError> {
+        let backup_directory = self.directory.join(name);
+        let manifest: BackupManifest =
+            serde_json::from_slice(&std::fs::read(backup_directory.join(MANIFEST_FILE))?)?;
+
+        let mut db = db_pool.get().await?;
+        let db_transaction = db.client.transaction().await?;
+        db_transaction
+            .batch_execute("SET LOCAL statement_timeout = 0")
+            .await?;
+
+        for table in &manifest.tables {
+            if !BACKUP_TABLES.contains(&table.name.as_str()) {
+                return Err(BackupError::Verification(format!(
+                    "Unknown table `{}` in backup {name}",
+                    table.name
+                )));
+            }
+
+            let content = std::fs::read_to_string(backup_directory.join(table_file(&table.name)))?;
+            let sha256 = format!("{:x}", sha2::Sha256::digest(content.as_bytes()));
+            if sha256 != table.sha256 {
+                return Err(BackupError::Verification(format!(
+                    "The hash of the table `{}` in backup {name} does not match the manifest",
+                    table.name
+                )));
+            }
+
+            let rows = content
+                .lines()
+                .map(serde_json::from_str)
+                .collect::<Result<Vec<serde_json::Value>, _>>()?;
+
+            let restore_table = format!("restore_{}", table.name);
+            db_transaction
+                .batch_execute(&format!(
+                    "CREATE TEMPORARY TABLE {restore_table} (LIKE {} INCLUDING ALL)",
+                    table.name
+                ))
+                .await?;
+            let restored = db_transaction
+                .execute(
+                    &format!(
+                        "INSERT INTO {restore_table} SELECT * FROM \
+                         json_populate_recordset(NULL::{}, $1::JSON)",
+                        table.name
+                    ),
+                    &[&serde_json::Value::Array(rows)],
+                )
+                .await?;
+
+            if restored != table.rows {
+                return Err(BackupError::Verification(format!(
+                    "Restored {restored} rows of the table `{}` from backup {name} but expected {}",
+                    table.name, table.rows
+                )));
+            }
+        }
+        db_transaction.rollback().await?;
+
+        tracing::info!("Verified that backup {name} can be restored.");
+        Ok(manifest)
+    }
+
+    /// Delete the oldest backups exceeding the number of backups to keep.
+    fn prune(&self) -> Result<(), BackupError> {
+        let names = self.backup_names()?;
+        let expired = names.len().saturating_sub(self.keep);
+        for name in &names[..expired] {
+            std::fs::remove_dir_all(self.directory.join(name))?;
+            tracing::info!("Deleted backup {name}.");
+        }
+        Ok(())
+    }
+
+    /// The names of the completed backups ordered from oldest to latest.
+    fn backup_names(&self) -> Result<Vec<String>, BackupError> {
+        let mut names = Vec::new();
+        for entry in std::fs::read_dir(&self.directory)? {
+            let entry = entry?;
+            let Ok(name) = entry.file_name().into_string() else {
+                continue;
+            };
+            if entry.file_type()?.is_dir()
+                && name.starts_with(BACKUP_DIRECTORY_PREFIX)
+                && !name.ends_with(".partial")
+            {
+                names.push(name);
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+}
+
+/// The name of the file storing the rows of the `table` in a backup.
+fn table_file(table: &str) -> String {
+    format!("{table}.jsonl")
+}
+
+/// Verify the restorability of the latest existing backup and then
+/// periodically create a new backup and verify its restorability. The first
+/// backup is created after one `interval` has elapsed. Failures are logged and
+/// the next backup is attempted after the next `interval`.
+pub async fn run_scheduled(backups: Backups, db_pool: DatabasePool, interval: std::time::Duration) {
+    let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    match backups.verify_latest(&db_pool).await {
+        Ok(_) | Err(BackupError::NoBackup) => (),
+        Err(error) => tracing::error!("Verification of the latest backup failed: {error}"),
+    }
+
+    loop {
+        interval.tick().await;
+
+        if let Err(error) = backups.create(&db_pool, true).await {
+            tracing::error!("Scheduled backup failed: {error}");
+        }
+    }
+}
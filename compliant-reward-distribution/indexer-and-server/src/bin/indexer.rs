@@ -1,20 +1,57 @@
 //! A tool for indexing newly created accounts on Concordium into a
 //! postgres database. The database is configured with the tables from the file
 //! `../resources/schema.sql`. A table
-//! `settings` exists to store global configurations.
-use ::indexer::db::DatabasePool;
+//! `settings` exists to store global configurations. Credential updates of
+//! indexed accounts are recorded to flag accounts for re-review whose
+//! credentials changed after their ZK proof was submitted.
+//!
+//! The accounts and credential updates of several blocks can be buffered and
+//! written with multi-row inserts in one database transaction during catch-up
+//! (see `--batch-size` and `--flush-interval-ms`). Blocks close to the chain
+//! head are always committed right away.
+//!
+//! Failed blocks are retried until the retry budget set with `--max-retries`
+//! and `--max-retry-duration` is exhausted. The indexer then exits with the
+//! exit code [`RETRY_BUDGET_EXHAUSTED_EXIT_CODE`] so that an orchestrator can
+//! restart it or alert an operator.
+use ::indexer::db::{DatabasePool, DatabasePoolConfig};
 use anyhow::Context;
+use chrono::{DateTime, Utc};
 use clap::Parser;
 use concordium_rust_sdk::{
     indexer::{self, ProcessorConfig, TransactionIndexer},
     types::{
-        queries::BlockInfo, AbsoluteBlockHeight, BlockItemSummary,
-        BlockItemSummaryDetails::AccountCreation,
+        hashes::{BlockHash, TransactionHash},
+        queries::BlockInfo,
+        AbsoluteBlockHeight, AccountAddress, AccountTransactionDetails, AccountTransactionEffects,
+        BlockItemSummary,
+        BlockItemSummaryDetails::{AccountCreation, AccountTransaction},
     },
     v2::{self as sdk, Client, QueryError},
 };
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
 use tokio_postgres::types::ToSql;
 
+/// The exit code of the indexer if processing a block failed more often or for
+/// longer than allowed by `--max-retries` and `--max-retry-duration`. It
+/// differs from the exit code `1` of other errors, e.g. invalid settings.
+const RETRY_BUDGET_EXHAUSTED_EXIT_CODE: i32 = 3;
+
+/// The maximum number of accounts inserted by a single multi-row `INSERT`
+/// statement, which keeps the number of parameters of the statement below the
+/// limit of postgres.
+const MAX_ROWS_PER_INSERT: usize = 1000;
+
+/// Blocks with a slot time at most this long ago are close to the chain head
+/// and are committed right away instead of being buffered.
+const CHAIN_HEAD_LAG: Duration = Duration::from_secs(60);
+
 /// Command line configuration of the application.
 #[derive(Debug, clap::Parser)]
 #[command(author, version, about)]
@@ -48,6 +85,162 @@ struct Args {
         env = "CCD_INDEXER_LOG_LEVEL"
     )]
     log_level: tracing_subscriber::filter::LevelFilter,
+    /// The maximum number of retries of a block that failed to be processed,
+    /// e.g. because the database is unreachable. The indexer exits with exit
+    /// code 3 once the retries are exhausted. If not specified, a failed block
+    /// is retried without limit.
+    #[arg(long = "max-retries", env = "CCD_INDEXER_MAX_RETRIES")]
+    max_retries: Option<u32>,
+    /// The maximum time in seconds for which a block that failed to be
+    /// processed is retried. The time is counted from the first failure of the
+    /// block. The indexer exits with exit code 3 once the time has passed. If
+    /// not specified, a failed block is retried without time limit.
+    #[arg(long = "max-retry-duration", env = "CCD_INDEXER_MAX_RETRY_DURATION")]
+    max_retry_duration: Option<u64>,
+    /// The block height to start indexing from when the indexer is started for
+    /// the first time. If neither this nor `--start-hash` is specified, the
+    /// indexing starts from the last finalized block.
+    #[arg(
+        long = "start",
+        env = "CCD_INDEXER_START",
+        conflicts_with = "start_hash"
+    )]
+    start: Option<AbsoluteBlockHeight>,
+    /// The hash of the block to start indexing from when the indexer is
+    /// started for the first time, e.g. copied from a block explorer. The hash
+    /// is resolved to the height of the block via the node. The block has to
+    /// be finalized.
+    #[arg(long = "start-hash", env = "CCD_INDEXER_START_HASH", value_parser = parse_block_hash)]
+    start_hash: Option<BlockHash>,
+    /// The number of new accounts and credential updates that are buffered
+    /// before they are written to the database in one transaction. Blocks
+    /// close to the chain head are always written right away.
+    #[arg(
+        long = "batch-size",
+        env = "CCD_INDEXER_BATCH_SIZE",
+        default_value = "1"
+    )]
+    batch_size: usize,
+    /// The maximum time in milliseconds the buffered accounts and credential
+    /// updates are kept before they are written to the database, counted from
+    /// the first buffered block.
+    #[arg(
+        long = "flush-interval-ms",
+        env = "CCD_INDEXER_FLUSH_INTERVAL_MS",
+        default_value = "0"
+    )]
+    flush_interval_ms: u64,
+    /// The maximum number of connections to the database.
+    #[arg(
+        long = "db-pool-size",
+        env = "CCD_INDEXER_DB_POOL_SIZE",
+        default_value = "2"
+    )]
+    db_pool_size: usize,
+    /// The maximum time in seconds to wait for a new connection to the
+    /// database to be established.
+    #[arg(
+        long = "db-create-timeout-secs",
+        env = "CCD_INDEXER_DB_CREATE_TIMEOUT_SECS",
+        default_value = "5"
+    )]
+    db_create_timeout_secs: u64,
+    /// The maximum time in seconds to wait for a connection to the database to
+    /// be verified before it is reused.
+    #[arg(
+        long = "db-recycle-timeout-secs",
+        env = "CCD_INDEXER_DB_RECYCLE_TIMEOUT_SECS",
+        default_value = "5"
+    )]
+    db_recycle_timeout_secs: u64,
+    /// The maximum time in seconds to wait for a connection to the database to
+    /// become available if all connections of the pool are in use.
+    #[arg(
+        long = "db-wait-timeout-secs",
+        env = "CCD_INDEXER_DB_WAIT_TIMEOUT_SECS",
+        default_value = "5"
+    )]
+    db_wait_timeout_secs: u64,
+    /// Whether the database tables are created on startup if they do not exist
+    /// yet. Can be disabled if the database user has no permission to create
+    /// tables and the schema is managed separately.
+    #[arg(
+        long = "db-try-create-tables",
+        env = "CCD_INDEXER_DB_TRY_CREATE_TABLES",
+        default_value_t = true,
+        action = clap::ArgAction::Set
+    )]
+    db_try_create_tables: bool,
+}
+
+/// Parse a block hash, ignoring surrounding whitespace and quotes picked up
+/// when copying the hash.
+fn parse_block_hash(value: &str) -> anyhow::Result<BlockHash> {
+    let hash = value.trim().trim_matches(|c| c == '"' || c == '\'');
+    hash.parse::<BlockHash>()
+        .with_context(|| format!("Invalid block hash `{hash}`."))
+}
+
+/// The limits for retrying a block that failed to be processed.
+#[derive(Debug, Clone, Copy)]
+struct RetryBudget {
+    /// The maximum number of retries of a block.
+    max_retries: Option<u32>,
+    /// The maximum time for which a block is retried, counted from its first
+    /// failure.
+    max_duration: Option<Duration>,
+}
+
+/// An account created on chain.
+#[derive(Debug)]
+struct NewAccount {
+    /// The address of the account.
+    address: AccountAddress,
+    /// The slot time of the block the account was created in.
+    block_time: DateTime<Utc>,
+    /// The hash of the transaction creating the account.
+    transaction_hash: TransactionHash,
+}
+
+/// An update of the credentials or the credential keys of an account.
+#[derive(Debug)]
+struct CredentialUpdate {
+    /// The account whose credentials were updated.
+    address: AccountAddress,
+    /// The slot time of the block the update was included in.
+    block_time: DateTime<Utc>,
+}
+
+/// The writes of the blocks processed since the last flush.
+#[derive(Debug, Default)]
+struct PendingWrites {
+    /// The accounts created in the buffered blocks.
+    accounts: Vec<NewAccount>,
+    /// The credential updates of the buffered blocks in the order they
+    /// happened.
+    credential_updates: Vec<CredentialUpdate>,
+    /// The height of the last buffered block.
+    last_block: Option<AbsoluteBlockHeight>,
+    /// The time the first block was buffered.
+    since: Option<Instant>,
+}
+
+impl PendingWrites {
+    /// The number of buffered accounts and credential updates.
+    fn len(&self) -> usize {
+        self.accounts.len() + self.credential_updates.len()
+    }
+}
+
+/// When the buffered writes are flushed to the database.
+#[derive(Debug, Clone, Copy)]
+struct FlushPolicy {
+    /// The number of buffered accounts and credential updates after which they
+    /// are flushed.
+    batch_size: usize,
+    /// The maximum time the writes are buffered, counted from the first
+    /// buffered block.
+    interval: Duration,
 }
 
 /// A handler for storing monitored events in the database. This implements
@@ -55,29 +248,100 @@ struct Args {
 struct StoreEvents {
     /// A database pool used for reconnects.
     db_pool: DatabasePool,
+    /// The limits for retrying a block that failed to be processed.
+    retry_budget: RetryBudget,
+    /// The time of the first failure of the block that is currently retried.
+    /// It is reset once a block was processed.
+    failing_since: Option<Instant>,
+    /// Set once the retry budget is exhausted and the processing of blocks
+    /// stopped.
+    exhausted: Arc<AtomicBool>,
+    /// When the buffered writes are flushed.
+    flush_policy: FlushPolicy,
+    /// The writes buffered since the last flush.
+    pending: PendingWrites,
 }
 
-#[indexer::async_trait]
-impl indexer::ProcessEvent for StoreEvents {
-    type Data = (BlockInfo, Vec<BlockItemSummary>);
-    type Description = String;
-    type Error = anyhow::Error;
+impl StoreEvents {
+    /// Add the new accounts and credential updates of the block to the
+    /// buffered writes.
+    fn buffer(&mut self, block_info: &BlockInfo, block_items: &[BlockItemSummary]) {
+        for tx in block_items {
+            match &tx.details {
+                AccountCreation(account_creation_details) => {
+                    tracing::debug!(
+                        "Preparing database transaction for account {:} from transaction hash {} \
+                         in block {}.",
+                        account_creation_details.address,
+                        tx.hash,
+                        block_info.block_height,
+                    );
+                    self.pending.accounts.push(NewAccount {
+                        address: account_creation_details.address,
+                        block_time: block_info.block_slot_time,
+                        transaction_hash: tx.hash,
+                    });
+                }
+                AccountTransaction(AccountTransactionDetails {
+                    sender,
+                    effects:
+                        AccountTransactionEffects::CredentialKeysUpdated { .. }
+                        | AccountTransactionEffects::CredentialsUpdated { .. },
+                    ..
+                }) => {
+                    tracing::debug!(
+                        "Preparing database transaction for credential update of account {:} \
+                         from transaction hash {} in block {}.",
+                        sender,
+                        tx.hash,
+                        block_info.block_height,
+                    );
+                    self.pending.credential_updates.push(CredentialUpdate {
+                        address: *sender,
+                        block_time: block_info.block_slot_time,
+                    });
+                }
+                _ => continue,
+            }
+        }
+        self.pending.last_block = Some(block_info.block_height);
+        self.pending.since.get_or_insert_with(Instant::now);
+    }
+
+    /// Whether the buffered writes have to be flushed after the block with the
+    /// `block_slot_time` was buffered.
+    fn should_flush(&self, block_slot_time: DateTime<Utc>) -> bool {
+        let near_chain_head = (Utc::now() - block_slot_time)
+            .to_std()
+            .map_or(true, |lag| lag <= CHAIN_HEAD_LAG);
+        near_chain_head
+            || self.pending.len() >= self.flush_policy.batch_size
+            || self
+                .pending
+                .since
+                .map_or(false, |since| since.elapsed() >= self.flush_policy.interval)
+    }
+
+    /// Write the buffered accounts and credential updates and the height of
+    /// the last buffered block as the `latest_processed_block_height` in one
+    /// database transaction. The buffer is only cleared once the transaction
+    /// was committed.
+    async fn flush(&mut self) -> anyhow::Result<()> {
+        let Some(last_block) = self.pending.last_block else {
+            return Ok(());
+        };
 
-    async fn process(
-        &mut self,
-        (block_info, block_items): &Self::Data,
-    ) -> Result<Self::Description, Self::Error> {
         let mut conn = self.db_pool.get().await?;
 
         // It is typically easiest to reason about a database if blocks are inserted
-        // in a single database transaction. So we do that here.
+        // in a single database transaction. So we do that here for all buffered blocks.
         let db_transaction = conn
             .client
             .transaction()
             .await
             .context("Failed to build database transaction")?;
 
-        let params: [&(dyn ToSql + Sync); 1] = [&(block_info.block_height.height as i64)];
+        let params: [&(dyn ToSql + Sync); 1] = [&(last_block.height as i64)];
 
         // Update latest_processed_block_height
         let statement = db_transaction
@@ -92,40 +356,69 @@ impl indexer::ProcessEvent for StoreEvents {
             .await
             .context("Failed to execute latest_processed_block_height transaction")?;
 
-        for tx in block_items {
-            match &tx.details {
-                AccountCreation(account_creation_details) => {
-                    let params: [&(dyn ToSql + Sync); 5] = [
-                        &account_creation_details.address.0.as_ref(),
-                        &block_info.block_slot_time,
-                        &tx.hash.as_ref(),
-                        &false,
-                        &false,
-                    ];
-                    let statement = db_transaction
-                        .prepare_cached(
-                            "INSERT INTO accounts (account_address, block_time, transaction_hash, \
-                             claimed, pending_approval) VALUES ($1, $2, $3, $4, $5);",
-                        )
-                        .await
-                        .context(
-                            "Failed to prepare transaction to add a new account to the database",
-                        )?;
-
-                    db_transaction.execute(&statement, &params).await.context(
-                        "Failed to execute transaction to add a new account to the database",
-                    )?;
-
-                    tracing::debug!(
-                        "Preparing database transaction for account {:} from transaction hash {} \
-                         in block {}.",
-                        account_creation_details.address,
-                        tx.hash,
-                        block_info.block_height,
-                    );
-                }
-                _ => continue,
+        // The accounts are inserted before the credential updates, since an account
+        // can only be updated after it was created.
+        for accounts in self.pending.accounts.chunks(MAX_ROWS_PER_INSERT) {
+            let values = (0..accounts.len())
+                .map(|row| {
+                    let first = 3 * row + 1;
+                    format!("(${first}, ${}, ${}, false, false)", first + 1, first + 2)
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            let addresses: Vec<&[u8]> = accounts
+                .iter()
+                .map(|account| account.address.0.as_ref())
+                .collect();
+            let transaction_hashes: Vec<&[u8]> = accounts
+                .iter()
+                .map(|account| account.transaction_hash.as_ref())
+                .collect();
+            let mut params: Vec<&(dyn ToSql + Sync)> = Vec::with_capacity(3 * accounts.len());
+            for (index, account) in accounts.iter().enumerate() {
+                params.push(&addresses[index]);
+                params.push(&account.block_time);
+                params.push(&transaction_hashes[index]);
             }
+            // An account imported by an admin before the indexer reached its
+            // creation keeps its import time and gets the transaction hash.
+            let statement = db_transaction
+                .prepare(&format!(
+                    "INSERT INTO accounts (account_address, block_time, transaction_hash, \
+                     claimed, pending_approval) VALUES {values} ON CONFLICT (account_address) DO \
+                     UPDATE SET transaction_hash = EXCLUDED.transaction_hash WHERE \
+                     accounts.imported;"
+                ))
+                .await
+                .context("Failed to prepare transaction to add new accounts to the database")?;
+
+            db_transaction
+                .execute(&statement, &params)
+                .await
+                .context("Failed to execute transaction to add new accounts to the database")?;
+        }
+
+        for update in &self.pending.credential_updates {
+            // Only accounts tracked in the `accounts` table are updated. If the ZK
+            // proof of the account was submitted before the credential update, the
+            // account is flagged for re-review since the update may invalidate the
+            // compliance assumption.
+            let params: [&(dyn ToSql + Sync); 2] = [&update.address.0.as_ref(), &update.block_time];
+            let statement = db_transaction
+                .prepare_cached(
+                    "UPDATE accounts SET credentials_updated_time = $2, re_review_required = \
+                     re_review_required OR EXISTS (SELECT 1 FROM zkProofs WHERE \
+                     zkProofs.account_address = accounts.account_address AND \
+                     zkProofs.zk_proof_verification_submit_time < $2) WHERE account_address = \
+                     $1;",
+                )
+                .await
+                .context("Failed to prepare transaction to record a credential update")?;
+
+            db_transaction
+                .execute(&statement, &params)
+                .await
+                .context("Failed to execute transaction to record a credential update")?;
         }
 
         // Commit the transaction
@@ -134,6 +427,41 @@ impl indexer::ProcessEvent for StoreEvents {
             .await
             .context("Failed to commit block transaction")?;
 
+        self.pending = PendingWrites::default();
+        Ok(())
+    }
+}
+
+#[indexer::async_trait]
+impl indexer::ProcessEvent for StoreEvents {
+    type Data = (BlockInfo, Vec<BlockItemSummary>);
+    type Description = String;
+    type Error = anyhow::Error;
+
+    async fn process(
+        &mut self,
+        (block_info, block_items): &Self::Data,
+    ) -> Result<Self::Description, Self::Error> {
+        // A block whose flush failed is processed again. Its writes are still
+        // buffered, so it is not buffered a second time.
+        if self.pending.last_block != Some(block_info.block_height) {
+            self.buffer(block_info, block_items);
+        }
+
+        if !self.should_flush(block_info.block_slot_time) {
+            return Ok(format!(
+                "Buffered block {} at height {} with timestamp {} ({} pending writes).",
+                block_info.block_hash,
+                block_info.block_height,
+                block_info.block_slot_time,
+                self.pending.len()
+            ));
+        }
+
+        self.flush().await?;
+
+        self.failing_since = None;
+
         // We return an informative message that will be logged by the `process_events`
         // method of the indexer.
         Ok(format!(
@@ -145,10 +473,33 @@ impl indexer::ProcessEvent for StoreEvents {
     async fn on_failure(
         &mut self,
         error: Self::Error,
-        _failed_attempts: u32,
+        failed_attempts: u32,
     ) -> Result<bool, Self::Error> {
         tracing::error!("Encountered error {error}");
 
+        let failing_since = *self.failing_since.get_or_insert_with(Instant::now);
+
+        if let Some(max_retries) = self.retry_budget.max_retries {
+            if failed_attempts > max_retries {
+                tracing::error!(
+                    "Giving up after {} failed attempts to process the block.",
+                    failed_attempts
+                );
+                self.exhausted.store(true, Ordering::Release);
+                return Ok(false);
+            }
+        }
+        if let Some(max_duration) = self.retry_budget.max_duration {
+            if failing_since.elapsed() >= max_duration {
+                tracing::error!(
+                    "Giving up after retrying the block for {} seconds.",
+                    failing_since.elapsed().as_secs()
+                );
+                self.exhausted.store(true, Ordering::Release);
+                return Ok(false);
+            }
+        }
+
         Ok(true)
     }
 }
@@ -193,7 +544,18 @@ async fn main() -> anyhow::Result<()> {
     let consensus_info = client.get_consensus_info().await?;
 
     // Establish connection to the postgres database.
-    let db_pool = DatabasePool::create(app.db_connection, 2, true)
+    anyhow::ensure!(
+        app.db_pool_size > 0,
+        "The database pool needs at least one connection"
+    );
+    let pool_config = DatabasePoolConfig {
+        max_size: app.db_pool_size,
+        create_timeout: Duration::from_secs(app.db_create_timeout_secs),
+        recycle_timeout: Duration::from_secs(app.db_recycle_timeout_secs),
+        wait_timeout: Duration::from_secs(app.db_wait_timeout_secs),
+        try_create_tables: app.db_try_create_tables,
+    };
+    let db_pool = DatabasePool::create(app.db_connection, pool_config)
         .await
         .context("Could not create database pool")?;
     let db = db_pool
@@ -201,13 +563,33 @@ async fn main() -> anyhow::Result<()> {
         .await
         .context("Could not get database connection from pool")?;
 
-    // If the indexer is started for the first time, lookup the last block finalized
-    // and initialize the database.
-    let current_block = consensus_info.last_finalized_block_height;
+    // If the indexer is started for the first time, start from the given block or
+    // lookup the last block finalized and initialize the database.
+    let initial_block = match (app.start, app.start_hash) {
+        (Some(height), _) => height,
+        (None, Some(hash)) => {
+            let block_info = client
+                .get_block_info(&hash)
+                .await
+                .with_context(|| format!("Unable to query the block {hash} from the node"))?
+                .response;
+            anyhow::ensure!(
+                block_info.finalized,
+                "The block {hash} to start indexing from is not finalized."
+            );
+            tracing::info!(
+                "Resolved the block {} to the block height {}.",
+                hash,
+                block_info.block_height
+            );
+            block_info.block_height
+        }
+        (None, None) => consensus_info.last_finalized_block_height,
+    };
 
     // This function only sets the settings in the database if they haven't been set
     // before. Meaning only if the indexer is run for the first time.
-    db.init_settings(&consensus_info.genesis_block, current_block)
+    db.init_settings(&consensus_info.genesis_block, initial_block)
         .await
         .context("Could not init settings for database")?;
 
@@ -226,14 +608,26 @@ async fn main() -> anyhow::Result<()> {
         settings.genesis_block_hash
     );
 
+    if (app.start.is_some() || app.start_hash.is_some())
+        && settings.start_block_height != initial_block
+    {
+        tracing::warn!(
+            "The database was initialized with the start block height {}. The given start block \
+             height {} is ignored.",
+            settings.start_block_height,
+            initial_block
+        );
+    }
+
     // Get the block to start indexing from.
     let start_block = match settings.latest_processed_block_height {
         // If the indexer is re-started with the same database settings,
         // it should resume indexing from the `latest_processed_block_height+1` as stored in the
         // database.
         Some(processed_block) => processed_block.next(),
-        // If the indexer is started for the first time, use the current block height.
-        None => current_block,
+        // If no block has been processed yet, start from the start block height stored when
+        // the database was initialized.
+        None => settings.start_block_height,
     };
 
     tracing::info!(
@@ -241,22 +635,63 @@ async fn main() -> anyhow::Result<()> {
         consensus_info.genesis_block
     );
 
-    handle_indexing(endpoint, start_block, db_pool)
-        .await
-        .map_err(anyhow::Error::new)
+    let retry_budget = RetryBudget {
+        max_retries: app.max_retries,
+        max_duration: app.max_retry_duration.map(Duration::from_secs),
+    };
+    let exhausted = Arc::new(AtomicBool::new(false));
+
+    anyhow::ensure!(app.batch_size > 0, "The batch size has to be at least one.");
+    let flush_policy = FlushPolicy {
+        batch_size: app.batch_size,
+        interval: Duration::from_millis(app.flush_interval_ms),
+    };
+
+    handle_indexing(
+        endpoint,
+        start_block,
+        db_pool,
+        retry_budget,
+        flush_policy,
+        exhausted.clone(),
+    )
+    .await
+    .map_err(anyhow::Error::new)?;
+
+    if exhausted.load(Ordering::Acquire) {
+        tracing::error!(
+            "Retry budget exhausted. Exiting with exit code {}.",
+            RETRY_BUDGET_EXHAUSTED_EXIT_CODE
+        );
+        std::process::exit(RETRY_BUDGET_EXHAUSTED_EXIT_CODE);
+    }
+
+    Ok(())
 }
 
-/// Handle indexing events.
+/// Handle indexing events. The writes of the blocks are buffered according to
+/// the `flush_policy`. The indexing stops and `exhausted` is set if a block
+/// could not be processed within the `retry_budget`.
 async fn handle_indexing(
     endpoint: sdk::Endpoint,
     start_block: AbsoluteBlockHeight,
     db_pool: DatabasePool,
+    retry_budget: RetryBudget,
+    flush_policy: FlushPolicy,
+    exhausted: Arc<AtomicBool>,
 ) -> Result<(), QueryError> {
     tracing::info!("Indexing from block height {}.", start_block);
 
     let traverse_config = indexer::TraverseConfig::new_single(endpoint, start_block);
 
-    let events = StoreEvents { db_pool };
+    let events = StoreEvents {
+        db_pool,
+        retry_budget,
+        failing_since: None,
+        exhausted,
+        flush_policy,
+        pending: PendingWrites::default(),
+    };
 
     indexer::traverse_and_process(
         traverse_config,
@@ -1,42 +1,55 @@
-use ::indexer::{db::DatabasePool, types::Server};
+use ::indexer::{
+    alerts::{CampaignHours, VelocityAnalyzer, VelocityCounters, VelocityThresholds},
+    backup::{self, Backups},
+    db::{DatabasePool, DatabasePoolConfig},
+    types::Server,
+};
 use anyhow::Context;
 use axum::{
     extract::State,
+    http::Request,
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
     routing::{get, post},
     Json, Router,
 };
 use chrono::Utc;
 use clap::Parser;
 use concordium_rust_sdk::{
+    common::types::{Amount, KeyPair},
     id::{
         constants::ArCurve,
-        id_proof_types::Statement,
+        id_proof_types::{AtomicStatement, Statement},
         types::{AccountAddress, AccountCredentialWithoutProofs},
     },
     v2::{AccountIdentifier, BlockIdentifier, Client},
     web3id::{
         did::Network,
-        get_public_data, Challenge, CredentialProof,
+        get_public_data, CredentialProof,
         CredentialStatement::{Account, Web3Id},
         Web3IdAttribute,
     },
 };
 use indexer::{
     constants::{
-        CONTEXT_STRING, CURRENT_TWEET_VERIFICATION_VERSION, CURRENT_ZK_PROOF_VERIFICATION_VERSION,
+        CURRENT_TWEET_VERIFICATION_VERSION, CURRENT_ZK_PROOF_VERIFICATION_VERSION,
         MAX_REQUEST_LIMIT, SIGNATURE_AND_PROOF_EXPIRY_DURATION_BLOCKS, TESTNET_GENESIS_BLOCK_HASH,
         ZK_STATEMENTS,
     },
-    db::{AccountData, Database, StoredAccountData},
+    db::{AccountData, Database, EligibilityData, StoredAccountData},
     error::ServerError,
     types::{
-        CanClaimParam, CanClaimReturn, ClaimExpiryDurationDays, GetAccountDataParam,
-        GetPendingApprovalsParam, HasSigningData, Health, PostTweetParam, PostZKProofParam,
-        SetClaimedParam, SigningData, UserData, VecAccountDataReturn, ZKProofExtractedData,
-        ZKProofStatementsReturn,
+        zk_proof_challenge, AcceptedZKStatement, AttestationIssuer, BackupReturn, CanClaimParam,
+        CanClaimReturn, ClaimAttestationReturn, ClaimExpiryDurationDays, ClaimReviewParam,
+        EligibilityRule, GetAccountDataParam, GetClaimAttestationParam, GetPendingApprovalsParam,
+        HasSigningData, Health, ImportAccountsParam, ImportAccountsReport, ImportAccountsReturn,
+        Info, PostTweetParam, PostZKProofParam, ReleaseReviewParam, ReviewLockReturn,
+        SetClaimedParam, SigningData, TriggerBackupParam, UserData, VecAccountDataReturn,
+        ZKProofExtractedData, ZKProofStatementsReturn,
     },
+    worker_pool::WorkerPool,
 };
-use sha2::Digest;
+use std::{collections::BTreeSet, path::PathBuf, sync::Arc};
 
 /// Command line configuration of the application.
 #[derive(Debug, clap::Parser)]
@@ -95,6 +108,189 @@ struct Args {
         default_value = "60"
     )]
     claim_expiry_duration_days: ClaimExpiryDurationDays,
+    /// A JSON file with the list of ZK statements that are accepted when verifying
+    /// submitted ZK proofs. The first entry is the current ZK statement served to the
+    /// front end. If not specified, only the built-in ZK statement is accepted.
+    #[arg(long = "zk_statements_file", env = "CCD_SERVER_ZK_STATEMENTS_FILE")]
+    zk_statements_file: Option<PathBuf>,
+    /// The directory where backups of the campaign tables are stored. If not
+    /// specified, backups are disabled.
+    #[arg(long = "backup_dir", env = "CCD_SERVER_BACKUP_DIR")]
+    backup_dir: Option<PathBuf>,
+    /// The number of hours between scheduled backups. Each scheduled backup is
+    /// verified by restoring it into temporary tables.
+    #[arg(
+        long = "backup_interval_hours",
+        env = "CCD_SERVER_BACKUP_INTERVAL_HOURS",
+        default_value = "24"
+    )]
+    backup_interval_hours: u64,
+    /// The number of backups that are kept in the backup directory. Older
+    /// backups are deleted after a new backup was created.
+    #[arg(
+        long = "backup_keep",
+        env = "CCD_SERVER_BACKUP_KEEP",
+        default_value = "7"
+    )]
+    backup_keep: usize,
+    /// The number of seconds a request may take before it is aborted with
+    /// `504 Gateway Timeout`. The deadline applies to all node queries and
+    /// database operations of the request. The `/api/triggerBackup` endpoint
+    /// is not limited.
+    #[arg(
+        long = "request_timeout_secs",
+        env = "CCD_SERVER_REQUEST_TIMEOUT_SECS",
+        default_value = "10"
+    )]
+    request_timeout_secs: u64,
+    /// The number of seconds an account stays locked after an admin claimed it
+    /// for review with the `/api/claimReview` endpoint.
+    #[arg(
+        long = "review_lock_ttl_secs",
+        env = "CCD_SERVER_REVIEW_LOCK_TTL_SECS",
+        default_value = "900"
+    )]
+    review_lock_ttl_secs: u32,
+    /// The minimum CCD balance an account needs before it can submit tasks. If
+    /// `--eligibility_min_weighted_transactions` is set as well, fulfilling one
+    /// of the two thresholds is sufficient. If neither is set, the on-chain
+    /// state of the accounts is not checked.
+    #[arg(
+        long = "eligibility_min_balance",
+        env = "CCD_SERVER_ELIGIBILITY_MIN_BALANCE"
+    )]
+    eligibility_min_balance: Option<Amount>,
+    /// The minimum number of transactions an account has sent multiplied by the
+    /// age of the account in days before it can submit tasks.
+    #[arg(
+        long = "eligibility_min_weighted_transactions",
+        env = "CCD_SERVER_ELIGIBILITY_MIN_WEIGHTED_TRANSACTIONS"
+    )]
+    eligibility_min_weighted_transactions: Option<u64>,
+    /// The number of threads verifying the cryptographic proofs of submitted
+    /// ZK proofs in parallel. If not specified, the number of available CPUs
+    /// is used.
+    #[arg(
+        long = "proof_verification_workers",
+        env = "CCD_SERVER_PROOF_VERIFICATION_WORKERS"
+    )]
+    proof_verification_workers: Option<usize>,
+    /// The number of ZK proofs waiting for a free verification thread. Further
+    /// ZK proofs are rejected with `503 Service Unavailable`.
+    #[arg(
+        long = "proof_verification_queue_size",
+        env = "CCD_SERVER_PROOF_VERIFICATION_QUEUE_SIZE",
+        default_value = "64"
+    )]
+    proof_verification_queue_size: usize,
+    /// The number of seconds the verification of the cryptographic proofs of a
+    /// ZK proof may take, including the time waiting for a free thread, before
+    /// the request is aborted with `504 Gateway Timeout`.
+    #[arg(
+        long = "proof_verification_timeout_secs",
+        env = "CCD_SERVER_PROOF_VERIFICATION_TIMEOUT_SECS",
+        default_value = "5"
+    )]
+    proof_verification_timeout_secs: u64,
+    /// A JSON file with the ed25519 key pair (`signKey` and `verifyKey`) the
+    /// server signs the claim attestations with. If not specified, no claim
+    /// attestations are issued.
+    #[arg(
+        long = "attestation_key_file",
+        env = "CCD_SERVER_ATTESTATION_KEY_FILE",
+        requires = "reward_amount"
+    )]
+    attestation_key_file: Option<PathBuf>,
+    /// The identifier of the campaign included in the claim attestations.
+    #[arg(
+        long = "campaign_id",
+        env = "CCD_SERVER_CAMPAIGN_ID",
+        default_value = "compliant-reward-distribution"
+    )]
+    campaign_id: String,
+    /// The reward amount in CCD paid out to every account included in the
+    /// claim attestations.
+    #[arg(long = "reward_amount", env = "CCD_SERVER_REWARD_AMOUNT")]
+    reward_amount: Option<Amount>,
+    /// The maximum number of accepted tweet and ZK proof submissions per
+    /// minute over the rolling alert window. A higher rate (e.g. caused by a
+    /// bot attack) fires an alert. If not specified, the alert is disabled.
+    #[arg(
+        long = "alert_max_submissions_per_minute",
+        env = "CCD_SERVER_ALERT_MAX_SUBMISSIONS_PER_MINUTE"
+    )]
+    alert_max_submissions_per_minute: Option<f64>,
+    /// The maximum number of accounts marked as claimed per minute over the
+    /// rolling alert window. If not specified, the alert is disabled.
+    #[arg(
+        long = "alert_max_claims_per_minute",
+        env = "CCD_SERVER_ALERT_MAX_CLAIMS_PER_MINUTE"
+    )]
+    alert_max_claims_per_minute: Option<f64>,
+    /// The hours of the day (UTC) during which submissions are expected, e.g.
+    /// `8-20`. If no submission is accepted during a rolling alert window
+    /// within these hours (e.g. caused by an outage), an alert is fired. If
+    /// not specified, the alert is disabled.
+    #[arg(long = "alert_campaign_hours", env = "CCD_SERVER_ALERT_CAMPAIGN_HOURS")]
+    alert_campaign_hours: Option<CampaignHours>,
+    /// The number of minutes of the rolling window the submission and claim
+    /// rates are computed over.
+    #[arg(
+        long = "alert_window_minutes",
+        env = "CCD_SERVER_ALERT_WINDOW_MINUTES",
+        default_value = "10"
+    )]
+    alert_window_minutes: u64,
+    /// The number of seconds between the checks of the alerts.
+    #[arg(
+        long = "alert_interval_secs",
+        env = "CCD_SERVER_ALERT_INTERVAL_SECS",
+        default_value = "60"
+    )]
+    alert_interval_secs: u64,
+    /// The URL the alerts are posted to as JSON. If not specified, the alerts
+    /// are only logged.
+    #[arg(long = "alert_webhook_url", env = "CCD_SERVER_ALERT_WEBHOOK_URL")]
+    alert_webhook_url: Option<reqwest::Url>,
+    /// The maximum number of connections to the database. If not specified,
+    /// one connection is used, or two if backups are enabled so that backups
+    /// do not block the endpoints.
+    #[arg(long = "db_pool_size", env = "CCD_SERVER_DB_POOL_SIZE")]
+    db_pool_size: Option<usize>,
+    /// The maximum number of seconds to wait for a new connection to the
+    /// database to be established.
+    #[arg(
+        long = "db_create_timeout_secs",
+        env = "CCD_SERVER_DB_CREATE_TIMEOUT_SECS",
+        default_value = "5"
+    )]
+    db_create_timeout_secs: u64,
+    /// The maximum number of seconds to wait for a connection to the database
+    /// to be verified before it is reused.
+    #[arg(
+        long = "db_recycle_timeout_secs",
+        env = "CCD_SERVER_DB_RECYCLE_TIMEOUT_SECS",
+        default_value = "5"
+    )]
+    db_recycle_timeout_secs: u64,
+    /// The maximum number of seconds to wait for a connection to the database
+    /// to become available if all connections of the pool are in use.
+    #[arg(
+        long = "db_wait_timeout_secs",
+        env = "CCD_SERVER_DB_WAIT_TIMEOUT_SECS",
+        default_value = "5"
+    )]
+    db_wait_timeout_secs: u64,
+    /// Whether the database tables are created on startup if they do not exist
+    /// yet. Can be disabled if the database user has no permission to create
+    /// tables and the schema is managed separately.
+    #[arg(
+        long = "db_try_create_tables",
+        env = "CCD_SERVER_DB_TRY_CREATE_TABLES",
+        default_value_t = true,
+        action = clap::ArgAction::Set
+    )]
+    db_try_create_tables: bool,
 }
 
 /// The main function.
@@ -114,8 +310,39 @@ async fn main() -> anyhow::Result<()> {
             .init();
     }
 
-    // Establish connection to the postgres database.
-    let db_pool = DatabasePool::create(app.db_connection, 1, true)
+    anyhow::ensure!(
+        app.request_timeout_secs > 0,
+        "The request timeout has to be at least one second"
+    );
+    let request_timeout = std::time::Duration::from_secs(app.request_timeout_secs);
+
+    // Database queries are cancelled by the database once the request deadline
+    // has passed, so that a slow query does not keep the connection busy after
+    // the request was aborted.
+    let mut db_connection = app.db_connection;
+    let options = format!(
+        "{} -c statement_timeout={}",
+        db_connection.get_options().unwrap_or_default(),
+        request_timeout.as_millis()
+    );
+    db_connection.options(options.trim_start());
+
+    // Establish connection to the postgres database. A second connection is
+    // used by backups so that they do not block the endpoints.
+    let default_pool_size = if app.backup_dir.is_some() { 2 } else { 1 };
+    let pool_size = app.db_pool_size.unwrap_or(default_pool_size);
+    anyhow::ensure!(
+        pool_size > 0,
+        "The database pool needs at least one connection"
+    );
+    let pool_config = DatabasePoolConfig {
+        max_size: pool_size,
+        create_timeout: std::time::Duration::from_secs(app.db_create_timeout_secs),
+        recycle_timeout: std::time::Duration::from_secs(app.db_recycle_timeout_secs),
+        wait_timeout: std::time::Duration::from_secs(app.db_wait_timeout_secs),
+        try_create_tables: app.db_try_create_tables,
+    };
+    let db_pool = DatabasePool::create(db_connection, pool_config)
         .await
         .context("Could not create database pool")?;
 
@@ -157,17 +384,93 @@ async fn main() -> anyhow::Result<()> {
         .context("Unable to get cryptographic parameters")?
         .response;
 
-    let zk_statements: Statement<ArCurve, Web3IdAttribute> =
-        serde_json::from_str(ZK_STATEMENTS).context("Unable to construct the ZK statements")?;
+    let zk_statements = load_zk_statements(app.zk_statements_file)?;
+
+    let proof_verification_workers = match app.proof_verification_workers {
+        Some(workers) => workers,
+        None => std::thread::available_parallelism()
+            .context("Unable to determine the number of available CPUs")?
+            .get(),
+    };
+    anyhow::ensure!(
+        proof_verification_workers > 0,
+        "The number of proof verification workers has to be at least one"
+    );
+    let proof_verifier = WorkerPool::new(
+        proof_verification_workers,
+        app.proof_verification_queue_size,
+        std::time::Duration::from_secs(app.proof_verification_timeout_secs),
+    );
+
+    let attestation_issuer = match (app.attestation_key_file, app.reward_amount) {
+        (Some(path), Some(amount)) => {
+            let json = std::fs::read_to_string(&path)
+                .with_context(|| format!("Unable to read the attestation key from {:?}", path))?;
+            let key_pair: KeyPair =
+                serde_json::from_str(&json).context("Unable to parse the attestation key")?;
+            Some(AttestationIssuer {
+                key_pair: Arc::new(key_pair),
+                campaign: app.campaign_id,
+                amount,
+            })
+        }
+        _ => None,
+    };
+
+    let backups = app
+        .backup_dir
+        .map(|backup_dir| Backups::new(backup_dir, app.backup_keep))
+        .transpose()
+        .context("Unable to create the backup directory")?;
+    if let Some(backups) = &backups {
+        anyhow::ensure!(
+            app.backup_interval_hours > 0,
+            "The backup interval has to be at least one hour"
+        );
+        tokio::spawn(backup::run_scheduled(
+            backups.clone(),
+            db_pool.clone(),
+            std::time::Duration::from_secs(app.backup_interval_hours * 60 * 60),
+        ));
+    }
+
+    let velocity = VelocityCounters::default();
+    let thresholds = VelocityThresholds {
+        max_submissions_per_minute: app.alert_max_submissions_per_minute,
+        max_claims_per_minute: app.alert_max_claims_per_minute,
+        campaign_hours: app.alert_campaign_hours,
+    };
+    if thresholds.is_enabled() {
+        anyhow::ensure!(
+            app.alert_window_minutes > 0 && app.alert_interval_secs > 0,
+            "The alert window and the alert interval have to be positive"
+        );
+        let analyzer = VelocityAnalyzer::new(
+            velocity.clone(),
+            thresholds,
+            std::time::Duration::from_secs(app.alert_window_minutes * 60),
+            app.alert_webhook_url,
+        );
+        tokio::spawn(analyzer.run(std::time::Duration::from_secs(app.alert_interval_secs)));
+    }
 
     let state = Server {
         db_pool,
         node_client,
         network,
-        cryptographic_params,
+        cryptographic_params: Arc::new(cryptographic_params),
         admin_accounts: app.admin_accounts,
         zk_statements,
         claim_expiry_duration_days: app.claim_expiry_duration_days,
+        backups,
+        review_lock_ttl: chrono::Duration::seconds(app.review_lock_ttl_secs.into()),
+        eligibility_rule: EligibilityRule {
+            min_balance: app.eligibility_min_balance,
+            min_weighted_transaction_count: app.eligibility_min_weighted_transactions,
+        },
+        proof_verifier,
+        attestation_issuer,
+        velocity,
     };
 
     tracing::info!("Starting server...");
@@ -176,11 +479,22 @@ async fn main() -> anyhow::Result<()> {
         .route("/api/postTweet", post(post_tweet))
         .route("/api/postZKProof", post(post_zk_proof))
         .route("/api/setClaimed", post(set_claimed))
+        .route("/api/importAccounts", post(import_accounts))
         .route("/api/getAccountData", post(get_account_data))
         .route("/api/getPendingApprovals", post(get_pending_approvals))
+        .route("/api/claimReview", post(claim_review))
+        .route("/api/releaseReview", post(release_review))
         .route("/api/canClaim", post(can_claim))
+        .route("/api/getClaimAttestation", post(get_claim_attestation))
         .route("/api/getZKProofStatements", get(get_zk_proof_statements))
         .route("/health", get(health))
+        .route("/info", get(info))
+        .route_layer(middleware::from_fn_with_state(
+            request_timeout,
+            enforce_deadline,
+        ))
+        // Backups can take longer than the request timeout.
+        .route("/api/triggerBackup", post(trigger_backup))
         .with_state(state)
         .layer(
             tower_http::trace::TraceLayer::new_for_http()
@@ -204,6 +518,62 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Load the accepted ZK statements from the `zk_statements_file`. If no file is
+/// given, the built-in `ZK_STATEMENTS` are the only accepted ZK statements.
+/// The function checks that:
+/// - at least one ZK statement is accepted.
+/// - the identifiers of the ZK statements are unique.
+/// - every ZK statement reveals the `national_id` and the `nationality` at
+///   positions `0` and `1`, which is expected by the `check_zk_proof` function.
+fn load_zk_statements(
+    zk_statements_file: Option<PathBuf>,
+) -> anyhow::Result<Vec<AcceptedZKStatement>> {
+    let zk_statements = match zk_statements_file {
+        Some(path) => {
+            let json = std::fs::read_to_string(&path)
+                .with_context(|| format!("Unable to read the ZK statements from {:?}", path))?;
+            serde_json::from_str(&json).context("Unable to construct the ZK statements")?
+        }
+        None => {
+            let statement: Statement<ArCurve, Web3IdAttribute> =
+                serde_json::from_str(ZK_STATEMENTS)
+                    .context("Unable to construct the ZK statements")?;
+            vec![AcceptedZKStatement {
+                id: 1,
+                statement,
+                accepted_until: None,
+            }]
+        }
+    };
+
+    anyhow::ensure!(
+        !zk_statements.is_empty(),
+        "At least one ZK statement has to be accepted"
+    );
+
+    let mut ids = BTreeSet::new();
+    for zk_statement in &zk_statements {
+        anyhow::ensure!(
+            ids.insert(zk_statement.id),
+            "The ZK statement id {} is not unique",
+            zk_statement.id
+        );
+
+        let reveals_national_id_and_nationality = zk_statement.statement.statements.len() >= 2
+            && zk_statement.statement.statements[..2]
+                .iter()
+                .all(|statement| matches!(statement, AtomicStatement::RevealAttribute { .. }));
+        anyhow::ensure!(
+            reveals_national_id_and_nationality,
+            "The ZK statement {} has to reveal the `national_id` and the `nationality` at \
+             positions 0 and 1",
+            zk_statement.id
+        );
+    }
+
+    Ok(zk_statements)
+}
+
 /// Check that the account is eligible for claiming the reward by checking that:
 /// - the account exists in the database.
 /// - the account creation has not expired.
@@ -233,16 +603,73 @@ pub async fn check_account_eligible(
     Ok(database_result)
 }
 
+/// Check that the account fulfills the on-chain eligibility rule by querying
+/// its balance and the number of transactions it has sent from the node. The
+/// number of transactions is weighted by the age of the account in days (at
+/// least one day), so that accounts that are only created and used for farming
+/// rewards need more transactions. The result of the check is recorded in the
+/// database. Every account is eligible if no eligibility rule is configured.
+async fn check_on_chain_eligibility(
+    db: &Database,
+    state: &mut Server,
+    account_data: &AccountData,
+) -> Result<(), ServerError> {
+    if !state.eligibility_rule.is_enabled() {
+        return Ok(());
+    }
+
+    let account_info = state
+        .node_client
+        .get_account_info(
+            &AccountIdentifier::Address(account_data.account_address),
+            BlockIdentifier::LastFinal,
+        )
+        .await
+        .map_err(ServerError::QueryError)?
+        .response;
+
+    // The next nonce of an account is one more than the number of transactions
+    // it has sent.
+    let transaction_count = account_info.account_nonce.nonce.saturating_sub(1);
+    let now = Utc::now();
+    let age_days = (now - account_data.block_time).num_days().max(1) as u64;
+    let weighted_transaction_count = transaction_count.saturating_mul(age_days);
+    let balance = account_info.account_amount;
+
+    let eligible = state
+        .eligibility_rule
+        .is_fulfilled(balance, weighted_transaction_count);
+
+    db.upsert_eligibility_check(&EligibilityData {
+        account_address: account_data.account_address,
+        balance,
+        transaction_count,
+        weighted_transaction_count,
+        eligible,
+        check_time: now,
+    })
+    .await?;
+
+    if !eligible {
+        return Err(ServerError::NotEligible {
+            balance,
+            weighted_transaction_count,
+        });
+    }
+
+    Ok(())
+}
+
 /// Check that the zk proof is valid by checking that:
 /// - the cryptographic proofs are valid.
 /// - exactly one credential statement is present in the proof (no multi-sig support).
-/// - the expected zk statements have been proven.
+/// - one of the currently accepted zk statements has been proven.
 /// - the proof has been generated for the correct network.
 /// - the proof is not expired.
 /// - the proof was intended for this service.
 /// - the proof is not from an `Initial` account (these accounts have no Pedersen commitment on chain).
 /// The function returns the revealed `national_id`, `nationality` and `prover`
-/// associated with the proof.
+/// associated with the proof, and the identifier of the zk statement proven.
 async fn check_zk_proof(
     state: &mut Server,
     param: PostZKProofParam,
@@ -258,11 +685,20 @@ async fn check_zk_proof(
     )
     .await?;
 
-    // Verify the cryptographic proofs.
-    let request = presentation.verify(
-        &state.cryptographic_params,
-        public_data.iter().map(|credential| &credential.inputs),
-    )?;
+    // Verify the cryptographic proofs. The verification is CPU-bound and runs
+    // on the worker pool so that it does not block the async runtime.
+    let cryptographic_params = state.cryptographic_params.clone();
+    let (presentation, request) = state
+        .proof_verifier
+        .run(move || {
+            let request = presentation.verify(
+                &cryptographic_params,
+                public_data.iter().map(|credential| &credential.inputs),
+            );
+            (presentation, request)
+        })
+        .await?;
+    let request = request?;
 
     // We support regular accounts with exactly one credential at index 0.
     if request.credential_statements.len() != 1 {
@@ -273,14 +709,18 @@ async fn check_zk_proof(
     let account_statement = &request.credential_statements[0];
 
     // Check the ZK proof has been generated as expected.
-    match account_statement {
+    let zk_statement_id = match account_statement {
         Account {
             network, statement, ..
         } => {
-            // Check that the expected ZK statement has been proven.
-            if *statement != state.zk_statements.statements {
-                return Err(ServerError::WrongStatement);
-            }
+            // Check that one of the currently accepted ZK statements has been proven.
+            let now = Utc::now();
+            let zk_statement = state
+                .zk_statements
+                .iter()
+                .filter(|zk_statement| zk_statement.is_accepted(now))
+                .find(|zk_statement| *statement == zk_statement.statement.statements)
+                .ok_or(ServerError::WrongStatement)?;
 
             // Check that the proof has been generated for the correct network.
             if *network != state.network {
@@ -289,9 +729,11 @@ async fn check_zk_proof(
                     actual: *network,
                 });
             }
+
+            zk_statement.id
         }
         Web3Id { .. } => return Err(ServerError::AccountStatement),
-    }
+    };
 
     // Check if the proof is not expired by checking if a recent block hash was
     // included in the challenge (also called presentation_context).
@@ -308,9 +750,7 @@ async fn check_zk_proof(
     // SIGNATURE_AND_PROOF_EXPIRY_DURATION_BLOCKS. The `CONTEXT_STRING` ensures
     // that the proof is generated for this specific service. These checks are
     // done similarly in the `signature` verification flow in this service.
-    let challenge_hash = sha2::Sha256::digest([block_hash.as_ref(), &CONTEXT_STRING].concat());
-    let challenge = Challenge::try_from(challenge_hash.as_slice())
-        .map_err(|e| ServerError::TypeConversion("challenge".to_string(), e))?;
+    let challenge = zk_proof_challenge(&block_hash)?;
 
     if presentation.presentation_context != challenge {
         return Err(ServerError::ChallengeInvalid);
@@ -343,7 +783,7 @@ async fn check_zk_proof(
         } => {
             // Get the revealed `national_id` from the proof.
             // Accessing the index at position `0` is safe because we checked that
-            // `state.zk_statements.statements` were proven, which means we know
+            // one of the `state.zk_statements` was proven, which means we know
             // that the first proof is a revealed `national_id` attribute proof.
             let index_0 = 0;
             let national_id = match &proofs[index_0].1 {
@@ -356,7 +796,7 @@ async fn check_zk_proof(
 
             // Get the revealed `nationality` from the proof.
             // Accessing the index at position `1` is safe because we checked that
-            // `state.zk_statements.statements` were proven, which means we know
+            // one of the `state.zk_statements` was proven, which means we know
             // that the second proof is a revealed `nationality` attribute proof.
             let index_1 = 1;
             let nationality = match &proofs[index_1].1 {
@@ -404,6 +844,7 @@ async fn check_zk_proof(
         national_id,
         nationality,
         prover,
+        zk_statement_id,
     })
 }
 
@@ -419,9 +860,9 @@ where
 {
     let SigningData {
         signer,
-        message,
         signature,
         block_height,
+        ..
     } = param.signing_data();
 
     let signer_account_info = state
@@ -453,17 +894,7 @@ where
     // string (this ensures that an account can be re-used for signing in different
     // Concordium services).
     // Add the prepend to the message and calculate the message hash.
-    let message_bytes = bincode::serialize(&message)?;
-    let message_hash = sha2::Sha256::digest(
-        [
-            &signer.as_ref() as &[u8],
-            &[0u8; 8],
-            &block_hash,
-            &CONTEXT_STRING,
-            &message_bytes,
-        ]
-        .concat(),
-    );
+    let message_hash = param.signing_data().message_hash(&block_hash)?;
 
     // Get the public key of the signer.
 
@@ -543,7 +974,11 @@ async fn post_tweet(
     // Check that:
     // - the account exists in the database.
     // - the account creation has not expired.
-    let AccountData { claimed, .. } = check_account_eligible(&db, &state, signer).await?;
+    let account_data = check_account_eligible(&db, &state, signer).await?;
+
+    // Check that the account fulfills the on-chain eligibility rule.
+    check_on_chain_eligibility(&db, &mut state, &account_data).await?;
+    let AccountData { claimed, .. } = account_data;
 
     // Calculate the `new_pending_approval` flag`.
     let zk_proof_valid = db
@@ -560,6 +995,7 @@ async fn post_tweet(
         CURRENT_TWEET_VERIFICATION_VERSION,
     )
     .await?;
+    state.velocity.record_submission();
 
     Ok(())
 }
@@ -573,17 +1009,18 @@ async fn post_zk_proof(
     // Check that:
     // - the cryptographic proofs are valid.
     // - exactly one credential statement is present in the proof (no multi-sig support).
-    // - the expected zk statements have been proven.
+    // - one of the currently accepted zk statements has been proven.
     // - the proof has been generated for the correct network.
     // - the proof is not expired.
     // - the proof was intended for this service.
     // - the proof is not from an `Initial` account (these accounts have no Pedersen commitment on chain).
     // Return the extracted `national_id`, `nationality` and `prover` associated
-    // with the proof.
+    // with the proof, and the identifier of the zk statement proven.
     let ZKProofExtractedData {
         national_id,
         nationality,
         prover,
+        zk_statement_id,
     } = check_zk_proof(&mut state, param).await?;
 
     let db = state.db_pool.get().await?;
@@ -591,7 +1028,11 @@ async fn post_zk_proof(
     // Check that:
     // - the account exists in the database.
     // - the account creation has not expired.
-    let AccountData { claimed, .. } = check_account_eligible(&db, &state, prover).await?;
+    let account_data = check_account_eligible(&db, &state, prover).await?;
+
+    // Check that the account fulfills the on-chain eligibility rule.
+    check_on_chain_eligibility(&db, &mut state, &account_data).await?;
+    let AccountData { claimed, .. } = account_data;
 
     // Calculate the `new_pending_approval` flag`.
     let tweet_valid = db.get_tweet_data(prover).await?.map(|x| x.tweet_valid);
@@ -604,8 +1045,10 @@ async fn post_zk_proof(
         prover,
         new_pending_approval,
         CURRENT_ZK_PROOF_VERIFICATION_VERSION,
+        zk_statement_id,
     )
     .await?;
+    state.velocity.record_submission();
 
     Ok(())
 }
@@ -627,14 +1070,88 @@ async fn set_claimed(
         return Err(ServerError::SignerNotAdmin);
     }
 
+    let account_addresses = param.signing_data.message.account_addresses;
+
     // Update the database.
     let db = state.db_pool.get().await?;
-    db.set_claimed(param.signing_data.message.account_addresses)
-        .await?;
+    db.set_claimed(account_addresses.clone(), signer).await?;
+    state.velocity.record_claims(account_addresses.len() as u64);
+
+    // Issue the claim attestations of the accounts.
+    if let Some(attestation_issuer) = &state.attestation_issuer {
+        let claimed_at = Utc::now();
+        for account_address in account_addresses {
+            let signed_attestation =
+                attestation_issuer.attest(account_address, state.network, claimed_at)?;
+            db.insert_claim_attestation(account_address, &signed_attestation)
+                .await?;
+        }
+    }
 
     Ok(())
 }
 
+/// Pre-approve the accounts (e.g. from a partner allowlist) without requiring
+/// them to complete the tasks. Every account is checked to exist on chain and
+/// is inserted as pending approval with the `imported` flag, so it is paid out
+/// in the same way as the accounts that completed the tasks. Accounts that are
+/// already in the database are left unchanged.
+async fn import_accounts(
+    State(mut state): State<Server>,
+    request: Json<ImportAccountsParam>,
+) -> Result<Json<ImportAccountsReturn>, ServerError> {
+    let Json(param) = request;
+
+    let account_addresses = param.signing_data.message.accounts()?;
+
+    // Check that:
+    // - the signature is valid.
+    // - the signature is not expired.
+    // - the signature was intended for this service.
+    let signer = check_signature(&mut state, &param).await?;
+
+    // Check that the signer is an admin account.
+    if !state.admin_accounts.contains(&signer) {
+        return Err(ServerError::SignerNotAdmin);
+    }
+
+    let db = state.db_pool.get().await?;
+    let import_time = Utc::now();
+    let mut report = ImportAccountsReport::default();
+    for account_address in account_addresses {
+        // Check that the account exists on chain.
+        if let Err(e) = state
+            .node_client
+            .get_account_info(
+                &AccountIdentifier::Address(account_address),
+                BlockIdentifier::LastFinal,
+            )
+            .await
+        {
+            if e.is_not_found() {
+                report.not_on_chain.push(account_address);
+                continue;
+            }
+            return Err(ServerError::QueryError(e));
+        }
+
+        if db.import_account(account_address, import_time).await? {
+            report.imported.push(account_address);
+        } else {
+            report.already_present.push(account_address);
+        }
+    }
+
+    tracing::info!(
+        "Admin {signer} imported {} accounts ({} already present, {} not on chain).",
+        report.imported.len(),
+        report.already_present.len(),
+        report.not_on_chain.len()
+    );
+
+    Ok(Json(ImportAccountsReturn { data: report }))
+}
+
 async fn get_account_data(
     State(mut state): State<Server>,
     request: Json<GetAccountDataParam>,
@@ -658,11 +1175,13 @@ async fn get_account_data(
     let account_data = db.get_account_data(lookup_account_address).await?;
     let zk_proof_data = db.get_zk_proof_data(lookup_account_address).await?;
     let tweet_data = db.get_tweet_data(lookup_account_address).await?;
+    let eligibility_data = db.get_eligibility_data(lookup_account_address).await?;
 
     Ok(Json(StoredAccountData {
         account_data,
         tweet_data,
         zk_proof_data,
+        eligibility_data,
     }))
 }
 
@@ -702,6 +1221,68 @@ async fn get_pending_approvals(
     }))
 }
 
+/// Claim a pending approval for review, locking the account for the
+/// `review_lock_ttl` so that no other admin reviews it at the same time.
+/// Claiming an account again extends the lock.
+async fn claim_review(
+    State(mut state): State<Server>,
+    request: Json<ClaimReviewParam>,
+) -> Result<Json<ReviewLockReturn>, ServerError> {
+    let Json(param) = request;
+
+    // Check that:
+    // - the signature is valid.
+    // - the signature is not expired.
+    // - the signature was intended for this service.
+    let signer = check_signature(&mut state, &param).await?;
+
+    // Check that the signer is an admin account.
+    if !state.admin_accounts.contains(&signer) {
+        return Err(ServerError::SignerNotAdmin);
+    }
+
+    let locked_until = Utc::now() + state.review_lock_ttl;
+
+    // Update the database.
+    let db = state.db_pool.get().await?;
+    let review_lock = db
+        .claim_review(
+            param.signing_data.message.account_address,
+            signer,
+            locked_until,
+        )
+        .await?;
+
+    Ok(Json(ReviewLockReturn { data: review_lock }))
+}
+
+/// Release the review lock of an account claimed by the signer, so that other
+/// admins can review it before the lock expires.
+async fn release_review(
+    State(mut state): State<Server>,
+    request: Json<ReleaseReviewParam>,
+) -> Result<(), ServerError> {
+    let Json(param) = request;
+
+    // Check that:
+    // - the signature is valid.
+    // - the signature is not expired.
+    // - the signature was intended for this service.
+    let signer = check_signature(&mut state, &param).await?;
+
+    // Check that the signer is an admin account.
+    if !state.admin_accounts.contains(&signer) {
+        return Err(ServerError::SignerNotAdmin);
+    }
+
+    // Update the database.
+    let db = state.db_pool.get().await?;
+    db.release_review(param.signing_data.message.account_address, signer)
+        .await?;
+
+    Ok(())
+}
+
 async fn can_claim(
     State(state): State<Server>,
     request: Json<CanClaimParam>,
@@ -723,6 +1304,64 @@ async fn can_claim(
     Ok(Json(CanClaimReturn { data: user_data }))
 }
 
+/// Get the claim attestation signed by the server after the account was marked
+/// as claimed, so that users have a portable proof of their participation.
+async fn get_claim_attestation(
+    State(state): State<Server>,
+    request: Json<GetClaimAttestationParam>,
+) -> Result<Json<ClaimAttestationReturn>, ServerError> {
+    let Json(param) = request;
+
+    let db = state.db_pool.get().await?;
+    let signed_attestation = db.get_claim_attestation(param.account_address).await?;
+
+    Ok(Json(ClaimAttestationReturn {
+        data: signed_attestation,
+    }))
+}
+
+/// Create a backup of the campaign tables and optionally verify that it can be
+/// restored. Backups can take a while for large tables, so the request should
+/// be sent with a generous timeout.
+async fn trigger_backup(
+    State(mut state): State<Server>,
+    request: Json<TriggerBackupParam>,
+) -> Result<Json<BackupReturn>, ServerError> {
+    let Json(param) = request;
+
+    // Check that:
+    // - the signature is valid.
+    // - the signature is not expired.
+    // - the signature was intended for this service.
+    let signer = check_signature(&mut state, &param).await?;
+
+    // Check that the signer is an admin account.
+    if !state.admin_accounts.contains(&signer) {
+        return Err(ServerError::SignerNotAdmin);
+    }
+
+    let backups = state.backups.as_ref().ok_or(ServerError::BackupsDisabled)?;
+    let manifest = backups
+        .create(&state.db_pool, param.signing_data.message.verify)
+        .await?;
+
+    Ok(Json(BackupReturn { data: manifest }))
+}
+
+/// Abort the request with [`ServerError::DeadlineExceeded`] if it does not
+/// complete within the `request_timeout`. Aborting the request cancels its
+/// pending node queries and database operations.
+async fn enforce_deadline<B>(
+    State(request_timeout): State<std::time::Duration>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    match tokio::time::timeout(request_timeout, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => ServerError::DeadlineExceeded(request_timeout).into_response(),
+    }
+}
+
 /// Handle the `health` endpoint, returning the version of the backend.
 async fn health() -> Json<Health> {
     Json(Health {
@@ -730,11 +1369,19 @@ async fn health() -> Json<Health> {
     })
 }
 
-/// Handle the `getZKProofStatements` endpoint, returning the ZK statements that
-/// should be used at the front end to construct the proof.
+/// Handle the `info` endpoint, returning the build metadata of the backend.
+/// The backend does not track any contracts.
+async fn info() -> Json<Info> {
+    Json(Info::new(Vec::new()))
+}
+
+/// Handle the `getZKProofStatements` endpoint, returning the current ZK
+/// statements that should be used at the front end to construct the proof.
 async fn get_zk_proof_statements(State(state): State<Server>) -> Json<ZKProofStatementsReturn> {
+    // Accessing the index at position `0` is safe because the server only
+    // starts if at least one ZK statement is accepted.
     Json(ZKProofStatementsReturn {
-        data: state.zk_statements,
+        data: state.zk_statements[0].statement.clone(),
     })
 }
 
@@ -1,6 +1,10 @@
 /// The maximum number of rows allowed in a request to the database.
 pub const MAX_REQUEST_LIMIT: u32 = 40;
 
+/// The maximum number of accounts imported with one request to the
+/// `importAccounts` endpoint.
+pub const MAX_IMPORT_ACCOUNTS: usize = 1000;
+
 /// The testnet genesis block hash.
 pub const TESTNET_GENESIS_BLOCK_HASH: [u8; 32] = [
     66, 33, 51, 45, 52, 225, 105, 65, 104, 194, 160, 192, 179, 253, 15, 39, 56, 9, 97, 44, 177, 61,
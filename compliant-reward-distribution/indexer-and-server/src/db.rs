@@ -2,12 +2,14 @@ use crate::error::{ConversionError, DatabaseError};
 use chrono::{DateTime, Utc};
 use concordium_rust_sdk::{
     base::{contracts_common::AccountAddressParseError, hashes::TransactionHash},
+    common::types::{Amount, Signature},
     id::types::AccountAddress,
     types::{hashes::BlockHash, AbsoluteBlockHeight},
 };
 use deadpool_postgres::{GenericClient, Object};
 use serde::Serialize;
 use sha2::Digest;
+use std::time::Duration;
 use tokio_postgres::{types::ToSql, NoTls};
 
 /// Alias for returning results with [`DatabaseError`]s as the `Err` variant.
@@ -25,10 +27,12 @@ type UniquenessHash = BlockHash;
 pub struct AccountData {
     /// The account address that was indexed.
     pub account_address: AccountAddress,
-    /// The timestamp of the block the event was included in.
+    /// The timestamp of the block the event was included in. For imported
+    /// accounts, the time of the import.
     pub block_time: DateTime<Utc>,
-    /// The transaction hash that the event was recorded in.
-    pub transaction_hash: TransactionHash,
+    /// The transaction hash that the event was recorded in. Imported accounts
+    /// have no transaction hash.
+    pub transaction_hash: Option<TransactionHash>,
     /// A boolean specifying if the account has already claimed its rewards (got
     /// a reward payout). Every account can only claim rewards once.
     pub claimed: bool,
@@ -37,6 +41,17 @@ pub struct AccountData {
     /// A manual check of the completed tasks is required now before releasing
     /// the reward.
     pub pending_approval: bool,
+    /// The timestamp of the block in which the credentials (or credential
+    /// keys) of the account were last updated.
+    pub credentials_updated_time: Option<DateTime<Utc>>,
+    /// A boolean specifying if the credentials of the account were updated
+    /// after the ZK proof was submitted. A key rotation or credential update
+    /// may invalidate the compliance assumption, so the account has to be
+    /// reviewed again before releasing the reward.
+    pub re_review_required: bool,
+    /// A boolean specifying if the account was pre-approved by an admin with
+    /// the `importAccounts` endpoint instead of completing the tasks.
+    pub imported: bool,
 }
 
 /// The tweet data stored in the database.
@@ -79,6 +94,31 @@ pub struct ZkProofData {
     pub zk_proof_verification_version: u64,
     /// The timestamp when the ZK proof verification was submitted.
     pub zk_proof_verification_submit_time: DateTime<Utc>,
+    /// The identifier of the accepted ZK statement that the ZK proof
+    /// satisfied. ZK proofs submitted before the identifier was recorded
+    /// have no identifier.
+    pub zk_statement_id: Option<u64>,
+}
+
+/// The latest on-chain eligibility check of an account stored in the
+/// `eligibilityChecks` table in the database.
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EligibilityData {
+    /// The account address that was checked.
+    pub account_address: AccountAddress,
+    /// The CCD balance of the account at the time of the check.
+    pub balance: Amount,
+    /// The number of transactions sent by the account at the time of the
+    /// check.
+    pub transaction_count: u64,
+    /// The number of transactions sent by the account multiplied by the age
+    /// of the account in days.
+    pub weighted_transaction_count: u64,
+    /// A boolean specifying if the account fulfilled the eligibility rule.
+    pub eligible: bool,
+    /// The timestamp when the check was done.
+    pub check_time: DateTime<Utc>,
 }
 
 /// The account data stored in the database across all tables.
@@ -91,6 +131,8 @@ pub struct StoredAccountData {
     pub tweet_data: Option<TweetData>,
     /// Data from the `zkProofs` table.
     pub zk_proof_data: Option<ZkProofData>,
+    /// Data from the `eligibilityChecks` table.
+    pub eligibility_data: Option<EligibilityData>,
 }
 
 impl TryFrom<tokio_postgres::Row> for AccountData {
@@ -98,7 +140,7 @@ impl TryFrom<tokio_postgres::Row> for AccountData {
 
     fn try_from(value: tokio_postgres::Row) -> DatabaseResult<Self> {
         let raw_account_address: &[u8] = value.try_get("account_address")?;
-        let raw_transaction_hash: &[u8] = value.try_get("transaction_hash")?;
+        let raw_transaction_hash: Option<&[u8]> = value.try_get("transaction_hash")?;
 
         let data = Self {
             account_address: raw_account_address.try_into().map_err(
@@ -112,12 +154,19 @@ impl TryFrom<tokio_postgres::Row> for AccountData {
             block_time: value.try_get("block_time")?,
             claimed: value.try_get("claimed")?,
             pending_approval: value.try_get("pending_approval")?,
-            transaction_hash: raw_transaction_hash.try_into().map_err(|e| {
-                DatabaseError::TypeConversion(
-                    "transaction_hash".to_string(),
-                    ConversionError::IncorrectLength(e),
-                )
-            })?,
+            credentials_updated_time: value.try_get("credentials_updated_time")?,
+            re_review_required: value.try_get("re_review_required")?,
+            imported: value.try_get("imported")?,
+            transaction_hash: raw_transaction_hash
+                .map(|raw| {
+                    raw.try_into().map_err(|e| {
+                        DatabaseError::TypeConversion(
+                            "transaction_hash".to_string(),
+                            ConversionError::IncorrectLength(e),
+                        )
+                    })
+                })
+                .transpose()?,
         };
 
         Ok(data)
@@ -169,6 +218,7 @@ impl TryFrom<tokio_postgres::Row> for ZkProofData {
         let raw_zk_proof_verification_version: i64 =
             value.try_get("zk_proof_verification_version")?;
         let raw_account_address: &[u8] = value.try_get("account_address")?;
+        let raw_zk_statement_id: Option<i64> = value.try_get("zk_statement_id")?;
 
         let data = Self {
             zk_proof_valid: value.try_get("zk_proof_valid")?,
@@ -190,6 +240,112 @@ impl TryFrom<tokio_postgres::Row> for ZkProofData {
             )?,
             zk_proof_verification_submit_time: value
                 .try_get("zk_proof_verification_submit_time")?,
+            zk_statement_id: raw_zk_statement_id.map(|id| id as u64),
+        };
+
+        Ok(data)
+    }
+}
+
+impl TryFrom<tokio_postgres::Row> for EligibilityData {
+    type Error = DatabaseError;
+
+    fn try_from(value: tokio_postgres::Row) -> DatabaseResult<Self> {
+        let raw_account_address: &[u8] = value.try_get("account_address")?;
+        let raw_balance: i64 = value.try_get("balance")?;
+        let raw_transaction_count: i64 = value.try_get("transaction_count")?;
+        let raw_weighted_transaction_count: i64 = value.try_get("weighted_transaction_count")?;
+
+        let data = Self {
+            account_address: raw_account_address.try_into().map_err(
+                |e: AccountAddressParseError| {
+                    DatabaseError::TypeConversion(
+                        "account_address".to_string(),
+                        ConversionError::AccountAddressParse(e),
+                    )
+                },
+            )?,
+            balance: Amount::from_micro_ccd(raw_balance as u64),
+            transaction_count: raw_transaction_count as u64,
+            weighted_transaction_count: raw_weighted_transaction_count as u64,
+            eligible: value.try_get("eligible")?,
+            check_time: value.try_get("check_time")?,
+        };
+
+        Ok(data)
+    }
+}
+
+/// A lock of an account that an admin has claimed for review, stored in the
+/// `reviewLocks` table in the database.
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewLock {
+    /// The account address that is reviewed.
+    pub account_address: AccountAddress,
+    /// The admin account address that claimed the account for review.
+    pub reviewer: AccountAddress,
+    /// The timestamp when the lock expires and the account can be claimed by
+    /// another admin.
+    pub locked_until: DateTime<Utc>,
+}
+
+/// A claim attestation signed by the server stored in the
+/// `claimAttestations` table in the database.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedClaimAttestation {
+    /// The [`ClaimAttestation`](crate::types::ClaimAttestation) serialized as
+    /// JSON. The signature is computed over exactly these bytes, so the
+    /// attestation is kept as a string instead of being re-serialized.
+    pub attestation: String,
+    /// The signature of the server on the SHA256 hash of the `CONTEXT_STRING`
+    /// followed by the bytes of the `attestation`.
+    pub signature: Signature,
+}
+
+impl TryFrom<tokio_postgres::Row> for SignedClaimAttestation {
+    type Error = DatabaseError;
+
+    fn try_from(value: tokio_postgres::Row) -> DatabaseResult<Self> {
+        let raw_signature: &[u8] = value.try_get("signature")?;
+
+        let data = Self {
+            attestation: value.try_get("attestation")?,
+            signature: Signature {
+                sig: raw_signature.to_vec(),
+            },
+        };
+
+        Ok(data)
+    }
+}
+
+impl TryFrom<tokio_postgres::Row> for ReviewLock {
+    type Error = DatabaseError;
+
+    fn try_from(value: tokio_postgres::Row) -> DatabaseResult<Self> {
+        let raw_account_address: &[u8] = value.try_get("account_address")?;
+        let raw_reviewer: &[u8] = value.try_get("reviewer")?;
+
+        let data = Self {
+            account_address: raw_account_address.try_into().map_err(
+                |e: AccountAddressParseError| {
+                    DatabaseError::TypeConversion(
+                        "account_address".to_string(),
+                        ConversionError::AccountAddressParse(e),
+                    )
+                },
+            )?,
+            reviewer: raw_reviewer
+                .try_into()
+                .map_err(|e: AccountAddressParseError| {
+                    DatabaseError::TypeConversion(
+                        "reviewer".to_string(),
+                        ConversionError::AccountAddressParse(e),
+                    )
+                })?,
+            locked_until: value.try_get("locked_until")?,
         };
 
         Ok(data)
@@ -295,6 +451,7 @@ impl Database {
         account_address: AccountAddress,
         pending_approval: bool,
         current_zk_proof_verification_version: u16,
+        zk_statement_id: u64,
     ) -> DatabaseResult<()> {
         // Create an `uniqueness_hash` to identify the identity associated with the
         // account by hashing the concatenated string of `national_id` and
@@ -345,28 +502,31 @@ impl Database {
         let set_zk_proof = self
             .client
             .prepare_cached(
-                "INSERT INTO zkProofs (zk_proof_valid, zk_proof_verification_version, uniqueness_hash, zk_proof_verification_submit_time, account_address) VALUES ($1, $2, $3, $4, $5)
+                "INSERT INTO zkProofs (zk_proof_valid, zk_proof_verification_version, uniqueness_hash, zk_proof_verification_submit_time, account_address, zk_statement_id) VALUES ($1, $2, $3, $4, $5, $6)
                 ON CONFLICT (account_address) DO UPDATE
                 SET zk_proof_valid = EXCLUDED.zk_proof_valid,
                     zk_proof_verification_version = EXCLUDED.zk_proof_verification_version,
                     uniqueness_hash = EXCLUDED.uniqueness_hash,
-                    zk_proof_verification_submit_time = EXCLUDED.zk_proof_verification_submit_time",
+                    zk_proof_verification_submit_time = EXCLUDED.zk_proof_verification_submit_time,
+                    zk_statement_id = EXCLUDED.zk_statement_id",
             ).await?;
-        let params: [&(dyn ToSql + Sync); 5] = [
+        let params: [&(dyn ToSql + Sync); 6] = [
             &true,
             &(current_zk_proof_verification_version as i64),
             &uniqueness_hash.as_slice(),
             &Utc::now(),
             &account_address.0.as_ref(),
+            &(zk_statement_id as i64),
         ];
         self.client.execute(&set_zk_proof, &params).await?;
 
-        // Update the `accounts` table with the new pending approval.
+        // Update the `accounts` table with the new pending approval. A new ZK proof
+        // covers any earlier credential updates, so no re-review is required.
         let set_pending_approval = self
             .client
             .prepare_cached(
                 "UPDATE accounts \
-                SET pending_approval = $1 \
+                SET pending_approval = $1, re_review_required = false \
                 WHERE account_address = $2",
             )
             .await?;
@@ -419,22 +579,209 @@ impl Database {
         Ok(())
     }
 
-    pub async fn set_claimed(&self, account_addresses: Vec<AccountAddress>) -> DatabaseResult<()> {
+    /// Set the `claimed` flag of the accounts on behalf of the admin `signer`.
+    /// Fails with [`DatabaseError::ReviewLocked`] without updating any account
+    /// if one of the accounts is claimed for review by another admin. The
+    /// review locks of the accounts are released.
+    pub async fn set_claimed(
+        &self,
+        account_addresses: Vec<AccountAddress>,
+        signer: AccountAddress,
+    ) -> DatabaseResult<()> {
+        for account_address in &account_addresses {
+            if let Some(review_lock) = self.get_review_lock(*account_address).await? {
+                if review_lock.reviewer != signer && review_lock.locked_until > Utc::now() {
+                    return Err(DatabaseError::ReviewLocked(review_lock));
+                }
+            }
+        }
+
         for account_address in account_addresses {
             let set_claimed = self
                 .client
                 .prepare_cached(
                     "UPDATE accounts \
-                    SET claimed = $1, pending_approval = $2 \
+                    SET claimed = $1, pending_approval = $2, re_review_required = false \
                     WHERE account_address = $3",
                 )
                 .await?;
             let params: [&(dyn ToSql + Sync); 3] = [&true, &false, &account_address.0.as_ref()];
             self.client.execute(&set_claimed, &params).await?;
+
+            let release_review = self
+                .client
+                .prepare_cached("DELETE FROM reviewLocks WHERE account_address = $1")
+                .await?;
+            let params: [&(dyn ToSql + Sync); 1] = [&account_address.0.as_ref()];
+            self.client.execute(&release_review, &params).await?;
+        }
+        Ok(())
+    }
+
+    /// Insert the account imported by an admin as pending approval, so that it
+    /// is paid out in the same way as accounts that completed the tasks. The
+    /// `import_time` is stored as the `block_time` of the account. Returns
+    /// false without updating the account if it is already in the database.
+    pub async fn import_account(
+        &self,
+        account_address: AccountAddress,
+        import_time: DateTime<Utc>,
+    ) -> DatabaseResult<bool> {
+        let import_account = self
+            .client
+            .prepare_cached(
+                "INSERT INTO accounts (account_address, block_time, transaction_hash, claimed, \
+                pending_approval, imported) \
+                VALUES ($1, $2, NULL, false, true, true) \
+                ON CONFLICT (account_address) DO NOTHING",
+            )
+            .await?;
+        let params: [&(dyn ToSql + Sync); 2] = [&account_address.0.as_ref(), &import_time];
+        let inserted = self.client.execute(&import_account, &params).await?;
+        Ok(inserted == 1)
+    }
+
+    /// Claim the account for review by the admin `reviewer` until
+    /// `locked_until`. Claiming an account again by the same admin extends the
+    /// lock. Fails with [`DatabaseError::ReviewLocked`] if the account is
+    /// claimed for review by another admin and the lock has not expired.
+    pub async fn claim_review(
+        &self,
+        account_address: AccountAddress,
+        reviewer: AccountAddress,
+        locked_until: DateTime<Utc>,
+    ) -> DatabaseResult<ReviewLock> {
+        // The lock is only taken over if it is held by the same admin or has expired.
+        // The check and the update are done in one statement, so that two admins
+        // cannot claim the same account at the same time.
+        let claim_review = self
+            .client
+            .prepare_cached(
+                "INSERT INTO reviewLocks (account_address, reviewer, locked_until) \
+                VALUES ($1, $2, $3) \
+                ON CONFLICT (account_address) DO UPDATE \
+                SET reviewer = EXCLUDED.reviewer, locked_until = EXCLUDED.locked_until \
+                WHERE reviewLocks.reviewer = EXCLUDED.reviewer OR reviewLocks.locked_until <= $4 \
+                RETURNING account_address, reviewer, locked_until",
+            )
+            .await?;
+        loop {
+            let now = Utc::now();
+            let params: [&(dyn ToSql + Sync); 4] = [
+                &account_address.0.as_ref(),
+                &reviewer.0.as_ref(),
+                &locked_until,
+                &now,
+            ];
+            if let Some(row) = self.client.query_opt(&claim_review, &params).await? {
+                return row.try_into();
+            }
+
+            // The account is locked by another admin unless the lock has been released
+            // or has expired in the meantime, in which case claiming is re-tried.
+            if let Some(review_lock) = self.get_review_lock(account_address).await? {
+                if review_lock.locked_until > now {
+                    return Err(DatabaseError::ReviewLocked(review_lock));
+                }
+            }
         }
+    }
+
+    /// Release the review lock of the account held by the admin `reviewer`.
+    /// Releasing an account that is not locked or whose lock has expired
+    /// succeeds. Fails with [`DatabaseError::ReviewLocked`] if the account is
+    /// claimed for review by another admin and the lock has not expired.
+    pub async fn release_review(
+        &self,
+        account_address: AccountAddress,
+        reviewer: AccountAddress,
+    ) -> DatabaseResult<()> {
+        let release_review = self
+            .client
+            .prepare_cached(
+                "DELETE FROM reviewLocks \
+                WHERE account_address = $1 AND (reviewer = $2 OR locked_until <= $3)",
+            )
+            .await?;
+        let now = Utc::now();
+        let params: [&(dyn ToSql + Sync); 3] =
+            [&account_address.0.as_ref(), &reviewer.0.as_ref(), &now];
+        let released = self.client.execute(&release_review, &params).await?;
+
+        if released == 0 {
+            if let Some(review_lock) = self.get_review_lock(account_address).await? {
+                if review_lock.reviewer != reviewer && review_lock.locked_until > now {
+                    return Err(DatabaseError::ReviewLocked(review_lock));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Store the signed claim attestation of the account. An account keeps the
+    /// attestation issued when it was first marked as claimed, so marking it
+    /// as claimed again does not change the attestation.
+    pub async fn insert_claim_attestation(
+        &self,
+        account_address: AccountAddress,
+        signed_attestation: &SignedClaimAttestation,
+    ) -> DatabaseResult<()> {
+        let insert_claim_attestation = self
+            .client
+            .prepare_cached(
+                "INSERT INTO claimAttestations (account_address, attestation, signature) \
+                VALUES ($1, $2, $3) \
+                ON CONFLICT (account_address) DO NOTHING",
+            )
+            .await?;
+        let params: [&(dyn ToSql + Sync); 3] = [
+            &account_address.0.as_ref(),
+            &signed_attestation.attestation,
+            &signed_attestation.signature.sig,
+        ];
+        self.client
+            .execute(&insert_claim_attestation, &params)
+            .await?;
         Ok(())
     }
 
+    pub async fn get_claim_attestation(
+        &self,
+        account_address: AccountAddress,
+    ) -> DatabaseResult<Option<SignedClaimAttestation>> {
+        let get_claim_attestation = self
+            .client
+            .prepare_cached(
+                "SELECT attestation, signature \
+                FROM claimAttestations \
+                WHERE account_address = $1",
+            )
+            .await?;
+        let params: [&(dyn ToSql + Sync); 1] = [&(account_address.0.as_ref())];
+        let opt_row = self
+            .client
+            .query_opt(&get_claim_attestation, &params)
+            .await?;
+        opt_row.map(SignedClaimAttestation::try_from).transpose()
+    }
+
+    pub async fn get_review_lock(
+        &self,
+        account_address: AccountAddress,
+    ) -> DatabaseResult<Option<ReviewLock>> {
+        let get_review_lock = self
+            .client
+            .prepare_cached(
+                "SELECT account_address, reviewer, locked_until \
+                FROM reviewLocks \
+                WHERE account_address = $1",
+            )
+            .await?;
+        let params: [&(dyn ToSql + Sync); 1] = [&(account_address.0.as_ref())];
+        let opt_row = self.client.query_opt(&get_review_lock, &params).await?;
+        opt_row.map(ReviewLock::try_from).transpose()
+    }
+
     pub async fn get_settings(&self) -> DatabaseResult<StoredConfiguration> {
         let get_settings = self
             .client
@@ -453,7 +800,7 @@ impl Database {
         let get_account_data = self
             .client
             .prepare_cached(
-                "SELECT account_address, block_time, transaction_hash, claimed, pending_approval
+                "SELECT account_address, block_time, transaction_hash, claimed, pending_approval, credentials_updated_time, re_review_required, imported
                 FROM accounts
                 WHERE account_address = $1",
             )
@@ -486,7 +833,7 @@ impl Database {
         let get_account_data: tokio_postgres::Statement = self
             .client
             .prepare_cached(
-                "SELECT account_address, uniqueness_hash, zk_proof_valid, zk_proof_verification_version, zk_proof_verification_submit_time
+                "SELECT account_address, uniqueness_hash, zk_proof_valid, zk_proof_verification_version, zk_proof_verification_submit_time, zk_statement_id
                 FROM zkProofs
                 WHERE account_address = $1",
             ).await?;
@@ -495,6 +842,56 @@ impl Database {
         opt_row.map(ZkProofData::try_from).transpose()
     }
 
+    /// Record the on-chain eligibility check of the account. A previous check
+    /// of the account is overwritten.
+    pub async fn upsert_eligibility_check(&self, data: &EligibilityData) -> DatabaseResult<()> {
+        let upsert_eligibility_check = self
+            .client
+            .prepare_cached(
+                "INSERT INTO eligibilityChecks (account_address, balance, transaction_count, \
+                weighted_transaction_count, eligible, check_time) \
+                VALUES ($1, $2, $3, $4, $5, $6) \
+                ON CONFLICT (account_address) DO UPDATE \
+                SET balance = EXCLUDED.balance, transaction_count = EXCLUDED.transaction_count, \
+                weighted_transaction_count = EXCLUDED.weighted_transaction_count, \
+                eligible = EXCLUDED.eligible, check_time = EXCLUDED.check_time",
+            )
+            .await?;
+        let params: [&(dyn ToSql + Sync); 6] = [
+            &data.account_address.0.as_ref(),
+            &(data.balance.micro_ccd() as i64),
+            &(data.transaction_count as i64),
+            &(data.weighted_transaction_count as i64),
+            &data.eligible,
+            &data.check_time,
+        ];
+        self.client
+            .execute(&upsert_eligibility_check, &params)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_eligibility_data(
+        &self,
+        account_address: AccountAddress,
+    ) -> DatabaseResult<Option<EligibilityData>> {
+        let get_eligibility_data = self
+            .client
+            .prepare_cached(
+                "SELECT account_address, balance, transaction_count, weighted_transaction_count, \
+                eligible, check_time \
+                FROM eligibilityChecks \
+                WHERE account_address = $1",
+            )
+            .await?;
+        let params: [&(dyn ToSql + Sync); 1] = [&(account_address.0.as_ref())];
+        let opt_row = self
+            .client
+            .query_opt(&get_eligibility_data, &params)
+            .await?;
+        opt_row.map(EligibilityData::try_from).transpose()
+    }
+
     pub async fn get_pending_approvals(
         &self,
         limit: u32,
@@ -503,9 +900,9 @@ impl Database {
         let get_pending_approvals = self
             .client
             .prepare_cached(
-                "SELECT account_address, block_time, transaction_hash, claimed, pending_approval \
+                "SELECT account_address, block_time, transaction_hash, claimed, pending_approval, credentials_updated_time, re_review_required, imported \
                 FROM accounts \
-                WHERE pending_approval = true \
+                WHERE pending_approval = true OR (re_review_required = true AND claimed = false) \
                 LIMIT $1 \
                 OFFSET $2",
             )
@@ -523,6 +920,36 @@ impl Database {
     }
 }
 
+/// The configuration of a [`DatabasePool`].
+#[derive(Debug, Clone, Copy)]
+pub struct DatabasePoolConfig {
+    /// The maximum number of connections of the pool.
+    pub max_size: usize,
+    /// The maximum time to wait for a new connection to be established.
+    pub create_timeout: Duration,
+    /// The maximum time to wait for a connection to be verified before it is
+    /// reused.
+    pub recycle_timeout: Duration,
+    /// The maximum time to wait for a connection to become available if all
+    /// connections of the pool are in use.
+    pub wait_timeout: Duration,
+    /// Whether the database tables are created using
+    /// `../resources/schema.sql` when the pool is created.
+    pub try_create_tables: bool,
+}
+
+impl Default for DatabasePoolConfig {
+    fn default() -> Self {
+        Self {
+            max_size: 1,
+            create_timeout: Duration::from_secs(5),
+            recycle_timeout: Duration::from_secs(5),
+            wait_timeout: Duration::from_secs(5),
+            try_create_tables: true,
+        }
+    }
+}
+
 /// Representation of a database pool
 #[derive(Debug, Clone)]
 pub struct DatabasePool {
@@ -531,13 +958,13 @@ pub struct DatabasePool {
 }
 
 impl DatabasePool {
-    /// Create a new [`DatabasePool`] from [`tokio_postgres::Config`] of size
-    /// `pool_size`. If `try_create_tables` is true, database tables are
-    /// created using `../resources/schema.sql`.
+    /// Create a new [`DatabasePool`] from [`tokio_postgres::Config`] with the
+    /// size and timeouts of the `pool_config`. If `try_create_tables` of the
+    /// `pool_config` is true, database tables are created using
+    /// `../resources/schema.sql`.
     pub async fn create(
         db_config: tokio_postgres::Config,
-        pool_size: usize,
-        try_create_tables: bool,
+        pool_config: DatabasePoolConfig,
     ) -> DatabaseResult<Self> {
         let manager_config = deadpool_postgres::ManagerConfig {
             recycling_method: deadpool_postgres::RecyclingMethod::Verified,
@@ -545,15 +972,15 @@ impl DatabasePool {
 
         let manager = deadpool_postgres::Manager::from_config(db_config, NoTls, manager_config);
         let pool = deadpool_postgres::Pool::builder(manager)
-            .create_timeout(Some(std::time::Duration::from_secs(5)))
-            .recycle_timeout(Some(std::time::Duration::from_secs(5)))
-            .wait_timeout(Some(std::time::Duration::from_secs(5)))
-            .max_size(pool_size)
+            .create_timeout(Some(pool_config.create_timeout))
+            .recycle_timeout(Some(pool_config.recycle_timeout))
+            .wait_timeout(Some(pool_config.wait_timeout))
+            .max_size(pool_config.max_size)
             .runtime(deadpool_postgres::Runtime::Tokio1)
             .build()
             .map_err(|e| DatabaseError::Configuration(e.into()))?;
 
-        if try_create_tables {
+        if pool_config.try_create_tables {
             let client = pool.get().await?;
             client
                 .batch_execute(include_str!("../resources/schema.sql"))
@@ -1,19 +1,20 @@
-use crate::types::ClaimExpiryDurationDays;
+use crate::{db::ReviewLock, types::ClaimExpiryDurationDays};
 use axum::{
     response::{IntoResponse, Response},
     Json,
 };
 use concordium_rust_sdk::{
     base::{contracts_common::AccountAddressParseError, hashes::IncorrectLength},
-    common::types::AccountAddress,
+    common::types::{AccountAddress, Amount},
     types::AbsoluteBlockHeight,
     v2::QueryError,
     web3id::{did::Network, CredentialLookupError, PresentationVerificationError},
 };
 use deadpool_postgres::PoolError;
 use http::StatusCode;
-use std::string::FromUtf8Error;
+use std::{string::FromUtf8Error, time::Duration};
 use thiserror::Error;
+use tokio_postgres::error::SqlState;
 
 #[derive(Debug, Error)]
 pub enum ConversionError {
@@ -50,6 +51,38 @@ pub enum DatabaseError {
         expected: AccountAddress,
         actual: AccountAddress,
     },
+    /// Failed because the account is claimed for review by another admin.
+    #[error(
+        "The account {} is claimed for review by the admin {} until {}.",
+        .0.account_address,
+        .0.reviewer,
+        .0.locked_until
+    )]
+    ReviewLocked(ReviewLock),
+}
+
+/// Represents possible errors returned from [`Backups`](crate::backup::Backups)
+/// functions.
+#[derive(Error, Debug)]
+pub enum BackupError {
+    /// Failed to get a database connection.
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+    /// An error happened while interacting with the postgres DB.
+    #[error("{0}")]
+    Postgres(#[from] tokio_postgres::Error),
+    /// Failed to read or write a backup file.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// Failed to serialize or deserialize a backup file.
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    /// No backup exists in the backup directory.
+    #[error("No backup exists")]
+    NoBackup,
+    /// The backup could not be restored as expected.
+    #[error("Backup verification failed: {0}")]
+    Verification(String),
 }
 
 /// Errors that this server can produce.
@@ -117,15 +150,78 @@ pub enum ServerError {
     OnlyRegularAccounts,
     #[error("No credential commitment on chain.")]
     NoCredentialCommitment,
+    #[error("Backups are not enabled on this server.")]
+    BackupsDisabled,
+    #[error("Backup error: {0}")]
+    Backup(#[from] BackupError),
+    #[error("The request did not complete within {} seconds.", .0.as_secs())]
+    DeadlineExceeded(Duration),
+    #[error(
+        "The account does not fulfill the eligibility rule. Balance: {balance}. Weighted \
+         transaction count: {weighted_transaction_count}."
+    )]
+    NotEligible {
+        balance: Amount,
+        weighted_transaction_count: u64,
+    },
+    #[error("Too many requests are waiting for verification. Please try again later.")]
+    WorkerPoolBusy,
+    #[error("The verification failed unexpectedly: {0}")]
+    WorkerFailed(String),
+    #[error("Unable to serialize the claim attestation: {0}")]
+    AttestationSerialization(#[from] serde_json::Error),
+    #[error("Line {line} of the CSV does not start with an account address: {value}")]
+    InvalidImportLine { line: usize, value: String },
+    #[error("At most {0} accounts can be imported with one request")]
+    MaxImportLimit(usize),
+}
+
+impl ServerError {
+    /// Whether the error was caused by the database cancelling a query because
+    /// it exceeded the `statement_timeout` of the connection.
+    fn is_statement_timeout(&self) -> bool {
+        matches!(
+            self,
+            ServerError::DatabaseError(DatabaseError::Postgres(error))
+                if error.code() == Some(&SqlState::QUERY_CANCELED)
+        )
+    }
 }
 
 impl IntoResponse for ServerError {
     fn into_response(self) -> Response {
         let r = match self {
+            // Timeout errors.
+            ServerError::DeadlineExceeded(_) => {
+                let error_message = format!("Deadline exceeded: {self}");
+                tracing::warn!(error_message);
+                (StatusCode::GATEWAY_TIMEOUT, error_message.into())
+            }
+            ref error if error.is_statement_timeout() => {
+                tracing::warn!("Deadline exceeded: {self}");
+                let error_message =
+                    "Deadline exceeded: The database query did not complete in time.".to_string();
+                (StatusCode::GATEWAY_TIMEOUT, error_message.into())
+            }
+            // Overload errors.
+            ServerError::WorkerPoolBusy => {
+                let error_message = format!("Service unavailable: {self}");
+                tracing::warn!(error_message);
+                (StatusCode::SERVICE_UNAVAILABLE, error_message.into())
+            }
+            // Conflict errors.
+            ServerError::DatabaseError(DatabaseError::ReviewLocked(_)) => {
+                let error_message = format!("Conflict: {self}");
+                tracing::info!(error_message);
+                (StatusCode::CONFLICT, error_message.into())
+            }
             // Internal errors.
             ServerError::DatabaseError(_)
             | ServerError::QueryError(..)
-            | ServerError::UnderFlow => {
+            | ServerError::UnderFlow
+            | ServerError::Backup(_)
+            | ServerError::WorkerFailed(_)
+            | ServerError::AttestationSerialization(_) => {
                 tracing::error!("Internal error: {self}");
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -157,7 +253,11 @@ impl IntoResponse for ServerError {
             | ServerError::ProofExpired(_)
             | ServerError::TypeConversion(..)
             | ServerError::OnlyRegularAccounts
-            | ServerError::NoCredentialCommitment => {
+            | ServerError::NoCredentialCommitment
+            | ServerError::BackupsDisabled
+            | ServerError::InvalidImportLine { .. }
+            | ServerError::MaxImportLimit(_)
+            | ServerError::NotEligible { .. } => {
                 let error_message = format!("Bad request: {self}");
                 tracing::info!(error_message);
                 (StatusCode::BAD_REQUEST, error_message.into())
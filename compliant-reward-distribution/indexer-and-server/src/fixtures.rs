@@ -0,0 +1,233 @@
+//! Fixtures for the integration tests of the `server`, only available with the
+//! `fixtures` feature. Testing the verification of ZK proofs and signatures
+//! otherwise requires a wallet and an identity on testnet. The fixtures
+//! generate test accounts with a single key pair and an account credential
+//! committing to sample identity attributes, ZK proofs of the ZK statements
+//! generated with the id library, and messages signed the way the Concordium
+//! browser wallet signs them (e.g. the messages of the admin endpoints).
+use crate::{
+    constants::ZK_STATEMENTS,
+    types::{zk_proof_challenge, PostZKProofParam, SigningData},
+};
+use concordium_rust_sdk::{
+    common::types::{KeyPair, Signature},
+    id::{
+        constants::{ArCurve, AttributeKind},
+        curve_arithmetic::Curve,
+        id_proof_types::Statement,
+        pedersen_commitment::{Commitment, Randomness, Value},
+        types::{
+            AccountAddress, AccountCredentialWithoutProofs, Attribute, AttributeTag,
+            CredentialDeploymentCommitments, CredentialDeploymentValues, CredentialPublicKeys,
+            GlobalContext, IpIdentity, Policy, SignatureThreshold, Threshold, VerifyKey, YearMonth,
+        },
+    },
+    types::{hashes::BlockHash, AbsoluteBlockHeight, CredentialRegistrationID},
+    web3id::{
+        did::Network, CommitmentInputs, CredentialStatement, CredentialsInputs, Request,
+        Web3IdAttribute,
+    },
+};
+use rand::{CryptoRng, Rng};
+use std::collections::BTreeMap;
+
+/// The revealed `nationalIdNo` attribute of the sample identity.
+pub const SAMPLE_NATIONAL_ID: &str = "AB123456";
+
+/// The revealed `nationality` attribute of the sample identity.
+pub const SAMPLE_NATIONALITY: &str = "DK";
+
+/// The global cryptographic parameters used by the fixtures. They are generated
+/// deterministically, so the `cryptographic_params` of a test server have to be
+/// set to the same parameters for the generated ZK proofs to verify.
+pub fn global_context() -> GlobalContext<ArCurve> {
+    GlobalContext::generate("compliant-reward-distribution-fixtures".to_string())
+}
+
+/// The ZK statement served to the front end (see `ZK_STATEMENTS`).
+pub fn zk_statement() -> anyhow::Result<Statement<ArCurve, Web3IdAttribute>> {
+    Ok(serde_json::from_str(ZK_STATEMENTS)?)
+}
+
+/// Identity attributes of an adult from Denmark that satisfy the ZK statement
+/// served to the front end.
+pub fn sample_attributes() -> anyhow::Result<BTreeMap<AttributeTag, Web3IdAttribute>> {
+    let attribute = |value: &str| Web3IdAttribute::String(AttributeKind(value.to_string()));
+    Ok(BTreeMap::from([
+        ("nationalIdNo".parse()?, attribute(SAMPLE_NATIONAL_ID)),
+        ("nationality".parse()?, attribute(SAMPLE_NATIONALITY)),
+        ("dob".parse()?, attribute("19900101")),
+        ("countryOfResidence".parse()?, attribute("DK")),
+    ]))
+}
+
+/// A regular account with a single credential and a single key pair, as
+/// supported by the `server`.
+pub struct TestAccount {
+    /// The address of the account.
+    pub address: AccountAddress,
+    /// The key pair of the account at index 0 of the credential.
+    pub key_pair: KeyPair,
+    /// The registration id of the credential.
+    pub cred_id: ArCurve,
+    /// The identity provider that issued the identity of the credential.
+    pub issuer: IpIdentity,
+    /// The identity attributes that the credential commits to.
+    pub attributes: BTreeMap<AttributeTag, Web3IdAttribute>,
+    /// The randomness of the commitments to the `attributes`.
+    pub randomness: BTreeMap<AttributeTag, Randomness<ArCurve>>,
+}
+
+impl TestAccount {
+    /// Generate an account with a random address, key pair, and credential
+    /// committing to the `attributes`.
+    pub fn generate<R: Rng + CryptoRng>(
+        rng: &mut R,
+        attributes: BTreeMap<AttributeTag, Web3IdAttribute>,
+    ) -> Self {
+        let randomness = attributes
+            .keys()
+            .map(|tag| (*tag, Randomness::generate(rng)))
+            .collect();
+        Self {
+            address: AccountAddress(rng.gen()),
+            key_pair: KeyPair::generate(rng),
+            cred_id: ArCurve::generate(rng),
+            issuer: IpIdentity(0),
+            attributes,
+            randomness,
+        }
+    }
+
+    /// Generate an account whose credential commits to the
+    /// [`sample_attributes`].
+    pub fn with_sample_attributes<R: Rng + CryptoRng>(rng: &mut R) -> anyhow::Result<Self> {
+        Ok(Self::generate(rng, sample_attributes()?))
+    }
+
+    /// The on-chain commitments to the `attributes` of the credential.
+    pub fn commitments(
+        &self,
+        params: &GlobalContext<ArCurve>,
+    ) -> BTreeMap<AttributeTag, Commitment<ArCurve>> {
+        self.attributes
+            .iter()
+            .map(|(tag, value)| {
+                let value = Value::new(value.to_field_element());
+                let commitment = params
+                    .on_chain_commitment_key
+                    .hide(&value, &self.randomness[tag]);
+                (*tag, commitment)
+            })
+            .collect()
+    }
+
+    /// The public data of the credential needed to verify the ZK proofs of the
+    /// account, as returned by `get_public_data` for the account.
+    pub fn public_data(&self, params: &GlobalContext<ArCurve>) -> CredentialsInputs<ArCurve> {
+        CredentialsInputs::Account {
+            commitments: self.commitments(params),
+        }
+    }
+
+    /// The credential of the account as returned by the node in the account
+    /// info. The commitments that the `server` does not use (e.g. the
+    /// commitment to the PRF key) are random.
+    pub fn credential<R: Rng>(
+        &self,
+        rng: &mut R,
+        params: &GlobalContext<ArCurve>,
+    ) -> AccountCredentialWithoutProofs<ArCurve, AttributeKind> {
+        let created_at = YearMonth {
+            year: 2024,
+            month: 1,
+        };
+        let valid_to = YearMonth {
+            year: 2099,
+            month: 12,
+        };
+        AccountCredentialWithoutProofs::Normal {
+            cdv: CredentialDeploymentValues {
+                cred_key_info: CredentialPublicKeys {
+                    keys: BTreeMap::from([(
+                        0.into(),
+                        VerifyKey::Ed25519VerifyKey(self.key_pair.public()),
+                    )]),
+                    threshold: SignatureThreshold::ONE,
+                },
+                cred_id: self.cred_id,
+                ip_identity: self.issuer,
+                threshold: Threshold(1),
+                ar_data: BTreeMap::new(),
+                policy: Policy {
+                    valid_to,
+                    created_at,
+                    policy_vec: BTreeMap::new(),
+                    _phantom: Default::default(),
+                },
+            },
+            commitments: CredentialDeploymentCommitments {
+                cmm_prf: Commitment(ArCurve::generate(rng)),
+                cmm_cred_counter: Commitment(ArCurve::generate(rng)),
+                cmm_max_accounts: Commitment(ArCurve::generate(rng)),
+                cmm_attributes: self.commitments(params),
+                cmm_id_cred_sec_sharing_coeff: vec![Commitment(ArCurve::generate(rng))],
+            },
+        }
+    }
+
+    /// Sign the `message` with the key pair of the account like the Concordium
+    /// browser wallet does, for the block with the `block_hash` at the
+    /// `block_height`. The result is the signing data of the parameter of an
+    /// endpoint, e.g. `SetClaimedParam` if the account is an admin account.
+    pub fn sign<T: serde::Serialize>(
+        &self,
+        message: T,
+        block_height: AbsoluteBlockHeight,
+        block_hash: &BlockHash,
+    ) -> anyhow::Result<SigningData<T>> {
+        let mut signing_data = SigningData {
+            signer: self.address,
+            message,
+            signature: Signature { sig: Vec::new() },
+            block_height,
+        };
+        let message_hash = signing_data.message_hash(block_hash)?;
+        signing_data.signature = Signature {
+            sig: self.key_pair.sign(&message_hash).to_bytes().to_vec(),
+        };
+        Ok(signing_data)
+    }
+
+    /// Generate a ZK proof of the `statement` about the attributes of the
+    /// credential on the `network`, for the block with the `block_hash` at the
+    /// `block_height`. The result is the parameter of the `postZKProof`
+    /// endpoint.
+    pub fn prove(
+        &self,
+        params: &GlobalContext<ArCurve>,
+        network: Network,
+        statement: &Statement<ArCurve, Web3IdAttribute>,
+        block_height: AbsoluteBlockHeight,
+        block_hash: &BlockHash,
+    ) -> anyhow::Result<PostZKProofParam> {
+        let request = Request::<ArCurve, Web3IdAttribute> {
+            challenge: zk_proof_challenge(block_hash)?,
+            credential_statements: vec![CredentialStatement::Account {
+                network,
+                cred_id: CredentialRegistrationID::new(self.cred_id),
+                statement: statement.statements.clone(),
+            }],
+        };
+        let inputs = CommitmentInputs::<ArCurve, Web3IdAttribute, KeyPair>::Account {
+            issuer: self.issuer,
+            values: &self.attributes,
+            randomness: &self.randomness,
+        };
+        let presentation = request.prove(params, std::iter::once(inputs))?;
+        Ok(PostZKProofParam {
+            block_height,
+            presentation,
+        })
+    }
+}
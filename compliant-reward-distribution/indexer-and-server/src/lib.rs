@@ -1,5 +1,10 @@
+pub mod alerts;
+pub mod backup;
 pub mod constants;
 pub mod db;
 pub mod error;
+#[cfg(feature = "fixtures")]
+pub mod fixtures;
 pub mod types;
+pub mod worker_pool;
 pub use crate::db::DatabasePool;
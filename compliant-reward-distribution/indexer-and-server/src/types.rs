@@ -1,20 +1,26 @@
 use crate::{
-    db::{AccountData, StoredAccountData},
+    alerts::VelocityCounters,
+    backup::{BackupManifest, Backups},
+    constants::{CONTEXT_STRING, MAX_IMPORT_ACCOUNTS},
+    db::{AccountData, ReviewLock, SignedClaimAttestation, StoredAccountData},
+    error::ServerError,
+    worker_pool::WorkerPool,
     DatabasePool,
 };
-use chrono::Days;
+use chrono::{DateTime, Days, Duration, Utc};
 use concordium_rust_sdk::{
-    common::types::Signature,
+    common::types::{Amount, KeyPair, Signature},
     id::{
         constants::ArCurve,
         id_proof_types::Statement,
-        types::{AccountAddress, GlobalContext},
+        types::{AccountAddress, GlobalContext, VerifyKey},
     },
-    types::AbsoluteBlockHeight,
+    types::{hashes::BlockHash, AbsoluteBlockHeight, ContractAddress},
     v2::Client,
-    web3id::{did::Network, Presentation, Web3IdAttribute},
+    web3id::{did::Network, Challenge, Presentation, Web3IdAttribute},
 };
-use std::{num::ParseIntError, str::FromStr};
+use sha2::Digest;
+use std::{collections::BTreeSet, num::ParseIntError, str::FromStr, sync::Arc};
 
 /// Server struct to store values that are not persisted in the database.
 /// When re-starting the server this struct will be re-initialized based on the
@@ -28,15 +34,162 @@ pub struct Server {
     /// The network used (testnet or mainnet).
     pub network: Network,
     /// The global cryptographic parameters that are stored publicly on chain.
-    pub cryptographic_params: GlobalContext<ArCurve>,
+    pub cryptographic_params: Arc<GlobalContext<ArCurve>>,
     /// The admin accounts that have elevated permission to read/write from/to
     /// the database.
     pub admin_accounts: Vec<AccountAddress>,
-    /// The ZK statements that are used to verify submitted ZK proofs.
-    pub zk_statements: Statement<ArCurve, Web3IdAttribute>,
+    /// The ZK statements that are accepted when verifying submitted ZK proofs.
+    /// The first entry is the current ZK statement served to the front end.
+    pub zk_statements: Vec<AcceptedZKStatement>,
     /// The duration in days after a new account is created that the account is
     /// eligible to claim the reward.
     pub claim_expiry_duration_days: ClaimExpiryDurationDays,
+    /// The backups of the campaign tables. If not set, backups are disabled.
+    pub backups: Option<Backups>,
+    /// The duration an account stays locked after an admin claimed it for
+    /// review.
+    pub review_lock_ttl: Duration,
+    /// The on-chain eligibility rule that accounts have to fulfill before
+    /// submitting tasks.
+    pub eligibility_rule: EligibilityRule,
+    /// The worker pool verifying the cryptographic proofs of submitted ZK
+    /// proofs off the async runtime.
+    pub proof_verifier: WorkerPool,
+    /// The issuer of the claim attestations. If not set, no attestations are
+    /// issued.
+    pub attestation_issuer: Option<AttestationIssuer>,
+    /// The counters of the accepted submissions and the claimed accounts
+    /// sampled by the velocity alerts.
+    pub velocity: VelocityCounters,
+}
+
+/// Issues the attestations that an account claimed the reward of the
+/// campaign, signed with the key of the server.
+#[derive(Clone)]
+pub struct AttestationIssuer {
+    /// The key pair of the server signing the attestations.
+    pub key_pair: Arc<KeyPair>,
+    /// The identifier of the campaign included in the attestations.
+    pub campaign: String,
+    /// The reward amount paid out to every account that claimed the reward.
+    pub amount: Amount,
+}
+
+impl std::fmt::Debug for AttestationIssuer {
+    // The key pair is not printed to not leak the secret key into logs.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AttestationIssuer")
+            .field("campaign", &self.campaign)
+            .field("amount", &self.amount)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AttestationIssuer {
+    /// Create the attestation that the `account_address` claimed the reward at
+    /// `claimed_at` and sign the SHA256 hash of the `CONTEXT_STRING` followed
+    /// by the attestation serialized as JSON.
+    pub fn attest(
+        &self,
+        account_address: AccountAddress,
+        network: Network,
+        claimed_at: DateTime<Utc>,
+    ) -> Result<SignedClaimAttestation, serde_json::Error> {
+        let attestation = serde_json::to_string(&ClaimAttestation {
+            account_address,
+            campaign: self.campaign.clone(),
+            amount: self.amount,
+            network,
+            claimed_at,
+            issuer: VerifyKey::Ed25519VerifyKey(self.key_pair.public()),
+        })?;
+
+        let message_hash =
+            sha2::Sha256::digest([&CONTEXT_STRING as &[u8], attestation.as_bytes()].concat());
+        let signature = self.key_pair.sign(&message_hash);
+
+        Ok(SignedClaimAttestation {
+            attestation,
+            signature: Signature {
+                sig: signature.to_bytes().to_vec(),
+            },
+        })
+    }
+}
+
+/// The attestation that an account claimed the reward of the campaign.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaimAttestation {
+    /// The account that claimed the reward.
+    pub account_address: AccountAddress,
+    /// The identifier of the campaign.
+    pub campaign: String,
+    /// The reward amount paid out to the account.
+    pub amount: Amount,
+    /// The network (testnet or mainnet) of the account.
+    pub network: Network,
+    /// The time an admin marked the account as claimed.
+    pub claimed_at: DateTime<Utc>,
+    /// The public key of the server that signed the attestation.
+    pub issuer: VerifyKey,
+}
+
+/// An optional rule checked against the on-chain state of an account before it
+/// can submit tasks. This raises the cost of sybil farming beyond requiring a
+/// unique identity. If both thresholds are set, fulfilling one of them is
+/// sufficient. If no threshold is set, every account is eligible.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EligibilityRule {
+    /// The minimum CCD balance of the account.
+    pub min_balance: Option<Amount>,
+    /// The minimum number of transactions sent by the account multiplied by
+    /// the age of the account in days.
+    pub min_weighted_transaction_count: Option<u64>,
+}
+
+impl EligibilityRule {
+    /// Whether any threshold is set, i.e. whether accounts have to be checked.
+    pub fn is_enabled(&self) -> bool {
+        self.min_balance.is_some() || self.min_weighted_transaction_count.is_some()
+    }
+
+    /// Whether an account with the `balance` and the
+    /// `weighted_transaction_count` fulfills the rule.
+    pub fn is_fulfilled(&self, balance: Amount, weighted_transaction_count: u64) -> bool {
+        if !self.is_enabled() {
+            return true;
+        }
+        self.min_balance
+            .map_or(false, |min_balance| balance >= min_balance)
+            || self
+                .min_weighted_transaction_count
+                .map_or(false, |min_count| weighted_transaction_count >= min_count)
+    }
+}
+
+/// A ZK statement accepted by the server when verifying submitted ZK proofs.
+/// Several ZK statements can be accepted at the same time (e.g. the old and the
+/// new version during a rotation of the ZK statement).
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AcceptedZKStatement {
+    /// The identifier of the ZK statement. It is recorded in the database
+    /// together with the ZK proofs that satisfied the ZK statement.
+    pub id: u64,
+    /// The ZK statement.
+    pub statement: Statement<ArCurve, Web3IdAttribute>,
+    /// ZK proofs for this ZK statement are accepted until this time. If not
+    /// set, ZK proofs for this ZK statement are accepted without time limit.
+    pub accepted_until: Option<DateTime<Utc>>,
+}
+
+impl AcceptedZKStatement {
+    /// Check if ZK proofs for this ZK statement are accepted at time `now`.
+    pub fn is_accepted(&self, now: DateTime<Utc>) -> bool {
+        self.accepted_until
+            .map_or(true, |accepted_until| now < accepted_until)
+    }
 }
 
 /// Generalised parameter struct used by all endpoints that require a signature
@@ -56,6 +209,37 @@ pub struct SigningData<T> {
     pub block_height: AbsoluteBlockHeight,
 }
 
+impl<T: serde::Serialize> SigningData<T> {
+    /// The SHA256 hash of the message that the `signer` signed. The message
+    /// signed in the Concordium browser wallet is prepended with the `signer`
+    /// address and 8 zero bytes. In addition, the `block_hash` of the
+    /// `block_height` and the `CONTEXT_STRING` are prepended to the `message`
+    /// serialized with bincode.
+    pub fn message_hash(&self, block_hash: &BlockHash) -> Result<[u8; 32], bincode::Error> {
+        let message_bytes = bincode::serialize(&self.message)?;
+        let message_hash = sha2::Sha256::digest(
+            [
+                self.signer.as_ref() as &[u8],
+                &[0u8; 8],
+                block_hash.as_ref(),
+                &CONTEXT_STRING,
+                &message_bytes,
+            ]
+            .concat(),
+        );
+        Ok(message_hash.into())
+    }
+}
+
+/// The challenge (also called presentation context) that a ZK proof generated
+/// at the block with the `block_hash` has to be generated for. It is the
+/// SHA256 hash of the `block_hash` followed by the `CONTEXT_STRING`.
+pub fn zk_proof_challenge(block_hash: &BlockHash) -> Result<Challenge, ServerError> {
+    let challenge_hash = sha2::Sha256::digest([block_hash.as_ref(), &CONTEXT_STRING].concat());
+    Challenge::try_from(challenge_hash.as_slice())
+        .map_err(|e| ServerError::TypeConversion("challenge".to_string(), e))
+}
+
 /// Trait definition of `HasSigningData`. This trait is implemented for all
 /// input parameter structs used by endpoints that require a signature check.
 pub trait HasSigningData {
@@ -83,6 +267,9 @@ pub struct ZKProofExtractedData {
     pub nationality: String,
     /// Prover that generated the ZK proof.
     pub prover: AccountAddress,
+    /// The identifier of the accepted ZK statement that the ZK proof
+    /// satisfied.
+    pub zk_statement_id: u64,
 }
 
 /// Message struct for the `postTweet` endpoint.
@@ -136,6 +323,97 @@ pub struct SetClaimedParam {
     pub signing_data: SigningData<SetClaimedMessage>,
 }
 
+/// Message struct for the `importAccounts` endpoint. The accounts can be given
+/// as a JSON list, as CSV, or both.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportAccountsMessage {
+    /// Vector of accounts that should be pre-approved.
+    #[serde(default)]
+    pub account_addresses: Vec<AccountAddress>,
+    /// Accounts that should be pre-approved as CSV (e.g. a partner allowlist
+    /// exported from a spreadsheet). The first column of every line is an
+    /// account address. Empty lines and a header line are skipped.
+    #[serde(default)]
+    pub csv: Option<String>,
+}
+
+impl ImportAccountsMessage {
+    /// The accounts of the JSON list followed by the accounts of the CSV
+    /// without duplicates. Fails if a line of the CSV (other than the header
+    /// line) does not start with an account address or if there are more than
+    /// [`MAX_IMPORT_ACCOUNTS`] accounts.
+    pub fn accounts(&self) -> Result<Vec<AccountAddress>, ServerError> {
+        let mut accounts = self.account_addresses.clone();
+        if let Some(csv) = &self.csv {
+            for (index, line) in csv.lines().enumerate() {
+                let value = line.split(',').next().unwrap_or_default().trim();
+                let value = value.trim_matches('"');
+                if value.is_empty() {
+                    continue;
+                }
+                match value.parse::<AccountAddress>() {
+                    Ok(account) => accounts.push(account),
+                    // The first line is a header if it does not start with an account address.
+                    Err(_) if index == 0 => continue,
+                    Err(_) => {
+                        return Err(ServerError::InvalidImportLine {
+                            line: index + 1,
+                            value: value.to_string(),
+                        })
+                    }
+                }
+            }
+        }
+
+        let mut seen = BTreeSet::new();
+        accounts.retain(|account| seen.insert(*account));
+
+        if accounts.len() > MAX_IMPORT_ACCOUNTS {
+            return Err(ServerError::MaxImportLimit(MAX_IMPORT_ACCOUNTS));
+        }
+        Ok(accounts)
+    }
+}
+
+/// Implement the `HasSigningData` trait for `ImportAccountsParam`.
+impl HasSigningData for ImportAccountsParam {
+    type Message = ImportAccountsMessage;
+
+    fn signing_data(&self) -> &SigningData<ImportAccountsMessage> {
+        &self.signing_data
+    }
+}
+
+/// Parameter struct for the `importAccounts` endpoint.
+#[repr(transparent)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportAccountsParam {
+    pub signing_data: SigningData<ImportAccountsMessage>,
+}
+
+/// The outcome of importing accounts with the `importAccounts` endpoint.
+#[derive(Debug, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportAccountsReport {
+    /// The accounts that were inserted as pending approval.
+    pub imported: Vec<AccountAddress>,
+    /// The accounts that were already in the database and were left
+    /// unchanged.
+    pub already_present: Vec<AccountAddress>,
+    /// The accounts that do not exist on chain and were not imported.
+    pub not_on_chain: Vec<AccountAddress>,
+}
+
+/// Struct returned by the `importAccounts` endpoint.
+#[repr(transparent)]
+#[derive(serde::Serialize)]
+pub struct ImportAccountsReturn {
+    /// The outcome of the import.
+    pub data: ImportAccountsReport,
+}
+
 /// Partial struct returned by the `canClaim` endpoint.
 #[derive(serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -227,6 +505,91 @@ pub struct VecAccountDataReturn {
     pub data: Vec<AccountData>,
 }
 
+/// Message struct for the `claimReview` and `releaseReview` endpoints.
+#[repr(transparent)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewMessage {
+    /// Account address that should be claimed for review or released.
+    pub account_address: AccountAddress,
+}
+
+/// Implement the `HasSigningData` trait for `ClaimReviewParam`.
+impl HasSigningData for ClaimReviewParam {
+    type Message = ReviewMessage;
+
+    fn signing_data(&self) -> &SigningData<ReviewMessage> {
+        &self.signing_data
+    }
+}
+
+/// Parameter struct for the `claimReview` endpoint.
+#[repr(transparent)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaimReviewParam {
+    pub signing_data: SigningData<ReviewMessage>,
+}
+
+/// Implement the `HasSigningData` trait for `ReleaseReviewParam`.
+impl HasSigningData for ReleaseReviewParam {
+    type Message = ReviewMessage;
+
+    fn signing_data(&self) -> &SigningData<ReviewMessage> {
+        &self.signing_data
+    }
+}
+
+/// Parameter struct for the `releaseReview` endpoint.
+#[repr(transparent)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseReviewParam {
+    pub signing_data: SigningData<ReviewMessage>,
+}
+
+/// Struct returned by the `claimReview` endpoint.
+#[repr(transparent)]
+#[derive(serde::Serialize)]
+pub struct ReviewLockReturn {
+    /// The lock of the account claimed for review.
+    pub data: ReviewLock,
+}
+
+/// Message struct for the `triggerBackup` endpoint.
+#[repr(transparent)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TriggerBackupMessage {
+    /// True, if the restorability of the new backup should be verified.
+    pub verify: bool,
+}
+
+/// Implement the `HasSigningData` trait for `TriggerBackupParam`.
+impl HasSigningData for TriggerBackupParam {
+    type Message = TriggerBackupMessage;
+
+    fn signing_data(&self) -> &SigningData<TriggerBackupMessage> {
+        &self.signing_data
+    }
+}
+
+/// Parameter struct for the `triggerBackup` endpoint.
+#[repr(transparent)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TriggerBackupParam {
+    pub signing_data: SigningData<TriggerBackupMessage>,
+}
+
+/// Struct returned by the `triggerBackup` endpoint.
+#[repr(transparent)]
+#[derive(serde::Serialize)]
+pub struct BackupReturn {
+    /// The manifest of the created backup.
+    pub data: BackupManifest,
+}
+
 /// Parameter struct for the `canClaim` endpoint.
 #[repr(transparent)]
 #[derive(serde::Deserialize, serde::Serialize)]
@@ -236,6 +599,24 @@ pub struct CanClaimParam {
     pub account_address: AccountAddress,
 }
 
+/// Parameter struct for the `getClaimAttestation` endpoint.
+#[repr(transparent)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetClaimAttestationParam {
+    /// Account address for which the attestation should be retrieved.
+    pub account_address: AccountAddress,
+}
+
+/// Struct returned by the `getClaimAttestation` endpoint.
+#[repr(transparent)]
+#[derive(serde::Serialize)]
+pub struct ClaimAttestationReturn {
+    /// The signed attestation or `None` if the account has not claimed the
+    /// reward or no attestation was issued.
+    pub data: Option<SignedClaimAttestation>,
+}
+
 /// Struct returned by the `health` endpoint.
 /// It returns the version of the backend.
 #[derive(serde::Serialize)]
@@ -243,6 +624,40 @@ pub struct Health {
     pub version: &'static str,
 }
 
+/// Struct returned by the `info` endpoint.
+/// It returns the build metadata of the backend. The git commit, build time,
+/// and enabled features are emitted at build time by `vergen` (see
+/// `build.rs`).
+#[derive(serde::Serialize)]
+pub struct Info {
+    /// The version of the backend.
+    pub version: &'static str,
+    /// The git commit the backend was built from.
+    pub git_commit: &'static str,
+    /// The time the backend was built (RFC 3339).
+    pub build_time: &'static str,
+    /// The cargo features the backend was built with.
+    pub features: Vec<&'static str>,
+    /// The contracts tracked by the backend.
+    pub contracts: Vec<ContractAddress>,
+}
+
+impl Info {
+    /// The build metadata of the backend tracking the `contracts`.
+    pub fn new(contracts: Vec<ContractAddress>) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            git_commit: env!("VERGEN_GIT_SHA"),
+            build_time: env!("VERGEN_BUILD_TIMESTAMP"),
+            features: env!("VERGEN_CARGO_FEATURES")
+                .split(',')
+                .filter(|feature| !feature.is_empty())
+                .collect(),
+            contracts,
+        }
+    }
+}
+
 /// Struct returned by the `getZKProofStatements` endpoint.
 #[repr(transparent)]
 #[derive(serde::Serialize)]
@@ -0,0 +1,68 @@
+//! A worker pool for CPU-bound work such as verifying the cryptographic proofs
+//! of submitted ZK proofs. The work runs on the blocking thread pool of the
+//! runtime, so that heavy proof traffic does not starve the async tasks
+//! serving the other endpoints.
+use crate::error::ServerError;
+use std::{sync::Arc, time::Duration};
+use tokio::sync::Semaphore;
+
+/// Runs CPU-bound work on at most `workers` blocking threads at a time. At most
+/// `queue_size` further jobs wait for a free worker, additional jobs are
+/// rejected right away.
+#[derive(Debug, Clone)]
+pub struct WorkerPool {
+    /// Permits for running a job on a blocking thread.
+    workers: Arc<Semaphore>,
+    /// Permits for running or waiting for a worker.
+    queue: Arc<Semaphore>,
+    /// The time a job may take, including the time waiting for a worker.
+    timeout: Duration,
+}
+
+impl WorkerPool {
+    /// Create a worker pool running at most `workers` jobs in parallel with
+    /// at most `queue_size` jobs waiting for a free worker.
+    pub fn new(workers: usize, queue_size: usize, timeout: Duration) -> Self {
+        Self {
+            workers: Arc::new(Semaphore::new(workers)),
+            queue: Arc::new(Semaphore::new(workers + queue_size)),
+            timeout,
+        }
+    }
+
+    /// Run the `job` on a worker and return its result. Returns
+    /// [`ServerError::WorkerPoolBusy`] if the queue is full and
+    /// [`ServerError::DeadlineExceeded`] if the job did not finish within the
+    /// timeout. A job that timed out keeps its worker until it finishes, so
+    /// that the number of busy threads stays bounded.
+    pub async fn run<T, F>(&self, job: F) -> Result<T, ServerError>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let _queued = self
+            .queue
+            .clone()
+            .try_acquire_owned()
+            .map_err(|_| ServerError::WorkerPoolBusy)?;
+
+        let run = async {
+            let worker = self
+                .workers
+                .clone()
+                .acquire_owned()
+                .await
+                .map_err(|_| ServerError::WorkerPoolBusy)?;
+            tokio::task::spawn_blocking(move || {
+                let _worker = worker;
+                job()
+            })
+            .await
+            .map_err(|error| ServerError::WorkerFailed(error.to_string()))
+        };
+
+        tokio::time::timeout(self.timeout, run)
+            .await
+            .map_err(|_| ServerError::DeadlineExceeded(self.timeout))?
+    }
+}
@@ -0,0 +1,22 @@
+//! A library for relaying [CIS-3](https://proposals.concordium.software/CIS/cis-3.html)
+//! sponsored transactions. A user signs a [`PermitMessage`] in the wallet and
+//! a back end submits it with a [`Relayer`] to the `permit` entrypoint of the
+//! contract, paying the transaction fees with the sponsor account.
+//!
+//! The [`Relayer`] simulates the transaction, keeps track of the nonce of the
+//! sponsor account, and submits the transaction. A [`SponsorPolicy`] decides
+//! which requests are sponsored, e.g. with an [`Allowlist`] of accounts and
+//! contracts or an [`AccountQuota`] per account.
+pub mod permit;
+pub mod policy;
+pub mod relayer;
+
+pub use crate::{
+    permit::{PermitMessage, PermitParam},
+    policy::{
+        AccountQuota, AllowedAccounts, AllowedContracts, AllowedEntities, Allowlist, PolicyError,
+        SponsorPolicy,
+    },
+    relayer::{Relayer, SponsorError},
+};
+pub use async_trait::async_trait;
@@ -0,0 +1,81 @@
+//! The parameter of the `permit` entrypoint of the CIS-3 standard.
+use concordium_rust_sdk::smart_contracts::common::{
+    self as concordium_std, AccountAddress, AccountSignatures, ContractAddress,
+    CredentialSignatures, OwnedEntrypointName, OwnedParameter, Serial, Signature, SignatureEd25519,
+    Timestamp,
+};
+use hex::FromHexError;
+use std::collections::BTreeMap;
+
+/// The message signed by the user in the wallet.
+#[derive(Debug, Serial, Clone)]
+pub struct PermitMessage {
+    /// The contract address that the signature is intended for.
+    pub contract_address: ContractAddress,
+    /// Nonce (as stored in the state of the contract) of the signer when it
+    /// signed the message. The nonce prevents replay attacks.
+    pub nonce:            u64,
+    /// A timestamp to make signatures expire.
+    pub timestamp:        Timestamp,
+    /// The entrypoint that the signature is intended for.
+    pub entry_point:      OwnedEntrypointName,
+    /// The serialized parameter that is forwarded to the entrypoint
+    /// `entry_point`.
+    pub payload:          OwnedParameter,
+}
+
+/// The parameter of the `permit` entrypoint.
+#[derive(Debug, Serial, Clone)]
+pub struct PermitParam {
+    /// The signature that the signer generated when it signed the `message`.
+    pub signature: AccountSignatures,
+    /// The account that signed the `message`.
+    pub signer:    AccountAddress,
+    /// The message signed.
+    pub message:   PermitMessage,
+}
+
+/// Errors parsing the signature of a [`PermitMessage`].
+#[derive(Debug, thiserror::Error)]
+pub enum SignatureError {
+    /// The signature could not be parsed.
+    #[error("Unable to parse signature into a hex string: {0}.")]
+    Hex(#[from] FromHexError),
+    /// The signature does not have the right length.
+    #[error("Unable to parse signature because it wasn't 64 bytes long.")]
+    Length,
+}
+
+impl PermitParam {
+    /// Create the parameter from the hex encoded ed25519 `signature` of the
+    /// `message`. Only accounts with a single credential and key (at index
+    /// `0`) are supported, which is the case for all accounts created with
+    /// the wallets. Whether the signature is valid is checked by the contract
+    /// when the transaction is simulated.
+    pub fn new(
+        signer: AccountAddress,
+        signature: &str,
+        message: PermitMessage,
+    ) -> Result<Self, SignatureError> {
+        if signature.len() != 128 {
+            return Err(SignatureError::Length);
+        }
+        let mut signature_bytes = [0; 64];
+        hex::decode_to_slice(signature, &mut signature_bytes)?;
+
+        let mut inner_signature_map = BTreeMap::new();
+        inner_signature_map.insert(0, Signature::Ed25519(SignatureEd25519(signature_bytes)));
+        let mut signature_map = BTreeMap::new();
+        signature_map.insert(0, CredentialSignatures {
+            sigs: inner_signature_map,
+        });
+
+        Ok(Self {
+            signature: AccountSignatures {
+                sigs: signature_map,
+            },
+            signer,
+            message,
+        })
+    }
+}
@@ -0,0 +1,295 @@
+//! Policies deciding which requests are sponsored. A [`SponsorPolicy`] is
+//! consulted by the [`Relayer`](crate::Relayer) before a transaction is
+//! simulated and before it is submitted. Policies are combined by using a
+//! tuple of policies, e.g. `(Allowlist, AccountQuota)`, which rejects a request
+//! if any of the policies rejects it.
+use crate::permit::PermitParam;
+use concordium_rust_sdk::{
+    smart_contracts::common::{AccountAddress, ContractAddress},
+    types::Energy,
+};
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+
+/// Errors returned by a [`SponsorPolicy`] to reject a request.
+#[derive(Debug, thiserror::Error)]
+pub enum PolicyError {
+    /// The signer account is not allowed to use the service.
+    #[error("Signer account is not allowed to use the service: {account}.")]
+    AccountNotAllowed { account: AccountAddress },
+    /// The contract is not allowed to be used by the service.
+    #[error("Contract address is not allowed to be used by the service: {contract}.")]
+    ContractNotAllowed { contract: ContractAddress },
+    /// The signer account has reached its quota of sponsored transactions.
+    #[error("The signer account reached its limit of {limit} sponsored transactions{}.", period_text(.period))]
+    QuotaExceeded {
+        limit:  u16,
+        period: Option<Duration>,
+    },
+    /// Deriving the alias account failed.
+    #[error("Unable to derive alias account of signer.")]
+    NoAliasAccount,
+    /// The simulated energy exceeds the energy budget of the entrypoint.
+    #[error(
+        "Simulation of transaction used {used_energy} energy which exceeds the sponsored energy \
+         budget of {max_energy} for the entrypoint `{entrypoint}`."
+    )]
+    EnergyBudgetExceeded {
+        entrypoint:  String,
+        used_energy: u64,
+        max_energy:  u64,
+    },
+    /// The request was rejected by a custom policy.
+    #[error("The request was rejected: {0}")]
+    Rejected(String),
+}
+
+/// The period of a [`PolicyError::QuotaExceeded`] error as text.
+fn period_text(period: &Option<Duration>) -> String {
+    match period {
+        Some(period) => format!(" per {} seconds", period.as_secs()),
+        None => String::new(),
+    }
+}
+
+/// Hooks deciding whether a request is sponsored. Both hooks accept every
+/// request by default.
+#[async_trait::async_trait]
+pub trait SponsorPolicy: Send + Sync {
+    /// Check the request before the transaction is simulated. This hook should
+    /// reject requests that are never sponsored, e.g. from accounts that are
+    /// not allowed to use the service. The signature of the request has not
+    /// been checked yet.
+    async fn check_request(&self, _permit: &PermitParam) -> Result<(), PolicyError> { Ok(()) }
+
+    /// Check the request after the simulation of the transaction succeeded and
+    /// right before it is submitted. The signature of the request has been
+    /// checked by the contract during the simulation, so this hook can count
+    /// the request against quotas of the signer. The hook is called while the
+    /// nonce of the sponsor account is locked, so it is never called
+    /// concurrently and should return quickly.
+    async fn check_submission(
+        &self,
+        _permit: &PermitParam,
+        _used_energy: Energy,
+    ) -> Result<(), PolicyError> {
+        Ok(())
+    }
+}
+
+/// A policy sponsoring every request.
+#[async_trait::async_trait]
+impl SponsorPolicy for () {}
+
+#[async_trait::async_trait]
+impl<A: SponsorPolicy, B: SponsorPolicy> SponsorPolicy for (A, B) {
+    async fn check_request(&self, permit: &PermitParam) -> Result<(), PolicyError> {
+        self.0.check_request(permit).await?;
+        self.1.check_request(permit).await
+    }
+
+    async fn check_submission(
+        &self,
+        permit: &PermitParam,
+        used_energy: Energy,
+    ) -> Result<(), PolicyError> {
+        self.0.check_submission(permit, used_energy).await?;
+        self.1.check_submission(permit, used_energy).await
+    }
+}
+
+#[async_trait::async_trait]
+impl<P: SponsorPolicy + ?Sized> SponsorPolicy for Arc<P> {
+    async fn check_request(&self, permit: &PermitParam) -> Result<(), PolicyError> {
+        self.as_ref().check_request(permit).await
+    }
+
+    async fn check_submission(
+        &self,
+        permit: &PermitParam,
+        used_energy: Energy,
+    ) -> Result<(), PolicyError> {
+        self.as_ref().check_submission(permit, used_energy).await
+    }
+}
+
+/// The accounts allowed to use the service.
+pub type AllowedAccounts = AllowedEntities<AccountAddress>;
+/// The contracts allowed to be used by the service.
+pub type AllowedContracts = AllowedEntities<ContractAddress>;
+
+/// Allowed entities to be used in the service.
+/// Either accounts or contracts.
+///
+/// The expected argument format is
+/// "any", "Any", or "ANY" for `Self::Any`, and a white-space-separated set of
+/// the entities for the `Self::LimitedTo` variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AllowedEntities<T> {
+    /// Any entities allowed to use or be used by the service. This should only
+    /// be used if other authorizations schemes are in place. Otherwise the
+    /// sponsor account can easily be drained of funds.
+    Any,
+    /// The restricted set entities allowed to use or be used by the service.
+    LimitedTo { entities: BTreeSet<T> },
+}
+
+/// Error parsing [`AllowedEntities`].
+#[derive(Debug, thiserror::Error)]
+#[error("Unable to parse the allowed entity `{0}`.")]
+pub struct ParseAllowedEntitiesError(String);
+
+impl<T> FromStr for AllowedEntities<T>
+where
+    T: FromStr + Ord,
+{
+    type Err = ParseAllowedEntitiesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s_trim = s.trim();
+        if s_trim == "any" || s_trim == "Any" || s_trim == "ANY" {
+            return Ok(Self::Any);
+        }
+        let entities = s
+            .split_whitespace()
+            .map(|entity| {
+                T::from_str(entity).map_err(|_| ParseAllowedEntitiesError(entity.to_string()))
+            })
+            .collect::<Result<BTreeSet<_>, _>>()?;
+        Ok(Self::LimitedTo { entities })
+    }
+}
+
+impl<T> fmt::Display for AllowedEntities<T>
+where
+    T: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AllowedEntities::Any => write!(f, "Any"),
+            AllowedEntities::LimitedTo { entities } => write!(
+                f,
+                "[{}]",
+                entities
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+impl<T: Ord> AllowedEntities<T> {
+    /// Check whether the entity is allowed.
+    pub fn allowed(&self, entity: &T) -> bool {
+        match self {
+            AllowedEntities::Any => true,
+            AllowedEntities::LimitedTo { entities } => entities.contains(entity),
+        }
+    }
+}
+
+/// A policy only sponsoring requests signed by the allowed accounts for the
+/// allowed contracts.
+#[derive(Debug, Clone)]
+pub struct Allowlist {
+    /// The accounts allowed to submit transactions.
+    pub accounts:  AllowedAccounts,
+    /// The contracts allowed to be used by the service.
+    pub contracts: AllowedContracts,
+}
+
+#[async_trait::async_trait]
+impl SponsorPolicy for Allowlist {
+    async fn check_request(&self, permit: &PermitParam) -> Result<(), PolicyError> {
+        if !self.accounts.allowed(&permit.signer) {
+            return Err(PolicyError::AccountNotAllowed {
+                account: permit.signer,
+            });
+        }
+        let contract = permit.message.contract_address;
+        if !self.contracts.allowed(&contract) {
+            return Err(PolicyError::ContractNotAllowed { contract });
+        }
+        Ok(())
+    }
+}
+
+/// A policy limiting the number of transactions sponsored per signer account.
+///
+/// The quotas are primarily used to limit the costs in terms of CCD of the
+/// sponsor account. It is therefore not necessary to limit bursts of requests
+/// from a single account. The implementation here is thus very simple, where
+/// all quotas are reset once per `period`. This means that an account can
+/// actually use twice its quota within a few minutes if the transactions are
+/// timed just before and after the reset. Without a period, the quotas are
+/// never reset. The quotas are transient and are reset on restart.
+///
+/// Since account addresses have aliases, the quotas are tracked by using the
+/// 0th alias for every account. For more info on aliases, see: https://developer.concordium.software/en/mainnet/net/references/transactions.html#account-aliases
+#[derive(Debug, Clone)]
+pub struct AccountQuota {
+    /// The number of transactions sponsored per account and period.
+    limit:  u16,
+    /// The period after which all quotas are reset.
+    period: Option<Duration>,
+    /// The number of transactions sponsored per account since the last reset
+    /// and the time of the last reset.
+    usage:  Arc<Mutex<(HashMap<AccountAddress, u16>, Instant)>>,
+}
+
+impl AccountQuota {
+    /// Sponsor up to `limit` transactions per account and `period`, or in
+    /// total if no period is given.
+    pub fn new(limit: u16, period: Option<Duration>) -> Self {
+        Self {
+            limit,
+            period,
+            usage: Arc::new(Mutex::new((HashMap::new(), Instant::now()))),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl SponsorPolicy for AccountQuota {
+    async fn check_submission(
+        &self,
+        permit: &PermitParam,
+        _used_energy: Energy,
+    ) -> Result<(), PolicyError> {
+        let mut usage = self.usage.lock().await;
+        let (counts, last_reset) = &mut *usage;
+
+        // Reset the quotas if the period has passed since the last reset.
+        if self
+            .period
+            .map_or(false, |period| last_reset.elapsed() >= period)
+        {
+            counts.clear();
+            *last_reset = Instant::now();
+        }
+
+        let alias_account_0 = permit
+            .signer
+            .get_alias(0)
+            .ok_or(PolicyError::NoAliasAccount)?;
+
+        let count = counts.entry(alias_account_0).or_insert(0);
+        if *count >= self.limit {
+            tracing::warn!("Quota of account {} reached.", permit.signer);
+            return Err(PolicyError::QuotaExceeded {
+                limit:  self.limit,
+                period: self.period,
+            });
+        }
+        *count += 1;
+        Ok(())
+    }
+}
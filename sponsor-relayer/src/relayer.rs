@@ -0,0 +1,328 @@
+//! The [`Relayer`] submitting sponsored transactions to the `permit`
+//! entrypoint of CIS-3 contracts.
+use crate::{
+    permit::{PermitParam, SignatureError},
+    policy::{PolicyError, SponsorPolicy},
+};
+use concordium_rust_sdk::{
+    common::types::TransactionTime,
+    contract_client::{
+        ContractClient, ContractUpdateBuilder, DecodedReason, InvokeContractOutcome,
+    },
+    endpoints::QueryError,
+    smart_contracts::common::{AccountAddress, Amount, NewReceiveNameError},
+    types::{
+        hashes::TransactionHash, smart_contracts::ExceedsParameterSize, Energy, Nonce,
+        RejectReason, WalletAccount,
+    },
+    v2,
+};
+use std::{fmt, sync::Arc, time::Duration};
+use tokio::sync::Mutex;
+use tracing::Instrument;
+
+/// Before submitting a transaction we simulate/dry-run the transaction to get
+/// an estimate of the energy needed for executing the transaction. In
+/// addition, we allow an additional small amount of energy to be consumed by
+/// the transaction to cover small variations (e.g. changes to the smart
+/// contract state) caused by transactions that have been executed meanwhile.
+const DEFAULT_EXTRA_ENERGY: u64 = 1000;
+
+/// Errors that can occur when relaying a sponsored transaction.
+#[derive(Debug, thiserror::Error)]
+pub enum SponsorError {
+    /// The signature of the request could not be parsed.
+    #[error("{0}")]
+    Signature(#[from] SignatureError),
+    /// The parameter exceeds the length limit.
+    #[error("The parameter exceeds the length limit: {0}")]
+    Parameter(#[from] ExceedsParameterSize),
+    /// The receive name of the `permit` entrypoint is invalid.
+    #[error("Invalid receive name: {0}")]
+    ReceiveName(#[from] NewReceiveNameError),
+    /// The contract client could not be created because of a network error.
+    #[error("Failed to create contract client: {0}")]
+    ContractClient(QueryError),
+    /// Querying the node failed due to a network error.
+    #[error("Failed querying the node due to a network error: {0}")]
+    Network(#[from] QueryError),
+    /// The transaction simulation returned with a contract rejection.
+    #[error(
+        "Simulation of transaction rejected in smart contract with reject reason: {reason:?}{}.",
+        decoded_text(.decoded_reason)
+    )]
+    SimulationRejected {
+        reason:         RejectReason,
+        decoded_reason: Option<DecodedReason>,
+    },
+    /// Sending the transaction failed.
+    #[error("Unable to submit transaction on chain successfully: {0}.")]
+    Submit(QueryError),
+    /// The request was rejected by the sponsor policy.
+    #[error("{0}")]
+    Policy(#[from] PolicyError),
+}
+
+/// The decoded reason of a [`SponsorError::SimulationRejected`] error as text.
+fn decoded_text(decoded_reason: &Option<DecodedReason>) -> String {
+    match decoded_reason {
+        Some(decoded_reason) => format!(" (decoded: `{decoded_reason}`)"),
+        None => String::new(),
+    }
+}
+
+// TODO: Use `#[from] RejectReason` instead when [`RejectReason`] implements
+// `std::error::Error`.
+impl From<RejectReason> for SponsorError {
+    fn from(reason: RejectReason) -> Self {
+        Self::SimulationRejected {
+            reason,
+            decoded_reason: None,
+        }
+    }
+}
+
+impl SponsorError {
+    /// Whether the error is caused by the service or the node rather than by
+    /// the request. Internal errors should be reported as server errors, all
+    /// other errors as bad requests.
+    pub fn is_internal(&self) -> bool {
+        matches!(
+            self,
+            SponsorError::Parameter(_)
+                | SponsorError::ContractClient(_)
+                | SponsorError::Network(_)
+                | SponsorError::Submit(_)
+        )
+    }
+}
+
+/// Callback observing the time spent waiting for the nonce lock.
+type NonceLockObserver = Arc<dyn Fn(Duration) + Send + Sync>;
+
+/// Relays sponsored transactions to the `permit` entrypoint of CIS-3
+/// contracts, paying the transaction fees with the sponsor account.
+///
+/// The relayer tracks the nonce of the sponsor account, so that requests can
+/// be handled in parallel. Only one relayer should submit transactions for a
+/// sponsor account at a time. Cloning the relayer is cheap and shares the
+/// nonce.
+#[derive(Clone)]
+pub struct Relayer {
+    /// A connection to a node.
+    node_client:  v2::Client,
+    /// The account keys of the account sponsoring the transactions.
+    sponsor:      Arc<WalletAccount>,
+    /// The next nonce of the sponsor account.
+    nonce:        Arc<Mutex<Nonce>>,
+    /// The energy added to the simulated energy of a transaction.
+    extra_energy: Energy,
+    /// The time after which submitted transactions expire.
+    expiry:       Option<u32>,
+    /// Callback observing the time spent waiting for the nonce lock.
+    observer:     Option<NonceLockObserver>,
+}
+
+impl fmt::Debug for Relayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Relayer")
+            .field("sponsor", &self.sponsor.address)
+            .field("extra_energy", &self.extra_energy)
+            .field("expiry", &self.expiry)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Relayer {
+    /// Create a relayer sponsoring transactions with the `sponsor` account.
+    /// The next nonce of the account is queried from the node.
+    pub async fn new(
+        mut node_client: v2::Client,
+        sponsor: WalletAccount,
+    ) -> Result<Self, QueryError> {
+        let nonce = node_client
+            .get_next_account_sequence_number(&sponsor.address)
+            .await?
+            .nonce;
+        Ok(Self {
+            node_client,
+            sponsor: Arc::new(sponsor),
+            nonce: Arc::new(Mutex::new(nonce)),
+            extra_energy: Energy::from(DEFAULT_EXTRA_ENERGY),
+            expiry: None,
+            observer: None,
+        })
+    }
+
+    /// Set the energy added to the simulated energy of every transaction.
+    /// Defaults to `1000`.
+    pub fn with_extra_energy(mut self, extra_energy: Energy) -> Self {
+        self.extra_energy = extra_energy;
+        self
+    }
+
+    /// Set the number of seconds after which submitted transactions expire. If
+    /// not set, the default expiry of the SDK is used.
+    pub fn with_expiry(mut self, seconds: u32) -> Self {
+        self.expiry = Some(seconds);
+        self
+    }
+
+    /// Observe the time every request spends waiting for the lock on the nonce
+    /// of the sponsor account, e.g. to record it in a metric.
+    pub fn observe_nonce_lock_wait(
+        mut self,
+        observer: impl Fn(Duration) + Send + Sync + 'static,
+    ) -> Self {
+        self.observer = Some(Arc::new(observer));
+        self
+    }
+
+    /// The address of the sponsor account.
+    pub fn sponsor_address(&self) -> AccountAddress { self.sponsor.address }
+
+    /// The next nonce of the sponsor account as tracked by the relayer.
+    pub fn nonce(&self) -> Arc<Mutex<Nonce>> { self.nonce.clone() }
+
+    /// Simulate the `permit` transaction and submit it if the simulation
+    /// succeeds and the `policy` accepts it. Returns the hash of the submitted
+    /// transaction.
+    ///
+    /// The phases of the submission are recorded as the child spans
+    /// `validate_request`, `simulate`, `acquire_nonce`, and `sign_and_send` of
+    /// the `submit` span, which records the `transaction_hash` once the
+    /// transaction is submitted.
+    #[tracing::instrument(
+        level = "info",
+        skip_all,
+        fields(
+            contract = %permit.message.contract_address,
+            signer = %permit.signer,
+            transaction_hash = tracing::field::Empty,
+        )
+    )]
+    pub async fn submit<P: SponsorPolicy + ?Sized>(
+        &self,
+        permit: &PermitParam,
+        policy: &P,
+    ) -> Result<TransactionHash, SponsorError> {
+        policy
+            .check_request(permit)
+            .instrument(tracing::info_span!("validate_request"))
+            .await?;
+
+        let dry_run = self
+            .simulate(permit)
+            .instrument(tracing::info_span!(
+                "simulate",
+                used_energy = tracing::field::Empty
+            ))
+            .await?;
+
+        let used_energy = dry_run.current_energy();
+        let mut dry_run = dry_run.extra_energy(self.extra_energy);
+        if let Some(expiry) = self.expiry {
+            dry_run = dry_run.expiry(TransactionTime::seconds_after(expiry));
+        }
+
+        let acquire_nonce = tracing::info_span!("acquire_nonce", nonce = tracing::field::Empty);
+        let mut nonce = async {
+            // Get the current nonce for the sponsor account and lock it. This is
+            // necessary since it is possible that requests come in parallel. The
+            // nonce is increased by 1 and its lock is released after the
+            // transaction is submitted to the blockchain.
+            let lock_wait_start = std::time::Instant::now();
+            let nonce = self.nonce.lock().await;
+            if let Some(observer) = &self.observer {
+                observer(lock_wait_start.elapsed());
+            }
+            tracing::Span::current().record("nonce", nonce.nonce);
+
+            // The policy is checked *after* the simulation, which ensures that it
+            // indeed is the specified signer account that sent the request (since
+            // the signature is checked by the contract). If quotas were updated
+            // *before* the simulation, then it would be easy for attackers to block
+            // other accounts from using the service by spamming requests with the
+            // victim account specified as the signer. Checking the policy while
+            // holding the nonce lock guarantees that parallel requests are counted
+            // correctly.
+            policy.check_submission(permit, used_energy).await?;
+            Ok::<_, SponsorError>(nonce)
+        }
+        .instrument(acquire_nonce)
+        .await?;
+
+        // The SDK signs the transaction and sends it in one call, so both are
+        // recorded in the same span.
+        let sign_and_send =
+            tracing::info_span!("sign_and_send", transaction_hash = tracing::field::Empty);
+        let result = dry_run
+            .nonce(*nonce)
+            .send(self.sponsor.as_ref())
+            .instrument(sign_and_send.clone())
+            .await;
+        match result {
+            Ok(handle) => {
+                let tx_hash = handle.hash();
+                tracing::debug!("Submitted transaction {} ...", tx_hash);
+                sign_and_send.record("transaction_hash", tracing::field::display(tx_hash));
+                tracing::Span::current()
+                    .record("transaction_hash", tracing::field::display(tx_hash));
+                *nonce = nonce.next();
+                Ok(tx_hash)
+            }
+            Err(error) => {
+                tracing::warn!("Unable to submit the sponsored transaction: {error}.");
+                // The nonce tracked by the relayer might be out of sync with the
+                // chain, e.g. if another process used the sponsor account, so it
+                // is queried again for the next request.
+                match self
+                    .node_client
+                    .clone()
+                    .get_next_account_sequence_number(&self.sponsor.address)
+                    .await
+                {
+                    Ok(response) => *nonce = response.nonce,
+                    Err(error) => {
+                        tracing::warn!("Unable to query the nonce of the sponsor account: {error}.")
+                    }
+                }
+                Err(SponsorError::Submit(error))
+            }
+        }
+    }
+
+    /// Simulate the `permit` transaction with the sponsor account as the
+    /// sender and return the update ready to be sent.
+    async fn simulate(&self, permit: &PermitParam) -> Result<ContractUpdateBuilder, SponsorError> {
+        let mut contract_client =
+            ContractClient::<()>::create(self.node_client.clone(), permit.message.contract_address)
+                .await
+                .map_err(SponsorError::ContractClient)?;
+
+        match contract_client
+            .dry_run_update_with_reject_reason_info::<PermitParam, SponsorError>(
+                "permit",
+                Amount::zero(),
+                self.sponsor.address,
+                permit,
+            )
+            .await?
+        {
+            InvokeContractOutcome::Success(dry_run) => {
+                tracing::Span::current().record("used_energy", dry_run.current_energy().energy);
+                Ok(dry_run)
+            }
+            InvokeContractOutcome::Failure(rejected_transaction) => {
+                tracing::debug!(
+                    "Simulation of transaction rejected: {:?}.",
+                    rejected_transaction.reason
+                );
+                Err(SponsorError::SimulationRejected {
+                    reason:         rejected_transaction.reason,
+                    decoded_reason: rejected_transaction.decoded_reason,
+                })
+            }
+        }
+    }
+}
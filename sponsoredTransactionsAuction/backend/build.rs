@@ -0,0 +1,14 @@
+//! Emits the build metadata (build time, git commit, and enabled features)
+//! returned by the `/info` endpoint.
+use vergen::EmitBuilder;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Without a git repository (e.g. in a docker build), vergen emits a
+    // placeholder instead of the commit and only prints a warning.
+    EmitBuilder::builder()
+        .build_timestamp()
+        .cargo_features()
+        .git_sha(false)
+        .emit()?;
+    Ok(())
+}
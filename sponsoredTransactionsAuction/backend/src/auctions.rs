@@ -0,0 +1,98 @@
+use concordium_rust_sdk::{
+    cis2::TokenAmount,
+    smart_contracts::common::{ContractAddress, Timestamp},
+};
+use std::collections::BTreeMap;
+
+/// The id of the auction configured with the `--auction-smart-contract-index`
+/// and `--cis2-token-smart-contract-index` options if no auctions file is
+/// given. Bids without an auction id are placed in this auction.
+pub const DEFAULT_AUCTION_ID: &str = "default";
+
+/// The configuration of an auction whose bids are sponsored.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct AuctionConfig {
+    /// Contract address of the auction contract.
+    pub auction_contract: ContractAddress,
+    /// Contract address of the token contract whose tokens are used to bid.
+    pub token_contract:   ContractAddress,
+    /// The minimum amount of tokens of a sponsored bid. If not set, bids are
+    /// not limited from below.
+    #[serde(default)]
+    pub min_bid_amount:   Option<TokenAmount>,
+    /// The maximum amount of tokens of a sponsored bid. If not set, bids are
+    /// not limited from above.
+    #[serde(default)]
+    pub max_bid_amount:   Option<TokenAmount>,
+    /// The time from which bids are sponsored. If not set, bids are sponsored
+    /// until the `end` of the auction.
+    #[serde(default)]
+    pub start:            Option<Timestamp>,
+    /// The time until which bids are sponsored. If not set, bids are sponsored
+    /// from the `start` of the auction on.
+    #[serde(default)]
+    pub end:              Option<Timestamp>,
+}
+
+impl AuctionConfig {
+    /// Whether bids are sponsored at the time `now`.
+    pub fn is_open(&self, now: Timestamp) -> bool {
+        self.start.map_or(true, |start| start <= now) && self.end.map_or(true, |end| now < end)
+    }
+}
+
+/// The registry of the auctions whose bids are sponsored, keyed by the auction
+/// id that is passed to the `/bid` endpoint.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+#[serde(transparent)]
+pub struct AuctionRegistry {
+    pub auctions: BTreeMap<String, AuctionConfig>,
+}
+
+impl AuctionRegistry {
+    /// A registry with the single auction `config` under the id
+    /// [`DEFAULT_AUCTION_ID`].
+    pub fn single(config: AuctionConfig) -> Self {
+        Self {
+            auctions: BTreeMap::from([(DEFAULT_AUCTION_ID.to_string(), config)]),
+        }
+    }
+
+    /// Read the registry from the JSON file at `path`.
+    pub fn from_file(path: &std::path::Path) -> anyhow::Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// The configuration of the auction with the id `auction_id`, if
+    /// registered.
+    pub fn get(&self, auction_id: &str) -> Option<&AuctionConfig> { self.auctions.get(auction_id) }
+
+    /// Check that the registry contains an auction and that the bid bounds and
+    /// the time window of every auction are consistent.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            !self.auctions.is_empty(),
+            "At least one auction has to be configured."
+        );
+        for (id, auction) in &self.auctions {
+            if let (Some(min_bid_amount), Some(max_bid_amount)) =
+                (&auction.min_bid_amount, &auction.max_bid_amount)
+            {
+                anyhow::ensure!(
+                    min_bid_amount <= max_bid_amount,
+                    "The minimum bid amount of auction {id} should not exceed its maximum bid \
+                     amount."
+                );
+            }
+            if let (Some(start), Some(end)) = (auction.start, auction.end) {
+                anyhow::ensure!(
+                    start < end,
+                    "The start of auction {id} should be before its end."
+                );
+            }
+        }
+        Ok(())
+    }
+}
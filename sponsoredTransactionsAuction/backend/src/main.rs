@@ -1,57 +1,90 @@
+mod auctions;
+mod metrics;
+#[cfg(feature = "mock-node")]
+mod mock_node;
+mod policy;
+mod scheduler;
+mod telemetry;
 mod types;
-use crate::types::*;
+use crate::{
+    auctions::{AuctionConfig, AuctionRegistry},
+    metrics::Metrics,
+    policy::{EnergyBudget, EnergyPolicy},
+    scheduler::{BidScheduler, BidTicket},
+    types::*,
+};
 use anyhow::Context;
 use axum::{
-    extract::{rejection::JsonRejection, State},
+    error_handling::HandleErrorLayer,
+    extract::{rejection::JsonRejection, Path, State},
     response::Html,
     routing::{get, post},
     Json, Router,
 };
 use clap::Parser;
 use concordium_rust_sdk::{
-    cis2::{AdditionalData, Receiver, Transfer},
-    common::types::TransactionTime,
+    cis2::{AdditionalData, Cis2Contract, Receiver, TokenAmount, Transfer},
     smart_contracts::common::{
-        to_bytes, AccountSignatures, Address, Amount, ContractAddress, CredentialSignatures,
-        OwnedEntrypointName, Signature, SignatureEd25519,
+        to_bytes, Address, ContractAddress, OwnedContractName, OwnedEntrypointName, Timestamp,
     },
     types::{
-        hashes::TransactionHash,
-        smart_contracts,
-        smart_contracts::{ContractContext, InvokeContractResult, OwnedReceiveName},
-        transactions, Energy, WalletAccount,
+        smart_contracts::{OwnedParameter, OwnedReceiveName},
+        WalletAccount,
     },
     v2::{self, BlockIdentifier, Endpoint},
+    web3id::did::Network,
 };
-use std::{
-    collections::{BTreeMap, HashMap},
-    fs,
-    sync::Arc,
-};
-use tokio::sync::Mutex;
+use concordium_sponsor_relayer::{AccountQuota, PermitMessage, PermitParam, Relayer, SponsorError};
+use std::{collections::BTreeSet, fs, sync::Arc};
+use tokio::sync::RwLock;
 use tonic::transport::ClientTlsConfig;
+use tower::ServiceBuilder;
 use tower_http::services::ServeDir;
 
-// Before submitting a transaction we simulate/dry-run the transaction to get an
-// estimate of the energy needed for executing the transaction. In addition, we
-// allow an additional small amount of energy `EPSILON_ENERGY` to be consumed by
-// the transaction to cover small variations (e.g. changes to the smart contract
-// state) caused by transactions that have been executed meanwhile.
-const EPSILON_ENERGY: u64 = 1000;
 const CONTRACT_NAME: &str = "cis2_multi";
-const ENERGY: u64 = 60000;
-const RATE_LIMIT_PER_ACCOUNT: u8 = 30;
+// There should be rate limiting in place to prevent the sponsor wallet from
+// being drained. We only allow up to RATE_LIMIT_PER_ACCOUNT sponsored bids per
+// user account. The rate limits are transient and are reset on server restart.
+//
+// On mainnet, a user can only create around 25 accounts per identity.
+// In production, a user registration/authentication at the frontend can be
+// added or a database that permanently keeps track of the rate limits so
+// that the server can be restarted and reload the rate limit values from the
+// database.
+const RATE_LIMIT_PER_ACCOUNT: u16 = 30;
+// The maximum size of the buffer used to read a request on a HTTP/1
+// connection. This bounds the size of the request line and headers.
+const MAX_HTTP1_BUF_SIZE: usize = 16 * 1024;
+// The maximum number of concurrent requests (streams) on a single HTTP/2
+// connection.
+const MAX_HTTP2_CONCURRENT_STREAMS: u32 = 16;
+// The genesis block hashes of mainnet and testnet used to derive the network
+// served by the `/api/uiConfig` endpoint.
+const MAINNET_GENESIS_BLOCK_HASH: &str =
+    "9dd9ca4d19e9393877d2c44b70f89acbfc0883c2243e5eeaecc0d1cd0503f478";
+const TESTNET_GENESIS_BLOCK_HASH: &str =
+    "4221332d34e1694168c2a0c0b3fd0f273809612cb13d000d5c2e00e85f50f796";
 
 #[derive(clap::Parser, Debug)]
 #[clap(version, author)]
 struct App {
     #[clap(
         long = "node",
-        help = "GRPC V2 interface of the node.",
+        help = "GRPC V2 interface of the node. A `mock://` URL serves a fake node for testing if \
+                the backend is built with the `mock-node` feature.",
         default_value = "http://localhost:20000",
         env = "NODE"
     )]
     endpoint: Endpoint,
+    #[clap(
+        long = "service-name",
+        help = "Name identifying this backend to the node. It is sent with the version of the \
+                backend as the user agent of the requests to the node and labels the node usage \
+                metrics.",
+        default_value = "sponsored-transaction-backend",
+        env = "SERVICE_NAME"
+    )]
+    service_name: String,
     #[clap(
         long = "log-level",
         default_value = "info",
@@ -66,6 +99,58 @@ struct App {
         env = "REQUEST_TIMEOUT"
     )]
     request_timeout: u64,
+    #[clap(
+        long = "header-read-timeout",
+        help = "Timeout for reading the request headers of a connection in milliseconds.",
+        default_value = "5000",
+        env = "HEADER_READ_TIMEOUT"
+    )]
+    header_read_timeout: u64,
+    #[clap(
+        long = "body-read-timeout",
+        help = "Timeout for reading the request body in milliseconds.",
+        default_value = "5000",
+        env = "BODY_READ_TIMEOUT"
+    )]
+    body_read_timeout: u64,
+    #[clap(
+        long = "max-concurrent-requests",
+        help = "Maximum number of requests processed concurrently. Further requests are rejected \
+                with `503 Service Unavailable`.",
+        default_value = "100",
+        env = "MAX_CONCURRENT_REQUESTS"
+    )]
+    max_concurrent_requests: usize,
+    #[clap(
+        long = "prometheus-address",
+        help = "Address where the Prometheus metrics are served at `/metrics`. If not specified, \
+                no metrics are served.",
+        env = "PROMETHEUS_ADDRESS"
+    )]
+    prometheus_address: Option<std::net::SocketAddr>,
+    #[clap(
+        long = "nonce-lag-interval",
+        help = "Interval in milliseconds between queries of the on-chain nonce of the sponsorer \
+                account used for the nonce lag metric.",
+        default_value = "10000",
+        env = "NONCE_LAG_INTERVAL"
+    )]
+    nonce_lag_interval: u64,
+    #[clap(
+        long = "energy-policy-file",
+        help = "Path to a JSON file with the maximum energy sponsored per entrypoint. The file is \
+                reloaded at runtime when it changes. If not specified, the sponsored energy is \
+                not limited per entrypoint.",
+        env = "ENERGY_POLICY_FILE"
+    )]
+    energy_policy_file: Option<std::path::PathBuf>,
+    #[clap(
+        long = "energy-policy-reload-interval",
+        help = "Interval in milliseconds between checks whether the energy policy file changed.",
+        default_value = "10000",
+        env = "ENERGY_POLICY_RELOAD_INTERVAL"
+    )]
+    energy_policy_reload_interval: u64,
     #[clap(
         long = "listen-address",
         default_value = "0.0.0.0:8080",
@@ -85,16 +170,55 @@ struct App {
         default_value = "7723",
         env = "CIS2_TOKEN_CONTRACT_INDEX",
         help = "The cis2 token smart contract index which the sponsored transaction is submitted \
-                to."
+                to. Ignored if an auctions file is given."
     )]
     cis2_token_smart_contract_index: u64,
     #[clap(
         long = "auction-smart-contract-index",
         default_value = "7724",
         env = "AUCTION_CONTRACT_INDEX",
-        help = "The auction smart contract index which the sponsored transaction is submitted to."
+        help = "The auction smart contract index which the sponsored transaction is submitted to. \
+                Ignored if an auctions file is given."
     )]
     auction_smart_contract_index: u64,
+    #[clap(
+        long = "min-bid-amount",
+        env = "MIN_BID_AMOUNT",
+        help = "The minimum amount of tokens of a sponsored bid. If not specified, bids are not \
+                limited from below. Ignored if an auctions file is given."
+    )]
+    min_bid_amount: Option<TokenAmount>,
+    #[clap(
+        long = "max-bid-amount",
+        env = "MAX_BID_AMOUNT",
+        help = "The maximum amount of tokens of a sponsored bid. If not specified, bids are not \
+                limited from above. Ignored if an auctions file is given."
+    )]
+    max_bid_amount: Option<TokenAmount>,
+    #[clap(
+        long = "auctions-file",
+        env = "AUCTIONS_FILE",
+        help = "Path to a JSON file with the registry of auctions whose bids are sponsored, each \
+                with its own contracts, bid bounds, and time window. If not specified, the single \
+                auction given by the contract index and bid amount options is sponsored."
+    )]
+    auctions_file: Option<std::path::PathBuf>,
+    #[clap(
+        long = "closing-window",
+        env = "CLOSING_WINDOW",
+        help = "Time in milliseconds before the end of an auction in which bids are acknowledged \
+                with a ticket and submitted one at a time in the order they arrived. If not \
+                specified, all bids are submitted right away."
+    )]
+    closing_window: Option<u64>,
+    #[clap(
+        long = "otlp-endpoint",
+        env = "OTLP_ENDPOINT",
+        help = "Endpoint of an OpenTelemetry collector (gRPC) the spans of the requests are \
+                exported to, e.g. `http://localhost:4317`. If not specified, no spans are \
+                exported."
+    )]
+    otlp_endpoint: Option<String>,
     #[structopt(
         long = "account-key-file",
         env = "ACCOUNT_KEY_FILE",
@@ -111,10 +235,18 @@ async fn main() -> anyhow::Result<()> {
         use tracing_subscriber::prelude::*;
         let log_filter = tracing_subscriber::filter::Targets::new()
             .with_target(module_path!(), app.log_level)
+            .with_target("concordium_sponsor_relayer", app.log_level)
             .with_target("tower_http", app.log_level);
 
+        let otlp_layer = app
+            .otlp_endpoint
+            .as_deref()
+            .map(|endpoint| telemetry::otlp_layer(endpoint, &app.service_name))
+            .transpose()?;
+
         tracing_subscriber::registry()
             .with(tracing_subscriber::fmt::layer())
+            .with(otlp_layer)
             .with(log_filter)
             .init();
     }
@@ -124,7 +256,9 @@ async fn main() -> anyhow::Result<()> {
         "Request timeout should be at least 1s."
     );
 
-    let endpoint = if app
+    let endpoint = if app.endpoint.uri().scheme_str() == Some("mock") {
+        mock_endpoint(&app.endpoint).await?
+    } else if app
         .endpoint
         .uri()
         .scheme()
@@ -146,38 +280,122 @@ async fn main() -> anyhow::Result<()> {
         .timeout(node_timeout)
         .http2_keep_alive_interval(std::time::Duration::from_secs(300))
         .keep_alive_timeout(std::time::Duration::from_secs(10))
-        .keep_alive_while_idle(true);
+        .keep_alive_while_idle(true)
+        // Identify the backend to the node, so that node operators serving several
+        // backends can attribute the load.
+        .user_agent(format!("{}/{}", app.service_name, env!("CARGO_PKG_VERSION")))
+        .context("Invalid service name.")?;
 
     let mut node_client = v2::Client::new(endpoint)
         .await
         .context("Unable to establish connection to the node.")?;
 
+    let genesis_block = node_client
+        .get_consensus_info()
+        .await
+        .context("Unable to query the consensus info of the node.")?
+        .genesis_block;
+    let network = match genesis_block.to_string().as_str() {
+        MAINNET_GENESIS_BLOCK_HASH => Some(Network::Mainnet),
+        TESTNET_GENESIS_BLOCK_HASH => Some(Network::Testnet),
+        _ => None,
+    };
+
     // Load account keys and sender address from a file
     let keys: WalletAccount =
         WalletAccount::from_json_file(app.keys_path).context("Could not read the keys file.")?;
 
-    let sponsorer_key = Arc::new(keys);
+    let metrics = Metrics::new(&app.service_name).context("Unable to create the metrics.")?;
 
-    let nonce_response = node_client
-        .get_next_account_sequence_number(&sponsorer_key.address)
+    let nonce_lock_wait_seconds = metrics.nonce_lock_wait_seconds.clone();
+    let relayer = Relayer::new(node_client.clone(), keys)
         .await
-        .context("NonceQueryError.")?;
+        .context("NonceQueryError.")?
+        // Transaction should expiry after one hour.
+        .with_expiry(60 * 60)
+        .observe_nonce_lock_wait(move |wait| nonce_lock_wait_seconds.observe(wait.as_secs_f64()));
 
     tracing::debug!(
         "Starting server with sponsorer {}. Current sponsorer nonce: {}.",
-        sponsorer_key.address,
-        nonce_response.nonce
+        relayer.sponsor_address(),
+        *relayer.nonce().lock().await
     );
 
+    if let Some(prometheus_address) = app.prometheus_address {
+        tokio::spawn(metrics::monitor_nonce_lag(
+            node_client.clone(),
+            relayer.sponsor_address(),
+            relayer.nonce(),
+            metrics.clone(),
+            std::time::Duration::from_millis(app.nonce_lag_interval),
+        ));
+        let metrics_server = metrics::serve(prometheus_address, metrics.clone());
+        tokio::spawn(async move {
+            if let Err(error) = metrics_server.await {
+                tracing::error!("Prometheus metrics server stopped: {error}.");
+            }
+        });
+    }
+
+    let energy_policy = match &app.energy_policy_file {
+        Some(path) => EnergyPolicy::from_file(path).context("Unable to read the energy policy.")?,
+        None => EnergyPolicy::default(),
+    };
+    let energy_policy = Arc::new(RwLock::new(energy_policy));
+
+    if let Some(path) = app.energy_policy_file {
+        tokio::spawn(policy::reload_policy(
+            path,
+            energy_policy.clone(),
+            std::time::Duration::from_millis(app.energy_policy_reload_interval),
+        ));
+    }
+
+    let auctions = match &app.auctions_file {
+        Some(path) => {
+            AuctionRegistry::from_file(path).context("Unable to read the auctions file.")?
+        }
+        None => AuctionRegistry::single(AuctionConfig {
+            auction_contract: ContractAddress::new(app.auction_smart_contract_index, 0),
+            token_contract:   ContractAddress::new(app.cis2_token_smart_contract_index, 0),
+            min_bid_amount:   app.min_bid_amount,
+            max_bid_amount:   app.max_bid_amount,
+            start:            None,
+            end:              None,
+        }),
+    };
+    auctions
+        .validate()
+        .context("Invalid auction configuration.")?;
+    tracing::info!("Sponsoring bids of {} auctions.", auctions.auctions.len());
+
+    let energy_budget = EnergyBudget {
+        policy:     energy_policy,
+        entrypoint: "bid",
+    };
+
+    let scheduler = app
+        .closing_window
+        .map(|window| BidScheduler::new(std::time::Duration::from_millis(window)));
+
     let state = Server {
         node_client,
-        nonce: Arc::new(Mutex::new(nonce_response.nonce)),
-        rate_limits: Arc::new(Mutex::new(HashMap::new())),
-        auction_smart_contract: ContractAddress::new(app.auction_smart_contract_index, 0),
-        cis2_token_smart_contract: ContractAddress::new(app.cis2_token_smart_contract_index, 0),
-        key: sponsorer_key,
+        relayer,
+        metrics,
+        auctions: Arc::new(auctions),
+        network,
+        policy: Arc::new((
+            energy_budget,
+            AccountQuota::new(RATE_LIMIT_PER_ACCOUNT, None),
+        )),
+        scheduler: scheduler.clone(),
+        trace_finalization: app.otlp_endpoint.is_some(),
     };
 
+    if let Some(scheduler) = scheduler {
+        tokio::spawn(scheduler.run(state.clone()));
+    }
+
     // Render index.html
     let index_template = fs::read_to_string(app.frontend_assets.join("index.html"))
         .context("Frontend was not built or wrong path to the frontend files.")?;
@@ -187,7 +405,10 @@ async fn main() -> anyhow::Result<()> {
         .route("/", get(|| async { Html(index_template) }))
         .nest_service("/assets", serve_dir_service)
         .route("/api/bid", post(handle_signature_bid))
+        .route("/api/bidTicket/:ticket", get(bid_ticket))
+        .route("/api/uiConfig", get(ui_config))
         .route("/health", get(health))
+        .route("/info", get(info))
         .with_state(state)
         .layer(
             tower_http::trace::TraceLayer::new_for_http()
@@ -197,34 +418,108 @@ async fn main() -> anyhow::Result<()> {
         .layer(tower_http::timeout::TimeoutLayer::new(
             std::time::Duration::from_millis(app.request_timeout),
         ))
+        .layer(tower_http::timeout::RequestBodyTimeoutLayer::new(
+            std::time::Duration::from_millis(app.body_read_timeout),
+        ))
         .layer(tower_http::limit::RequestBodyLimitLayer::new(1_000_000)) // at most 1000kB of data.
-        .layer(tower_http::compression::CompressionLayer::new());
+        .layer(tower_http::compression::CompressionLayer::new())
+        // Reject requests immediately instead of queuing them once the maximum
+        // number of concurrent requests is reached.
+        .layer(
+            ServiceBuilder::new()
+                .layer(HandleErrorLayer::new(handle_overload))
+                .load_shed()
+                .concurrency_limit(app.max_concurrent_requests),
+        );
 
     tracing::info!("Listening at {}", app.listen_address);
 
     let socket = app.listen_address;
     let shutdown_signal = set_shutdown()?;
 
-    // Create the server.
+    // Create the server. Slow clients are disconnected if they do not send the
+    // request headers within the `header_read_timeout`.
     axum::Server::bind(&socket)
+        .http1_header_read_timeout(std::time::Duration::from_millis(app.header_read_timeout))
+        .http1_max_buf_size(MAX_HTTP1_BUF_SIZE)
+        .http2_max_concurrent_streams(MAX_HTTP2_CONCURRENT_STREAMS)
         .serve(router.into_make_service())
         .with_graceful_shutdown(shutdown_signal)
         .await?;
 
+    if app.otlp_endpoint.is_some() {
+        telemetry::shutdown();
+    }
+
     Ok(())
 }
 
-#[tracing::instrument(level = "info", skip_all)]
+/// Start the mock node configured by the `mock://` URL and return the endpoint
+/// to connect to it.
+#[cfg(feature = "mock-node")]
+async fn mock_endpoint(endpoint: &Endpoint) -> anyhow::Result<Endpoint> {
+    let config = mock_node::MockConfig::from_uri(endpoint.uri())
+        .context("Invalid configuration of the mock node.")?;
+    let address = mock_node::spawn(config).await?;
+    Endpoint::from_shared(format!("http://{address}"))
+        .context("Unable to construct the endpoint of the mock node.")
+}
+
+#[cfg(not(feature = "mock-node"))]
+async fn mock_endpoint(_endpoint: &Endpoint) -> anyhow::Result<Endpoint> {
+    anyhow::bail!(
+        "The mock node is only available if the backend is built with the `mock-node` feature."
+    )
+}
+
+/// Handles requests rejected by the concurrency limit.
+async fn handle_overload(error: tower::BoxError) -> (http::StatusCode, String) {
+    if error.is::<tower::load_shed::error::Overloaded>() {
+        (
+            http::StatusCode::SERVICE_UNAVAILABLE,
+            "Too many concurrent requests. Please try again later.".to_string(),
+        )
+    } else {
+        (
+            http::StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Unhandled internal error: {error}"),
+        )
+    }
+}
+
+#[tracing::instrument(level = "info", skip_all, fields(auction_id = tracing::field::Empty))]
 async fn handle_signature_bid(
     State(mut state): State<Server>,
     request: Result<Json<BidParams>, JsonRejection>,
-) -> Result<Json<TransactionHash>, ServerError> {
+) -> Result<(http::StatusCode, Json<BidResponse>), ServerError> {
     let Json(request) = request?;
+    tracing::Span::current().record("auction_id", request.auction_id.as_str());
+
+    let auctions = state.auctions.clone();
+    let auction = auctions
+        .get(&request.auction_id)
+        .ok_or_else(|| ServerError::UnknownAuction(request.auction_id.clone()))?;
+
+    if !auction.is_open(now()) {
+        return Err(ServerError::AuctionNotOpen(request.auction_id));
+    }
+
+    check_bid_bounds(auction, &request)?;
+
+    // Check that the tokens of the bid can be transferred before sponsoring the
+    // bid, so that the user gets a precise error instead of a failed simulation.
+    let metrics = state.metrics.clone();
+    metrics
+        .observe_node_request(
+            "check_allowance",
+            check_allowance(&mut state, auction, &request),
+        )
+        .await?;
 
     let transfer = Transfer {
         from:     Address::Account(request.from),
         to:       Receiver::Contract(
-            state.auction_smart_contract,
+            auction.auction_contract,
             OwnedReceiveName::new_unchecked("bid".to_owned()),
         ),
         token_id: request.token_id,
@@ -236,172 +531,163 @@ async fn handle_signature_bid(
 
     tracing::debug!("Created payload: {:?}", payload);
 
-    let message: PermitMessage = PermitMessage {
-        contract_address: state.cis2_token_smart_contract,
+    let message = PermitMessage {
+        contract_address: auction.token_contract,
         nonce:            request.nonce,
         timestamp:        request.expiry_timestamp,
         entry_point:      OwnedEntrypointName::new_unchecked("transfer".into()),
-        payload:          concordium_rust_sdk::smart_contracts::common::to_bytes(&payload),
+        payload:          OwnedParameter::from_serial(&payload).map_err(SponsorError::from)?,
     };
 
     tracing::debug!("Created {:?}", message);
 
-    let mut signature = [0; 64];
-
-    if request.signature.len() != 128 {
-        return Err(ServerError::SignatureLengthError);
+    let param = PermitParam::new(request.signer, &request.signature, message)
+        .map_err(SponsorError::from)?;
+
+    // Bids in the closing window of the auction are submitted in arrival order by
+    // the scheduler instead of racing for the nonce of the sponsorer account.
+    if let Some(scheduler) = &state.scheduler {
+        if let Some(end) = auction.end.filter(|&end| scheduler.is_closing(end, now())) {
+            let ticket = scheduler.schedule(request.auction_id, end, param);
+            return Ok((
+                http::StatusCode::ACCEPTED,
+                Json(BidResponse::Scheduled(ticket)),
+            ));
+        }
     }
 
-    hex::decode_to_slice(request.signature, &mut signature).map_err(ServerError::SignatureError)?;
-
-    let mut inner_signature_map = BTreeMap::new();
-    inner_signature_map.insert(0, Signature::Ed25519(SignatureEd25519(signature)));
-
-    let mut signature_map = BTreeMap::new();
-    signature_map.insert(0, CredentialSignatures {
-        sigs: inner_signature_map,
-    });
+    let tx_hash = state
+        .metrics
+        .observe_node_request(
+            "submit",
+            state.relayer.submit(&param, state.policy.as_ref()),
+        )
+        .await?;
 
-    tracing::debug!("Created signature_map {:?}", signature_map);
+    if state.trace_finalization {
+        telemetry::trace_finalization(state.node_client.clone(), tx_hash);
+    }
 
-    let param: PermitParam = PermitParam {
-        message,
-        signature: AccountSignatures {
-            sigs: signature_map,
-        },
-        signer: request.signer,
-    };
+    Ok((http::StatusCode::OK, Json(BidResponse::Submitted(tx_hash))))
+}
 
-    let parameter = smart_contracts::OwnedParameter::from_serial(&param)
-        .map_err(|_| ServerError::ParameterError)?;
+/// Serves the state of a ticket of a bid that was scheduled in the closing
+/// window of its auction, e.g. its position in the queue or the hash of the
+/// transaction submitting it.
+#[tracing::instrument(level = "info", skip(state))]
+async fn bid_ticket(
+    State(state): State<Server>,
+    Path(ticket): Path<u64>,
+) -> Result<Json<BidTicket>, ServerError> {
+    state
+        .scheduler
+        .as_ref()
+        .and_then(|scheduler| scheduler.ticket(ticket))
+        .map(Json)
+        .ok_or(ServerError::UnknownTicket(ticket))
+}
 
-    tracing::debug!("Created {:?}", parameter);
+/// The current time as a timestamp.
+fn now() -> Timestamp {
+    let millis = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as u64);
+    Timestamp::from_timestamp_millis(millis)
+}
 
-    let payload = transactions::UpdateContractPayload {
-        amount:       Amount::zero(),
-        address:      state.cis2_token_smart_contract,
-        receive_name: smart_contracts::OwnedReceiveName::new_unchecked(format!(
-            "{}.permit",
-            CONTRACT_NAME
-        )),
-        message:      parameter,
-    };
+/// Check that the token amount of the bid is within the minimum and maximum
+/// bid amount of the auction.
+fn check_bid_bounds(auction: &AuctionConfig, request: &BidParams) -> Result<(), ServerError> {
+    if let Some(minimum) = &auction.min_bid_amount {
+        if &request.token_amount < minimum {
+            return Err(ServerError::BidBelowMinimum {
+                amount:  request.token_amount.clone(),
+                minimum: minimum.clone(),
+            });
+        }
+    }
+    if let Some(maximum) = &auction.max_bid_amount {
+        if &request.token_amount > maximum {
+            return Err(ServerError::BidAboveMaximum {
+                amount:  request.token_amount.clone(),
+                maximum: maximum.clone(),
+            });
+        }
+    }
+    Ok(())
+}
 
-    let context = ContractContext::new_from_payload(
-        state.key.address,
-        Energy { energy: ENERGY },
-        payload.clone(),
+/// Check that the sponsored transfer of the bid can succeed by querying the
+/// token contract of the auction. The check rejects the bid if:
+/// - the signer is neither the owner of the tokens (`from`) nor an operator of
+///   the owner.
+/// - the balance of the owner is below the bid amount.
+async fn check_allowance(
+    state: &mut Server,
+    auction: &AuctionConfig,
+    request: &BidParams,
+) -> Result<(), ServerError> {
+    let mut token_contract = Cis2Contract::new(
+        state.node_client.clone(),
+        auction.token_contract,
+        OwnedContractName::new_unchecked(format!("init_{CONTRACT_NAME}")),
     );
 
-    let info = state
-        .node_client
-        .invoke_instance(&BlockIdentifier::Best, &context)
-        .await;
-
-    let info = match info {
-        Ok(info) => info,
-        Err(e) => {
-            tracing::warn!("SimulationInvokeError {e}.");
-            return Err(ServerError::SimulationInvokeError(e));
-        }
-    };
-
-    let used_energy = match info.response {
-        InvokeContractResult::Success {
-            return_value: _,
-            events: _,
-            used_energy,
-        } => {
+    if request.signer != request.from {
+        let is_operator = token_contract
+            .operator_of_single(
+                &BlockIdentifier::Best,
+                Address::Account(request.from),
+                Address::Account(request.signer),
+            )
+            .await?;
+        if !is_operator {
             tracing::debug!(
-                "TransactionSimulationSuccess with used energy: {:#?}.",
-                used_energy
+                "Signer {} is not an operator of {}.",
+                request.signer,
+                request.from
             );
-            used_energy
-        }
-        InvokeContractResult::Failure {
-            return_value: _,
-            reason,
-            used_energy: _,
-        } => {
-            tracing::warn!("TransactionSimulationError with reason: {:#?}.", reason);
-            return Err(ServerError::TransactionSimulationError(RevertReason {
-                reason,
-            }));
+            return Err(ServerError::MissingOperator {
+                owner:  request.from,
+                signer: request.signer,
+            });
         }
-    };
-
-    // Transaction should expiry after one hour.
-    let transaction_expiry = TransactionTime::hours_after(1);
-
-    // Get the current nonce for the backend wallet and lock it. This is necessary
-    // since it is possible that API requests come in parallel. The nonce is
-    // increased by 1 and its lock is released after the transaction is submitted to
-    // the blockchain.
-    let mut nonce = state.nonce.lock().await;
-
-    // There should be rate limiting in place to prevent the sponsor wallet from
-    // being drained. We only allow up to RATE_LIMIT_PER_ACCOUNT API calls to
-    // this backend. The rate_limits are transient and are reset on server
-    // restart.
-
-    // We only check the rate_limits after acquiring the nonce lock. If we do it
-    // before we don't have guarantees due to possible parallel API requests.
-
-    // On mainnet, a user can only create around 25 accounts per identity.
-    // In production, a user registration/authentication at the frontend can be
-    // added or a database that permanently keeps track of the rate_limits so
-    // that the server can be restarted and reload the rate_limit values from the
-    // database.
-
-    tracing::debug!("Check rate limit of account {} ...", request.signer);
-
-    let mut rate_limits = state.rate_limits.lock().await;
-
-    // Account addresses on Concordium have account aliases. We track the
-    // rate-limits by using the alias 0 for every account. https://developer.concordium.software/en/mainnet/net/references/transactions.html#account-aliases
-    let alias_account_0 = request
-        .signer
-        .get_alias(0)
-        .ok_or_else(|| ServerError::NoAliasAccount)?;
-
-    let limit = rate_limits.entry(alias_account_0).or_insert(0u8);
-
-    if *limit >= RATE_LIMIT_PER_ACCOUNT {
-        tracing::warn!("Rate limit for account {} reached.", request.signer);
-        return Err(ServerError::RateLimitError);
     }
 
-    *limit += 1;
-
-    let tx = transactions::send::make_and_sign_transaction(
-        &state.key.keys,
-        state.key.address,
-        *nonce,
-        transaction_expiry,
-        // We add a small amount of energy `EPSILON_ENERGY` to the previously simulated
-        // `used_energy` to cover variations (e.g. smart contract state changes) caused by
-        // transactions that have been executed meanwhile.
-        concordium_rust_sdk::types::transactions::send::GivenEnergy::Add(
-            used_energy + Energy::from(EPSILON_ENERGY),
-        ),
-        concordium_rust_sdk::types::transactions::Payload::Update { payload },
-    );
-
-    let bi = transactions::BlockItem::AccountTransaction(tx);
-
-    match state.node_client.send_block_item(&bi).await {
-        Ok(hash) => {
-            tracing::debug!("Submit transaction {} ...", hash);
+    let balance = token_contract
+        .balance_of_single(
+            &BlockIdentifier::Best,
+            request.token_id.clone(),
+            Address::Account(request.from),
+        )
+        .await?;
+    if balance < request.token_amount {
+        tracing::debug!(
+            "Balance {} of {} is below the bid amount {}.",
+            balance,
+            request.from,
+            request.token_amount
+        );
+        return Err(ServerError::InsufficientBalance {
+            owner: request.from,
+            token_id: request.token_id.clone(),
+            balance,
+            required: request.token_amount.clone(),
+        });
+    }
 
-            *nonce = nonce.next();
+    Ok(())
+}
 
-            Ok(hash.into())
-        }
-        Err(e) => {
-            tracing::warn!("SubmitSponsoredTransactionError {e}.");
-            Err(ServerError::SubmitSponsoredTransactionError(e))
-        }
-    }
+/// Serves the configuration of the frontend, so that the frontend can be built
+/// once and configured at runtime by the backend.
+#[tracing::instrument(level = "info", skip_all)]
+async fn ui_config(State(state): State<Server>) -> Json<UiConfig> {
+    Json(UiConfig {
+        auctions: state.auctions.auctions.clone(),
+        network:  state.network,
+        sponsor:  state.relayer.sponsor_address(),
+    })
 }
 
 #[derive(serde::Serialize)]
@@ -416,6 +702,44 @@ async fn health() -> Json<Health> {
     })
 }
 
+/// The build metadata returned by the `/info` endpoint. The git commit, build
+/// time, and enabled features are emitted at build time by `vergen` (see
+/// `build.rs`).
+#[derive(serde::Serialize)]
+struct Info {
+    /// The version of the backend.
+    version:    &'static str,
+    /// The git commit the backend was built from.
+    git_commit: &'static str,
+    /// The time the backend was built (RFC 3339).
+    build_time: &'static str,
+    /// The cargo features the backend was built with.
+    features:   Vec<&'static str>,
+    /// The auction and token contracts of all auctions whose bids are
+    /// sponsored.
+    contracts:  BTreeSet<ContractAddress>,
+}
+
+#[tracing::instrument(level = "info", skip_all)]
+async fn info(State(state): State<Server>) -> Json<Info> {
+    let contracts = state
+        .auctions
+        .auctions
+        .values()
+        .flat_map(|auction| [auction.auction_contract, auction.token_contract])
+        .collect();
+    Json(Info {
+        version: env!("CARGO_PKG_VERSION"),
+        git_commit: env!("VERGEN_GIT_SHA"),
+        build_time: env!("VERGEN_BUILD_TIMESTAMP"),
+        features: env!("VERGEN_CARGO_FEATURES")
+            .split(',')
+            .filter(|feature| !feature.is_empty())
+            .collect(),
+        contracts,
+    })
+}
+
 /// Construct a future for shutdown signals (for unix: SIGINT and SIGTERM) (for
 /// windows: ctrl c and ctrl break). The signal handler is set when the future
 /// is polled and until then the default signal handler.
@@ -0,0 +1,171 @@
+use axum::{extract::State, routing::get, Router};
+use concordium_rust_sdk::{smart_contracts::common::AccountAddress, types::Nonce, v2};
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry,
+    TextEncoder,
+};
+use std::{future::Future, sync::Arc};
+use tokio::sync::Mutex;
+
+/// Prometheus metrics of the backend.
+#[derive(Clone)]
+pub struct Metrics {
+    /// The registry all metrics are registered in.
+    registry:                    Registry,
+    /// The difference between the nonce of the sponsorer account tracked by
+    /// this backend and the next sequence number of the account on chain.
+    pub nonce_lag:               IntGauge,
+    /// The time requests spend waiting to acquire the nonce mutex.
+    pub nonce_lock_wait_seconds: Histogram,
+    /// The number of operations querying the node by operation and outcome.
+    node_requests:               IntCounterVec,
+    /// The time operations querying the node take by operation.
+    node_request_seconds:        HistogramVec,
+}
+
+impl std::fmt::Debug for Metrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Metrics").finish_non_exhaustive()
+    }
+}
+
+impl Metrics {
+    /// Create the metrics and register them in a new registry. The node
+    /// usage metrics are labeled with the `service` name, so that the load of
+    /// several backends using the same node can be told apart.
+    pub fn new(service: &str) -> anyhow::Result<Self> {
+        let registry = Registry::new();
+
+        let nonce_lag = IntGauge::new(
+            "sponsor_nonce_lag",
+            "Difference between the nonce of the sponsorer account tracked by the backend and the \
+             next sequence number of the account on chain.",
+        )?;
+        registry.register(Box::new(nonce_lag.clone()))?;
+
+        let nonce_lock_wait_seconds = Histogram::with_opts(
+            HistogramOpts::new(
+                "sponsor_nonce_lock_wait_seconds",
+                "Time requests spend waiting to acquire the lock on the nonce of the sponsorer \
+                 account.",
+            )
+            .buckets(vec![
+                0.001, 0.005, 0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+            ]),
+        )?;
+        registry.register(Box::new(nonce_lock_wait_seconds.clone()))?;
+
+        let node_requests = IntCounterVec::new(
+            Opts::new(
+                "sponsor_node_requests_total",
+                "Number of operations of the backend querying the node.",
+            )
+            .const_label("service", service),
+            &["operation", "outcome"],
+        )?;
+        registry.register(Box::new(node_requests.clone()))?;
+
+        let node_request_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "sponsor_node_request_duration_seconds",
+                "Time operations of the backend querying the node take.",
+            )
+            .const_label("service", service)
+            .buckets(vec![0.005, 0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0]),
+            &["operation"],
+        )?;
+        registry.register(Box::new(node_request_seconds.clone()))?;
+
+        Ok(Self {
+            registry,
+            nonce_lag,
+            nonce_lock_wait_seconds,
+            node_requests,
+            node_request_seconds,
+        })
+    }
+
+    /// Run the `operation` querying the node and record its duration and
+    /// whether it succeeded in the node usage metrics.
+    pub async fn observe_node_request<T, E>(
+        &self,
+        operation: &str,
+        request: impl Future<Output = Result<T, E>>,
+    ) -> Result<T, E> {
+        let start = std::time::Instant::now();
+        let result = request.await;
+        self.node_request_seconds
+            .with_label_values(&[operation])
+            .observe(start.elapsed().as_secs_f64());
+        let outcome = if result.is_ok() { "success" } else { "failure" };
+        self.node_requests
+            .with_label_values(&[operation, outcome])
+            .inc();
+        result
+    }
+}
+
+/// Serve the metrics in the Prometheus text format at `/metrics` on
+/// `listen_address`.
+pub async fn serve(listen_address: std::net::SocketAddr, metrics: Metrics) -> anyhow::Result<()> {
+    let router = Router::new()
+        .route("/metrics", get(handle_metrics))
+        .with_state(metrics);
+
+    tracing::info!("Prometheus metrics listening at {}", listen_address);
+
+    axum::Server::bind(&listen_address)
+        .serve(router.into_make_service())
+        .await?;
+
+    Ok(())
+}
+
+/// Handles the `metrics` endpoint, returning all registered metrics.
+async fn handle_metrics(State(metrics): State<Metrics>) -> Result<String, http::StatusCode> {
+    let mut buffer = Vec::new();
+    TextEncoder::new()
+        .encode(&metrics.registry.gather(), &mut buffer)
+        .map_err(|error| {
+            tracing::error!("Unable to encode metrics: {error}.");
+            http::StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    String::from_utf8(buffer).map_err(|_| http::StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Periodically compare the `nonce` tracked by this backend with the next
+/// sequence number of the sponsorer account on chain and record the
+/// difference in the `nonce_lag` gauge.
+pub async fn monitor_nonce_lag(
+    mut node_client: v2::Client,
+    sponsorer: AccountAddress,
+    nonce: Arc<Mutex<Nonce>>,
+    metrics: Metrics,
+    interval: std::time::Duration,
+) {
+    let mut interval = tokio::time::interval(interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    loop {
+        interval.tick().await;
+
+        let on_chain = match metrics
+            .observe_node_request(
+                "nonce_lag",
+                node_client.get_next_account_sequence_number(&sponsorer),
+            )
+            .await
+        {
+            Ok(response) => response.nonce,
+            Err(error) => {
+                tracing::warn!("Unable to query the nonce of the sponsorer account: {error}.");
+                continue;
+            }
+        };
+
+        let tracked = *nonce.lock().await;
+        metrics
+            .nonce_lag
+            .set(tracked.nonce as i64 - on_chain.nonce as i64);
+    }
+}
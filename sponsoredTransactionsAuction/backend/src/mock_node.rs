@@ -0,0 +1,449 @@
+//! A deterministic fake node for testing the backend without a chain. The mock
+//! node serves the subset of the GRPC V2 interface used by the backend on a
+//! local port, so that the retry, nonce, and rate-limit logic can be exercised
+//! in local runs and load tests. It is selected with `--node mock://...` and
+//! only available with the `mock-node` feature.
+//!
+//! The mock node accepts every signature and sponsored transaction. Its
+//! behavior is configured with the query parameters of the node URL, e.g.
+//! `mock://node?latency=200&fail-every=10&finalize-after=2000&drop-every=5`.
+use anyhow::Context;
+use concordium_rust_sdk::{
+    cis2::TokenAmount,
+    smart_contracts::common::to_bytes,
+    v2::generated::{
+        self, instance_info, invoke_instance_response, send_block_item_request,
+        versioned_module_source,
+    },
+};
+use futures::future::BoxFuture;
+use std::{
+    collections::HashMap,
+    future::Future,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context as TaskContext, Poll},
+    time::{Duration, Instant},
+};
+use tonic::{body::BoxBody, transport::Body, Status};
+
+/// The hash of the single block of the mock chain. It is returned as the block
+/// of every query.
+const MOCK_BLOCK_HASH: [u8; 32] = [0x11; 32];
+
+/// The configuration of the mock node parsed from the query parameters of the
+/// `mock://` URL.
+#[derive(Debug, Clone)]
+pub struct MockConfig {
+    /// The time every request takes before it is answered (`latency`).
+    pub latency:        Duration,
+    /// Every n-th request fails with `UNAVAILABLE` (`fail-every`). If `0`, no
+    /// failures are injected.
+    pub fail_every:     u64,
+    /// The time after which submitted transactions are finalized
+    /// (`finalize-after`).
+    pub finalize_after: Duration,
+    /// Every n-th submitted transaction is dropped instead of finalized
+    /// (`drop-every`). The next nonce of the sender is reset to the nonce of
+    /// the dropped transaction. If `0`, no transactions are dropped.
+    pub drop_every:     u64,
+    /// The next nonce of every account before any transaction is submitted
+    /// (`nonce`).
+    pub initial_nonce:  u64,
+    /// The token balance of every account returned by `balanceOf`
+    /// (`balance`).
+    pub balance:        TokenAmount,
+    /// Whether every account is an operator of every other account returned
+    /// by `operatorOf` (`operator`).
+    pub operator:       bool,
+    /// The energy used by every contract invocation (`energy`).
+    pub energy:         u64,
+}
+
+impl Default for MockConfig {
+    fn default() -> Self {
+        Self {
+            latency:        Duration::ZERO,
+            fail_every:     0,
+            finalize_after: Duration::ZERO,
+            drop_every:     0,
+            initial_nonce:  1,
+            balance:        TokenAmount::from(1_000_000u64),
+            operator:       true,
+            energy:         5000,
+        }
+    }
+}
+
+impl MockConfig {
+    /// Parse the configuration from the query parameters of the `mock://` URL.
+    /// Parameters that are not given keep their default value.
+    pub fn from_uri(uri: &http::Uri) -> anyhow::Result<Self> {
+        let mut config = Self::default();
+        for parameter in uri.query().unwrap_or_default().split('&') {
+            if parameter.is_empty() {
+                continue;
+            }
+            let (key, value) = parameter
+                .split_once('=')
+                .with_context(|| format!("Mock node parameter `{parameter}` has no value."))?;
+            match key {
+                "latency" => config.latency = Duration::from_millis(parse(key, value)?),
+                "fail-every" => config.fail_every = parse(key, value)?,
+                "finalize-after" => {
+                    config.finalize_after = Duration::from_millis(parse(key, value)?)
+                }
+                "drop-every" => config.drop_every = parse(key, value)?,
+                "nonce" => config.initial_nonce = parse(key, value)?,
+                "balance" => config.balance = parse(key, value)?,
+                "operator" => config.operator = parse(key, value)?,
+                "energy" => config.energy = parse(key, value)?,
+                _ => anyhow::bail!("Unknown mock node parameter `{key}`."),
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// Parse the value of the mock node parameter `key`.
+fn parse<T: FromStr>(key: &str, value: &str) -> anyhow::Result<T> {
+    value
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid value `{value}` of mock node parameter `{key}`."))
+}
+
+/// A transaction that was submitted but is not finalized yet.
+#[derive(Debug)]
+struct PendingTransaction {
+    nonce:        u64,
+    submitted_at: Instant,
+    /// Whether the transaction is dropped instead of finalized.
+    dropped:      bool,
+}
+
+/// The state of an account on the mock chain.
+#[derive(Debug)]
+struct MockAccount {
+    /// The next nonce of the account including pending transactions.
+    next_nonce: u64,
+    pending:    Vec<PendingTransaction>,
+}
+
+impl MockAccount {
+    /// Finalize or drop the pending transactions that were submitted at least
+    /// `finalize_after` ago. If a transaction is dropped, all later
+    /// transactions of the account are dropped as well, since their nonces
+    /// can no longer be used.
+    fn settle(&mut self, finalize_after: Duration) {
+        let settled = self
+            .pending
+            .iter()
+            .take_while(|transaction| transaction.submitted_at.elapsed() >= finalize_after)
+            .count();
+        let dropped = self.pending[..settled]
+            .iter()
+            .find(|transaction| transaction.dropped)
+            .map(|transaction| transaction.nonce);
+        if let Some(nonce) = dropped {
+            tracing::info!("Mock node dropped transaction with nonce {nonce}.");
+            self.next_nonce = nonce;
+            self.pending.clear();
+        } else {
+            self.pending.drain(..settled);
+        }
+    }
+}
+
+/// The state shared by all requests to the mock node.
+#[derive(Debug)]
+struct MockState {
+    config:       MockConfig,
+    /// The number of requests received so far.
+    requests:     AtomicU64,
+    /// The number of transactions accepted so far.
+    transactions: AtomicU64,
+    accounts:     Mutex<HashMap<Vec<u8>, MockAccount>>,
+}
+
+/// The GRPC service of the mock node.
+#[derive(Debug, Clone)]
+pub struct MockNode {
+    state: Arc<MockState>,
+}
+
+impl tonic::server::NamedService for MockNode {
+    const NAME: &'static str = "concordium.v2.Queries";
+}
+
+/// Start the mock node on a free local port and return its address.
+pub async fn spawn(config: MockConfig) -> anyhow::Result<std::net::SocketAddr> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .context("Unable to bind the mock node.")?;
+    let address = listener.local_addr()?;
+    tracing::warn!("Serving a mock node with {config:?} at {address}.");
+
+    let node = MockNode {
+        state: Arc::new(MockState {
+            config,
+            requests: AtomicU64::new(0),
+            transactions: AtomicU64::new(0),
+            accounts: Mutex::new(HashMap::new()),
+        }),
+    };
+    let server = tonic::transport::Server::builder()
+        .add_service(node)
+        .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener));
+    tokio::spawn(async move {
+        if let Err(error) = server.await {
+            tracing::error!("Mock node stopped: {error}.");
+        }
+    });
+
+    Ok(address)
+}
+
+impl tower::Service<http::Request<Body>> for MockNode {
+    type Error = std::convert::Infallible;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+    type Response = http::Response<BoxBody>;
+
+    fn poll_ready(&mut self, _cx: &mut TaskContext<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<Body>) -> Self::Future {
+        let state = self.state.clone();
+        let method = request
+            .uri()
+            .path()
+            .trim_start_matches("/concordium.v2.Queries/")
+            .to_owned();
+        Box::pin(async move {
+            let response = match method.as_str() {
+                "GetConsensusInfo" => unary(state, request, consensus_info).await,
+                "GetNextAccountSequenceNumber" => {
+                    unary(state, request, next_account_sequence_number).await
+                }
+                "GetInstanceInfo" => unary(state, request, instance_info).await,
+                "GetModuleSource" => unary(state, request, module_source).await,
+                "InvokeInstance" => unary(state, request, invoke_instance).await,
+                "SendBlockItem" => unary(state, request, send_block_item).await,
+                _ => Status::unimplemented(format!("The mock node does not support `{method}`."))
+                    .to_http(),
+            };
+            Ok(response)
+        })
+    }
+}
+
+/// Answer a unary GRPC request with `handler` after the configured latency,
+/// unless a failure is injected.
+async fn unary<Req, Res, F, Fut>(
+    state: Arc<MockState>,
+    request: http::Request<Body>,
+    handler: F,
+) -> http::Response<BoxBody>
+where
+    Req: prost::Message + Default + Send + 'static,
+    Res: prost::Message + Send + 'static,
+    F: Fn(Arc<MockState>, Req) -> Fut + Clone + Send + 'static,
+    Fut: Future<Output = Result<Res, Status>> + Send + 'static, {
+    let service = tower::service_fn(move |request: tonic::Request<Req>| {
+        let state = state.clone();
+        let handler = handler.clone();
+        async move {
+            tokio::time::sleep(state.config.latency).await;
+            let count = state.requests.fetch_add(1, Ordering::SeqCst) + 1;
+            if state.config.fail_every > 0 && count % state.config.fail_every == 0 {
+                tracing::info!("Mock node injected failure of request {count}.");
+                return Err(Status::unavailable("Injected failure of the mock node."));
+            }
+            let mut response = tonic::Response::new(handler(state, request.into_inner()).await?);
+            response.metadata_mut().insert(
+                "blockhash",
+                MOCK_BLOCK_HASH
+                    .iter()
+                    .map(|byte| format!("{byte:02x}"))
+                    .collect::<String>()
+                    .parse()
+                    .map_err(|_| Status::internal("Invalid block hash metadata."))?,
+            );
+            Ok(response)
+        }
+    });
+    let mut grpc = tonic::server::Grpc::new(tonic::codec::ProstCodec::<Res, Req>::default());
+    grpc.unary(service, request).await
+}
+
+fn block_hash() -> Option<generated::BlockHash> {
+    Some(generated::BlockHash {
+        value: MOCK_BLOCK_HASH.to_vec(),
+    })
+}
+
+async fn consensus_info(
+    _state: Arc<MockState>,
+    _request: generated::Empty,
+) -> Result<generated::ConsensusInfo, Status> {
+    Ok(generated::ConsensusInfo {
+        best_block: block_hash(),
+        genesis_block: block_hash(),
+        current_era_genesis_block: block_hash(),
+        last_finalized_block: block_hash(),
+        genesis_time: Some(generated::Timestamp { value: 0 }),
+        current_era_genesis_time: Some(generated::Timestamp { value: 0 }),
+        epoch_duration: Some(generated::Duration { value: 3_600_000 }),
+        best_block_height: Some(generated::AbsoluteBlockHeight { value: 0 }),
+        last_finalized_block_height: Some(generated::AbsoluteBlockHeight { value: 0 }),
+        genesis_index: Some(generated::GenesisIndex { value: 0 }),
+        ..Default::default()
+    })
+}
+
+async fn next_account_sequence_number(
+    state: Arc<MockState>,
+    request: generated::AccountAddress,
+) -> Result<generated::NextAccountSequenceNumber, Status> {
+    let mut accounts = state.accounts.lock().unwrap();
+    let account = accounts
+        .entry(request.value)
+        .or_insert_with(|| MockAccount {
+            next_nonce: state.config.initial_nonce,
+            pending:    Vec::new(),
+        });
+    account.settle(state.config.finalize_after);
+    Ok(generated::NextAccountSequenceNumber {
+        sequence_number: Some(generated::SequenceNumber {
+            value: account.next_nonce,
+        }),
+        all_final:       account.pending.is_empty(),
+    })
+}
+
+async fn instance_info(
+    _state: Arc<MockState>,
+    _request: generated::InstanceInfoRequest,
+) -> Result<generated::InstanceInfo, Status> {
+    Ok(generated::InstanceInfo {
+        version: Some(instance_info::Version::V1(instance_info::V1 {
+            owner:         Some(generated::AccountAddress { value: vec![0; 32] }),
+            amount:        Some(generated::Amount { value: 0 }),
+            methods:       Vec::new(),
+            name:          Some(generated::InitName {
+                value: format!("init_{}", crate::CONTRACT_NAME),
+            }),
+            source_module: Some(generated::ModuleRef { value: vec![0; 32] }),
+        })),
+    })
+}
+
+async fn module_source(
+    _state: Arc<MockState>,
+    _request: generated::ModuleSourceRequest,
+) -> Result<generated::VersionedModuleSource, Status> {
+    // The module has no embedded schema, so reject reasons are not decoded.
+    Ok(generated::VersionedModuleSource {
+        module: Some(versioned_module_source::Module::V1(
+            versioned_module_source::ModuleSourceV1 { value: Vec::new() },
+        )),
+    })
+}
+
+/// Answer the CIS-2 queries of the backend with the configured balance and
+/// operator status. All other invocations succeed without a return value.
+async fn invoke_instance(
+    state: Arc<MockState>,
+    request: generated::InvokeInstanceRequest,
+) -> Result<generated::InvokeInstanceResponse, Status> {
+    let receive_name = request
+        .entrypoint
+        .map(|name| name.value)
+        .unwrap_or_default();
+    let return_value = match receive_name
+        .split_once('.')
+        .map(|(_, entrypoint)| entrypoint)
+    {
+        // The responses contain a single entry, since the backend only queries
+        // one token or operator at a time.
+        Some("balanceOf") => {
+            let mut response = to_bytes(&1u16);
+            response.extend(to_bytes(&state.config.balance));
+            response
+        }
+        Some("operatorOf") => {
+            let mut response = to_bytes(&1u16);
+            response.extend(to_bytes(&state.config.operator));
+            response
+        }
+        _ => Vec::new(),
+    };
+    Ok(generated::InvokeInstanceResponse {
+        result: Some(invoke_instance_response::Result::Success(
+            invoke_instance_response::Success {
+                return_value: Some(return_value),
+                used_energy:  Some(generated::Energy {
+                    value: state.config.energy,
+                }),
+                effects:      Vec::new(),
+            },
+        )),
+    })
+}
+
+/// Accept an account transaction if its nonce is the next nonce of the sender.
+/// Transactions with other nonces are rejected like a node rejects transactions
+/// with a duplicate nonce.
+async fn send_block_item(
+    state: Arc<MockState>,
+    request: generated::SendBlockItemRequest,
+) -> Result<generated::TransactionHash, Status> {
+    let Some(send_block_item_request::BlockItem::AccountTransaction(transaction)) =
+        request.block_item
+    else {
+        return Err(Status::unimplemented(
+            "The mock node only supports account transactions.",
+        ));
+    };
+    let header = transaction
+        .header
+        .ok_or_else(|| Status::invalid_argument("Missing transaction header."))?;
+    let sender = header
+        .sender
+        .ok_or_else(|| Status::invalid_argument("Missing transaction sender."))?;
+    let nonce = header
+        .sequence_number
+        .ok_or_else(|| Status::invalid_argument("Missing transaction nonce."))?
+        .value;
+
+    let mut accounts = state.accounts.lock().unwrap();
+    let account = accounts.entry(sender.value).or_insert_with(|| MockAccount {
+        next_nonce: state.config.initial_nonce,
+        pending:    Vec::new(),
+    });
+    account.settle(state.config.finalize_after);
+    if nonce != account.next_nonce {
+        return Err(Status::invalid_argument(format!(
+            "Invalid nonce {nonce}, the next nonce of the sender is {}.",
+            account.next_nonce
+        )));
+    }
+
+    let count = state.transactions.fetch_add(1, Ordering::SeqCst) + 1;
+    account.next_nonce += 1;
+    account.pending.push(PendingTransaction {
+        nonce,
+        submitted_at: Instant::now(),
+        dropped: state.config.drop_every > 0 && count % state.config.drop_every == 0,
+    });
+
+    // The hash only has to be unique on the mock chain.
+    let mut hash = [0u8; 32];
+    hash[..8].copy_from_slice(&count.to_be_bytes());
+    Ok(generated::TransactionHash {
+        value: hash.to_vec(),
+    })
+}
@@ -0,0 +1,119 @@
+use concordium_rust_sdk::types::Energy;
+use concordium_sponsor_relayer::{PermitParam, PolicyError, SponsorPolicy};
+use std::{collections::BTreeMap, path::PathBuf, sync::Arc, time::SystemTime};
+use tokio::sync::RwLock;
+
+/// Policy limiting the energy that is sponsored per entrypoint. The entrypoint
+/// is the entrypoint of the action that is sponsored, e.g. `bid` for a bid in
+/// the auction, even though the transaction submitted by the sponsorer invokes
+/// the `permit` entrypoint of the token contract.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct EnergyPolicy {
+    /// The maximum energy sponsored for an entrypoint that is not listed in
+    /// `entrypoints`. If not set, the energy of such entrypoints is not
+    /// limited by the policy.
+    #[serde(default)]
+    pub default_max_energy: Option<u64>,
+    /// The maximum energy sponsored per entrypoint name.
+    #[serde(default)]
+    pub entrypoints:        BTreeMap<String, u64>,
+}
+
+impl EnergyPolicy {
+    /// The maximum energy sponsored for the `entrypoint`, if limited.
+    pub fn max_energy(&self, entrypoint: &str) -> Option<u64> {
+        self.entrypoints
+            .get(entrypoint)
+            .copied()
+            .or(self.default_max_energy)
+    }
+
+    /// Read the policy from the JSON file at `path`.
+    pub fn from_file(path: &std::path::Path) -> anyhow::Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+}
+
+/// Sponsor policy rejecting requests whose simulated energy exceeds the energy
+/// budget of the sponsored `entrypoint` in the energy policy.
+#[derive(Debug, Clone)]
+pub struct EnergyBudget {
+    /// The energy policy, which is reloaded at runtime.
+    pub policy:     Arc<RwLock<EnergyPolicy>>,
+    /// The entrypoint of the action that is sponsored.
+    pub entrypoint: &'static str,
+}
+
+#[concordium_sponsor_relayer::async_trait]
+impl SponsorPolicy for EnergyBudget {
+    async fn check_submission(
+        &self,
+        _permit: &PermitParam,
+        used_energy: Energy,
+    ) -> Result<(), PolicyError> {
+        let max_energy = self.policy.read().await.max_energy(self.entrypoint);
+        if let Some(max_energy) = max_energy {
+            if used_energy.energy > max_energy {
+                tracing::warn!(
+                    "Energy budget of entrypoint {} exceeded: used {}, max {}.",
+                    self.entrypoint,
+                    used_energy.energy,
+                    max_energy
+                );
+                return Err(PolicyError::EnergyBudgetExceeded {
+                    entrypoint: self.entrypoint.to_string(),
+                    used_energy: used_energy.energy,
+                    max_energy,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Periodically check the modification time of the policy file at `path` and
+/// reload the `policy` if the file changed. If the file cannot be read or
+/// parsed, the error is logged and the previous policy stays in place.
+pub async fn reload_policy(
+    path: PathBuf,
+    policy: Arc<RwLock<EnergyPolicy>>,
+    interval: std::time::Duration,
+) {
+    let mut interval = tokio::time::interval(interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    let mut last_modified = modified(&path);
+
+    loop {
+        interval.tick().await;
+
+        let modified = modified(&path);
+        if modified == last_modified {
+            continue;
+        }
+        last_modified = modified;
+
+        match EnergyPolicy::from_file(&path) {
+            Ok(new_policy) => {
+                tracing::info!("Reloaded the energy policy from {}.", path.display());
+                *policy.write().await = new_policy;
+            }
+            Err(error) => {
+                tracing::error!(
+                    "Unable to reload the energy policy from {}: {error}. Keeping the previous \
+                     policy.",
+                    path.display()
+                );
+            }
+        }
+    }
+}
+
+/// The modification time of the file at `path`, if available.
+fn modified(path: &std::path::Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
@@ -0,0 +1,233 @@
+//! Scheduling of the bids that arrive in the closing window of an auction.
+//! During a sniping storm, bids submitted directly race for the nonce of the
+//! sponsorer account and are submitted in the order they acquire it. Instead,
+//! bids in the closing window are acknowledged with a ticket and submitted one
+//! at a time by a single task in the order they arrived. If several auctions
+//! close at the same time, the bids of the auction closing first are submitted
+//! first.
+use crate::types::Server;
+use concordium_rust_sdk::{smart_contracts::common::Timestamp, types::hashes::TransactionHash};
+use concordium_sponsor_relayer::PermitParam;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::Notify;
+
+/// The maximum number of tickets whose outcome is kept after their bid was
+/// submitted. The outcomes of older tickets are forgotten.
+const MAX_FINISHED_TICKETS: usize = 10_000;
+
+/// The state of a ticket returned by the `/api/bid` and `/api/bidTicket`
+/// endpoints.
+#[derive(serde::Serialize, Debug, Clone)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum TicketState {
+    /// The bid waits for its submission. The `position` is the number of bids
+    /// of the same auction that are submitted before it.
+    Queued { position: usize },
+    /// The bid is being submitted.
+    Submitting,
+    /// The bid was submitted in the transaction with the `transaction_hash`.
+    Submitted { transaction_hash: TransactionHash },
+    /// The submission of the bid failed with the `error`.
+    Failed { error: String },
+    /// The auction closed before the bid was submitted.
+    Expired,
+}
+
+/// A ticket acknowledging a scheduled bid.
+#[derive(serde::Serialize, Debug, Clone)]
+pub struct BidTicket {
+    /// The number of the ticket. Tickets are numbered in the order the bids
+    /// arrived.
+    pub ticket:     u64,
+    /// The id of the auction the bid is placed in.
+    pub auction_id: String,
+    /// The state of the ticket.
+    #[serde(flatten)]
+    pub state:      TicketState,
+}
+
+/// A bid waiting for its submission.
+#[derive(Debug)]
+struct ScheduledBid {
+    ticket: u64,
+    param:  PermitParam,
+}
+
+/// The bids of an auction waiting for their submission in arrival order.
+#[derive(Debug)]
+struct AuctionQueue {
+    /// The end of the auction.
+    end:  Timestamp,
+    bids: VecDeque<ScheduledBid>,
+}
+
+/// The scheduled bids and the outcomes of the tickets.
+#[derive(Debug, Default)]
+struct SchedulerState {
+    /// The number of the next ticket.
+    next_ticket: u64,
+    /// The queues of scheduled bids by auction id.
+    queues:      BTreeMap<String, AuctionQueue>,
+    /// The states of the tickets whose bids left the queue.
+    finished:    BTreeMap<u64, BidTicket>,
+}
+
+/// Accepts the bids arriving in the closing window of an auction and submits
+/// them in arrival order. Cloning the scheduler is cheap and shares the queue.
+#[derive(Debug, Clone)]
+pub struct BidScheduler {
+    /// The time before the end of an auction in which bids are scheduled.
+    window:   Duration,
+    state:    Arc<Mutex<SchedulerState>>,
+    /// Notifies the submission task about newly scheduled bids.
+    notifier: Arc<Notify>,
+}
+
+impl BidScheduler {
+    /// Create a scheduler for the bids arriving at most `window` before the
+    /// end of their auction.
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            state: Arc::default(),
+            notifier: Arc::default(),
+        }
+    }
+
+    /// Whether a bid arriving at `now` in an auction ending at `end` is in the
+    /// closing window and should be scheduled.
+    pub fn is_closing(&self, end: Timestamp, now: Timestamp) -> bool {
+        now < end
+            && end.timestamp_millis() - now.timestamp_millis() <= self.window.as_millis() as u64
+    }
+
+    /// Schedule the bid with the `param` in the auction with the `auction_id`
+    /// ending at `end`, returning its ticket.
+    pub fn schedule(&self, auction_id: String, end: Timestamp, param: PermitParam) -> BidTicket {
+        let mut state = self.state.lock().expect("The lock is never poisoned");
+        let ticket = state.next_ticket;
+        state.next_ticket += 1;
+        let queue = state
+            .queues
+            .entry(auction_id.clone())
+            .or_insert_with(|| AuctionQueue {
+                end,
+                bids: VecDeque::new(),
+            });
+        queue.bids.push_back(ScheduledBid { ticket, param });
+        let position = queue.bids.len() - 1;
+        drop(state);
+        self.notifier.notify_one();
+
+        tracing::debug!("Scheduled bid with ticket {ticket} at position {position}.");
+        BidTicket {
+            ticket,
+            auction_id,
+            state: TicketState::Queued { position },
+        }
+    }
+
+    /// The current state of the `ticket`, or `None` if the ticket is unknown
+    /// or its outcome was forgotten.
+    pub fn ticket(&self, ticket: u64) -> Option<BidTicket> {
+        let state = self.state.lock().expect("The lock is never poisoned");
+        if let Some(finished) = state.finished.get(&ticket) {
+            return Some(finished.clone());
+        }
+        state.queues.iter().find_map(|(auction_id, queue)| {
+            let position = queue.bids.iter().position(|bid| bid.ticket == ticket)?;
+            Some(BidTicket {
+                ticket,
+                auction_id: auction_id.clone(),
+                state: TicketState::Queued { position },
+            })
+        })
+    }
+
+    /// Take the next bid to submit, which is the earliest bid of the auction
+    /// ending first.
+    fn next_bid(&self) -> Option<(String, Timestamp, ScheduledBid)> {
+        let mut state = self.state.lock().expect("The lock is never poisoned");
+        let auction_id = state
+            .queues
+            .iter()
+            .min_by_key(|(_, queue)| queue.end)
+            .map(|(auction_id, _)| auction_id.clone())?;
+        let queue = state.queues.get_mut(&auction_id)?;
+        let end = queue.end;
+        let bid = queue.bids.pop_front()?;
+        if queue.bids.is_empty() {
+            state.queues.remove(&auction_id);
+        }
+        state.finished.insert(bid.ticket, BidTicket {
+            ticket:     bid.ticket,
+            auction_id: auction_id.clone(),
+            state:      TicketState::Submitting,
+        });
+        Some((auction_id, end, bid))
+    }
+
+    /// Record the outcome of the submission of the `ticket`.
+    fn finish(&self, ticket: BidTicket) {
+        let mut state = self.state.lock().expect("The lock is never poisoned");
+        state.finished.insert(ticket.ticket, ticket);
+        while state.finished.len() > MAX_FINISHED_TICKETS {
+            state.finished.pop_first();
+        }
+    }
+
+    /// Submit the scheduled bids one at a time in arrival order with the
+    /// relayer of the `server`. Bids whose auction closed before their
+    /// submission are not submitted.
+    pub async fn run(self, server: Server) {
+        loop {
+            // The future is created before the queue is checked, so that a bid
+            // scheduled in between is not missed.
+            let scheduled = self.notifier.notified();
+            let Some((auction_id, end, bid)) = self.next_bid() else {
+                scheduled.await;
+                continue;
+            };
+
+            let state = if crate::now() >= end {
+                tracing::debug!("Auction closed before the bid with ticket {}.", bid.ticket);
+                TicketState::Expired
+            } else {
+                let submission = server.relayer.submit(&bid.param, server.policy.as_ref());
+                match server
+                    .metrics
+                    .observe_node_request("submit", submission)
+                    .await
+                {
+                    Ok(transaction_hash) => {
+                        if server.trace_finalization {
+                            crate::telemetry::trace_finalization(
+                                server.node_client.clone(),
+                                transaction_hash,
+                            );
+                        }
+                        TicketState::Submitted { transaction_hash }
+                    }
+                    Err(error) => {
+                        tracing::warn!(
+                            "Unable to submit the bid with ticket {}: {error}.",
+                            bid.ticket
+                        );
+                        TicketState::Failed {
+                            error: error.to_string(),
+                        }
+                    }
+                }
+            };
+            self.finish(BidTicket {
+                ticket: bid.ticket,
+                auction_id,
+                state,
+            });
+        }
+    }
+}
@@ -0,0 +1,82 @@
+//! Export of the tracing spans via OTLP. The spans of a sponsored bid (the
+//! request handler, the phases of the submission recorded by the relayer, and
+//! the wait for the finalization of the transaction) are exported to an
+//! OpenTelemetry collector, so that the latency of every phase can be
+//! inspected per transaction.
+use anyhow::Context;
+use concordium_rust_sdk::{types::hashes::TransactionHash, v2};
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use tracing::Instrument;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Create a layer exporting the spans to the OTLP collector at the `endpoint`
+/// via gRPC. The spans are exported in batches and attributed to the
+/// `service_name`.
+pub fn otlp_layer<S>(
+    endpoint: &str,
+    service_name: &str,
+) -> anyhow::Result<tracing_opentelemetry::OpenTelemetryLayer<S, opentelemetry_sdk::trace::Tracer>>
+where
+    S: tracing::Subscriber + for<'span> LookupSpan<'span>, {
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint),
+        )
+        .with_trace_config(opentelemetry_sdk::trace::config().with_resource(
+            opentelemetry_sdk::Resource::new(vec![
+                KeyValue::new("service.name", service_name.to_owned()),
+                KeyValue::new("service.version", env!("CARGO_PKG_VERSION")),
+            ]),
+        ))
+        .install_batch(opentelemetry_sdk::runtime::Tokio)
+        .context("Unable to install the OTLP exporter.")?;
+    Ok(tracing_opentelemetry::layer().with_tracer(tracer))
+}
+
+/// Export the spans that have not been exported yet. Called before the backend
+/// exits.
+pub fn shutdown() { opentelemetry::global::shutdown_tracer_provider(); }
+
+/// Record the wait for the finalization of the transaction with the `tx_hash`
+/// in a span in the background. The span is a root span linked to the current
+/// span, so that it does not extend the span of the request that submitted the
+/// transaction.
+pub fn trace_finalization(node_client: v2::Client, tx_hash: TransactionHash) {
+    let span = tracing::info_span!(
+        parent: None,
+        "wait_for_finalization",
+        transaction_hash = %tx_hash,
+        block_hash = tracing::field::Empty,
+        outcome = tracing::field::Empty,
+    );
+    span.follows_from(tracing::Span::current());
+    tokio::spawn(wait_for_finalization(node_client, tx_hash).instrument(span));
+}
+
+/// Wait until the transaction with the `tx_hash` is finalized and record the
+/// block and the outcome in the current span.
+async fn wait_for_finalization(mut node_client: v2::Client, tx_hash: TransactionHash) {
+    let span = tracing::Span::current();
+    match node_client.wait_until_finalized(&tx_hash).await {
+        Ok((block_hash, summary)) => {
+            span.record("block_hash", tracing::field::display(block_hash));
+            match summary.is_rejected_account_transaction() {
+                Some(reason) => {
+                    span.record("outcome", "rejected");
+                    tracing::debug!("Transaction {tx_hash} was rejected: {reason:?}.");
+                }
+                None => {
+                    span.record("outcome", "success");
+                }
+            }
+        }
+        Err(error) => {
+            span.record("outcome", "unknown");
+            tracing::debug!("Unable to wait for the finalization of {tx_hash}: {error}.");
+        }
+    }
+}
@@ -1,67 +1,90 @@
+use crate::{
+    auctions::{AuctionConfig, AuctionRegistry, DEFAULT_AUCTION_ID},
+    metrics::Metrics,
+    policy::EnergyBudget,
+    scheduler::{BidScheduler, BidTicket},
+};
 use axum::{extract::rejection::JsonRejection, Json};
 use concordium_rust_sdk::{
-    cis2::{TokenAmount, TokenId, Transfer},
+    cis2::{Cis2QueryError, TokenAmount, TokenId, Transfer},
     smart_contracts::{
         common as concordium_std,
-        common::{
-            AccountAddress, AccountSignatures, ContractAddress, OwnedEntrypointName, Serial,
-            Timestamp,
-        },
+        common::{AccountAddress, Serial, Timestamp},
     },
-    types::{Nonce, RejectReason, WalletAccount},
-    v2::{self, QueryError, RPCError},
+    types::hashes::TransactionHash,
+    v2,
+    web3id::did::Network,
 };
-use hex::FromHexError;
+use concordium_sponsor_relayer::{AccountQuota, Relayer, SponsorError};
 use http::StatusCode;
-use std::{collections::HashMap, sync::Arc};
-use tokio::sync::Mutex;
+use std::{collections::BTreeMap, sync::Arc};
 
 #[derive(Debug, thiserror::Error)]
 pub enum ServerError {
     #[error("Unable to parse request: {0}.")]
     InvalidRequest(#[from] JsonRejection),
-    #[error("Unable to parse signature into a hex string: {0}.")]
-    SignatureError(#[from] FromHexError),
-    #[error("Unable to parse signature because it wasn't 64 bytes long.")]
-    SignatureLengthError,
-    #[error("Unable to create parameter.")]
-    ParameterError,
-    #[error("Unable to invoke the node to simulate the transaction: {0}.")]
-    SimulationInvokeError(#[from] QueryError),
-    #[error("Simulation of transaction reverted in smart contract with reason: {0:?}.")]
-    TransactionSimulationError(RevertReason),
-    #[error("The signer account reached its rate limit.")]
-    RateLimitError,
-    #[error("Unable to submit transaction on chain successfully: {0}.")]
-    SubmitSponsoredTransactionError(#[from] RPCError),
-    #[error("Unable to derive alias account of signer.")]
-    NoAliasAccount,
+    #[error("{0}")]
+    Sponsor(#[from] SponsorError),
+    #[error("Unable to query the token contract: {0}.")]
+    TokenQueryError(#[from] Cis2QueryError),
+    #[error(
+        "The signer {signer} is not an operator of the account {owner} and cannot transfer its \
+         tokens. Sign the bid with the account {owner} or add {signer} as an operator of {owner} \
+         with the `updateOperator` function of the token contract first."
+    )]
+    MissingOperator {
+        owner:  AccountAddress,
+        signer: AccountAddress,
+    },
+    #[error(
+        "The account {owner} owns {balance} tokens with token id {token_id} but the bid requires \
+         {required} tokens. Mint or receive more tokens before bidding."
+    )]
+    InsufficientBalance {
+        owner:    AccountAddress,
+        token_id: TokenId,
+        balance:  TokenAmount,
+        required: TokenAmount,
+    },
+    #[error("The bid of {amount} tokens is below the minimum bid of {minimum} tokens.")]
+    BidBelowMinimum {
+        amount:  TokenAmount,
+        minimum: TokenAmount,
+    },
+    #[error("The bid of {amount} tokens is above the maximum bid of {maximum} tokens.")]
+    BidAboveMaximum {
+        amount:  TokenAmount,
+        maximum: TokenAmount,
+    },
+    #[error("The auction {0} does not exist.")]
+    UnknownAuction(String),
+    #[error("The auction {0} does not accept sponsored bids at this time.")]
+    AuctionNotOpen(String),
+    #[error("The bid ticket {0} does not exist.")]
+    UnknownTicket(u64),
 }
 
 impl axum::response::IntoResponse for ServerError {
     fn into_response(self) -> axum::response::Response {
         let r = match self {
-            ServerError::ParameterError => {
-                tracing::error!("Internal error: Unable to create parameter.");
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json("Unable to create parameter.".to_string()),
-                )
-            }
-            ServerError::SimulationInvokeError(error) => {
+            ServerError::Sponsor(error) if error.is_internal() => {
                 tracing::error!("Internal error: {error}.");
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
                     Json(format!("{}", error)),
                 )
             }
-            ServerError::SubmitSponsoredTransactionError(error) => {
+            ServerError::TokenQueryError(error) => {
                 tracing::error!("Internal error: {error}.");
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
                     Json(format!("{}", error)),
                 )
             }
+            error @ ServerError::UnknownTicket(_) => {
+                tracing::debug!("Not found: {error}.");
+                (StatusCode::NOT_FOUND, Json(format!("{}", error)))
+            }
             error => {
                 tracing::debug!("Bad request: {error}.");
                 (StatusCode::BAD_REQUEST, Json(format!("{}", error)))
@@ -71,17 +94,14 @@ impl axum::response::IntoResponse for ServerError {
     }
 }
 
-/// Struct to store the revert reason.
-#[derive(serde::Serialize, Debug)]
-pub struct RevertReason {
-    /// Smart contract revert reason.
-    pub reason: RejectReason,
-}
-
 /// Parameters passed from the front end to this back end when calling the API
 /// endpoint `/bid`.
 #[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
 pub struct BidParams {
+    /// The id of the auction in the auction registry of the back end. If not
+    /// given, the bid is placed in the auction with the id `default`.
+    #[serde(default = "default_auction_id")]
+    pub auction_id:         String,
     /// Wallet account that signed the `permit_message` at the front end.
     pub signer:             AccountAddress,
     /// Nonce (as stored in the state of the `cis2-token-smart-contract`) of the
@@ -106,62 +126,64 @@ pub struct BidParams {
     pub token_amount:       TokenAmount,
 }
 
-/// The parameters for the transfer function of a cis2 token.
-#[derive(Debug, Serial, Clone)]
-pub struct TransferParams(#[concordium(size_length = 2)] pub Vec<Transfer>);
+fn default_auction_id() -> String { DEFAULT_AUCTION_ID.to_string() }
 
-/// The parameters for the permit function of the cis3 standard.
-#[derive(Debug, Serial)]
-pub struct PermitParam {
-    /// Signature that the above account generated when it signed the
-    /// `permit_message` at the front end.
-    pub signature: AccountSignatures,
-    /// Wallet account that signed the `permit_message` at the front end.
-    pub signer:    AccountAddress,
-    /// The `permit_message` that the signer signed at the front end.
-    pub message:   PermitMessage,
+/// The response of the API endpoint `/bid`.
+#[derive(serde::Serialize, Debug)]
+#[serde(untagged)]
+pub enum BidResponse {
+    /// The hash of the transaction submitting the bid.
+    Submitted(TransactionHash),
+    /// The ticket of a bid in the closing window of the auction, which is
+    /// submitted by the scheduler in arrival order.
+    Scheduled(BidTicket),
 }
 
-/// Part of the parameters for the permit function of the cis3 standard.
+/// The parameters for the transfer function of a cis2 token.
 #[derive(Debug, Serial, Clone)]
-pub struct PermitMessage {
-    /// The contract_address that the signature is intended for.
-    pub contract_address: ContractAddress,
-    /// Nonce (as stored in the state of the `cis2-token-smart-contract`) of the
-    /// signer when it signed the `permit_message` at the front end.
-    /// The nonce prevents replay attacks.
-    pub nonce:            u64,
-    /// A timestamp to make signatures expire.
-    pub timestamp:        Timestamp,
-    /// The entry_point that the signature is intended for.
-    pub entry_point:      OwnedEntrypointName,
-    /// The serialized payload that should be forwarded to the `transfer`
-    /// function.
-    #[concordium(size_length = 2)]
-    pub payload:          Vec<u8>,
-}
+pub struct TransferParams(#[concordium(size_length = 2)] pub Vec<Transfer>);
 
-/// Server struct to store the contract addresses, the node client,
-/// the nonce and key of the sponsorer account, and the
-/// rate_limits of user accounts.
+/// Server struct to store the auctions, the node client, the relayer
+/// submitting the sponsored transactions, and the policy deciding which bids
+/// are sponsored.
 #[derive(Clone, Debug)]
 pub struct Server {
     /// Client to interact with the node.
-    pub node_client: v2::Client,
-    /// Key and address of the sponsorer account.
-    pub key: Arc<WalletAccount>,
-    /// Contract address of the auction contract.
-    pub auction_smart_contract: ContractAddress,
-    /// Contract address of the token contract.
-    pub cis2_token_smart_contract: ContractAddress,
-    /// Nonce of the sponsorer account.
-    pub nonce: Arc<Mutex<Nonce>>,
-    /// The rate limit value for each user account is incremented
-    /// every time this user account signs a `permit_message` at the front end
-    /// and the signature is submitted to the `bid` entry point of this back
-    /// end. This server only allows up to `RATE_LIMIT_PER_ACCOUNT`
-    /// transactions to be submitted with a signature generated from a given
-    /// user account. The rate limit values stored here are transient and
-    /// are reset on server restart.
-    pub rate_limits: Arc<Mutex<HashMap<AccountAddress, u8>>>,
+    pub node_client:        v2::Client,
+    /// Relayer submitting the sponsored transactions with the sponsorer
+    /// account and tracking its nonce.
+    pub relayer:            Relayer,
+    /// The metrics recording the node usage of the requests.
+    pub metrics:            Metrics,
+    /// The auctions whose bids are sponsored with their contracts, bid bounds,
+    /// and time windows.
+    pub auctions:           Arc<AuctionRegistry>,
+    /// The network of the node, if it is mainnet or testnet.
+    pub network:            Option<Network>,
+    /// The policy deciding which bids are sponsored. It limits the energy that
+    /// is sponsored per bid and the number of bids per user account. The
+    /// energy policy is reloaded at runtime when the policy file changes. The
+    /// quotas of user accounts are transient and are reset on server restart.
+    pub policy:             Arc<(EnergyBudget, AccountQuota)>,
+    /// The scheduler submitting the bids in the closing window of an auction
+    /// in arrival order. If not set, all bids are submitted right away.
+    pub scheduler:          Option<BidScheduler>,
+    /// Whether the wait for the finalization of the submitted transactions is
+    /// recorded in a span. Set if the spans are exported via OTLP.
+    pub trace_finalization: bool,
+}
+
+/// The configuration of the frontend returned by the API endpoint
+/// `/uiConfig`.
+#[derive(serde::Serialize, Debug, Clone)]
+pub struct UiConfig {
+    /// The auctions whose bids are sponsored, keyed by the auction id. The
+    /// bid bounds of an auction are `null` if bids are not limited, and the
+    /// `start` and `end` are `null` if the time window is open on that side.
+    pub auctions: BTreeMap<String, AuctionConfig>,
+    /// The network of the node (`mainnet` or `testnet`). It is `null` for
+    /// other chains, e.g. a local chain.
+    pub network:  Option<Network>,
+    /// The account sponsoring the bids.
+    pub sponsor:  AccountAddress,
 }
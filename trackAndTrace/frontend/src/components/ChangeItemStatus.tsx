@@ -46,7 +46,10 @@ function generateMessage(
         // Create ChangeItemStatus parameter
         const changeItemStatusParameter: TrackAndTraceContract.ChangeItemStatusParameter = {
             additional_data: {
-                bytes: [],
+                type: 'Raw',
+                content: {
+                    bytes: [],
+                },
             },
             item_id: tokenIdU64,
             new_status: {
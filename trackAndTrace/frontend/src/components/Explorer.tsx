@@ -7,8 +7,13 @@ interface ChangeItem {
     block_time: string;
     transaction_hash: string;
     new_status: string;
-    additional_data: { bytes: number[] };
+    additional_data:
+        | { Raw: { bytes: number[] } }
+        | { Location: { latitude: number; longitude: number } }
+        | { Temperature: { millidegrees_celsius: number } }
+        | { DocumentHash: { hash: number[] } };
     event_index: number;
+    sender: string | null;
     item_id: number;
 }
 
@@ -16,9 +21,21 @@ interface CreateItem {
     block_time: string;
     transaction_hash: string;
     event_index: number;
+    sender: string | null;
     initial_status: string;
 }
 
+/**
+ * This function shortens the account that sent the transaction of an event for display. Events indexed before the
+ * sender was recorded have no sender.
+ *
+ * @param sender - The sender of the event.
+ * @returns The shortened sender.
+ */
+function displaySender(sender: string | null) {
+    return sender ? `${sender.slice(0, 5)}...${sender.slice(-5)}` : '-';
+}
+
 /**
  * This function gets the historical ItemStatusChangedEvents for a given itemID.
  *
@@ -147,6 +164,7 @@ export function Explorer() {
                                     <th>Timestamp</th>
                                     <th>Transaction Hash</th>
                                     <th>New Status</th>
+                                    <th>Sender</th>
                                 </tr>
                             </thead>
                             <tbody id="table">
@@ -164,6 +182,7 @@ export function Explorer() {
                                         </a>
                                     </td>
                                     <td>{itemCreated.initial_status}</td>
+                                    <td>{displaySender(itemCreated.sender)}</td>
                                 </tr>
 
                                 {itemChanged.map((event: ChangeItem, parentIndex) => {
@@ -182,6 +201,7 @@ export function Explorer() {
                                                 </a>
                                             </td>
                                             <td>{event.new_status}</td>
+                                            <td>{displaySender(event.sender)}</td>
                                         </tr>
                                     );
                                 })}
@@ -0,0 +1,34 @@
+-- Table containing item_summary_events successfully submitted to the database from the contract monitored. The event
+-- is logged after every mutation of an item and contains the state of the item after the mutation.
+CREATE TABLE IF NOT EXISTS item_summary_events (
+  -- Primary key.
+  id INT8 PRIMARY KEY,
+  -- The height of the block the event was included in.
+  block_height INT8 NOT NULL,
+  -- The timestamp of the block the event was included in.
+  block_time TIMESTAMP WITH TIME ZONE NOT NULL,
+  -- The transaction hash that the event was included in.
+  transaction_hash BYTEA NOT NULL,
+  -- The index from the array of logged events in a transaction.
+  event_index INT8 NOT NULL,
+  -- The account that sent the transaction the event was included in.
+  sender BYTEA NOT NULL,
+  -- The network (`mainnet` or `testnet`) the event was indexed from. `NULL` for other chains.
+  network TEXT,
+  -- Increases with every inserted or overwritten event (shared with the other event tables).
+  change_id INT8 NOT NULL DEFAULT nextval('change_id_seq'),
+  -- The item's id as logged in the event.
+  item_id INT8 NOT NULL,
+  -- The version of the event as logged in the event.
+  version INT2 NOT NULL,
+  -- The item's current status as logged in the event.
+  status JSONB NOT NULL,
+  -- The hash of the item's metadata as logged in the event. `NULL` if the item has no metadata hash.
+  metadata_hash BYTEA
+);
+
+-- An event is uniquely identified by its location on chain.
+CREATE UNIQUE INDEX IF NOT EXISTS item_summary_events_location_index
+  ON item_summary_events (block_height, transaction_hash, event_index);
+-- Improve performance on queries for events with given item_id.
+CREATE INDEX IF NOT EXISTS item_summary_index ON item_summary_events (item_id);
@@ -0,0 +1,41 @@
+-- Table containing the CIS-2 transfer events of the token contract followed with `--token-contract`. The tokens are
+-- minted per item and the token id is the item id, so joining with the other event tables by `item_id` gives the
+-- combined ownership and status history of an item.
+CREATE TABLE IF NOT EXISTS token_transfer_events (
+  -- Primary key.
+  id INT8 PRIMARY KEY,
+  -- The height of the block the event was included in.
+  block_height INT8 NOT NULL,
+  -- The timestamp of the block the event was included in.
+  block_time TIMESTAMP WITH TIME ZONE NOT NULL,
+  -- The transaction hash that the event was included in.
+  transaction_hash BYTEA NOT NULL,
+  -- The index from the array of logged events in a transaction.
+  event_index INT8 NOT NULL,
+  -- The account that sent the transaction the event was included in.
+  sender BYTEA NOT NULL,
+  -- The network (`mainnet` or `testnet`) the event was indexed from. `NULL` for other chains.
+  network TEXT,
+  -- Increases with every inserted or overwritten event (shared with the other event tables).
+  change_id INT8 NOT NULL DEFAULT nextval('change_id_seq'),
+  -- The index of the token contract that logged the event.
+  token_contract_index INT8 NOT NULL,
+  -- The subindex of the token contract that logged the event.
+  token_contract_subindex INT8 NOT NULL,
+  -- The token id as logged in the event.
+  token_id BYTEA NOT NULL,
+  -- The id of the item the token was minted for. `NULL` if the token id is not an item id.
+  item_id INT8,
+  -- The amount of tokens transferred as a decimal string, since CIS-2 amounts can exceed 64 bits.
+  amount TEXT NOT NULL,
+  -- The address the tokens were transferred from.
+  from_address JSONB NOT NULL,
+  -- The address the tokens were transferred to.
+  to_address JSONB NOT NULL
+);
+
+-- An event is uniquely identified by its location on chain.
+CREATE UNIQUE INDEX IF NOT EXISTS token_transfer_events_location_index
+  ON token_transfer_events (block_height, transaction_hash, event_index);
+-- Improve performance on queries for events with given item_id.
+CREATE INDEX IF NOT EXISTS token_transfer_item_index ON token_transfer_events (item_id);
@@ -0,0 +1,44 @@
+-- Add the SHA-256 hash of the `additional_data` of status changed events whose payload was redacted or dropped after the
+-- retention period set with `--additional-data-retention-days`. The hash proves which payload was logged on chain
+-- without keeping its contents. `NULL` for events whose payload is kept.
+ALTER TABLE item_status_changed_events ADD COLUMN IF NOT EXISTS additional_data_hash BYTEA;
+
+-- Improve performance on finding the events whose payload has not been redacted yet.
+CREATE INDEX IF NOT EXISTS item_status_changed_events_unredacted_index
+  ON item_status_changed_events (block_time) WHERE additional_data_hash IS NULL;
+
+-- Add the `additional_data_hash` to the timeline of the items. New columns can only be appended to an existing view.
+CREATE OR REPLACE VIEW item_timeline AS
+  SELECT
+    'ItemCreated' AS event_type,
+    id,
+    block_height,
+    block_time,
+    transaction_hash,
+    event_index,
+    item_id,
+    initial_status AS status,
+    metadata_url,
+    NULL::BYTEA AS additional_data,
+    sender,
+    network,
+    change_id,
+    NULL::BYTEA AS additional_data_hash
+  FROM item_created_events
+  UNION ALL
+  SELECT
+    'ItemStatusChanged' AS event_type,
+    id,
+    block_height,
+    block_time,
+    transaction_hash,
+    event_index,
+    item_id,
+    new_status AS status,
+    NULL::BYTEA AS metadata_url,
+    additional_data,
+    sender,
+    network,
+    change_id,
+    additional_data_hash
+  FROM item_status_changed_events;
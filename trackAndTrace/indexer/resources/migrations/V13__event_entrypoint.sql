@@ -0,0 +1,44 @@
+-- Add the name of the contract entrypoint whose invocation logged the event, e.g. `changeItemStatus` for a direct call or
+-- `permit` for a sponsored transaction. Events indexed before this column was introduced have no entrypoint.
+ALTER TABLE item_status_changed_events ADD COLUMN IF NOT EXISTS entrypoint TEXT;
+ALTER TABLE item_created_events ADD COLUMN IF NOT EXISTS entrypoint TEXT;
+ALTER TABLE item_summary_events ADD COLUMN IF NOT EXISTS entrypoint TEXT;
+ALTER TABLE token_transfer_events ADD COLUMN IF NOT EXISTS entrypoint TEXT;
+
+-- Add the entrypoint to the timeline of the items. New columns can only be appended to an existing view.
+CREATE OR REPLACE VIEW item_timeline AS
+  SELECT
+    'ItemCreated' AS event_type,
+    id,
+    block_height,
+    block_time,
+    transaction_hash,
+    event_index,
+    item_id,
+    initial_status AS status,
+    metadata_url,
+    NULL::BYTEA AS additional_data,
+    sender,
+    network,
+    change_id,
+    NULL::BYTEA AS additional_data_hash,
+    entrypoint
+  FROM item_created_events
+  UNION ALL
+  SELECT
+    'ItemStatusChanged' AS event_type,
+    id,
+    block_height,
+    block_time,
+    transaction_hash,
+    event_index,
+    item_id,
+    new_status AS status,
+    NULL::BYTEA AS metadata_url,
+    additional_data,
+    sender,
+    network,
+    change_id,
+    additional_data_hash,
+    entrypoint
+  FROM item_status_changed_events;
@@ -0,0 +1,33 @@
+-- Table containing the nonce_events logged by the `permit` entrypoint of the contract monitored. The event contains the
+-- nonce of the sponsored transaction signed by the account, so the next nonce of an account can be read from the
+-- database instead of invoking the contract.
+CREATE TABLE IF NOT EXISTS nonce_events (
+  -- Primary key.
+  id INT8 PRIMARY KEY,
+  -- The height of the block the event was included in.
+  block_height INT8 NOT NULL,
+  -- The timestamp of the block the event was included in.
+  block_time TIMESTAMP WITH TIME ZONE NOT NULL,
+  -- The transaction hash that the event was included in.
+  transaction_hash BYTEA NOT NULL,
+  -- The index from the array of logged events in a transaction.
+  event_index INT8 NOT NULL,
+  -- The account that sent the transaction the event was included in (the sponsor of the transaction).
+  sender BYTEA NOT NULL,
+  -- The name of the contract entrypoint whose invocation logged the event.
+  entrypoint TEXT,
+  -- The network (`mainnet` or `testnet`) the event was indexed from. `NULL` for other chains.
+  network TEXT,
+  -- Increases with every inserted or overwritten event (shared with the other event tables).
+  change_id INT8 NOT NULL DEFAULT nextval('change_id_seq'),
+  -- The account that signed the sponsored transaction as logged in the event.
+  account BYTEA NOT NULL,
+  -- The nonce used by the account as logged in the event.
+  nonce INT8 NOT NULL
+);
+
+-- An event is uniquely identified by its location on chain.
+CREATE UNIQUE INDEX IF NOT EXISTS nonce_events_location_index
+  ON nonce_events (block_height, transaction_hash, event_index);
+-- Improve performance on queries for the latest nonce of an account.
+CREATE INDEX IF NOT EXISTS nonce_account_index ON nonce_events (account, nonce);
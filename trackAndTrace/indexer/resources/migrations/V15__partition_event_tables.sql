@@ -0,0 +1,102 @@
+-- Partition the event tables by block height, so that the indexes and the vacuum of each partition stay bounded in
+-- long-lived deployments. Each event table becomes a table partitioned by range of `block_height`. The existing
+-- table is kept as the default partition `<table>_default`, which holds the events stored before this migration and
+-- the events of block ranges without a partition (e.g. events indexed before the `block_height` column was
+-- introduced). The partitions of the block ranges (`<table>_<from>_<to>`) are created by the indexer when it reaches
+-- a new range.
+--
+-- Unique indexes of a partitioned table have to include the partition key, so the `id` and the `change_id` are no
+-- longer enforced to be unique by an index. They are still unique, since they are assigned by the indexer and a
+-- sequence.
+
+ALTER TABLE item_created_events RENAME TO item_created_events_default;
+CREATE TABLE item_created_events (LIKE item_created_events_default INCLUDING DEFAULTS)
+  PARTITION BY RANGE (block_height);
+ALTER TABLE item_created_events ATTACH PARTITION item_created_events_default DEFAULT;
+CREATE UNIQUE INDEX IF NOT EXISTS item_created_events_by_location
+  ON item_created_events (block_height, transaction_hash, event_index);
+CREATE INDEX IF NOT EXISTS item_created_events_by_id ON item_created_events (id);
+CREATE INDEX IF NOT EXISTS item_created_events_by_item_id ON item_created_events (item_id);
+CREATE INDEX IF NOT EXISTS item_created_events_by_sender ON item_created_events (sender);
+CREATE INDEX IF NOT EXISTS item_created_events_by_network ON item_created_events (network);
+CREATE INDEX IF NOT EXISTS item_created_events_by_change_id ON item_created_events (change_id);
+
+ALTER TABLE item_status_changed_events RENAME TO item_status_changed_events_default;
+CREATE TABLE item_status_changed_events (LIKE item_status_changed_events_default INCLUDING DEFAULTS)
+  PARTITION BY RANGE (block_height);
+ALTER TABLE item_status_changed_events ATTACH PARTITION item_status_changed_events_default DEFAULT;
+CREATE UNIQUE INDEX IF NOT EXISTS item_status_changed_events_by_location
+  ON item_status_changed_events (block_height, transaction_hash, event_index);
+CREATE INDEX IF NOT EXISTS item_status_changed_events_by_id ON item_status_changed_events (id);
+CREATE INDEX IF NOT EXISTS item_status_changed_events_by_item_id ON item_status_changed_events (item_id);
+CREATE INDEX IF NOT EXISTS item_status_changed_events_by_new_status ON item_status_changed_events (new_status);
+CREATE INDEX IF NOT EXISTS item_status_changed_events_by_sender ON item_status_changed_events (sender);
+CREATE INDEX IF NOT EXISTS item_status_changed_events_by_network ON item_status_changed_events (network);
+CREATE INDEX IF NOT EXISTS item_status_changed_events_by_change_id ON item_status_changed_events (change_id);
+CREATE INDEX IF NOT EXISTS item_status_changed_events_by_unredacted
+  ON item_status_changed_events (block_time) WHERE additional_data_hash IS NULL;
+
+ALTER TABLE item_summary_events RENAME TO item_summary_events_default;
+CREATE TABLE item_summary_events (LIKE item_summary_events_default INCLUDING DEFAULTS)
+  PARTITION BY RANGE (block_height);
+ALTER TABLE item_summary_events ATTACH PARTITION item_summary_events_default DEFAULT;
+CREATE UNIQUE INDEX IF NOT EXISTS item_summary_events_by_location
+  ON item_summary_events (block_height, transaction_hash, event_index);
+CREATE INDEX IF NOT EXISTS item_summary_events_by_id ON item_summary_events (id);
+CREATE INDEX IF NOT EXISTS item_summary_events_by_item_id ON item_summary_events (item_id);
+
+ALTER TABLE token_transfer_events RENAME TO token_transfer_events_default;
+CREATE TABLE token_transfer_events (LIKE token_transfer_events_default INCLUDING DEFAULTS)
+  PARTITION BY RANGE (block_height);
+ALTER TABLE token_transfer_events ATTACH PARTITION token_transfer_events_default DEFAULT;
+CREATE UNIQUE INDEX IF NOT EXISTS token_transfer_events_by_location
+  ON token_transfer_events (block_height, transaction_hash, event_index);
+CREATE INDEX IF NOT EXISTS token_transfer_events_by_id ON token_transfer_events (id);
+CREATE INDEX IF NOT EXISTS token_transfer_events_by_item_id ON token_transfer_events (item_id);
+
+ALTER TABLE nonce_events RENAME TO nonce_events_default;
+CREATE TABLE nonce_events (LIKE nonce_events_default INCLUDING DEFAULTS) PARTITION BY RANGE (block_height);
+ALTER TABLE nonce_events ATTACH PARTITION nonce_events_default DEFAULT;
+CREATE UNIQUE INDEX IF NOT EXISTS nonce_events_by_location
+  ON nonce_events (block_height, transaction_hash, event_index);
+CREATE INDEX IF NOT EXISTS nonce_events_by_id ON nonce_events (id);
+CREATE INDEX IF NOT EXISTS nonce_events_by_account ON nonce_events (account, nonce);
+
+-- The timeline of the items still refers to the renamed default partitions, so it is recreated on the partitioned
+-- tables.
+CREATE OR REPLACE VIEW item_timeline AS
+  SELECT
+    'ItemCreated' AS event_type,
+    id,
+    block_height,
+    block_time,
+    transaction_hash,
+    event_index,
+    item_id,
+    initial_status AS status,
+    metadata_url,
+    NULL::BYTEA AS additional_data,
+    sender,
+    network,
+    change_id,
+    NULL::BYTEA AS additional_data_hash,
+    entrypoint
+  FROM item_created_events
+  UNION ALL
+  SELECT
+    'ItemStatusChanged' AS event_type,
+    id,
+    block_height,
+    block_time,
+    transaction_hash,
+    event_index,
+    item_id,
+    new_status AS status,
+    NULL::BYTEA AS metadata_url,
+    additional_data,
+    sender,
+    network,
+    change_id,
+    additional_data_hash,
+    entrypoint
+  FROM item_status_changed_events;
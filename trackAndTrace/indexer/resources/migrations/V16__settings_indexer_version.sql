@@ -0,0 +1,3 @@
+-- The version of the `indexer` crate whose binaries last applied the migrations. It is updated every time the
+-- migrations are run.
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS indexer_version TEXT;
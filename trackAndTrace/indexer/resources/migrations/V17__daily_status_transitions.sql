@@ -0,0 +1,26 @@
+-- Materialized view counting the status transitions of the items per day (UTC) of the block the transition was included
+-- in, e.g. how many items moved from `Produced` to `InTransit` on a day. The previous status of a status change is the
+-- status set by the preceding event of the item in the `item_timeline` (its created event or its previous status
+-- change). The view is refreshed periodically by the indexer (`--analytics-refresh-interval`).
+CREATE MATERIALIZED VIEW IF NOT EXISTS daily_status_transitions AS
+  SELECT
+    (block_time AT TIME ZONE 'UTC')::DATE AS day,
+    from_status,
+    to_status,
+    COUNT(*) AS transitions
+  FROM (
+    SELECT
+      event_type,
+      block_time,
+      LAG(status) OVER (
+        PARTITION BY item_id ORDER BY block_height ASC NULLS FIRST, block_time, event_type, id
+      ) AS from_status,
+      status AS to_status
+    FROM item_timeline
+  ) AS status_changes
+  WHERE event_type = 'ItemStatusChanged' AND from_status IS NOT NULL
+  GROUP BY day, from_status, to_status;
+
+-- A unique index is required to refresh the view without locking out concurrent reads.
+CREATE UNIQUE INDEX IF NOT EXISTS daily_status_transitions_key
+  ON daily_status_transitions (day, from_status, to_status);
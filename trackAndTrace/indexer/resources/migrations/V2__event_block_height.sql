@@ -0,0 +1,3 @@
+-- Add the height of the block the event was included in. Events indexed before this column was introduced have no block height.
+ALTER TABLE item_status_changed_events ADD COLUMN IF NOT EXISTS block_height INT8;
+ALTER TABLE item_created_events ADD COLUMN IF NOT EXISTS block_height INT8;
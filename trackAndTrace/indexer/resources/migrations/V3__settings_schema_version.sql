@@ -0,0 +1,2 @@
+-- The version of the latest migration applied to the database. It is updated every time the migrations are run.
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS schema_version INT8;
@@ -0,0 +1,28 @@
+-- View merging the created event and all status changed events of the items into a single timeline. The `event_type`
+-- distinguishes the events. Columns that only exist for one of the event types are `NULL` for the other one.
+CREATE OR REPLACE VIEW item_timeline AS
+  SELECT
+    'ItemCreated' AS event_type,
+    id,
+    block_height,
+    block_time,
+    transaction_hash,
+    event_index,
+    item_id,
+    initial_status AS status,
+    metadata_url,
+    NULL::BYTEA AS additional_data
+  FROM item_created_events
+  UNION ALL
+  SELECT
+    'ItemStatusChanged' AS event_type,
+    id,
+    block_height,
+    block_time,
+    transaction_hash,
+    event_index,
+    item_id,
+    new_status AS status,
+    NULL::BYTEA AS metadata_url,
+    additional_data
+  FROM item_status_changed_events;
@@ -0,0 +1,38 @@
+-- Add the account that sent the transaction the event was recorded in. Events indexed before this column was introduced
+-- have no sender.
+ALTER TABLE item_status_changed_events ADD COLUMN IF NOT EXISTS sender BYTEA;
+ALTER TABLE item_created_events ADD COLUMN IF NOT EXISTS sender BYTEA;
+
+-- Improve performance on queries for the events sent by an account.
+CREATE INDEX IF NOT EXISTS item_status_changed_events_sender_index ON item_status_changed_events (sender);
+CREATE INDEX IF NOT EXISTS item_created_events_sender_index ON item_created_events (sender);
+
+-- Add the sender to the timeline of the items. New columns can only be appended to an existing view.
+CREATE OR REPLACE VIEW item_timeline AS
+  SELECT
+    'ItemCreated' AS event_type,
+    id,
+    block_height,
+    block_time,
+    transaction_hash,
+    event_index,
+    item_id,
+    initial_status AS status,
+    metadata_url,
+    NULL::BYTEA AS additional_data,
+    sender
+  FROM item_created_events
+  UNION ALL
+  SELECT
+    'ItemStatusChanged' AS event_type,
+    id,
+    block_height,
+    block_time,
+    transaction_hash,
+    event_index,
+    item_id,
+    new_status AS status,
+    NULL::BYTEA AS metadata_url,
+    additional_data,
+    sender
+  FROM item_status_changed_events;
@@ -0,0 +1,15 @@
+-- Remove events that were stored more than once (e.g. by re-indexing an overlapping range of blocks), keeping the event
+-- with the lowest id.
+DELETE FROM item_status_changed_events a USING item_status_changed_events b
+  WHERE a.id > b.id AND a.block_height = b.block_height AND a.transaction_hash = b.transaction_hash
+    AND a.event_index = b.event_index;
+DELETE FROM item_created_events a USING item_created_events b
+  WHERE a.id > b.id AND a.block_height = b.block_height AND a.transaction_hash = b.transaction_hash
+    AND a.event_index = b.event_index;
+
+-- An event is uniquely identified by its location on chain. Inserting an event that is already stored is skipped and
+-- reported by the indexer.
+CREATE UNIQUE INDEX IF NOT EXISTS item_status_changed_events_location_index
+  ON item_status_changed_events (block_height, transaction_hash, event_index);
+CREATE UNIQUE INDEX IF NOT EXISTS item_created_events_location_index
+  ON item_created_events (block_height, transaction_hash, event_index);
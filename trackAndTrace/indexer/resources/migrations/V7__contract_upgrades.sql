@@ -0,0 +1,19 @@
+-- Table containing the upgrades of the contract monitored. The events emitted after an upgrade are produced by the new
+-- module, so the event schema expected by the indexer may no longer match.
+CREATE TABLE IF NOT EXISTS upgrades (
+  -- Primary key.
+  id SERIAL8 PRIMARY KEY,
+  -- The height of the block the upgrade was included in.
+  block_height INT8 NOT NULL,
+  -- The timestamp of the block the upgrade was included in.
+  block_time TIMESTAMP WITH TIME ZONE NOT NULL,
+  -- The transaction hash that the upgrade was included in.
+  transaction_hash BYTEA NOT NULL,
+  -- The reference of the module the contract was an instance of before the upgrade.
+  old_module_ref BYTEA NOT NULL,
+  -- The reference of the module the contract is an instance of after the upgrade.
+  new_module_ref BYTEA NOT NULL
+);
+
+-- Prevent that an upgrade is stored twice if a block is processed again.
+CREATE UNIQUE INDEX IF NOT EXISTS upgrades_location_index ON upgrades (transaction_hash, old_module_ref, new_module_ref);
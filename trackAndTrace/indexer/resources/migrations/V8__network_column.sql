@@ -0,0 +1,52 @@
+-- Add the network (`mainnet` or `testnet`) the data was indexed from, derived from the genesis block hash of the node.
+-- The network is `NULL` for other chains (e.g. a local chain). This allows one database to host the data of both
+-- networks and the queries to filter by network.
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS network TEXT;
+ALTER TABLE item_status_changed_events ADD COLUMN IF NOT EXISTS network TEXT;
+ALTER TABLE item_created_events ADD COLUMN IF NOT EXISTS network TEXT;
+ALTER TABLE upgrades ADD COLUMN IF NOT EXISTS network TEXT;
+
+-- Populate the network of the existing rows from the genesis block hash stored in the settings.
+UPDATE settings SET network = CASE encode(genesis_block_hash, 'hex')
+    WHEN '9dd9ca4d19e9393877d2c44b70f89acbfc0883c2243e5eeaecc0d1cd0503f478' THEN 'mainnet'
+    WHEN '4221332d34e1694168c2a0c0b3fd0f273809612cb13d000d5c2e00e85f50f796' THEN 'testnet'
+  END;
+UPDATE item_status_changed_events SET network = (SELECT network FROM settings);
+UPDATE item_created_events SET network = (SELECT network FROM settings);
+UPDATE upgrades SET network = (SELECT network FROM settings);
+
+-- Improve performance on queries for events of a given network.
+CREATE INDEX IF NOT EXISTS item_status_changed_events_network_index ON item_status_changed_events (network);
+CREATE INDEX IF NOT EXISTS item_created_events_network_index ON item_created_events (network);
+
+-- Add the network to the timeline of the items. New columns can only be appended to an existing view.
+CREATE OR REPLACE VIEW item_timeline AS
+  SELECT
+    'ItemCreated' AS event_type,
+    id,
+    block_height,
+    block_time,
+    transaction_hash,
+    event_index,
+    item_id,
+    initial_status AS status,
+    metadata_url,
+    NULL::BYTEA AS additional_data,
+    sender,
+    network
+  FROM item_created_events
+  UNION ALL
+  SELECT
+    'ItemStatusChanged' AS event_type,
+    id,
+    block_height,
+    block_time,
+    transaction_hash,
+    event_index,
+    item_id,
+    new_status AS status,
+    NULL::BYTEA AS metadata_url,
+    additional_data,
+    sender,
+    network
+  FROM item_status_changed_events;
@@ -0,0 +1,75 @@
+-- Add a `change_id` shared by both event tables that increases with every inserted or overwritten event. It is used as
+-- the cursor of the `/api/changes` endpoint, so that clients can sync the changes after the last `change_id` they have
+-- seen instead of downloading the complete histories of the items again.
+CREATE SEQUENCE IF NOT EXISTS change_id_seq;
+ALTER TABLE item_created_events ADD COLUMN IF NOT EXISTS change_id INT8;
+ALTER TABLE item_status_changed_events ADD COLUMN IF NOT EXISTS change_id INT8;
+
+-- Number the existing events in the order of the timeline of the items.
+WITH ordered AS (
+  SELECT
+    event_type,
+    id,
+    ROW_NUMBER() OVER (ORDER BY block_height ASC NULLS FIRST, block_time, event_type, id) AS change_id
+  FROM item_timeline
+)
+UPDATE item_created_events SET change_id = ordered.change_id
+  FROM ordered WHERE ordered.event_type = 'ItemCreated' AND ordered.id = item_created_events.id;
+WITH ordered AS (
+  SELECT
+    event_type,
+    id,
+    ROW_NUMBER() OVER (ORDER BY block_height ASC NULLS FIRST, block_time, event_type, id) AS change_id
+  FROM item_timeline
+)
+UPDATE item_status_changed_events SET change_id = ordered.change_id
+  FROM ordered WHERE ordered.event_type = 'ItemStatusChanged' AND ordered.id = item_status_changed_events.id;
+
+-- Continue numbering new events after the existing ones.
+SELECT setval('change_id_seq', COALESCE(GREATEST(
+    (SELECT MAX(change_id) FROM item_created_events),
+    (SELECT MAX(change_id) FROM item_status_changed_events)
+  ), 0) + 1, false);
+
+ALTER TABLE item_created_events ALTER COLUMN change_id SET DEFAULT nextval('change_id_seq');
+ALTER TABLE item_created_events ALTER COLUMN change_id SET NOT NULL;
+ALTER TABLE item_status_changed_events ALTER COLUMN change_id SET DEFAULT nextval('change_id_seq');
+ALTER TABLE item_status_changed_events ALTER COLUMN change_id SET NOT NULL;
+
+-- Improve performance on queries for the changes after a cursor.
+CREATE UNIQUE INDEX IF NOT EXISTS item_created_events_change_id_index ON item_created_events (change_id);
+CREATE UNIQUE INDEX IF NOT EXISTS item_status_changed_events_change_id_index ON item_status_changed_events (change_id);
+
+-- Add the `change_id` to the timeline of the items. New columns can only be appended to an existing view.
+CREATE OR REPLACE VIEW item_timeline AS
+  SELECT
+    'ItemCreated' AS event_type,
+    id,
+    block_height,
+    block_time,
+    transaction_hash,
+    event_index,
+    item_id,
+    initial_status AS status,
+    metadata_url,
+    NULL::BYTEA AS additional_data,
+    sender,
+    network,
+    change_id
+  FROM item_created_events
+  UNION ALL
+  SELECT
+    'ItemStatusChanged' AS event_type,
+    id,
+    block_height,
+    block_time,
+    transaction_hash,
+    event_index,
+    item_id,
+    new_status AS status,
+    NULL::BYTEA AS metadata_url,
+    additional_data,
+    sender,
+    network,
+    change_id
+  FROM item_status_changed_events;
@@ -0,0 +1,115 @@
+-- Convert the event tables into TimescaleDB hypertables partitioned into chunks by `block_time`. The statements are
+-- applied by the indexer in a single transaction if it is started with `--timescale` and the event tables are not
+-- hypertables yet. They are not a migration, since the TimescaleDB extension is optional.
+--
+-- A table partitioned by `block_height` cannot be converted into a hypertable, so every event table is recreated as a
+-- hypertable and the stored events are copied into it. Unique indexes of a hypertable have to include the time column,
+-- so the location of an event is made unique together with its `block_time`, which is the same for all events of a
+-- block.
+
+CREATE EXTENSION IF NOT EXISTS timescaledb;
+
+ALTER TABLE item_created_events RENAME TO item_created_events_partitioned;
+CREATE TABLE item_created_events (LIKE item_created_events_partitioned INCLUDING DEFAULTS);
+SELECT create_hypertable('item_created_events', 'block_time', chunk_time_interval => INTERVAL '7 days');
+INSERT INTO item_created_events SELECT * FROM item_created_events_partitioned;
+
+ALTER TABLE item_status_changed_events RENAME TO item_status_changed_events_partitioned;
+CREATE TABLE item_status_changed_events (LIKE item_status_changed_events_partitioned INCLUDING DEFAULTS);
+SELECT create_hypertable('item_status_changed_events', 'block_time', chunk_time_interval => INTERVAL '7 days');
+INSERT INTO item_status_changed_events SELECT * FROM item_status_changed_events_partitioned;
+
+ALTER TABLE item_summary_events RENAME TO item_summary_events_partitioned;
+CREATE TABLE item_summary_events (LIKE item_summary_events_partitioned INCLUDING DEFAULTS);
+SELECT create_hypertable('item_summary_events', 'block_time', chunk_time_interval => INTERVAL '7 days');
+INSERT INTO item_summary_events SELECT * FROM item_summary_events_partitioned;
+
+ALTER TABLE token_transfer_events RENAME TO token_transfer_events_partitioned;
+CREATE TABLE token_transfer_events (LIKE token_transfer_events_partitioned INCLUDING DEFAULTS);
+SELECT create_hypertable('token_transfer_events', 'block_time', chunk_time_interval => INTERVAL '7 days');
+INSERT INTO token_transfer_events SELECT * FROM token_transfer_events_partitioned;
+
+ALTER TABLE nonce_events RENAME TO nonce_events_partitioned;
+CREATE TABLE nonce_events (LIKE nonce_events_partitioned INCLUDING DEFAULTS);
+SELECT create_hypertable('nonce_events', 'block_time', chunk_time_interval => INTERVAL '7 days');
+INSERT INTO nonce_events SELECT * FROM nonce_events_partitioned;
+
+-- The timeline of the items still refers to the renamed partitioned tables, so it is recreated on the hypertables
+-- before the partitioned tables are dropped.
+CREATE OR REPLACE VIEW item_timeline AS
+  SELECT
+    'ItemCreated' AS event_type,
+    id,
+    block_height,
+    block_time,
+    transaction_hash,
+    event_index,
+    item_id,
+    initial_status AS status,
+    metadata_url,
+    NULL::BYTEA AS additional_data,
+    sender,
+    network,
+    change_id,
+    NULL::BYTEA AS additional_data_hash,
+    entrypoint
+  FROM item_created_events
+  UNION ALL
+  SELECT
+    'ItemStatusChanged' AS event_type,
+    id,
+    block_height,
+    block_time,
+    transaction_hash,
+    event_index,
+    item_id,
+    new_status AS status,
+    NULL::BYTEA AS metadata_url,
+    additional_data,
+    sender,
+    network,
+    change_id,
+    additional_data_hash,
+    entrypoint
+  FROM item_status_changed_events;
+
+-- The indexes are created after the partitioned tables were dropped, since they reuse the names of their indexes.
+DROP TABLE item_created_events_partitioned;
+DROP TABLE item_status_changed_events_partitioned;
+DROP TABLE item_summary_events_partitioned;
+DROP TABLE token_transfer_events_partitioned;
+DROP TABLE nonce_events_partitioned;
+
+CREATE UNIQUE INDEX IF NOT EXISTS item_created_events_by_location
+  ON item_created_events (block_height, transaction_hash, event_index, block_time);
+CREATE INDEX IF NOT EXISTS item_created_events_by_id ON item_created_events (id);
+CREATE INDEX IF NOT EXISTS item_created_events_by_item_id ON item_created_events (item_id);
+CREATE INDEX IF NOT EXISTS item_created_events_by_sender ON item_created_events (sender);
+CREATE INDEX IF NOT EXISTS item_created_events_by_network ON item_created_events (network);
+CREATE INDEX IF NOT EXISTS item_created_events_by_change_id ON item_created_events (change_id);
+
+CREATE UNIQUE INDEX IF NOT EXISTS item_status_changed_events_by_location
+  ON item_status_changed_events (block_height, transaction_hash, event_index, block_time);
+CREATE INDEX IF NOT EXISTS item_status_changed_events_by_id ON item_status_changed_events (id);
+CREATE INDEX IF NOT EXISTS item_status_changed_events_by_item_id ON item_status_changed_events (item_id);
+CREATE INDEX IF NOT EXISTS item_status_changed_events_by_new_status ON item_status_changed_events (new_status);
+CREATE INDEX IF NOT EXISTS item_status_changed_events_by_sender ON item_status_changed_events (sender);
+CREATE INDEX IF NOT EXISTS item_status_changed_events_by_network ON item_status_changed_events (network);
+CREATE INDEX IF NOT EXISTS item_status_changed_events_by_change_id ON item_status_changed_events (change_id);
+CREATE INDEX IF NOT EXISTS item_status_changed_events_by_unredacted
+  ON item_status_changed_events (block_time) WHERE additional_data_hash IS NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS item_summary_events_by_location
+  ON item_summary_events (block_height, transaction_hash, event_index, block_time);
+CREATE INDEX IF NOT EXISTS item_summary_events_by_id ON item_summary_events (id);
+CREATE INDEX IF NOT EXISTS item_summary_events_by_item_id ON item_summary_events (item_id);
+
+CREATE UNIQUE INDEX IF NOT EXISTS token_transfer_events_by_location
+  ON token_transfer_events (block_height, transaction_hash, event_index, block_time);
+CREATE INDEX IF NOT EXISTS token_transfer_events_by_id ON token_transfer_events (id);
+CREATE INDEX IF NOT EXISTS token_transfer_events_by_item_id ON token_transfer_events (item_id);
+
+CREATE UNIQUE INDEX IF NOT EXISTS nonce_events_by_location
+  ON nonce_events (block_height, transaction_hash, event_index, block_time);
+CREATE INDEX IF NOT EXISTS nonce_events_by_id ON nonce_events (id);
+CREATE INDEX IF NOT EXISTS nonce_events_by_account ON nonce_events (account, nonce);
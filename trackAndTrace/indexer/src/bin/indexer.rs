@@ -1,29 +1,86 @@
 //! A tool for indexing event data from the track and trace contract into a
-//! postgres database. The database is configured with the tables from the file
-//! `../resources/schema.sql`. The events `ItemStatusChangedEvent` and
-//! `ItemCreatedEvent` are indexed in their respective tables. A third table
-//! `settings` exists to store global configurations. Each event can be uniquely
-//! identified by the `transaction_hash` and `event_index`.
-use ::indexer::db::DatabasePool;
+//! postgres database. The database tables are created and updated by the
+//! migrations in `../resources/migrations`. The events `ItemStatusChangedEvent`
+//! and `ItemCreatedEvent` are indexed in their respective tables. A third table
+//! `settings` exists to store global configurations. The `ItemSummaryEvent`
+//! logged after every mutation of an item is indexed in the
+//! `item_summary_events` table. With `--token-contract`, the CIS-2 transfer
+//! events of the token contract minting a token per item are indexed in the
+//! `token_transfer_events` table. Each event can be uniquely identified by the
+//! `transaction_hash` and `event_index`.
+//!
+//! The `migrate` subcommand applies the pending migrations without indexing.
+//! The binaries refuse to run against a database migrated by a newer release.
+//!
+//! The `export-state` and `import-state` subcommands dump and restore the
+//! `settings` row (including the `latest_processed_block_height` checkpoint)
+//! as JSON so that the indexer can be re-pointed at a restored database or
+//! migrated between environments.
+//!
+//! The `verify` subcommand re-reads the events of a range of blocks from the
+//! node and compares them with the rows in the database. The `repair`
+//! subcommand additionally re-inserts the events missing in the database.
+//!
+//! The `check` subcommand validates the `latest_processed_block_height`
+//! checkpoint against the latest events stored in the event tables and, with
+//! `--repair`, fixes inconsistencies caused by partial writes or manual edits
+//! of the database.
+//!
+//! With `--dry-run`, the indexer traverses the chain and prints the parsed
+//! events without connecting to the database, e.g. for debugging the decoding
+//! of events.
+#[cfg(any(feature = "kafka", feature = "nats"))]
+use ::indexer::sink::EventSink;
+use ::indexer::{
+    db::{
+        self, notify_events, AdditionalDataRedaction, DatabasePool, EventLocation, EventTable,
+        StoredConfiguration, StoredEventCounts, TokenTransferEvent,
+    },
+    export::{self, ExportFormat},
+    handler::{ContractUpgrade, EventHandler, HandledEvent, RecordUpgrade, StoreEvent, StoreNonce},
+    health::{self, CatchUpProgress, IndexerProgress},
+    info::Info,
+    logging::{self, LogFormat},
+    published::PublishedEvent,
+    verify,
+    watchdog::Watchdog,
+};
 use anyhow::Context;
 use clap::Parser;
 use concordium_rust_sdk::{
-    indexer::{self, AffectedContractIndexer, ContractUpdateInfo, ProcessorConfig},
-    smart_contracts::common::to_bytes,
+    cis2,
+    indexer::{self, AffectedContractIndexer, ContractUpdateInfo, ProcessEvent},
     types::{
-        queries::BlockInfo, smart_contracts::OwnedReceiveName, AbsoluteBlockHeight, ContractAddress,
+        queries::BlockInfo,
+        smart_contracts::{InstanceInfo, ModuleReference, OwnedEntrypointName, OwnedReceiveName},
+        AbsoluteBlockHeight, ContractAddress, ExecutionTree, TraceV1,
     },
-    v2::{self as sdk, Client},
+    v2::{self as sdk, BlockIdentifier, Client},
 };
-use std::collections::{BTreeMap, BTreeSet};
-use tokio_postgres::types::{Json, ToSql};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+use tokio::sync::mpsc;
+use tokio_postgres::types::ToSql;
 use track_and_trace as contract;
 use track_and_trace::AdditionalData;
 
+/// The init name of the track and trace contract.
+const CONTRACT_INIT_NAME: &str = "init_track_and_trace";
+
 /// Command line configuration of the application.
 #[derive(Debug, clap::Parser)]
-#[command(author, version, about)]
+#[command(
+    author,
+    version,
+    about,
+    subcommand_negates_reqs = true,
+    args_conflicts_with_subcommands = true
+)]
 struct Args {
+    #[command(subcommand)]
+    command:            Option<Command>,
     #[arg(
         long = "node",
         short = 'n',
@@ -32,68 +89,924 @@ struct Args {
         global = true,
         env = "CCD_INDEXER_NODE"
     )]
-    node_endpoint:    concordium_rust_sdk::v2::Endpoint,
-    #[arg(
-        long = "contract",
-        short = 'c',
-        help = "The track and trace contract address.",
-        env = "CCD_INDEXER_CONTRACT"
-    )]
-    contract_address: ContractAddress,
+    node_endpoint:      concordium_rust_sdk::v2::Endpoint,
     /// Database connection string.
     #[arg(
         long = "db-connection",
         default_value = "host=localhost dbname=indexer user=postgres password=password port=5432",
         help = "A connection string detailing the connection to the database used by the \
                 application.",
+        global = true,
         env = "CCD_INDEXER_DB_CONNECTION"
     )]
-    db_connection:    tokio_postgres::config::Config,
+    db_connection:      tokio_postgres::config::Config,
+    /// File containing the database connection string.
+    #[arg(
+        long = "db-connection-file",
+        help = "A file containing the connection string of the database. If specified, it is used \
+                instead of `--db-connection` and the indexer re-reads the file and reconnects to \
+                the database when it receives a SIGHUP signal, e.g. after the password was \
+                rotated.",
+        global = true,
+        env = "CCD_INDEXER_DB_CONNECTION_FILE"
+    )]
+    db_connection_file: Option<PathBuf>,
     /// Maximum log level
     #[clap(
         long = "log-level",
         default_value = "info",
         help = "The maximum log level. Possible values are: `trace`, `debug`, `info`, `warn`, and \
                 `error`.",
+        global = true,
         env = "CCD_INDEXER_LOG_LEVEL"
     )]
-    log_level:        tracing_subscriber::filter::LevelFilter,
+    log_level:          tracing_subscriber::filter::LevelFilter,
+    /// Format of the log output.
+    #[arg(
+        long = "log-format",
+        value_enum,
+        default_value = "text",
+        help = "The format of the log output. Possible values are: `text` and `json`. With \
+                `json`, every log line is a JSON object with the block height, transaction hash, \
+                and event index as separate fields.",
+        global = true,
+        env = "CCD_INDEXER_LOG_FORMAT"
+    )]
+    log_format:         LogFormat,
+    #[command(flatten)]
+    node_tls:           NodeTlsArgs,
+    #[command(flatten)]
+    node_channel:       NodeChannelArgs,
+    #[command(flatten)]
+    index:              IndexArgs,
+}
+
+/// The client certificate presented to nodes that require mutual TLS.
+#[derive(Debug, clap::Args)]
+struct NodeTlsArgs {
+    #[arg(
+        long = "node-client-cert",
+        requires = "node_client_key",
+        help = "Path to a PEM encoded client certificate presented to the node for mutual TLS. \
+                Requires an `https` node endpoint.",
+        global = true,
+        env = "CCD_INDEXER_NODE_CLIENT_CERT"
+    )]
+    node_client_cert: Option<PathBuf>,
+    #[arg(
+        long = "node-client-key",
+        requires = "node_client_cert",
+        help = "Path to the PEM encoded private key of the client certificate.",
+        global = true,
+        env = "CCD_INDEXER_NODE_CLIENT_KEY"
+    )]
+    node_client_key:  Option<PathBuf>,
+}
+
+/// The options of the gRPC channel to the node.
+#[derive(Debug, clap::Args)]
+struct NodeChannelArgs {
+    /// Request timeout in seconds.
+    #[arg(
+        long = "node-request-timeout",
+        default_value = "10",
+        help = "The maximum number of seconds a query to the node may take before it is aborted.",
+        global = true,
+        env = "CCD_INDEXER_NODE_REQUEST_TIMEOUT"
+    )]
+    request_timeout:    u64,
+    /// Connect timeout in seconds.
+    #[arg(
+        long = "node-connect-timeout",
+        default_value = "5",
+        help = "The maximum number of seconds spent establishing a connection to the node.",
+        global = true,
+        env = "CCD_INDEXER_NODE_CONNECT_TIMEOUT"
+    )]
+    connect_timeout:    u64,
+    /// Interval in seconds between HTTP/2 keepalive pings.
+    #[arg(
+        long = "node-keepalive-interval",
+        help = "The number of seconds between HTTP/2 keepalive pings sent to the node, also while \
+                the connection is idle. A connection that does not acknowledge a ping within \
+                `--node-keepalive-timeout` is closed, so that dead connections are detected \
+                before the request timeout. If not specified, no pings are sent.",
+        global = true,
+        env = "CCD_INDEXER_NODE_KEEPALIVE_INTERVAL"
+    )]
+    keepalive_interval: Option<u64>,
+    /// Keepalive timeout in seconds.
+    #[arg(
+        long = "node-keepalive-timeout",
+        default_value = "20",
+        help = "The maximum number of seconds to wait for the acknowledgement of a keepalive ping \
+                before the connection to the node is closed.",
+        global = true,
+        env = "CCD_INDEXER_NODE_KEEPALIVE_TIMEOUT"
+    )]
+    keepalive_timeout:  u64,
+    /// Whether the HTTP/2 adaptive flow control window is used.
+    #[arg(
+        long = "node-adaptive-window",
+        help = "Use the HTTP/2 adaptive flow control window for the connection to the node, which \
+                improves the throughput of large responses on links with a high latency.",
+        global = true,
+        env = "CCD_INDEXER_NODE_ADAPTIVE_WINDOW"
+    )]
+    adaptive_window:    bool,
+    /// The number of times a failed traversal is re-tried.
+    #[arg(
+        long = "node-max-retries",
+        default_value = "3",
+        help = "The number of times querying the node is re-tried after a failure (e.g. a \
+                timeout) before the indexer exits. The traversal is re-started from the next \
+                unprocessed block. The count is reset once a block has been processed \
+                successfully.",
+        global = true,
+        env = "CCD_INDEXER_NODE_MAX_RETRIES"
+    )]
+    max_retries:        u32,
+}
+
+/// Command line configuration of the indexing. These arguments are given
+/// either without a subcommand or to the `index` subcommand.
+#[derive(Debug, clap::Args)]
+struct IndexArgs {
+    #[arg(
+        long = "contract",
+        short = 'c',
+        help = "The track and trace contract address.",
+        required = true,
+        env = "CCD_INDEXER_CONTRACT"
+    )]
+    contract_address:   Option<ContractAddress>,
+    /// The module reference the contract is expected to be an instance of.
+    #[arg(
+        long = "module-ref",
+        help = "The reference of the module the track and trace contract is expected to be an \
+                instance of. If specified, the indexer refuses to start if the contract is an \
+                instance of a different module.",
+        env = "CCD_INDEXER_MODULE_REF"
+    )]
+    module_ref:         Option<ModuleReference>,
+    /// Drain timeout in seconds.
+    #[arg(
+        long = "drain-timeout",
+        default_value = "30",
+        help = "The maximum number of seconds spent processing blocks that have already been \
+                fetched from the node after a shutdown signal has been received.",
+        env = "CCD_INDEXER_DRAIN_TIMEOUT"
+    )]
+    drain_timeout:      u64,
+    /// The CIS-2 token contract minting a token per item.
+    #[arg(
+        long = "token-contract",
+        help = "The CIS-2 token contract minting a token per item of the track and trace \
+                contract. If specified, the transfer events of the token contract are indexed in \
+                the `token_transfer_events` table linked to the item ids.",
+        env = "CCD_INDEXER_TOKEN_CONTRACT"
+    )]
+    token_contract:     Option<ContractAddress>,
+    /// The address of the health endpoint.
+    #[arg(
+        long = "health-address",
+        help = "Address where the `/health` endpoint will listen on. If not specified, the health \
+                endpoint is disabled.",
+        env = "CCD_INDEXER_HEALTH_ADDRESS"
+    )]
+    health_address:     Option<std::net::SocketAddr>,
+    /// Maximum lag in seconds before the indexer is reported as unhealthy.
+    #[arg(
+        long = "health-max-behind",
+        default_value = "300",
+        help = "The maximum number of seconds the slot time of the last processed block may lag \
+                behind the current time before the `/health` endpoint reports the indexer as \
+                unhealthy.",
+        env = "CCD_INDEXER_HEALTH_MAX_BEHIND"
+    )]
+    health_max_behind:  u64,
+    /// Whether the indexing can be paused and resumed at the health endpoint.
+    #[arg(
+        long = "pause-control",
+        help = "Serve the `/pause` and `/resume` endpoints at the `--health-address`, which pause \
+                and resume the processing of fetched blocks (e.g. during database maintenance) \
+                without stopping the indexer.",
+        env = "CCD_INDEXER_PAUSE_CONTROL"
+    )]
+    pause_control:      bool,
+    /// Whether the status dashboard is served at the health endpoint.
+    #[arg(
+        long = "dashboard",
+        help = "Serve a read-only HTML status page at `/dashboard` of the `--health-address` \
+                showing the checkpoint, the number of events indexed in the last hour, and the \
+                most recently created items.",
+        env = "CCD_INDEXER_DASHBOARD"
+    )]
+    dashboard:          bool,
+    /// Minutes without a processed block before a stall alert is raised.
+    #[arg(
+        long = "stall-alert-minutes",
+        help = "Raise an alert if no block has been processed for this number of minutes while \
+                the node keeps finalizing blocks, e.g. because the traversal is wedged. The alert \
+                is logged as an error and posted to the `--stall-alert-webhook`. If not \
+                specified, the watchdog is disabled.",
+        env = "CCD_INDEXER_STALL_ALERT_MINUTES"
+    )]
+    stall_after:        Option<u64>,
+    /// The webhook stall alerts are posted to.
+    #[arg(
+        long = "stall-alert-webhook",
+        requires = "stall_after",
+        help = "The URL the stall alerts are posted to as JSON. If not specified, the alerts are \
+                only logged.",
+        env = "CCD_INDEXER_STALL_ALERT_WEBHOOK"
+    )]
+    stall_webhook:      Option<reqwest::Url>,
+    /// Whether the event tables are converted into TimescaleDB hypertables.
+    #[arg(
+        long = "timescale",
+        help = "Convert the event tables into TimescaleDB hypertables partitioned by block time \
+                at startup, if they are not hypertables yet. Requires the TimescaleDB extension \
+                to be available in the database.",
+        env = "CCD_INDEXER_TIMESCALE"
+    )]
+    timescale:          bool,
+    /// Interval in seconds between catch-up progress reports.
+    #[arg(
+        long = "progress-interval",
+        default_value = "30",
+        help = "The number of seconds between reports of the progress of catching up with the \
+                last finalized block (percent complete, blocks per second, and estimated time to \
+                catch up).",
+        env = "CCD_INDEXER_PROGRESS_INTERVAL"
+    )]
+    progress_interval:  u64,
+    /// Interval in seconds between refreshes of the status transition
+    /// analytics.
+    #[arg(
+        long = "analytics-refresh-interval",
+        default_value = "3600",
+        help = "The number of seconds between refreshes of the `daily_status_transitions` \
+                materialized view counting the status transitions of the items per day.",
+        env = "CCD_INDEXER_ANALYTICS_REFRESH_INTERVAL"
+    )]
+    analytics_refresh:  u64,
+    /// Number of days events are retained.
+    #[arg(
+        long = "retention-days",
+        help = "The number of days events are kept in the database. Events from blocks with an \
+                older slot time are deleted periodically. If not specified, events are kept \
+                forever.",
+        env = "CCD_INDEXER_RETENTION_DAYS"
+    )]
+    retention_days:     Option<u32>,
+    /// Interval in seconds between the deletion of expired events.
+    #[arg(
+        long = "retention-interval",
+        default_value = "3600",
+        help = "The number of seconds between runs deleting events older than `--retention-days` \
+                and removing additional data older than `--additional-data-retention-days`.",
+        env = "CCD_INDEXER_RETENTION_INTERVAL"
+    )]
+    retention_interval: u64,
+    /// Number of days the additional data of status changed events is
+    /// retained.
+    #[arg(
+        long = "additional-data-retention-days",
+        help = "The number of days the additional data (e.g. sensor payloads) of status changed \
+                events is kept in the database. The additional data of events from blocks with an \
+                older slot time is removed periodically as given by \
+                `--additional-data-redaction`, keeping its SHA-256 hash. If not specified, the \
+                additional data is kept as long as the event.",
+        env = "CCD_INDEXER_ADDITIONAL_DATA_RETENTION_DAYS"
+    )]
+    payload_retention:  Option<u32>,
+    /// How the expired additional data is removed.
+    #[arg(
+        long = "additional-data-redaction",
+        value_enum,
+        default_value = "redact",
+        help = "How the additional data older than `--additional-data-retention-days` is removed. \
+                Possible values are: `redact` (overwrite the bytes with zeros, keeping the \
+                length) and `drop` (replace the data with empty bytes).",
+        env = "CCD_INDEXER_ADDITIONAL_DATA_REDACTION"
+    )]
+    payload_redaction:  AdditionalDataRedaction,
+    /// Whether the tables of the contract are stored in their own schema.
+    #[arg(
+        long = "schema-per-contract",
+        help = "Store the tables of the contract in its own postgres schema \
+                `contract_<index>_<subindex>` which is created on demand. This allows indexing \
+                many contracts into one database with isolation.",
+        env = "CCD_INDEXER_SCHEMA_PER_CONTRACT"
+    )]
+    tenant_schema:      bool,
+    /// The number of blocks queried from the node in parallel.
+    #[arg(
+        long = "max-parallel",
+        default_value = "4",
+        help = "The maximum number of blocks queried from the node in parallel. A higher value \
+                speeds up catching up with the chain.",
+        env = "CCD_INDEXER_MAX_PARALLEL"
+    )]
+    max_parallel:       usize,
+    /// The number of fetched blocks buffered before they are processed.
+    #[arg(
+        long = "buffer-size",
+        default_value = "20",
+        help = "The maximum number of blocks fetched from the node that are buffered before they \
+                are stored in the database.",
+        env = "CCD_INDEXER_BUFFER_SIZE"
+    )]
+    buffer_size:        usize,
+    /// The maximum number of blocks stored in one database transaction.
+    #[arg(
+        long = "batch-size",
+        default_value = "1",
+        help = "The maximum number of blocks stored in the database in one transaction together \
+                with a single checkpoint. A higher value speeds up catching up with the chain.",
+        env = "CCD_INDEXER_BATCH_SIZE"
+    )]
+    batch_size:         usize,
+    /// The maximum time in seconds blocks wait in an incomplete batch.
+    #[arg(
+        long = "batch-flush-interval",
+        default_value = "5",
+        help = "The maximum number of seconds a block waits for the batch to fill up before the \
+                batch is stored and the checkpoint is flushed. Only relevant if `--batch-size` is \
+                larger than 1.",
+        env = "CCD_INDEXER_BATCH_FLUSH_INTERVAL"
+    )]
+    flush_interval:     u64,
+    /// The message broker the events are published to.
+    #[arg(
+        long = "sink",
+        value_enum,
+        requires = "brokers",
+        help = "Additionally publish every indexed event as a JSON message to a message broker. \
+                Possible values are: `kafka` and `nats`. The indexer has to be built with the \
+                cargo feature of the same name. If not specified, events are only stored in the \
+                database.",
+        env = "CCD_INDEXER_SINK"
+    )]
+    sink:               Option<SinkKind>,
+    /// The addresses of the message brokers.
+    #[arg(
+        long = "brokers",
+        help = "A comma-separated list of the addresses of the message brokers the events are \
+                published to (e.g. `localhost:9092` for Kafka or `nats://localhost:4222` for \
+                NATS).",
+        env = "CCD_INDEXER_BROKERS"
+    )]
+    brokers:            Option<String>,
+    /// The topic the events are published to.
+    #[arg(
+        long = "sink-topic",
+        default_value = "track-and-trace-events",
+        help = "The Kafka topic or NATS subject the events are published to.",
+        env = "CCD_INDEXER_SINK_TOPIC"
+    )]
+    sink_topic:         String,
+    /// Whether new events are sent with `NOTIFY`.
+    #[arg(
+        long = "notify",
+        help = "Additionally send every new `ItemCreated` and `ItemStatusChanged` event as a JSON \
+                payload with `NOTIFY` on the `--notify-channel` from within the database \
+                transaction storing the event, so that services connected to the same database \
+                get push notifications without polling.",
+        env = "CCD_INDEXER_NOTIFY"
+    )]
+    notify:             bool,
+    /// The channel the new events are sent on with `NOTIFY`.
+    #[arg(
+        long = "notify-channel",
+        default_value = "tnt_events",
+        help = "The PostgreSQL channel the new events are sent on with `--notify`.",
+        env = "CCD_INDEXER_NOTIFY_CHANNEL"
+    )]
+    notify_channel:     String,
+    /// Whether only new blocks are indexed.
+    #[arg(
+        long = "follow",
+        help = "Skip the historical blocks and start indexing from the last finalized block of \
+                the node, e.g. for demo environments where only new activity matters. Blocks \
+                between the `latest_processed_block_height` stored in the database and the last \
+                finalized block are not indexed, but can be indexed later with the `backfill` \
+                subcommand.",
+        env = "CCD_INDEXER_FOLLOW"
+    )]
+    follow:             bool,
+    /// Whether the events are only parsed and printed.
+    #[arg(
+        long = "dry-run",
+        help = "Traverse the chain, parse the `ItemCreated` and `ItemStatusChanged` events, and \
+                print them as JSON to stdout without connecting to the database. The checkpoints \
+                that would be stored are validated and logged. Indexing starts from the block the \
+                contract was created in (or the last finalized block with `--follow`).",
+        env = "CCD_INDEXER_DRY_RUN"
+    )]
+    dry_run:            bool,
+}
+
+/// The message broker the events are published to with `--sink`.
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum SinkKind {
+    /// Apache Kafka. The events are published to a topic.
+    Kafka,
+    /// NATS. The events are published to a subject.
+    Nats,
+}
+
+impl SinkKind {
+    /// The cargo feature the `indexer` has to be built with to publish to the
+    /// message broker.
+    fn feature(self) -> &'static str {
+        match self {
+            SinkKind::Kafka => "kafka",
+            SinkKind::Nats => "nats",
+        }
+    }
+}
+
+/// The block range processed by the `verify`, `repair`, and `backfill`
+/// subcommands.
+#[derive(Debug, clap::Args)]
+struct RangeArgs {
+    #[arg(
+        long = "from-height",
+        visible_alias = "from",
+        help = "The first block height of the range (inclusive)."
+    )]
+    from_height: AbsoluteBlockHeight,
+    #[arg(
+        long = "to-height",
+        visible_alias = "to",
+        help = "The last block height of the range (inclusive). If not specified, the \
+                `latest_processed_block_height` stored in the database is used."
+    )]
+    to_height:   Option<AbsoluteBlockHeight>,
+}
+
+/// How the differences between the chain and the database found in a block
+/// range are handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RangeMode {
+    /// Only report the differences.
+    Verify,
+    /// Re-insert the events missing in the database.
+    Repair,
+    /// Re-insert the events missing in the database and overwrite the events
+    /// stored with different values.
+    Backfill,
+}
+
+/// Subcommands of the application. If no subcommand is given, the indexer
+/// indexes the contract as with the `index` subcommand.
+#[derive(Debug, clap::Subcommand)]
+enum Command {
+    /// Index the events of the contract into the database.
+    Index(IndexArgs),
+    /// Re-read the events of a block range from the node and check that the
+    /// rows in the database match.
+    Verify(RangeArgs),
+    /// Re-read the events of a block range from the node and re-insert the
+    /// events missing in the database.
+    Repair(RangeArgs),
+    /// Re-read the events of a block range from the node, re-insert the events
+    /// missing in the database, and overwrite the events stored with different
+    /// values (e.g. after a bug fix in event parsing). The
+    /// `latest_processed_block_height` checkpoint is not changed.
+    Backfill(RangeArgs),
+    /// Apply the pending database migrations and exit. The subcommands that
+    /// only read or repair the database refuse to run against a database
+    /// whose schema is older than the schema supported by the indexer.
+    Migrate,
+    /// Export the settings and the `latest_processed_block_height` checkpoint
+    /// stored in the database as JSON.
+    ExportState {
+        #[arg(
+            long = "output",
+            help = "The file the exported state is written to. If not specified, the state is \
+                    written to stdout."
+        )]
+        output: Option<PathBuf>,
+    },
+    /// Restore the settings and the `latest_processed_block_height` checkpoint
+    /// from a JSON file created by `export-state`.
+    ImportState {
+        #[arg(
+            long = "input",
+            help = "The file created by the `export-state` subcommand."
+        )]
+        input: PathBuf,
+    },
+    /// Check the `latest_processed_block_height` checkpoint against the latest
+    /// events stored in the event tables and optionally repair the
+    /// inconsistencies.
+    Check {
+        #[arg(
+            long = "repair",
+            help = "Repair the inconsistencies found. Events from blocks above the checkpoint are \
+                    deleted so that the indexer indexes these blocks again, and a missing \
+                    checkpoint is set to the block before the latest block with events."
+        )]
+        repair: bool,
+    },
+    /// Export the indexed events for offline analytics. Each event table is
+    /// written to its own file in the output directory.
+    Export {
+        #[arg(
+            long = "format",
+            value_enum,
+            default_value = "csv",
+            help = "The file format of the exported events."
+        )]
+        format:      ExportFormat,
+        #[arg(
+            long = "output",
+            default_value = "./dump/",
+            help = "The directory the exported files are written to."
+        )]
+        output:      PathBuf,
+        #[arg(
+            long = "from-height",
+            help = "Only export events from blocks at or above this block height."
+        )]
+        from_height: Option<AbsoluteBlockHeight>,
+        #[arg(
+            long = "to-height",
+            help = "Only export events from blocks at or below this block height."
+        )]
+        to_height:   Option<AbsoluteBlockHeight>,
+    },
+}
+
+/// The state exported by the `export-state` subcommand and restored by the
+/// `import-state` subcommand.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct ExportedState {
+    /// The version of the indexer that exported the state.
+    indexer_version: String,
+    /// The settings row including the `latest_processed_block_height`
+    /// checkpoint.
+    settings:        StoredConfiguration,
+    /// The number of events stored in the database at the time of the export.
+    /// It is used to verify that the database the state is imported into
+    /// holds the same events as the database the state was exported from.
+    event_counts:    StoredEventCounts,
+}
+
+/// Connect to the message broker of the given `kind`. The `brokers` are a
+/// comma-separated list of broker addresses and the `topic` is the Kafka topic
+/// or NATS subject the events are published to. Fails if the `indexer` is
+/// built without the cargo feature of the message broker.
+#[cfg_attr(not(all(feature = "kafka", feature = "nats")), allow(unused_variables))]
+async fn connect_sink(kind: SinkKind, brokers: &str, topic: String) -> anyhow::Result<EventSink> {
+    match kind {
+        #[cfg(feature = "kafka")]
+        SinkKind::Kafka => EventSink::kafka(brokers, topic),
+        #[cfg(feature = "nats")]
+        SinkKind::Nats => EventSink::nats(brokers, topic).await,
+        #[allow(unreachable_patterns)]
+        _ => anyhow::bail!(
+            "The `{0}` sink is only available if the indexer is built with the `{0}` feature.",
+            kind.feature()
+        ),
+    }
+}
+
+/// Without the `kafka` and the `nats` feature, no message broker can be
+/// connected, so there is no sink to publish the events to.
+#[cfg(not(any(feature = "kafka", feature = "nats")))]
+enum EventSink {}
+
+#[cfg(not(any(feature = "kafka", feature = "nats")))]
+impl EventSink {
+    async fn publish(&self, _events: &[PublishedEvent]) -> anyhow::Result<()> { match *self {} }
+}
+
+/// The time to wait before re-trying to process a block after a failure.
+const WAIT_AFTER_FAIL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// A block with the contract updates of the monitored contract as produced by
+/// the traversal of the chain.
+type ContractBlock = (
+    BlockInfo,
+    Vec<(
+        ContractUpdateInfo,
+        BTreeMap<ContractAddress, BTreeSet<OwnedReceiveName>>,
+    )>,
+);
+
+/// Consecutive blocks that are processed together, storing a single checkpoint
+/// after the last block.
+type ContractBatch = Vec<ContractBlock>;
+
+/// An entry of a block parsed by [`parse_batch`] that is stored in the
+/// database by [`StoreEvents`].
+enum ParsedEntry {
+    /// An upgrade of the contract from the old to the new module.
+    Upgrade(EventLocation, ContractUpgrade),
+    /// An `ItemCreated` event of the contract.
+    ItemCreated(EventLocation, contract::ItemCreatedEvent),
+    /// An `ItemStatusChanged` event of the contract.
+    ItemStatusChanged(
+        EventLocation,
+        contract::ItemStatusChangedEvent<AdditionalData>,
+    ),
+    /// An `ItemSummary` event of the contract.
+    ItemSummary(EventLocation, contract::ItemSummaryEvent),
+    /// A transfer event of the token contract.
+    TokenTransfer(EventLocation, TokenTransferEvent),
+    /// A `Nonce` event logged by the `permit` entrypoint of the contract.
+    Nonce(EventLocation, contract::NonceEvent),
+}
+
+/// A block together with the entries parsed from it by [`parse_batch`], in the
+/// order they were logged.
+struct ParsedBlock {
+    /// The block the entries were parsed from.
+    block_info: BlockInfo,
+    /// The entries of the block.
+    entries:    Vec<ParsedEntry>,
+}
+
+/// Consecutive parsed blocks that are stored together, storing a single
+/// checkpoint after the last block.
+type ParsedBatch = Vec<ParsedBlock>;
+
+/// Parse the upgrades and events of the `contract` and the transfer events of
+/// the `token_contract` from the blocks of the `batch`. Parsing is separated
+/// from storing the events in [`StoreEvents`], so that the next batch is parsed
+/// while the previous batch is committed to the database.
+fn parse_batch(
+    contract: ContractAddress,
+    token_contract: Option<ContractAddress>,
+    batch: ContractBatch,
+) -> anyhow::Result<ParsedBatch> {
+    let mut parsed_batch = Vec::with_capacity(batch.len());
+    for (block_info, contract_update_info) in batch {
+        let mut entries = Vec::new();
+        for single_contract_update_info in contract_update_info {
+            let mut upgrades = Vec::new();
+            collect_upgrades(
+                &single_contract_update_info.0.execution_tree,
+                contract,
+                &mut upgrades,
+            );
+            for (upgrade_entrypoint, old_module_ref, new_module_ref) in upgrades {
+                let location = EventLocation {
+                    block_height:     block_info.block_height,
+                    block_time:       block_info.block_slot_time,
+                    transaction_hash: single_contract_update_info.0.transaction_hash,
+                    event_index:      0,
+                    sender:           single_contract_update_info.0.sender,
+                    entrypoint:       upgrade_entrypoint,
+                };
+                entries.push(ParsedEntry::Upgrade(location, ContractUpgrade {
+                    old_module_ref,
+                    new_module_ref,
+                }));
+            }
+
+            for (contract_invoked, entry_point_name, events) in
+                single_contract_update_info.0.execution_tree.events()
+            {
+                let is_token_contract = Some(contract_invoked) == token_contract;
+                if contract_invoked != contract && !is_token_contract {
+                    continue;
+                }
+                for (event_index, event) in events.iter().enumerate() {
+                    let location = EventLocation {
+                        block_height:     block_info.block_height,
+                        block_time:       block_info.block_slot_time,
+                        transaction_hash: single_contract_update_info.0.transaction_hash,
+                        event_index:      event_index as u64,
+                        sender:           single_contract_update_info.0.sender,
+                        entrypoint:       entry_point_name.to_owned(),
+                    };
+
+                    let entry = if is_token_contract {
+                        // Only the transfers of the tokens are recorded, other events of the
+                        // token contract are skipped.
+                        let Ok(cis2::Event::Transfer {
+                            token_id,
+                            amount,
+                            from,
+                            to,
+                        }) = cis2::Event::try_from(event)
+                        else {
+                            continue;
+                        };
+                        ParsedEntry::TokenTransfer(location, TokenTransferEvent {
+                            token_contract: contract_invoked,
+                            token_id,
+                            amount,
+                            from,
+                            to,
+                        })
+                    } else {
+                        match event.parse::<contract::Event<AdditionalData>>()? {
+                            contract::Event::ItemStatusChanged(event) => {
+                                ParsedEntry::ItemStatusChanged(location, event)
+                            }
+                            contract::Event::ItemCreated(event) => {
+                                ParsedEntry::ItemCreated(location, event)
+                            }
+                            contract::Event::ItemSummary(event) => {
+                                ParsedEntry::ItemSummary(location, event)
+                            }
+                            contract::Event::Nonce(event) => ParsedEntry::Nonce(location, event),
+                            _ => continue,
+                        }
+                    };
+                    entries.push(entry);
+                }
+            }
+        }
+        parsed_batch.push(ParsedBlock {
+            block_info,
+            entries,
+        });
+    }
+    Ok(parsed_batch)
+}
+
+/// The handlers of the entries parsed by [`parse_batch`], one per kind of
+/// entry. An entry is routed to the handler of its variant by
+/// [`EventDispatcher::dispatch`].
+struct EventDispatcher {
+    /// The handler of the upgrades of the contract.
+    upgrade:             Box<dyn EventHandler<ContractUpgrade>>,
+    /// The handler of the `ItemCreated` events.
+    item_created:        Box<dyn EventHandler<contract::ItemCreatedEvent>>,
+    /// The handler of the `ItemStatusChanged` events.
+    item_status_changed: Box<dyn EventHandler<contract::ItemStatusChangedEvent<AdditionalData>>>,
+    /// The handler of the `ItemSummary` events.
+    item_summary:        Box<dyn EventHandler<contract::ItemSummaryEvent>>,
+    /// The handler of the transfer events of the token contract.
+    token_transfer:      Box<dyn EventHandler<TokenTransferEvent>>,
+    /// The handler of the `Nonce` events.
+    nonce:               Box<dyn EventHandler<contract::NonceEvent>>,
+}
+
+impl EventDispatcher {
+    /// The handlers storing the entries of the `contract` in the database.
+    /// The `ItemCreated` and `ItemStatusChanged` events are published. The
+    /// summaries only repeat the state of the item after these events, and the
+    /// transfers of the token contract and the nonces of sponsored
+    /// transactions are only relevant to queries of the database, so they are
+    /// stored but not published.
+    fn new(contract: ContractAddress) -> Self {
+        Self {
+            upgrade:             Box::new(RecordUpgrade { contract }),
+            item_created:        Box::new(StoreEvent::published(PublishedEvent::item_created)),
+            item_status_changed: Box::new(StoreEvent::published(
+                PublishedEvent::item_status_changed,
+            )),
+            item_summary:        Box::new(StoreEvent::unpublished()),
+            token_transfer:      Box::new(StoreEvent::unpublished()),
+            nonce:               Box::new(StoreNonce),
+        }
+    }
+
+    /// Route the `entry` to the handler of its variant, which stores it within
+    /// the database `transaction`.
+    async fn dispatch(
+        &self,
+        transaction: &deadpool_postgres::Transaction<'_>,
+        entry: &ParsedEntry,
+    ) -> anyhow::Result<HandledEvent> {
+        match entry {
+            ParsedEntry::Upgrade(location, upgrade) => {
+                self.upgrade.handle(transaction, location, upgrade).await
+            }
+            ParsedEntry::ItemCreated(location, event) => {
+                self.item_created.handle(transaction, location, event).await
+            }
+            ParsedEntry::ItemStatusChanged(location, event) => {
+                self.item_status_changed
+                    .handle(transaction, location, event)
+                    .await
+            }
+            ParsedEntry::ItemSummary(location, event) => {
+                self.item_summary.handle(transaction, location, event).await
+            }
+            ParsedEntry::TokenTransfer(location, event) => {
+                self.token_transfer
+                    .handle(transaction, location, event)
+                    .await
+            }
+            ParsedEntry::Nonce(location, event) => {
+                self.nonce.handle(transaction, location, event).await
+            }
+        }
+    }
+}
+
+impl ParsedEntry {
+    /// The location the entry was logged at.
+    fn location(&self) -> &EventLocation {
+        match self {
+            ParsedEntry::Upgrade(location, _)
+            | ParsedEntry::ItemCreated(location, _)
+            | ParsedEntry::ItemStatusChanged(location, _)
+            | ParsedEntry::ItemSummary(location, _)
+            | ParsedEntry::TokenTransfer(location, _)
+            | ParsedEntry::Nonce(location, _) => location,
+        }
+    }
 }
 
 /// A handler for storing monitored events in the database. This implements
-/// the `indexer::ProcessEvent` trait to store events in the database.
+/// the `indexer::ProcessEvent` trait to store the events parsed by
+/// [`parse_batch`] in the database. Every entry is stored by its handler in
+/// the `handlers`.
 struct StoreEvents {
+    /// The handlers storing the parsed entries.
+    handlers:          EventDispatcher,
     /// A database pool used for reconnects.
-    db_pool: DatabasePool,
+    db_pool:           DatabasePool,
+    /// The message broker the events are additionally published to.
+    sink:              Option<EventSink>,
+    /// The channel the new events are additionally sent on with `NOTIFY`.
+    notify:            Option<String>,
+    /// The range of [`db::PARTITION_SIZE`] blocks up to which the partitions of
+    /// the event tables have been created.
+    partitioned_range: Option<u64>,
+    /// Whether the event tables are TimescaleDB hypertables, whose chunks are
+    /// created by TimescaleDB instead of the indexer.
+    hypertables:       bool,
+    /// The progress of the indexer, counting the skipped duplicate events.
+    progress:          IndexerProgress,
+}
+
+/// Collect the upgrades of the `contract` from the execution `tree` of a
+/// transaction as the entrypoint that upgraded the contract together with the
+/// old and the new module reference.
+fn collect_upgrades(
+    tree: &ExecutionTree,
+    contract: ContractAddress,
+    upgrades: &mut Vec<(OwnedEntrypointName, ModuleReference, ModuleReference)>,
+) {
+    // Contracts of version 0 cannot be upgraded.
+    let ExecutionTree::V1(tree) = tree else {
+        return;
+    };
+    for trace in &tree.events {
+        match trace {
+            TraceV1::Upgrade { from, to } if tree.address == contract => {
+                let entrypoint = tree.receive_name.as_receive_name().entrypoint_name();
+                upgrades.push((entrypoint.to_owned(), *from, *to));
+            }
+            TraceV1::Call { call } => collect_upgrades(call, contract, upgrades),
+            _ => {}
+        }
+    }
 }
 
 #[indexer::async_trait]
 impl indexer::ProcessEvent for StoreEvents {
-    type Data = (
-        BlockInfo,
-        Vec<(
-            ContractUpdateInfo,
-            BTreeMap<ContractAddress, BTreeSet<OwnedReceiveName>>,
-        )>,
-    );
+    type Data = ParsedBatch;
     type Description = String;
     type Error = anyhow::Error;
 
-    async fn process(
-        &mut self,
-        (block_info, contract_update_info): &Self::Data,
-    ) -> Result<Self::Description, Self::Error> {
+    async fn process(&mut self, blocks: &Self::Data) -> Result<Self::Description, Self::Error> {
+        let (Some(first_block), Some(last_block)) = (blocks.first(), blocks.last()) else {
+            return Ok("Processed an empty batch of blocks.".to_string());
+        };
+        let (first_block, last_block) = (&first_block.block_info, &last_block.block_info);
+
         let mut conn = self.db_pool.get().await?;
 
+        // The partitions are created outside of the transaction of the batch, since
+        // creating a partition locks the partitioned table.
+        let first_range = first_block.block_height.height / db::PARTITION_SIZE;
+        let last_range = last_block.block_height.height / db::PARTITION_SIZE;
+        for range in first_range..=last_range {
+            if self.hypertables
+                || self
+                    .partitioned_range
+                    .map_or(false, |partitioned| range <= partitioned)
+            {
+                continue;
+            }
+            conn.ensure_partitions((range * db::PARTITION_SIZE).into())
+                .await
+                .context("Failed to create the partitions of the event tables")?;
+            self.partitioned_range = Some(range);
+        }
+
         // It is typically easiest to reason about a database if blocks are inserted
-        // in a single database transaction. So we do that here.
+        // in a single database transaction. So we do that here for all blocks of the
+        // batch, which stores the checkpoint once per batch.
         let db_transaction = conn
             .client
             .transaction()
             .await
             .context("Failed to build database transaction")?;
 
-        let params: [&(dyn ToSql + Sync); 1] = [&(block_info.block_height.height as i64)];
+        let params: [&(dyn ToSql + Sync); 1] = [&(last_block.block_height.height as i64)];
 
         // Update latest_processed_block_height
         let statement = db_transaction
@@ -108,109 +1021,209 @@ impl indexer::ProcessEvent for StoreEvents {
             .await
             .context("Failed to execute latest_processed_block_height transaction")?;
 
-        for single_contract_update_info in contract_update_info {
-            for (_contract_invoked, _entry_point_name, events) in
-                single_contract_update_info.0.execution_tree.events()
-            {
-                for (event_index, event) in events.iter().enumerate() {
-                    let parsed_event: contract::Event<AdditionalData> = event.parse()?;
-
-                    if let contract::Event::<AdditionalData>::ItemStatusChanged(
-                        item_status_change_event,
-                    ) = parsed_event
-                    {
-                        let params: [&(dyn ToSql + Sync); 6] = [
-                            &(block_info.block_slot_time),
-                            &single_contract_update_info.0.transaction_hash.as_ref(),
-                            &(event_index as i64),
-                            &(item_status_change_event.item_id.0 as i64),
-                            &Json(&item_status_change_event.new_status),
-                            &item_status_change_event.additional_data.bytes,
-                        ];
-
-                        let statement = db_transaction
-                            .prepare_cached(
-                                "INSERT INTO item_status_changed_events (id, block_time, \
-                                 transaction_hash, event_index, item_id, new_status, \
-                                 additional_data) SELECT COALESCE(MAX(id) + 1, 0), $1, $2, $3, \
-                                 $4, $5, $6 FROM item_status_changed_events;",
-                            )
-                            .await
-                            .context("Failed to prepare item_status_change_event transaction")?;
-
-                        db_transaction
-                            .execute(&statement, &params)
-                            .await
-                            .context("Failed to execute item_status_change_event transaction")?;
-
-                        tracing::debug!(
-                            "Preparing item_status_change_event from block {}, transaction hash \
-                             {}, and event index {}.",
-                            block_info.block_height,
-                            single_contract_update_info.0.transaction_hash,
-                            event_index
-                        );
-                    } else if let contract::Event::<AdditionalData>::ItemCreated(
-                        item_created_event,
-                    ) = parsed_event
-                    {
-                        let params: [&(dyn ToSql + Sync); 6] = [
-                            &(block_info.block_slot_time),
-                            &single_contract_update_info.0.transaction_hash.as_ref(),
-                            &(event_index as i64),
-                            &(item_created_event.item_id.0 as i64),
-                            &to_bytes(&item_created_event.metadata_url),
-                            &Json(&item_created_event.initial_status),
-                        ];
-
-                        let statement = db_transaction
-                            .prepare_cached(
-                                "INSERT INTO item_created_events (id, block_time, \
-                                 transaction_hash, event_index, item_id, metadata_url, \
-                                 initial_status) SELECT COALESCE(MAX(id) + 1, 0), $1, $2, $3, $4, \
-                                 $5, $6 FROM item_created_events;",
-                            )
-                            .await
-                            .context("Failed to prepare item_created_event transaction")?;
-
-                        db_transaction
-                            .execute(&statement, &params)
-                            .await
-                            .context("Failed to execute item_created_event transaction")?;
-
-                        tracing::debug!(
-                            "Preparing event from block {}, transaction hash {}, and event index \
-                             {}.",
-                            block_info.block_height,
-                            single_contract_update_info.0.transaction_hash,
-                            event_index
-                        );
-                    }
-                }
+        // The events published to the sink after they have been inserted.
+        let mut published_events = Vec::new();
+        // The number of events skipped because they are already stored.
+        let mut duplicate_events = 0u64;
+
+        for entry in blocks.iter().flat_map(|block| &block.entries) {
+            let location = entry.location();
+            let handled = self.handlers.dispatch(&db_transaction, entry).await?;
+
+            // Events that are already stored were published when they were first
+            // inserted, so they are not published again.
+            if handled.duplicate {
+                tracing::warn!(
+                    block_height = %location.block_height,
+                    transaction_hash = %location.transaction_hash,
+                    event_index = location.event_index,
+                    "Skipped duplicate event from block {}, transaction hash {}, and event index \
+                     {} that is already stored.",
+                    location.block_height,
+                    location.transaction_hash,
+                    location.event_index
+                );
+                duplicate_events += 1;
+                continue;
             }
+            published_events.extend(handled.published);
+
+            tracing::debug!(
+                block_height = %location.block_height,
+                transaction_hash = %location.transaction_hash,
+                event_index = location.event_index,
+                "Preparing event from block {}, transaction hash {}, and event index {}.",
+                location.block_height,
+                location.transaction_hash,
+                location.event_index
+            );
+        }
+        // The notifications are sent from within the transaction, so that they are
+        // delivered exactly when the events are committed.
+        if let Some(channel) = &self.notify {
+            notify_events(&db_transaction, channel, &published_events)
+                .await
+                .context("Failed to notify events")?;
         }
+
+        // Publish the events before committing, so that the block is processed and
+        // published again if publishing fails.
+        if let Some(sink) = &self.sink {
+            sink.publish(&published_events)
+                .await
+                .context("Failed to publish events")?;
+        }
+
         // Commit the transaction
         db_transaction
             .commit()
             .await
             .context("Failed to commit block transaction")?;
 
+        self.progress.duplicate_events_skipped(duplicate_events);
+
         // We return an informative message that will be logged by the `process_events`
         // method of the indexer.
+        if blocks.len() == 1 {
+            Ok(format!(
+                "Processed block {} at height {} with timestamp {}.",
+                last_block.block_hash, last_block.block_height, last_block.block_slot_time
+            ))
+        } else {
+            Ok(format!(
+                "Processed {} blocks from height {} to height {} with timestamp {}.",
+                blocks.len(),
+                first_block.block_height,
+                last_block.block_height,
+                last_block.block_slot_time
+            ))
+        }
+    }
+
+    async fn on_failure(
+        &mut self,
+        error: Self::Error,
+        _failed_attempts: u32,
+    ) -> Result<bool, Self::Error> {
+        tracing::error!("Encountered error {error}");
+
+        Ok(true)
+    }
+}
+
+/// A handler for printing the monitored events without storing them, used by
+/// `--dry-run`. This implements the `indexer::ProcessEvent` trait like
+/// [`StoreEvents`], but only keeps the checkpoint in memory.
+struct PrintEvents {
+    /// The contract whose upgrades are reported.
+    contract:   ContractAddress,
+    /// The `latest_processed_block_height` that would be stored.
+    checkpoint: Option<AbsoluteBlockHeight>,
+}
+
+impl PrintEvents {
+    /// Print the events of a single block and move the checkpoint to it.
+    fn print_block(
+        &mut self,
+        (block_info, contract_update_info): &ContractBlock,
+    ) -> anyhow::Result<String> {
+        // Blocks have to be processed in order and only once, otherwise the
+        // checkpoint stored after the block would skip or repeat blocks.
+        if let Some(checkpoint) = self.checkpoint {
+            anyhow::ensure!(
+                block_info.block_height > checkpoint,
+                "Block at height {} is processed after the checkpoint {}.",
+                block_info.block_height,
+                checkpoint
+            );
+        }
+
+        let mut parsed_events = 0u64;
+        for single_contract_update_info in contract_update_info {
+            let mut upgrades = Vec::new();
+            collect_upgrades(
+                &single_contract_update_info.0.execution_tree,
+                self.contract,
+                &mut upgrades,
+            );
+            for (_entrypoint, old_module_ref, new_module_ref) in upgrades {
+                tracing::warn!(
+                    "Contract {} was upgraded from module {} to module {} in block {}.",
+                    self.contract,
+                    old_module_ref,
+                    new_module_ref,
+                    block_info.block_height
+                );
+            }
+
+            for (contract_invoked, entry_point_name, events) in
+                single_contract_update_info.0.execution_tree.events()
+            {
+                if contract_invoked != self.contract {
+                    continue;
+                }
+                for (event_index, event) in events.iter().enumerate() {
+                    let location = EventLocation {
+                        block_height:     block_info.block_height,
+                        block_time:       block_info.block_slot_time,
+                        transaction_hash: single_contract_update_info.0.transaction_hash,
+                        event_index:      event_index as u64,
+                        sender:           single_contract_update_info.0.sender,
+                        entrypoint:       entry_point_name.to_owned(),
+                    };
+
+                    let parsed_event = match event.parse::<contract::Event<AdditionalData>>()? {
+                        contract::Event::ItemStatusChanged(event) => {
+                            PublishedEvent::item_status_changed(&location, &event)
+                        }
+                        contract::Event::ItemCreated(event) => {
+                            PublishedEvent::item_created(&location, &event)
+                        }
+                        _ => continue,
+                    };
+                    println!("{}", serde_json::to_string_pretty(&parsed_event)?);
+                    parsed_events += 1;
+                }
+            }
+        }
+
+        self.checkpoint = Some(block_info.block_height);
+
         Ok(format!(
-            "Processed block {} at height {} with timestamp {}.",
-            block_info.block_hash, block_info.block_height, block_info.block_slot_time
+            "Parsed {} events of block {} at height {} with timestamp {}. The checkpoint would be \
+             set to {}.",
+            parsed_events,
+            block_info.block_hash,
+            block_info.block_height,
+            block_info.block_slot_time,
+            block_info.block_height
         ))
     }
+}
+
+#[indexer::async_trait]
+impl indexer::ProcessEvent for PrintEvents {
+    type Data = ContractBatch;
+    type Description = String;
+    type Error = anyhow::Error;
+
+    async fn process(&mut self, blocks: &Self::Data) -> Result<Self::Description, Self::Error> {
+        let descriptions = blocks
+            .iter()
+            .map(|block| self.print_block(block))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(descriptions.join(" "))
+    }
 
     async fn on_failure(
         &mut self,
         error: Self::Error,
         _failed_attempts: u32,
     ) -> Result<bool, Self::Error> {
+        // Parsing a block fails the same way every time, so it is not re-tried.
         tracing::error!("Encountered error {error}");
 
-        Ok(true)
+        Ok(false)
     }
 }
 
@@ -220,48 +1233,228 @@ async fn main() -> anyhow::Result<()> {
 
     // Tracing configuration.
     {
-        use tracing_subscriber::prelude::*;
         let log_filter = tracing_subscriber::filter::Targets::new()
             .with_target(module_path!(), app.log_level)
+            .with_target("indexer", app.log_level)
             .with_target("ccd_indexer", app.log_level)
             .with_target("ccd_event_processor", app.log_level)
             .with_target("tokio_postgres", app.log_level);
 
-        tracing_subscriber::registry()
-            .with(tracing_subscriber::fmt::layer())
-            .with(log_filter)
-            .init();
+        logging::init(app.log_format, log_filter);
     }
 
-    // Set up endpoint to the node.
-    let endpoint = if app
-        .node_endpoint
-        .uri()
-        .scheme()
-        .map_or(false, |x| x == &sdk::Scheme::HTTPS)
-    {
-        app.node_endpoint
-            .tls_config(tonic::transport::channel::ClientTlsConfig::new())
-            .context("Unable to construct TLS configuration for the Concordium API.")?
-    } else {
-        app.node_endpoint
-    }
-    .connect_timeout(std::time::Duration::from_secs(5))
-    .timeout(std::time::Duration::from_secs(10));
+    // The endpoint to the node is only set up by the subcommands connecting to
+    // the node.
+    let endpoint = || node_endpoint(app.node_endpoint.clone(), &app.node_tls, &app.node_channel);
+    let max_retries = app.node_channel.max_retries;
 
-    // Establish connection to the blockchain node.
-    let mut client = Client::new(endpoint.clone()).await?;
+    let db_connection = match &app.db_connection_file {
+        Some(path) => db::read_db_connection_file(path)
+            .context("Could not read the database connection file")?,
+        None => app.db_connection,
+    };
+
+    match app.command {
+        Some(Command::Migrate) => migrate(db_connection).await,
+        Some(Command::ExportState { output }) => export_state(db_connection, output).await,
+        Some(Command::ImportState { input }) => import_state(db_connection, input).await,
+        Some(Command::Check { repair }) => check_checkpoint(db_connection, repair).await,
+        Some(Command::Export {
+            format,
+            output,
+            from_height,
+            to_height,
+        }) => {
+            let db_pool = DatabasePool::create(db_connection, 1, false)
+                .await
+                .context("Could not create database pool")?;
+            export::export_events(&db_pool, format, &output, from_height, to_height).await
+        }
+        Some(Command::Index(index_args)) => {
+            run_indexer(
+                endpoint()?,
+                db_connection,
+                app.db_connection_file,
+                index_args,
+                max_retries,
+            )
+            .await
+        }
+        Some(Command::Verify(range)) => {
+            verify_range(endpoint()?, db_connection, range, RangeMode::Verify).await
+        }
+        Some(Command::Repair(range)) => {
+            verify_range(endpoint()?, db_connection, range, RangeMode::Repair).await
+        }
+        Some(Command::Backfill(range)) => {
+            verify_range(endpoint()?, db_connection, range, RangeMode::Backfill).await
+        }
+        None => {
+            run_indexer(
+                endpoint()?,
+                db_connection,
+                app.db_connection_file,
+                app.index,
+                max_retries,
+            )
+            .await
+        }
+    }
+}
+
+/// Set up the endpoint to the node, enabling TLS for `https` endpoints. If a
+/// client certificate is given in the `tls` arguments, it is presented to the
+/// node (mutual TLS). The timeouts, keepalive pings, and flow control of the
+/// connection are configured by the `channel` arguments.
+fn node_endpoint(
+    node_endpoint: sdk::Endpoint,
+    tls: &NodeTlsArgs,
+    channel: &NodeChannelArgs,
+) -> anyhow::Result<sdk::Endpoint> {
+    let is_https = node_endpoint
+        .uri()
+        .scheme()
+        .map_or(false, |x| x == &sdk::Scheme::HTTPS);
+
+    let identity = match (&tls.node_client_cert, &tls.node_client_key) {
+        (Some(cert_path), Some(key_path)) => {
+            anyhow::ensure!(
+                is_https,
+                "A client certificate can only be used with an `https` node endpoint."
+            );
+            let cert = std::fs::read(cert_path).with_context(|| {
+                format!("Could not read client certificate {}", cert_path.display())
+            })?;
+            let key = std::fs::read(key_path)
+                .with_context(|| format!("Could not read client key {}", key_path.display()))?;
+            Some(tonic::transport::Identity::from_pem(cert, key))
+        }
+        _ => None,
+    };
+
+    let endpoint = if is_https {
+        let mut tls_config = tonic::transport::channel::ClientTlsConfig::new();
+        if let Some(identity) = identity {
+            tls_config = tls_config.identity(identity);
+        }
+        node_endpoint
+            .tls_config(tls_config)
+            .context("Unable to construct TLS configuration for the Concordium API.")?
+    } else {
+        node_endpoint
+    }
+    .connect_timeout(std::time::Duration::from_secs(channel.connect_timeout))
+    .timeout(std::time::Duration::from_secs(channel.request_timeout))
+    .http2_adaptive_window(channel.adaptive_window);
+
+    let endpoint = match channel.keepalive_interval {
+        Some(interval) => endpoint
+            .http2_keep_alive_interval(std::time::Duration::from_secs(interval))
+            .keep_alive_timeout(std::time::Duration::from_secs(channel.keepalive_timeout))
+            .keep_alive_while_idle(true),
+        None => endpoint,
+    };
+    Ok(endpoint)
+}
+
+/// Index the events of the contract into the database.
+async fn run_indexer(
+    endpoint: sdk::Endpoint,
+    db_connection: tokio_postgres::config::Config,
+    db_connection_file: Option<PathBuf>,
+    args: IndexArgs,
+    max_retries: u32,
+) -> anyhow::Result<()> {
+    let contract_address = args
+        .contract_address
+        .context("The contract address is required for indexing")?;
+
+    // Establish connection to the blockchain node.
+    let mut client = Client::new(endpoint.clone()).await?;
     let consensus_info = client.get_consensus_info().await?;
 
+    validate_contract(&mut client, contract_address, args.module_ref).await?;
+
+    anyhow::ensure!(
+        args.max_parallel > 0,
+        "The maximum number of parallel queries has to be at least 1"
+    );
+    anyhow::ensure!(args.buffer_size > 0, "The buffer size has to be at least 1");
+    anyhow::ensure!(args.batch_size > 0, "The batch size has to be at least 1");
+
+    let traversal_settings = TraversalSettings {
+        max_parallel: args.max_parallel,
+        buffer_size: args.buffer_size,
+        batch_size: args.batch_size,
+        batch_flush_interval: std::time::Duration::from_secs(args.flush_interval),
+        drain_timeout: std::time::Duration::from_secs(args.drain_timeout),
+        max_retries,
+    };
+
+    if args.dry_run {
+        let start_block = if args.follow {
+            consensus_info.last_finalized_block_height
+        } else {
+            client.find_instance_creation(.., contract_address).await?.0
+        };
+        tracing::info!("Dry run: the events are printed but not stored in the database.");
+
+        let progress = IndexerProgress::default();
+        tokio::spawn(report_progress(
+            client,
+            start_block,
+            progress.clone(),
+            std::time::Duration::from_secs(args.progress_interval),
+        ));
+
+        let events = PrintEvents {
+            contract:   contract_address,
+            checkpoint: None,
+        };
+        return handle_indexing(
+            endpoint,
+            start_block,
+            BTreeSet::from([contract_address]),
+            events,
+            Ok,
+            progress,
+            traversal_settings,
+        )
+        .await;
+    }
+
+    let db_connection = if args.tenant_schema {
+        tracing::info!(
+            "Storing the tables of contract {} in schema {}.",
+            contract_address,
+            db::contract_schema(&contract_address)
+        );
+        db::use_contract_schema(db_connection, &contract_address)
+            .await
+            .context("Could not set up the schema of the contract")?
+    } else {
+        db_connection
+    };
+
     // Establish connection to the postgres database.
-    let db_pool = DatabasePool::create(app.db_connection.clone(), 2, true)
+    let db_pool = DatabasePool::create(db_connection, 3, true)
         .await
         .context("Could not create database pool")?;
-    let db = db_pool
+
+    if let Some(path) = db_connection_file {
+        let contract_schema = args.tenant_schema.then_some(contract_address);
+        let reload = db::reload_on_hangup(db_pool.clone(), path, contract_schema);
+        tokio::spawn(async move {
+            if let Err(error) = reload.await {
+                tracing::error!("Stopped reloading the database connection: {error}");
+            }
+        });
+    }
+    let mut db = db_pool
         .get()
         .await
         .context("Could not get database connection from pool")?;
-    db.init_settings(&app.contract_address, &consensus_info.genesis_block)
+    db.init_settings(&contract_address, &consensus_info.genesis_block)
         .await
         .context("Could not init settings for database")?;
     let settings = db
@@ -273,9 +1466,9 @@ async fn main() -> anyhow::Result<()> {
     // `contract_address` settings of the indexer are compatible with the stored
     // indexer settings to prevent corrupting the database.
     anyhow::ensure!(
-        settings.contract_address == app.contract_address,
+        settings.contract_address == contract_address,
         "Contract address {} does not match the contract address {} found in the database",
-        app.contract_address,
+        contract_address,
         settings.contract_address
     );
 
@@ -290,13 +1483,66 @@ async fn main() -> anyhow::Result<()> {
         settings.genesis_block_hash
     );
 
+    let schema_version = db
+        .get_schema_version()
+        .await
+        .context("Could not get schema version from database")?;
     tracing::info!(
-        "Indexing contract {:?} on network with genesis hash {}.",
+        "Database schema version {:?} (supported schema version {}).",
+        schema_version,
+        db::supported_schema_version()
+    );
+
+    if args.timescale {
+        let converted = db
+            .create_hypertables()
+            .await
+            .context("Could not convert the event tables into TimescaleDB hypertables")?;
+        if converted {
+            tracing::info!("Converted the event tables into TimescaleDB hypertables.");
+        }
+    }
+    let hypertables = db
+        .uses_hypertables()
+        .await
+        .context("Could not check whether the event tables are TimescaleDB hypertables")?;
+
+    let upgrades = db
+        .get_upgrades()
+        .await
+        .context("Could not get the upgrades of the contract from the database")?;
+    if let Some(upgrade) = upgrades.last() {
+        tracing::warn!(
+            "Contract {} was upgraded {} time(s), last to module {} in block {}. Make sure the \
+             event schema of the indexer matches the current module.",
+            contract_address,
+            upgrades.len(),
+            upgrade.new_module_ref,
+            upgrade.block_height
+        );
+    }
+
+    tracing::info!(
+        "Indexing contract {:?} on network {} with genesis hash {}.",
         settings.contract_address.index,
+        db::network_of_genesis(&settings.genesis_block_hash)
+            .map_or_else(|| "unknown".to_string(), |network| network.to_string()),
         settings.genesis_block_hash
     );
 
     let start_block = match settings.latest_processed_block_height {
+        // In follow mode, the historical blocks are skipped and indexing starts
+        // from the last finalized block, unless the database is already ahead of it.
+        processed_block if args.follow => {
+            let last_finalized = consensus_info.last_finalized_block_height;
+            let start =
+                processed_block.map_or(last_finalized, |height| height.next().max(last_finalized));
+            tracing::info!(
+                "Following the chain, skipping the blocks before height {}.",
+                start
+            );
+            start
+        }
         // If the indexer is re-started with the same database settings,
         // it should resume indexing from the `latest_processed_block_height+1` as stored in the
         // database.
@@ -304,46 +1550,1123 @@ async fn main() -> anyhow::Result<()> {
         // If the indexer is started for the first time, lookup when the instance was created and
         // use that block as the starting block.
         None => {
-            let instance_created = client
-                .find_instance_creation(.., app.contract_address)
-                .await?;
+            let instance_created = client.find_instance_creation(.., contract_address).await?;
 
             instance_created.0
         }
     };
 
-    handle_indexing(endpoint, start_block, app.contract_address, db_pool).await
+    let progress = IndexerProgress::default();
+
+    if let Some(stall_after) = args.stall_after {
+        let watchdog = Watchdog::new(
+            client.clone(),
+            progress.clone(),
+            std::time::Duration::from_secs(stall_after * 60),
+            args.stall_webhook,
+        );
+        tokio::spawn(watchdog.run());
+    }
+
+    tokio::spawn(report_progress(
+        client,
+        start_block,
+        progress.clone(),
+        std::time::Duration::from_secs(args.progress_interval),
+    ));
+
+    tokio::spawn(refresh_status_transitions(
+        db_pool.clone(),
+        std::time::Duration::from_secs(args.analytics_refresh),
+    ));
+
+    if let Some(retention_days) = args.retention_days {
+        tokio::spawn(prune_events(
+            db_pool.clone(),
+            chrono::Duration::days(retention_days.into()),
+            std::time::Duration::from_secs(args.retention_interval),
+        ));
+    }
+
+    if let Some(payload_retention) = args.payload_retention {
+        tokio::spawn(redact_additional_data(
+            db_pool.clone(),
+            chrono::Duration::days(payload_retention.into()),
+            args.payload_redaction,
+            std::time::Duration::from_secs(args.retention_interval),
+        ));
+    }
+
+    if let Some(health_address) = args.health_address {
+        let health_server = health::serve(
+            health_address,
+            progress.clone(),
+            db_pool.clone(),
+            std::time::Duration::from_secs(args.health_max_behind),
+            args.pause_control,
+            args.dashboard,
+            Info::new(
+                "indexer",
+                std::iter::once(contract_address)
+                    .chain(args.token_contract)
+                    .collect(),
+            ),
+        );
+        tokio::spawn(async move {
+            if let Err(error) = health_server.await {
+                tracing::error!("Health endpoint stopped: {error}");
+            }
+        });
+    }
+
+    let sink = match (args.sink, args.brokers) {
+        (Some(kind), Some(brokers)) => Some(
+            connect_sink(kind, &brokers, args.sink_topic)
+                .await
+                .context("Could not connect to the event sink")?,
+        ),
+        _ => None,
+    };
+
+    // The token contract is traversed together with the track and trace contract,
+    // so that the transfers are stored in the same database transaction as the
+    // events of the items.
+    let mut contracts = BTreeSet::from([contract_address]);
+    if let Some(token_contract) = args.token_contract {
+        tracing::info!(
+            "Indexing the transfers of token contract {}.",
+            token_contract
+        );
+        contracts.insert(token_contract);
+    }
+
+    let events = StoreEvents {
+        handlers: EventDispatcher::new(contract_address),
+        db_pool,
+        sink,
+        notify: args.notify.then_some(args.notify_channel),
+        partitioned_range: None,
+        hypertables,
+        progress: progress.clone(),
+    };
+
+    let token_contract = args.token_contract;
+    handle_indexing(
+        endpoint,
+        start_block,
+        contracts,
+        events,
+        move |batch| parse_batch(contract_address, token_contract, batch),
+        progress,
+        traversal_settings,
+    )
+    .await
 }
 
-/// Handle indexing events.
-async fn handle_indexing(
-    endpoint: sdk::Endpoint,
-    start: AbsoluteBlockHeight,
+/// Check that the contract is a track and trace contract before indexing it,
+/// so that the indexer fails fast if the `--contract` points at an unrelated
+/// contract. The contract has to be initialized with `init_track_and_trace`
+/// and, if given, be an instance of the module `module_ref`.
+async fn validate_contract(
+    client: &mut Client,
     contract_address: ContractAddress,
-    db_pool: DatabasePool,
+    module_ref: Option<ModuleReference>,
 ) -> anyhow::Result<()> {
-    tracing::info!("Indexing from block height {}.", start);
+    let instance_info = client
+        .get_instance_info(contract_address, &BlockIdentifier::LastFinal)
+        .await
+        .with_context(|| format!("Could not query the contract instance {contract_address}"))?
+        .response;
+
+    let (name, source_module) = match instance_info {
+        InstanceInfo::V0 {
+            name,
+            source_module,
+            ..
+        }
+        | InstanceInfo::V1 {
+            name,
+            source_module,
+            ..
+        } => (name, source_module),
+    };
 
-    let contract_set = BTreeSet::from([contract_address]);
+    anyhow::ensure!(
+        name.as_contract_name().get_chain_name() == CONTRACT_INIT_NAME,
+        "Contract {} has the init name `{}` but a track and trace contract with the init name \
+         `{}` is expected",
+        contract_address,
+        name,
+        CONTRACT_INIT_NAME
+    );
 
-    let traverse_config = indexer::TraverseConfig::new_single(endpoint, start);
+    if let Some(module_ref) = module_ref {
+        anyhow::ensure!(
+            source_module == module_ref,
+            "Contract {} is an instance of module {} but module {} is expected",
+            contract_address,
+            source_module,
+            module_ref
+        );
+    }
 
-    let events = StoreEvents { db_pool };
+    tracing::info!(
+        "Contract {} is an instance of `{}` from module {}.",
+        contract_address,
+        name,
+        source_module
+    );
+    Ok(())
+}
 
-    // The program terminates only
-    // when the processor terminates, which in this example can only happen if
-    // there are sufficiently many errors when attempting to write to the
-    // database.
-    indexer::traverse_and_process(
-        traverse_config,
-        AffectedContractIndexer {
-            addresses: contract_set,
-            all:       true,
-        },
-        ProcessorConfig::new(),
-        events,
+/// Re-read the events of the contract stored in the settings from the blocks
+/// in the `range` and compare them with the rows in the database. Depending on
+/// the `mode`, the differences are fixed afterwards. Fails if the database does
+/// not match the chain after the (optional) fix.
+async fn verify_range(
+    endpoint: sdk::Endpoint,
+    db_connection: tokio_postgres::config::Config,
+    range: RangeArgs,
+    mode: RangeMode,
+) -> anyhow::Result<()> {
+    let mut client = Client::new(endpoint.clone()).await?;
+    let consensus_info = client.get_consensus_info().await?;
+
+    let db_pool = DatabasePool::create(db_connection, 1, false)
+        .await
+        .context("Could not create database pool")?;
+    let mut db = db_pool
+        .get()
+        .await
+        .context("Could not get database connection from pool")?;
+    let settings = db
+        .get_settings()
+        .await
+        .context("Could not get settings from database")?;
+
+    anyhow::ensure!(
+        settings.genesis_block_hash == consensus_info.genesis_block,
+        "Genesis hash from the connected node {} does not match the genesis hash {} found in the \
+         database",
+        consensus_info.genesis_block,
+        settings.genesis_block_hash
+    );
+
+    let to_height = range
+        .to_height
+        .or(settings.latest_processed_block_height)
+        .context("No block has been processed yet. Specify the `--to-height`.")?;
+    anyhow::ensure!(
+        range.from_height <= to_height,
+        "The from height {} is above the to height {}.",
+        range.from_height,
+        to_height
+    );
+    anyhow::ensure!(
+        to_height <= consensus_info.last_finalized_block_height,
+        "The to height {} is above the last finalized block height {}.",
+        to_height,
+        consensus_info.last_finalized_block_height
+    );
+
+    tracing::info!(
+        "Reading events of contract {} from block height {} to {}.",
+        settings.contract_address,
+        range.from_height,
+        to_height
+    );
+    let chain_events = verify::chain_events(
+        endpoint,
+        settings.contract_address,
+        range.from_height,
+        to_height,
     )
     .await?;
 
+    let mut report = verify::verify(&db, chain_events, range.from_height, to_height).await?;
+    log_report(&report);
+
+    match mode {
+        RangeMode::Verify => (),
+        RangeMode::Repair => {
+            if !report.missing.is_empty() {
+                verify::repair(&db, &report.missing).await?;
+                tracing::info!("Re-inserted {} missing events.", report.missing.len());
+                report.matching += report.missing.len() as u64;
+                report.missing.clear();
+            }
+        }
+        RangeMode::Backfill => {
+            verify::backfill(&mut db, &report).await?;
+            tracing::info!(
+                "Backfilled {} missing and {} mismatching events.",
+                report.missing.len(),
+                report.mismatched.len()
+            );
+            report.matching += (report.missing.len() + report.mismatched.len()) as u64;
+            report.missing.clear();
+            report.mismatched.clear();
+        }
+    }
+
+    anyhow::ensure!(
+        report.is_consistent(),
+        "The database does not match the chain between block height {} and {}.",
+        range.from_height,
+        to_height
+    );
     Ok(())
 }
+
+/// Log the outcome of a verification.
+fn log_report(report: &verify::VerificationReport) {
+    for missing in &report.missing {
+        tracing::warn!(
+            "Missing event from block {}, transaction hash {}, and event index {}.",
+            missing.location.block_height,
+            missing.location.transaction_hash,
+            missing.location.event_index
+        );
+    }
+    for mismatched in &report.mismatched {
+        tracing::warn!(
+            "Mismatching event from block {}, transaction hash {}, and event index {}.",
+            mismatched.location.block_height,
+            mismatched.location.transaction_hash,
+            mismatched.location.event_index
+        );
+    }
+    for (table, transaction_hash, event_index) in &report.unexpected {
+        tracing::warn!(
+            "Unexpected event in {} from transaction hash {} and event index {}.",
+            table.name(),
+            transaction_hash,
+            event_index
+        );
+    }
+    tracing::info!(
+        "Verified events: {} matching, {} missing, {} mismatching, {} unexpected.",
+        report.matching,
+        report.missing.len(),
+        report.mismatched.len(),
+        report.unexpected.len()
+    );
+}
+
+/// Apply the pending migrations to the database. Fails if the schema of the
+/// database is newer than the schema supported by the indexer.
+async fn migrate(db_connection: tokio_postgres::config::Config) -> anyhow::Result<()> {
+    DatabasePool::create(db_connection, 1, true)
+        .await
+        .context("Could not migrate the database")?;
+    tracing::info!(
+        "Database schema is at version {}.",
+        db::supported_schema_version()
+    );
+    Ok(())
+}
+
+/// Export the settings and the `latest_processed_block_height` checkpoint
+/// stored in the database as JSON to the `output` file or stdout.
+async fn export_state(
+    db_connection: tokio_postgres::config::Config,
+    output: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let db_pool = DatabasePool::create(db_connection, 1, false)
+        .await
+        .context("Could not create database pool")?;
+    let db = db_pool
+        .get()
+        .await
+        .context("Could not get database connection from pool")?;
+
+    let exported_state = ExportedState {
+        indexer_version: env!("CARGO_PKG_VERSION").to_string(),
+        settings:        db
+            .get_settings()
+            .await
+            .context("Could not get settings from database")?,
+        event_counts:    db
+            .get_event_counts()
+            .await
+            .context("Could not get event counts from database")?,
+    };
+
+    let json = serde_json::to_string_pretty(&exported_state)?;
+    match output {
+        Some(path) => {
+            std::fs::write(&path, json)
+                .with_context(|| format!("Could not write state to {}", path.display()))?;
+            tracing::info!("Exported state to {}.", path.display());
+        }
+        None => println!("{json}"),
+    }
+
+    Ok(())
+}
+
+/// Restore the settings and the `latest_processed_block_height` checkpoint
+/// from a JSON file created by `export-state`.
+///
+/// The import is only performed if the database is consistent with the
+/// exported state, i.e., the database either has no settings yet or the
+/// settings are for the same network and contract, and the database holds
+/// the same number of events as the database the state was exported from.
+/// After the import, the settings are read back and compared with the
+/// exported state. The indexer should not be running during the import.
+async fn import_state(
+    db_connection: tokio_postgres::config::Config,
+    input: PathBuf,
+) -> anyhow::Result<()> {
+    let json = std::fs::read(&input)
+        .with_context(|| format!("Could not read state from {}", input.display()))?;
+    let exported_state: ExportedState =
+        serde_json::from_slice(&json).context("Could not parse the exported state")?;
+
+    let db_pool = DatabasePool::create(db_connection, 1, true)
+        .await
+        .context("Could not create database pool")?;
+    let db = db_pool
+        .get()
+        .await
+        .context("Could not get database connection from pool")?;
+
+    if let Some(settings) = db
+        .get_settings_opt()
+        .await
+        .context("Could not get settings from database")?
+    {
+        anyhow::ensure!(
+            settings.genesis_block_hash == exported_state.settings.genesis_block_hash,
+            "Genesis hash {} of the exported state does not match the genesis hash {} found in \
+             the database",
+            exported_state.settings.genesis_block_hash,
+            settings.genesis_block_hash
+        );
+        anyhow::ensure!(
+            settings.contract_address == exported_state.settings.contract_address,
+            "Contract address {} of the exported state does not match the contract address {} \
+             found in the database",
+            exported_state.settings.contract_address,
+            settings.contract_address
+        );
+    }
+
+    let event_counts = db
+        .get_event_counts()
+        .await
+        .context("Could not get event counts from database")?;
+    anyhow::ensure!(
+        event_counts == exported_state.event_counts,
+        "The database holds {:?} but the exported state expects {:?}",
+        event_counts,
+        exported_state.event_counts
+    );
+
+    db.restore_settings(&exported_state.settings)
+        .await
+        .context("Could not restore settings in database")?;
+
+    let restored_settings = db
+        .get_settings()
+        .await
+        .context("Could not get settings from database")?;
+    anyhow::ensure!(
+        restored_settings == exported_state.settings,
+        "The restored settings {:?} do not match the exported settings {:?}",
+        restored_settings,
+        exported_state.settings
+    );
+
+    tracing::info!(
+        "Imported state from {} (exported by indexer version {}). Indexing resumes after block \
+         height {:?}.",
+        input.display(),
+        exported_state.indexer_version,
+        restored_settings.latest_processed_block_height
+    );
+
+    Ok(())
+}
+
+/// Check that the `latest_processed_block_height` checkpoint is consistent with
+/// the events stored in the event tables. The blocks up to the checkpoint are
+/// stored in one database transaction together with the checkpoint, so the
+/// checkpoint is inconsistent if
+/// - an event table holds events from blocks above the checkpoint, or
+/// - an event table holds events but no checkpoint is stored.
+///
+/// Such inconsistencies are caused by partial writes or manual edits of the
+/// database. With `repair`, the events from blocks above the checkpoint are
+/// deleted, so that the indexer indexes these blocks again, and a missing
+/// checkpoint is set to the block before the latest block with events, so
+/// that the indexer resumes with that block and skips its events that are
+/// already stored. All repairs are applied in one database transaction.
+/// Without `repair`, the subcommand fails if an inconsistency is found. The
+/// indexer should not be running during the check.
+async fn check_checkpoint(
+    db_connection: tokio_postgres::config::Config,
+    repair: bool,
+) -> anyhow::Result<()> {
+    let db_pool = DatabasePool::create(db_connection, 1, false)
+        .await
+        .context("Could not create database pool")?;
+    let mut db = db_pool
+        .get()
+        .await
+        .context("Could not get database connection from pool")?;
+
+    let checkpoint = db
+        .get_settings()
+        .await
+        .context("Could not get settings from database")?
+        .latest_processed_block_height;
+
+    let mut latest_height: Option<AbsoluteBlockHeight> = None;
+    // The event tables holding events from blocks above the checkpoint.
+    let mut tables_above_checkpoint = Vec::new();
+    for table in [
+        EventTable::ItemCreatedEvents,
+        EventTable::ItemStatusChangedEvents,
+        EventTable::ItemSummaryEvents,
+        EventTable::TokenTransferEvents,
+        EventTable::NonceEvents,
+    ] {
+        let position = db
+            .get_latest_event_position(table)
+            .await
+            .with_context(|| format!("Could not read the latest event from {}", table.name()))?;
+        let Some((block_height, event_index)) = position else {
+            tracing::info!("Table {} holds no events.", table.name());
+            continue;
+        };
+        tracing::info!(
+            "Latest event in table {} at block height {} with event index {}.",
+            table.name(),
+            block_height,
+            event_index
+        );
+        latest_height = latest_height.max(Some(block_height));
+        if let Some(checkpoint) = checkpoint {
+            if block_height > checkpoint {
+                tracing::warn!(
+                    "Table {} holds events from blocks above the checkpoint {}.",
+                    table.name(),
+                    checkpoint
+                );
+                tables_above_checkpoint.push(table);
+            }
+        }
+    }
+
+    let missing_checkpoint = checkpoint.is_none() && latest_height.is_some();
+    if missing_checkpoint {
+        tracing::warn!("The event tables hold events but no checkpoint is stored.");
+    }
+
+    if tables_above_checkpoint.is_empty() && !missing_checkpoint {
+        tracing::info!(
+            "The checkpoint {:?} is consistent with the stored events.",
+            checkpoint
+        );
+        return Ok(());
+    }
+
+    anyhow::ensure!(
+        repair,
+        "The checkpoint {:?} is inconsistent with the stored events. Run the `check` subcommand \
+         with `--repair` to repair it.",
+        checkpoint
+    );
+
+    let db_transaction = db
+        .client
+        .transaction()
+        .await
+        .context("Failed to build database transaction")?;
+
+    if let Some(checkpoint) = checkpoint {
+        for table in tables_above_checkpoint {
+            let deleted = db::delete_events_above(&db_transaction, table, checkpoint)
+                .await
+                .with_context(|| format!("Could not delete events from {}", table.name()))?;
+            tracing::info!(
+                "Deleted {} events from blocks above the checkpoint {} from table {}.",
+                deleted,
+                checkpoint,
+                table.name()
+            );
+        }
+    }
+
+    if let (None, Some(latest_height)) = (checkpoint, latest_height) {
+        // The latest block with events may have been stored partially, so it is
+        // indexed again.
+        let repaired_checkpoint = latest_height
+            .height
+            .checked_sub(1)
+            .map(AbsoluteBlockHeight::from);
+        db::set_latest_processed_block_height(&db_transaction, repaired_checkpoint)
+            .await
+            .context("Could not set the checkpoint")?;
+        tracing::info!("Set the checkpoint to {:?}.", repaired_checkpoint);
+    }
+
+    db_transaction
+        .commit()
+        .await
+        .context("Failed to commit repair transaction")?;
+
+    tracing::info!("Repaired the checkpoint.");
+
+    Ok(())
+}
+
+/// Periodically refresh the `daily_status_transitions` materialized view every
+/// `refresh_interval`.
+async fn refresh_status_transitions(db_pool: DatabasePool, refresh_interval: std::time::Duration) {
+    let mut interval = tokio::time::interval(refresh_interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    loop {
+        interval.tick().await;
+
+        let result = match db_pool.get().await {
+            Ok(db) => db.refresh_status_transitions().await,
+            Err(error) => Err(error),
+        };
+        match result {
+            Ok(()) => tracing::debug!("Refreshed the daily status transitions."),
+            Err(error) => tracing::warn!("Could not refresh the daily status transitions: {error}"),
+        }
+    }
+}
+
+/// Periodically delete the events from blocks with a slot time older than
+/// `retention`. Failures are logged and retried at the next interval.
+async fn prune_events(
+    db_pool: DatabasePool,
+    retention: chrono::Duration,
+    prune_interval: std::time::Duration,
+) {
+    let mut interval = tokio::time::interval(prune_interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    loop {
+        interval.tick().await;
+
+        let cutoff = chrono::Utc::now() - retention;
+        let result = match db_pool.get().await {
+            Ok(db) => db.prune_events(cutoff).await,
+            Err(error) => Err(error),
+        };
+        match result {
+            Ok(deleted) => tracing::info!(
+                "Deleted {} item created events and {} item status changed events from blocks \
+                 before {}.",
+                deleted.item_created_events,
+                deleted.item_status_changed_events,
+                cutoff
+            ),
+            Err(error) => tracing::warn!("Could not delete expired events: {error}"),
+        }
+    }
+}
+
+/// Periodically remove the additional data of the status changed events from
+/// blocks with a slot time older than `retention` as given by the `redaction`.
+/// Failures are logged and retried at the next interval.
+async fn redact_additional_data(
+    db_pool: DatabasePool,
+    retention: chrono::Duration,
+    redaction: AdditionalDataRedaction,
+    redact_interval: std::time::Duration,
+) {
+    let mut interval = tokio::time::interval(redact_interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    loop {
+        interval.tick().await;
+
+        let cutoff = chrono::Utc::now() - retention;
+        let result = match db_pool.get().await {
+            Ok(db) => db.redact_additional_data(cutoff, redaction).await,
+            Err(error) => Err(error),
+        };
+        match result {
+            Ok(redacted) => tracing::info!(
+                "Removed the additional data of {} item status changed events from blocks before \
+                 {}.",
+                redacted,
+                cutoff
+            ),
+            Err(error) => tracing::warn!("Could not remove expired additional data: {error}"),
+        }
+    }
+}
+
+/// Periodically query the last finalized block height from the node and report
+/// the progress of catching up with it. The progress is logged and recorded in
+/// the `progress` so that it is exposed by the health endpoint.
+async fn report_progress(
+    mut client: Client,
+    start: AbsoluteBlockHeight,
+    progress: IndexerProgress,
+    report_interval: std::time::Duration,
+) {
+    let mut interval = tokio::time::interval(report_interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    // The last processed block height and the time at the previous report.
+    let mut previous: Option<(AbsoluteBlockHeight, std::time::Instant)> = None;
+
+    loop {
+        interval.tick().await;
+
+        let Some(processed) = progress.last_processed_height() else {
+            continue;
+        };
+
+        let last_finalized = match client.get_consensus_info().await {
+            Ok(consensus_info) => consensus_info.last_finalized_block_height,
+            Err(error) => {
+                tracing::warn!("Could not query the last finalized block height: {error}");
+                continue;
+            }
+        };
+
+        let now = std::time::Instant::now();
+        let blocks_per_second = previous.map(|(previous_processed, previous_time)| {
+            processed.height.saturating_sub(previous_processed.height) as f64
+                / now.duration_since(previous_time).as_secs_f64()
+        });
+        previous = Some((processed, now));
+
+        let remaining_blocks = last_finalized.height.saturating_sub(processed.height);
+        let total_blocks = last_finalized.height.saturating_sub(start.height) + 1;
+        let processed_blocks = processed.height.saturating_sub(start.height) + 1;
+        let percent_complete = (100.0 * processed_blocks as f64 / total_blocks as f64).min(100.0);
+        let eta_seconds = blocks_per_second
+            .filter(|blocks_per_second| *blocks_per_second > 0.0)
+            .map(|blocks_per_second| (remaining_blocks as f64 / blocks_per_second).ceil() as u64);
+
+        let catch_up = CatchUpProgress {
+            last_finalized_block_height: last_finalized,
+            remaining_blocks,
+            percent_complete,
+            blocks_per_second,
+            eta_seconds,
+        };
+        progress.set_catch_up(catch_up);
+
+        if remaining_blocks > 0 {
+            tracing::info!(
+                target: "ccd_indexer",
+                processed_block_height = processed.height,
+                last_finalized_block_height = last_finalized.height,
+                remaining_blocks,
+                percent_complete,
+                blocks_per_second,
+                eta_seconds,
+                "Catching up: processed block {} of {} ({:.2}%), {} blocks remaining, ETA {}.",
+                processed,
+                last_finalized,
+                percent_complete,
+                remaining_blocks,
+                eta_seconds.map_or("unknown".to_string(), |eta| format!("{eta}s"))
+            );
+        } else {
+            tracing::debug!(
+                target: "ccd_indexer",
+                "Caught up with the last finalized block {}.",
+                last_finalized
+            );
+        }
+    }
+}
+
+/// The settings of the traversal of the chain by [`handle_indexing`].
+#[derive(Debug, Clone, Copy)]
+struct TraversalSettings {
+    /// The maximum number of blocks queried from the node in parallel.
+    max_parallel:         usize,
+    /// The maximum number of fetched blocks buffered before they are
+    /// processed.
+    buffer_size:          usize,
+    /// The maximum number of blocks processed together in one batch.
+    batch_size:           usize,
+    /// The maximum time the first block of an incomplete batch waits for
+    /// further blocks before the batch is processed.
+    batch_flush_interval: std::time::Duration,
+    /// The maximum time spent processing already fetched blocks after a
+    /// shutdown signal has been received.
+    drain_timeout:        std::time::Duration,
+    /// The number of times a failed traversal is re-started before giving up.
+    max_retries:          u32,
+}
+
+/// The maximum number of parsed batches waiting to be processed. One batch is
+/// parsed while the previous batch is processed, further waiting batches would
+/// only use more memory since processing is the slower stage when catching up.
+const PARSED_BATCH_QUEUE_SIZE: usize = 1;
+
+/// A batch of blocks parsed by the indexing loop and sent to the writer task
+/// of [`handle_indexing`].
+struct PendingBatch<D> {
+    /// The last block of the batch, which is recorded as processed once the
+    /// batch is processed.
+    last_block: BlockInfo,
+    /// The number of blocks in the batch.
+    blocks:     usize,
+    /// The data parsed from the blocks.
+    data:       D,
+}
+
+/// The task processing the parsed batches in [`handle_indexing`].
+type Writer = tokio::task::JoinHandle<anyhow::Result<()>>;
+
+/// Handle indexing events.
+///
+/// Blocks affecting any of the `contracts` are fetched from the node by a
+/// background traversal task and sent over a channel to this function where
+/// they are collected into batches of up to `batch_size` blocks. A batch is
+/// complete once it is full or `batch_flush_interval` after its first block
+/// was received, so that the checkpoint keeps up with the chain when only few
+/// blocks arrive.
+/// Every batch is parsed with `parse` and sent over a second channel to a
+/// writer task where it is processed by the `events` handler. This way the next
+/// batch is parsed while the previous batch is committed. The writer processes
+/// the batches one at a time in the order they were fetched, so the events of
+/// every item are stored in the order they were logged. Since parsing a block
+/// fails the same way every time, a parsing error is not re-tried.
+/// When a shutdown signal is received, the traversal is stopped and the blocks
+/// that have already been fetched are processed (drained) before returning,
+/// bounded by the `drain_timeout` of the `settings`. Since every block is
+/// committed together with the `latest_processed_block_height` checkpoint, any
+/// block not drained in time is picked up again when the indexer is re-started.
+async fn handle_indexing<P>(
+    endpoint: sdk::Endpoint,
+    start: AbsoluteBlockHeight,
+    contracts: BTreeSet<ContractAddress>,
+    events: P,
+    parse: impl Fn(ContractBatch) -> anyhow::Result<P::Data>,
+    progress: IndexerProgress,
+    settings: TraversalSettings,
+) -> anyhow::Result<()>
+where
+    P: ProcessEvent<Description = String, Error = anyhow::Error> + Send + 'static,
+    P::Data: Send + Sync + 'static, {
+    let TraversalSettings {
+        max_parallel,
+        buffer_size,
+        batch_size,
+        batch_flush_interval,
+        drain_timeout,
+        max_retries,
+    } = settings;
+
+    tracing::info!("Indexing from block height {}.", start);
+
+    let shutdown_signal = set_shutdown()?;
+    tokio::pin!(shutdown_signal);
+
+    let (batch_sender, batch_receiver) = mpsc::channel(PARSED_BATCH_QUEUE_SIZE);
+    let mut writer = tokio::spawn(write_batches(events, progress.clone(), batch_receiver));
+
+    let mut traverse_start = start;
+    // The last block sent to the writer. The blocks sent to the writer are
+    // processed even if the traversal fails, so it is re-started after this block.
+    let mut last_sent: Option<AbsoluteBlockHeight> = None;
+    let mut retries = 0u32;
+    let (mut receiver, traverser) = loop {
+        let traverse_config = indexer::TraverseConfig::new_single(endpoint.clone(), traverse_start)
+            .set_max_parallel(max_parallel);
+
+        let (sender, mut receiver) = mpsc::channel(buffer_size);
+        // The number of blocks waiting in the channel is the difference between
+        // the capacity of the channel and the capacity left to the sender. A weak
+        // sender is kept so that the channel still closes when the traversal stops.
+        let queue = sender.downgrade();
+
+        let traverser = tokio::spawn(traverse_config.traverse(
+            AffectedContractIndexer {
+                addresses: contracts.clone(),
+                all:       true,
+            },
+            sender,
+        ));
+
+        // Process blocks until either a shutdown signal is received or the
+        // traversal stops, which only happens if it fails to query the node.
+        let shutdown_received = loop {
+            // While the indexing is paused, no blocks are received, so the traversal
+            // stops fetching blocks once the channel is full and keeps its position.
+            let paused = progress.is_paused();
+            let block = tokio::select! {
+                biased;
+                _ = &mut shutdown_signal => break true,
+                // The writer only stops early if processing a batch failed.
+                _ = batch_sender.closed() => return Err(writer_error(&mut writer).await),
+                _ = progress.wait_until_resumed(), if paused => continue,
+                block = receiver.recv(), if !paused => block,
+            };
+            let Some(block) = block else {
+                break false;
+            };
+
+            // Collect further blocks into the batch until it is full or the
+            // flush interval has passed since its first block was received.
+            let flush_deadline = tokio::time::Instant::now() + batch_flush_interval;
+            let mut batch = vec![block];
+            let mut shutdown_received = false;
+            let mut traversal_stopped = false;
+            while batch.len() < batch_size {
+                tokio::select! {
+                    biased;
+                    _ = &mut shutdown_signal => {
+                        shutdown_received = true;
+                        break;
+                    }
+                    _ = tokio::time::sleep_until(flush_deadline) => break,
+                    block = receiver.recv() => match block {
+                        Some(block) => batch.push(block),
+                        None => {
+                            traversal_stopped = true;
+                            break;
+                        }
+                    },
+                }
+            }
+
+            progress.set_queue_depth(queue_depth(&queue));
+            last_sent = batch.last().map(|(block_info, _)| block_info.block_height);
+            send_batch(&batch_sender, &mut writer, &parse, batch).await?;
+
+            if shutdown_received {
+                break true;
+            }
+            if traversal_stopped {
+                break false;
+            }
+        };
+
+        if shutdown_received {
+            break (receiver, traverser);
+        }
+
+        let error = match traverser.await.context("The traversal task panicked")? {
+            Ok(()) => {
+                drop(batch_sender);
+                return join_writer(&mut writer).await;
+            }
+            Err(error) => error,
+        };
+
+        // Re-start the traversal from the next block not sent to the writer. The
+        // retries are only counted while no block is processed, so that occasional
+        // failures over a long time do not stop the indexer.
+        let next_start = last_sent.map_or(traverse_start, |height| height.next());
+        if next_start > traverse_start {
+            retries = 0;
+        }
+        if retries >= max_retries {
+            return Err(error).context("Failed to traverse the chain");
+        }
+        retries += 1;
+        tracing::warn!(
+            "Failed to traverse the chain: {error:#}. Re-trying from block height {} ({}/{}).",
+            next_start,
+            retries,
+            max_retries
+        );
+        tokio::time::sleep(WAIT_AFTER_FAIL).await;
+        traverse_start = next_start;
+    };
+
+    tracing::info!("Shutdown signal received. Draining blocks that were already fetched.");
+
+    // Closing the receiver stops the traversal from sending further blocks while
+    // the blocks already buffered in the channel can still be received.
+    receiver.close();
+    let drain = async {
+        let mut drained = 0u64;
+        while let Some(block) = receiver.recv().await {
+            let mut batch = vec![block];
+            while batch.len() < batch_size {
+                match receiver.try_recv() {
+                    Ok(block) => batch.push(block),
+                    Err(_) => break,
+                }
+            }
+            drained += batch.len() as u64;
+            send_batch(&batch_sender, &mut writer, &parse, batch).await?;
+        }
+        // Wait until the writer has processed all batches sent to it.
+        drop(batch_sender);
+        join_writer(&mut writer).await?;
+        Ok::<_, anyhow::Error>(drained)
+    };
+
+    match tokio::time::timeout(drain_timeout, drain).await {
+        Ok(drained) => tracing::info!("Drained {} blocks before shutting down.", drained?),
+        Err(_) => tracing::warn!(
+            "Drain timeout of {} seconds exceeded. The remaining blocks will be processed when \
+             the indexer is re-started.",
+            drain_timeout.as_secs()
+        ),
+    }
+
+    traverser.abort();
+    writer.abort();
+
+    Ok(())
+}
+
+/// Process the batches received from the indexing loop of [`handle_indexing`]
+/// with the `events` handler in the order they were sent. Returns once all
+/// batches are processed and the indexing loop has stopped sending batches, or
+/// once processing a batch fails.
+async fn write_batches<P>(
+    mut events: P,
+    progress: IndexerProgress,
+    mut batches: mpsc::Receiver<PendingBatch<P::Data>>,
+) -> anyhow::Result<()>
+where
+    P: ProcessEvent<Description = String, Error = anyhow::Error>, {
+    while let Some(batch) = batches.recv().await {
+        process_batch(&mut events, &progress, &batch).await?;
+    }
+    Ok(())
+}
+
+/// Parse the `batch` and send it to the `writer` task. Fails if parsing fails
+/// or if the writer has stopped because processing a batch failed.
+async fn send_batch<D>(
+    sender: &mpsc::Sender<PendingBatch<D>>,
+    writer: &mut Writer,
+    parse: &impl Fn(ContractBatch) -> anyhow::Result<D>,
+    batch: ContractBatch,
+) -> anyhow::Result<()> {
+    let Some((last_block, _)) = batch.last() else {
+        return Ok(());
+    };
+    let last_block = last_block.clone();
+    let blocks = batch.len();
+    let data = parse(batch).with_context(|| {
+        format!(
+            "Failed to parse the blocks up to height {}",
+            last_block.block_height
+        )
+    })?;
+
+    let pending_batch = PendingBatch {
+        last_block,
+        blocks,
+        data,
+    };
+    if sender.send(pending_batch).await.is_err() {
+        return Err(writer_error(writer).await);
+    }
+    Ok(())
+}
+
+/// Wait for the `writer` task to finish processing the batches sent to it.
+async fn join_writer(writer: &mut Writer) -> anyhow::Result<()> {
+    writer.await.context("The writer task panicked")?
+}
+
+/// The error the `writer` task stopped with before the indexing loop stopped
+/// sending batches, which only happens if processing a batch failed.
+async fn writer_error(writer: &mut Writer) -> anyhow::Error {
+    match join_writer(writer).await {
+        Ok(()) => anyhow::anyhow!("The writer task stopped unexpectedly."),
+        Err(error) => error,
+    }
+}
+
+/// The number of fetched blocks waiting in the channel, or zero once the
+/// traversal has stopped sending blocks.
+fn queue_depth(queue: &mpsc::WeakSender<ContractBlock>) -> u64 {
+    queue.upgrade().map_or(0, |sender| {
+        (sender.max_capacity() - sender.capacity()) as u64
+    })
+}
+
+/// Process a parsed batch of blocks and record its last block in the
+/// `progress`. If processing fails, `on_failure` decides whether the batch is
+/// re-tried after waiting `WAIT_AFTER_FAIL`.
+async fn process_batch<P>(
+    events: &mut P,
+    progress: &IndexerProgress,
+    batch: &PendingBatch<P::Data>,
+) -> anyhow::Result<()>
+where
+    P: ProcessEvent<Description = String, Error = anyhow::Error>, {
+    let last_block = &batch.last_block;
+    let mut successive_failures = 0u32;
+    loop {
+        match events.process(&batch.data).await {
+            Ok(description) => {
+                tracing::info!(
+                    target: "ccd_event_processor",
+                    block_height = %last_block.block_height,
+                    batch_size = batch.blocks,
+                    "{description}"
+                );
+                progress.block_processed(last_block.block_height, last_block.block_slot_time);
+                return Ok(());
+            }
+            Err(error) => {
+                successive_failures += 1;
+                if !events.on_failure(error, successive_failures).await? {
+                    anyhow::bail!(
+                        "Giving up on processing blocks up to height {}.",
+                        last_block.block_height
+                    );
+                }
+                tokio::time::sleep(WAIT_AFTER_FAIL).await;
+            }
+        }
+    }
+}
+
+/// Construct a future for shutdown signals (for unix: SIGINT and SIGTERM) (for
+/// windows: ctrl c and ctrl break). The signal handler is set when the future
+/// is polled and until then the default signal handler.
+fn set_shutdown() -> anyhow::Result<impl futures::Future<Output = ()>> {
+    use futures::FutureExt;
+
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix as unix_signal;
+
+        let mut terminate_stream = unix_signal::signal(unix_signal::SignalKind::terminate())?;
+        let mut interrupt_stream = unix_signal::signal(unix_signal::SignalKind::interrupt())?;
+
+        Ok(async move {
+            futures::future::select(
+                Box::pin(terminate_stream.recv()),
+                Box::pin(interrupt_stream.recv()),
+            )
+            .map(|_| ())
+            .await
+        })
+    }
+
+    #[cfg(windows)]
+    {
+        use tokio::signal::windows as windows_signal;
+
+        let mut ctrl_break_stream = windows_signal::ctrl_break()?;
+        let mut ctrl_c_stream = windows_signal::ctrl_c()?;
+
+        Ok(async move {
+            futures::future::select(
+                Box::pin(ctrl_break_stream.recv()),
+                Box::pin(ctrl_c_stream.recv()),
+            )
+            .map(|_| ())
+            .await
+        })
+    }
+}
@@ -1,27 +1,136 @@
-use ::indexer::db::{DatabaseError, DatabasePool, StoredItemStatusChangedEvent};
+use ::indexer::db::{
+    self, DatabaseError, DatabasePool, StoredChange, StoredItemStatusChangedEvent,
+};
 use anyhow::Context;
 use axum::{
-    extract::{rejection::JsonRejection, State},
+    extract::{rejection::JsonRejection, ConnectInfo, Path, Query, State},
     http,
     response::Html,
     routing::{get, post},
     Json, Router,
 };
+use chrono::{DateTime, Utc};
 use clap::Parser;
-use concordium_rust_sdk::types::ContractAddress;
+use concordium_rust_sdk::{
+    smart_contracts::common::AccountAddress, types::ContractAddress, web3id::did::Network,
+};
 use handlebars::{no_escape, Handlebars};
 use http::StatusCode;
-use indexer::db::StoredItemCreatedEvent;
-use std::fs;
+use indexer::{
+    db::{StoredItemCreatedEvent, StoredTokenTransfer},
+    info::Info,
+    logging::{self, LogFormat},
+};
+use std::{
+    collections::HashMap,
+    fs,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 use tower_http::services::ServeDir;
+use track_and_trace::Status;
 
 /// The maximum number of events allowed in a request to the database.
 const MAX_REQUEST_LIMIT: u32 = 30;
 
+/// The maximum number of changes returned by one request to the `changes`
+/// endpoint.
+const MAX_CHANGES_LIMIT: u32 = 1000;
+
+/// The maximum number of status updates included in a public item summary.
+const MAX_PUBLIC_STATUS_UPDATES: u32 = 100;
+
+/// The maximum number of entries kept in the public item cache and the public
+/// rate limiter before expired entries are evicted.
+const MAX_PUBLIC_ENTRIES: usize = 10_000;
+
 /// Server struct to store the db_pool.
 #[derive(Clone, Debug)]
 pub struct Server {
-    db_pool: DatabasePool,
+    db_pool:      DatabasePool,
+    /// The network whose events are served.
+    network:      Network,
+    /// Cache of the summaries returned by the public item endpoint.
+    public_cache: PublicItemCache,
+    /// Rate limiter of the public item endpoint.
+    rate_limiter: RateLimiter,
+    /// The build metadata returned by the `info` endpoint.
+    info:         Info,
+}
+
+/// A cache of public item summaries. Entries expire after `ttl`. Items that
+/// do not exist are cached as `None`.
+#[derive(Clone, Debug)]
+struct PublicItemCache {
+    ttl:     Duration,
+    entries: Arc<Mutex<HashMap<u64, (Instant, Option<PublicItemSummary>)>>>,
+}
+
+impl PublicItemCache {
+    fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Default::default(),
+        }
+    }
+
+    /// Get the cached summary of the item if it has not expired yet.
+    fn get(&self, item_id: u64) -> Option<Option<PublicItemSummary>> {
+        let entries = self.entries.lock().expect("The lock is never poisoned");
+        entries
+            .get(&item_id)
+            .filter(|(inserted_at, _)| inserted_at.elapsed() < self.ttl)
+            .map(|(_, summary)| summary.clone())
+    }
+
+    /// Insert the summary of the item, evicting expired entries if the cache
+    /// is full.
+    fn insert(&self, item_id: u64, summary: Option<PublicItemSummary>) {
+        let mut entries = self.entries.lock().expect("The lock is never poisoned");
+        if entries.len() >= MAX_PUBLIC_ENTRIES {
+            entries.retain(|_, (inserted_at, _)| inserted_at.elapsed() < self.ttl);
+            if entries.len() >= MAX_PUBLIC_ENTRIES {
+                entries.clear();
+            }
+        }
+        entries.insert(item_id, (Instant::now(), summary));
+    }
+}
+
+/// A fixed window rate limiter allowing at most `max_requests` per `window`
+/// from each IP address.
+#[derive(Clone, Debug)]
+struct RateLimiter {
+    max_requests: u32,
+    window:       Duration,
+    clients:      Arc<Mutex<HashMap<IpAddr, (Instant, u32)>>>,
+}
+
+impl RateLimiter {
+    fn new(max_requests: u32, window: Duration) -> Self {
+        Self {
+            max_requests,
+            window,
+            clients: Default::default(),
+        }
+    }
+
+    /// Record a request from `ip` and check whether it is within the limit.
+    fn check(&self, ip: IpAddr) -> bool {
+        let mut clients = self.clients.lock().expect("The lock is never poisoned");
+        if clients.len() >= MAX_PUBLIC_ENTRIES {
+            clients.retain(|_, (window_start, _)| window_start.elapsed() < self.window);
+        }
+        let now = Instant::now();
+        let (window_start, count) = clients.entry(ip).or_insert((now, 0));
+        if now.duration_since(*window_start) >= self.window {
+            *window_start = now;
+            *count = 0;
+        }
+        *count += 1;
+        *count <= self.max_requests
+    }
 }
 
 /// Errors that this server can produce.
@@ -37,6 +146,10 @@ pub enum ServerError {
     JsonRejection(#[from] JsonRejection),
     #[error("The requested events to the database were above the limit {0}")]
     MaxRequestLimit(u32),
+    #[error("The item {0} does not exist")]
+    ItemNotFound(u64),
+    #[error("Too many requests")]
+    TooManyRequests,
 }
 
 /// Mapping DatabaseError to ServerError
@@ -82,6 +195,14 @@ impl axum::response::IntoResponse for ServerError {
                 tracing::debug!("Bad request: {error}.");
                 (StatusCode::BAD_REQUEST, Json(format!("{}", error)))
             }
+            error @ ServerError::ItemNotFound(_) => {
+                tracing::debug!("Not found: {error}.");
+                (StatusCode::NOT_FOUND, Json(format!("{}", error)))
+            }
+            error @ ServerError::TooManyRequests => {
+                tracing::debug!("Rate limited: {error}.");
+                (StatusCode::TOO_MANY_REQUESTS, Json(format!("{}", error)))
+            }
         };
         r.into_response()
     }
@@ -115,6 +236,16 @@ struct Args {
         env = "CCD_SERVER_DB_CONNECTION"
     )]
     db_connection: tokio_postgres::config::Config,
+    /// File containing the database connection string.
+    #[arg(
+        long = "db-connection-file",
+        help = "A file containing the connection string of the database. If specified, it is used \
+                instead of `--db-connection` and the server re-reads the file and reconnects to \
+                the database when it receives a SIGHUP signal, e.g. after the password was \
+                rotated.",
+        env = "CCD_SERVER_DB_CONNECTION_FILE"
+    )]
+    db_connection_file: Option<std::path::PathBuf>,
     /// Maximum log level.
     #[clap(
         long = "log-level",
@@ -124,6 +255,15 @@ struct Args {
         env = "CCD_SERVER_LOG_LEVEL"
     )]
     log_level: tracing_subscriber::filter::LevelFilter,
+    /// Format of the log output.
+    #[arg(
+        long = "log-format",
+        value_enum,
+        default_value = "text",
+        help = "The format of the log output. Possible values are: `text` and `json`.",
+        env = "CCD_SERVER_LOG_FORMAT"
+    )]
+    log_format: LogFormat,
     /// The node used for querying (passed to frontend).
     #[arg(
         long = "node",
@@ -134,11 +274,13 @@ struct Args {
         env = "CCD_SERVER_NODE"
     )]
     node_endpoint: concordium_rust_sdk::v2::Endpoint,
-    /// The network to connect users to (passed to frontend).
+    /// The network to connect users to (passed to frontend). Only events of
+    /// this network are served.
     #[clap(
         long = "network",
         default_value_t = concordium_rust_sdk::web3id::did::Network::Testnet,
-        help = "The network to connect users to (passed to frontend). Possible values: testnet, mainnet",
+        help = "The network to connect users to (passed to frontend). Only events indexed from \
+                this network are served. Possible values: testnet, mainnet",
         env = "CCD_SERVER_NETWORK",
     )]
     network: concordium_rust_sdk::web3id::did::Network,
@@ -158,6 +300,32 @@ struct Args {
         env = "CCD_SERVER_SPONSORED_TRANSACTION_BACKEND"
     )]
     sponsored_transaction_backend: concordium_rust_sdk::v2::Endpoint,
+    /// How long summaries of the public item endpoint are cached.
+    #[arg(
+        long = "public-cache-ttl",
+        default_value = "60",
+        help = "The number of seconds the responses of the public `/api/public/item/{id}` \
+                endpoint are cached by the server and by clients.",
+        env = "CCD_SERVER_PUBLIC_CACHE_TTL"
+    )]
+    public_cache_ttl: u64,
+    /// The rate limit of the public item endpoint.
+    #[arg(
+        long = "public-rate-limit",
+        default_value = "60",
+        help = "The maximum number of requests per minute from a single IP address to the public \
+                `/api/public/item/{id}` endpoint.",
+        env = "CCD_SERVER_PUBLIC_RATE_LIMIT"
+    )]
+    public_rate_limit: u32,
+    /// Whether the tables of the contract are stored in their own schema.
+    #[arg(
+        long = "schema-per-contract",
+        help = "Read the tables of the contract from its own postgres schema as written by the \
+                indexer with `--schema-per-contract`.",
+        env = "CCD_SERVER_SCHEMA_PER_CONTRACT"
+    )]
+    schema_per_contract: bool,
 }
 
 impl Args {
@@ -181,23 +349,48 @@ async fn main() -> anyhow::Result<()> {
     let app = Args::parse();
 
     {
-        use tracing_subscriber::prelude::*;
         let log_filter = tracing_subscriber::filter::Targets::new()
             .with_target(module_path!(), app.log_level)
             .with_target("tower_http", app.log_level);
 
-        tracing_subscriber::registry()
-            .with(tracing_subscriber::fmt::layer())
-            .with(log_filter)
-            .init();
+        logging::init(app.log_format, log_filter);
     }
 
+    let db_connection = match &app.db_connection_file {
+        Some(path) => db::read_db_connection_file(path)
+            .context("Could not read the database connection file")?,
+        None => app.db_connection.clone(),
+    };
+    let db_connection = if app.schema_per_contract {
+        db::use_contract_schema(db_connection, &app.contract_address)
+            .await
+            .context("Could not set up the schema of the contract")?
+    } else {
+        db_connection
+    };
+
     // Establish connection to the postgres database.
-    let db_pool = DatabasePool::create(app.db_connection.clone(), 1, true)
+    let db_pool = DatabasePool::create(db_connection, 1, true)
         .await
         .context("Could not create database pool")?;
 
-    let state = Server { db_pool };
+    if let Some(path) = app.db_connection_file.clone() {
+        let contract_schema = app.schema_per_contract.then_some(app.contract_address);
+        let reload = db::reload_on_hangup(db_pool.clone(), path, contract_schema);
+        tokio::spawn(async move {
+            if let Err(error) = reload.await {
+                tracing::error!("Stopped reloading the database connection: {error}");
+            }
+        });
+    }
+
+    let state = Server {
+        db_pool,
+        network: app.network,
+        public_cache: PublicItemCache::new(Duration::from_secs(app.public_cache_ttl)),
+        rate_limiter: RateLimiter::new(app.public_rate_limit, Duration::from_secs(60)),
+        info: Info::new("server", vec![app.contract_address]),
+    };
 
     tracing::info!("Starting server...");
 
@@ -217,7 +410,12 @@ async fn main() -> anyhow::Result<()> {
     let router = Router::new()
         .route("/api/getItemStatusChangedEvents", post(get_item_status_changed_events))
         .route("/api/getItemCreatedEvent", post(get_item_created_event))
+        .route("/api/getItemTransfers", post(get_item_transfers))
+        .route("/api/getNonce", post(get_nonce))
+        .route("/api/changes", get(get_changes))
+        .route("/api/public/item/:id", get(get_public_item))
         .route("/health", get(health))
+        .route("/info", get(info))
         .nest_service("/assets", serve_dir_service)
         .fallback(get(|| async { Html(index_html) }))
         .with_state(state)
@@ -235,7 +433,7 @@ async fn main() -> anyhow::Result<()> {
 
     // Create the server.
     axum::Server::bind(&app.listen_address)
-        .serve(router.into_make_service())
+        .serve(router.into_make_service_with_connect_info::<SocketAddr>())
         .with_graceful_shutdown(shutdown_signal)
         .await?;
 
@@ -297,6 +495,9 @@ async fn health() -> Json<Health> {
     })
 }
 
+/// Handles the `info` endpoint, returning the build metadata of the server.
+async fn info(State(state): State<Server>) -> Json<Info> { Json(state.info) }
+
 /// Struct returned by the `getItemStatusChangedEvents` endpoint. It returns a
 /// vector of ItemStatusChangedEvents from the database if present.
 #[derive(serde::Serialize)]
@@ -328,7 +529,12 @@ async fn get_item_status_changed_events(
     }
 
     let database_result = db
-        .get_item_status_changed_events_submissions(param.item_id, param.limit, param.offset)
+        .get_item_status_changed_events_submissions(
+            param.item_id,
+            param.limit,
+            param.offset,
+            Some(state.network),
+        )
         .await?;
 
     Ok(Json(StoredItemStatusChangedEventsReturnValue {
@@ -353,9 +559,209 @@ async fn get_item_created_event(
 
     let Json(item_id) = request?;
 
-    let database_result = db.get_item_created_event_submission(item_id).await?;
+    let database_result = db
+        .get_item_created_event_submission(item_id, Some(state.network))
+        .await?;
 
     Ok(Json(StoredItemCreatedEventReturnValue {
         data: database_result,
     }))
 }
+
+/// Struct returned by the `getItemTransfers` endpoint. It returns the
+/// transfers of the token minted for the item.
+#[derive(serde::Serialize)]
+struct StoredTokenTransfersReturnValue {
+    data: Vec<StoredTokenTransfer>,
+}
+
+/// Handles the `getItemTransfers` endpoint, returning the transfers of the
+/// token minted for the item, ordered by block height. The transfers are only
+/// indexed if the `indexer` follows the token contract.
+async fn get_item_transfers(
+    State(state): State<Server>,
+    request: Result<Json<u64>, JsonRejection>,
+) -> Result<Json<StoredTokenTransfersReturnValue>, ServerError> {
+    let db = state.db_pool.get().await?;
+
+    let Json(item_id) = request?;
+
+    let database_result = db.get_item_transfers(item_id, Some(state.network)).await?;
+
+    Ok(Json(StoredTokenTransfersReturnValue {
+        data: database_result,
+    }))
+}
+
+/// Struct returned by the `getNonce` endpoint. It returns the next nonce of
+/// the account for signing a sponsored transaction.
+#[derive(serde::Serialize)]
+struct GetNonceReturnValue {
+    data: u64,
+}
+
+/// Handles the `getNonce` endpoint, returning the next nonce of the account
+/// (given as the JSON body) for signing a sponsored transaction. The nonce is
+/// read from the indexed `Nonce` events, so it lags behind the contract until
+/// the `indexer` has processed the latest permits of the account.
+async fn get_nonce(
+    State(state): State<Server>,
+    request: Result<Json<AccountAddress>, JsonRejection>,
+) -> Result<Json<GetNonceReturnValue>, ServerError> {
+    let db = state.db_pool.get().await?;
+
+    let Json(account) = request?;
+
+    let database_result = db.get_next_nonce(&account).await?;
+
+    Ok(Json(GetNonceReturnValue {
+        data: database_result,
+    }))
+}
+
+/// Query parameters of the `changes` endpoint.
+#[derive(serde::Deserialize)]
+struct GetChangesParam {
+    /// The `cursor` returned by the previous request. If not given, the
+    /// changes are returned from the start.
+    since: Option<u64>,
+    /// The maximum number of changes returned. Defaults to
+    /// `MAX_CHANGES_LIMIT`.
+    limit: Option<u32>,
+}
+
+/// Struct returned by the `changes` endpoint. It returns the changes after the
+/// requested cursor and the cursor to request the next changes with.
+#[derive(serde::Serialize)]
+struct GetChangesReturnValue {
+    data:     Vec<StoredChange>,
+    /// The `change_id` of the last returned change, or the requested cursor
+    /// if there are no new changes.
+    cursor:   Option<u64>,
+    /// Whether more changes are available after the `cursor`.
+    has_more: bool,
+}
+
+/// Handles the `changes` endpoint, returning all events of items inserted or
+/// overwritten after the cursor given in the `since` query parameter. This
+/// allows offline-capable clients to sync incrementally.
+async fn get_changes(
+    State(state): State<Server>,
+    Query(param): Query<GetChangesParam>,
+) -> Result<Json<GetChangesReturnValue>, ServerError> {
+    let limit = param.limit.unwrap_or(MAX_CHANGES_LIMIT);
+    if limit > MAX_CHANGES_LIMIT {
+        return Err(ServerError::MaxRequestLimit(MAX_CHANGES_LIMIT));
+    }
+
+    let db = state.db_pool.get().await?;
+
+    let changes = db
+        .get_changes(param.since, limit, Some(state.network))
+        .await?;
+
+    Ok(Json(GetChangesReturnValue {
+        cursor:   changes
+            .last()
+            .map(|change| change.change_id)
+            .or(param.since),
+        has_more: !changes.is_empty() && changes.len() as u32 == limit,
+        data:     changes,
+    }))
+}
+
+/// A status update of an item as exposed by the public item endpoint.
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PublicStatusUpdate {
+    status: Status,
+    time:   DateTime<Utc>,
+}
+
+/// Struct returned by the public item endpoint. It only contains the statuses
+/// of the item and the times they were set, and none of the additional data,
+/// transaction hashes, or metadata of the events.
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PublicItemSummary {
+    item_id:        u64,
+    created_at:     DateTime<Utc>,
+    current_status: Status,
+    history:        Vec<PublicStatusUpdate>,
+}
+
+/// Handles the public `/api/public/item/{id}` endpoint which does not require
+/// authentication, returning a sanitized provenance summary of the item.
+/// Requests are rate limited per IP address and responses are cached.
+async fn get_public_item(
+    State(state): State<Server>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    Path(item_id): Path<u64>,
+) -> Result<
+    (
+        [(http::header::HeaderName, String); 1],
+        Json<PublicItemSummary>,
+    ),
+    ServerError,
+> {
+    if !state.rate_limiter.check(client.ip()) {
+        return Err(ServerError::TooManyRequests);
+    }
+
+    let summary = match state.public_cache.get(item_id) {
+        Some(summary) => summary,
+        None => {
+            let summary = public_item_summary(&state.db_pool, item_id, state.network).await?;
+            state.public_cache.insert(item_id, summary.clone());
+            summary
+        }
+    };
+    let summary = summary.ok_or(ServerError::ItemNotFound(item_id))?;
+
+    let cache_control = format!("public, max-age={}", state.public_cache.ttl.as_secs());
+    Ok((
+        [(http::header::CACHE_CONTROL, cache_control)],
+        Json(summary),
+    ))
+}
+
+/// Build the public summary of the item from the database or `None` if the
+/// item does not exist.
+async fn public_item_summary(
+    db_pool: &DatabasePool,
+    item_id: u64,
+    network: Network,
+) -> Result<Option<PublicItemSummary>, ServerError> {
+    let db = db_pool.get().await?;
+
+    let Some(created_event) = db
+        .get_item_created_event_submission(item_id, Some(network))
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let mut history = vec![PublicStatusUpdate {
+        status: created_event.initial_status,
+        time:   created_event.block_time,
+    }];
+    history.extend(
+        db.get_item_status_history(item_id, MAX_PUBLIC_STATUS_UPDATES, Some(network))
+            .await?
+            .into_iter()
+            .map(|update| PublicStatusUpdate {
+                status: update.status,
+                time:   update.block_time,
+            }),
+    );
+
+    Ok(Some(PublicItemSummary {
+        item_id,
+        created_at: created_event.block_time,
+        current_status: history
+            .last()
+            .map(|update| update.status)
+            .expect("The history contains at least the initial status"),
+        history,
+    }))
+}
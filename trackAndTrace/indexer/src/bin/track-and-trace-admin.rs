@@ -0,0 +1,360 @@
+//! A tool for sending the admin transactions of the track and trace contract.
+//! The transactions are signed with the keys of an account in the browser
+//! wallet export format and the parameters are built from the command line
+//! arguments, so operators do not have to hand-craft the JSON parameters for
+//! `concordium-client`.
+//!
+//! Every transaction is simulated before it is sent. With `--dry-run` the
+//! parameter (in the JSON format of `concordium-client` and hex encoded), the
+//! nonce, and the energy of the simulation are printed without sending the
+//! transaction.
+use anyhow::Context;
+use clap::Parser;
+use concordium_rust_sdk::{
+    contract_client::{ContractClient, ViewError},
+    smart_contracts::common::{to_bytes, Address, Amount, Serial},
+    types::{AccountAddress, ContractAddress, Nonce, WalletAccount},
+    v2::{self as sdk},
+};
+use serde_json::json;
+use track_and_trace as contract;
+
+/// Marker type of the track and trace contract for the [`ContractClient`].
+enum TrackAndTraceContract {}
+
+/// Command line configuration of the application.
+#[derive(Debug, clap::Parser)]
+#[command(author, version, about)]
+struct Args {
+    #[command(subcommand)]
+    command:          Command,
+    #[arg(
+        long = "node",
+        short = 'n',
+        help = "The node endpoint.",
+        default_value = "https://grpc.testnet.concordium.com:20000",
+        global = true,
+        env = "CCD_ADMIN_NODE"
+    )]
+    node_endpoint:    sdk::Endpoint,
+    #[arg(
+        long = "contract",
+        short = 'c',
+        help = "The track and trace contract address.",
+        global = true,
+        env = "CCD_ADMIN_CONTRACT"
+    )]
+    contract_address: Option<ContractAddress>,
+    #[arg(
+        long = "admin-key-file",
+        short = 'a',
+        help = "Path to the key file of the admin account in the browser wallet export format.",
+        global = true,
+        env = "CCD_ADMIN_KEY_FILE"
+    )]
+    admin_keys_path:  Option<std::path::PathBuf>,
+    #[arg(
+        long = "nonce",
+        help = "The nonce of the transaction. If not specified, the next nonce of the admin \
+                account is queried from the node.",
+        global = true
+    )]
+    nonce:            Option<u64>,
+    #[arg(
+        long = "dry-run",
+        help = "Only simulate the transaction and print its parameter, nonce, and energy without \
+                sending it.",
+        global = true
+    )]
+    dry_run:          bool,
+}
+
+/// The admin transactions of the contract.
+#[derive(Debug, clap::Subcommand)]
+enum Command {
+    /// Grant a role to an account or contract address.
+    GrantRole(RoleArgs),
+    /// Revoke a role from an account or contract address.
+    RevokeRole(RoleArgs),
+    /// Allow an account to change the status of items from one status to
+    /// another.
+    AddTransitionEdge(TransitionEdgeArgs),
+    /// Remove the permission of an account to change the status of items from
+    /// one status to another.
+    RemoveTransitionEdge(TransitionEdgeArgs),
+}
+
+/// Arguments of the `grant-role` and `revoke-role` subcommands.
+#[derive(Debug, clap::Args)]
+struct RoleArgs {
+    #[arg(
+        long = "address",
+        help = "The account address or the contract address (format `<1234,0>`).",
+        value_parser = parse_address
+    )]
+    address: Address,
+    #[arg(long = "role", help = "The role.", value_enum, default_value = "admin")]
+    role:    Role,
+}
+
+/// Arguments of the `add-transition-edge` and `remove-transition-edge`
+/// subcommands.
+#[derive(Debug, clap::Args)]
+struct TransitionEdgeArgs {
+    #[arg(
+        long = "address",
+        help = "The account address that may change the status."
+    )]
+    address:     AccountAddress,
+    #[arg(
+        long = "from",
+        help = "The status of the item before the change.",
+        value_enum
+    )]
+    from_status: Status,
+    #[arg(
+        long = "to",
+        help = "The status of the item after the change.",
+        value_enum
+    )]
+    to_status:   Status,
+}
+
+/// The roles of the contract as command line values.
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum Role {
+    Admin,
+    Producer,
+}
+
+impl From<Role> for contract::Roles {
+    fn from(role: Role) -> Self {
+        match role {
+            Role::Admin => contract::Roles::Admin,
+            Role::Producer => contract::Roles::Producer,
+        }
+    }
+}
+
+/// The statuses of an item as command line values.
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum Status {
+    Produced,
+    InTransit,
+    InStore,
+    Sold,
+    Expired,
+}
+
+impl From<Status> for contract::Status {
+    fn from(status: Status) -> Self {
+        match status {
+            Status::Produced => contract::Status::Produced,
+            Status::InTransit => contract::Status::InTransit,
+            Status::InStore => contract::Status::InStore,
+            Status::Sold => contract::Status::Sold,
+            Status::Expired => contract::Status::Expired,
+        }
+    }
+}
+
+/// Parse an account address or a contract address in the format `<1234,0>`.
+fn parse_address(value: &str) -> anyhow::Result<Address> {
+    if value.starts_with('<') {
+        Ok(Address::Contract(value.parse()?))
+    } else {
+        Ok(Address::Account(value.parse()?))
+    }
+}
+
+/// The JSON representation of an address used by `concordium-client`.
+fn address_json(address: &Address) -> serde_json::Value {
+    match address {
+        Address::Account(address) => json!({ "Account": [address.to_string()] }),
+        Address::Contract(address) => json!({
+            "Contract": [{ "index": address.index, "subindex": address.subindex }]
+        }),
+    }
+}
+
+/// The JSON representation of a unit enum variant used by `concordium-client`.
+fn variant_json(variant: impl std::fmt::Debug) -> serde_json::Value {
+    json!({ format!("{variant:?}"): [] })
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+
+    let contract_address = args
+        .contract_address
+        .context("The contract address must be given with `--contract`.")?;
+    let admin_keys_path = args
+        .admin_keys_path
+        .context("The admin key file must be given with `--admin-key-file`.")?;
+
+    let endpoint = if args
+        .node_endpoint
+        .uri()
+        .scheme()
+        .map_or(false, |x| x == &sdk::Scheme::HTTPS)
+    {
+        args.node_endpoint
+            .tls_config(tonic::transport::channel::ClientTlsConfig::new())
+            .context("Unable to construct TLS configuration for the Concordium API.")?
+    } else {
+        args.node_endpoint
+    }
+    .connect_timeout(std::time::Duration::from_secs(5))
+    .timeout(std::time::Duration::from_secs(10));
+
+    let mut client = sdk::Client::new(endpoint)
+        .await
+        .context("Unable to establish connection to the node.")?;
+
+    let admin_key =
+        WalletAccount::from_json_file(admin_keys_path).context("Could not read the keys file.")?;
+
+    let nonce = match args.nonce {
+        Some(nonce) => Nonce::from(nonce),
+        None => {
+            let next_nonce = client
+                .get_next_account_sequence_number(&admin_key.address)
+                .await
+                .context("Unable to query the next nonce of the admin account.")?;
+            if !next_nonce.all_final {
+                eprintln!(
+                    "Warning: The admin account has non-finalized transactions. The transaction \
+                     might be rejected if one of them is not finalized."
+                );
+            }
+            next_nonce.nonce
+        }
+    };
+
+    let contract_client = ContractClient::<TrackAndTraceContract>::create(client, contract_address)
+        .await
+        .context("Unable to look up the contract instance.")?;
+
+    let transaction = match args.command {
+        Command::GrantRole(RoleArgs { address, role }) => AdminTransaction::new(
+            "grantRole",
+            &contract::GrantRoleParams {
+                address,
+                role: role.into(),
+            },
+            json!({ "address": address_json(&address), "role": variant_json(role) }),
+        ),
+        Command::RevokeRole(RoleArgs { address, role }) => AdminTransaction::new(
+            "revokeRole",
+            &contract::RevokeRoleParams {
+                address,
+                role: role.into(),
+            },
+            json!({ "address": address_json(&address), "role": variant_json(role) }),
+        ),
+        Command::AddTransitionEdge(edge) => {
+            AdminTransaction::transition_edge(edge, contract::Update::Add)
+        }
+        Command::RemoveTransitionEdge(edge) => {
+            AdminTransaction::transition_edge(edge, contract::Update::Remove)
+        }
+    };
+
+    transaction
+        .run(contract_client, &admin_key, nonce, args.dry_run)
+        .await
+}
+
+/// An admin transaction updating the contract.
+struct AdminTransaction {
+    /// The name of the invoked entrypoint.
+    entrypoint: &'static str,
+    /// The serialized parameter.
+    parameter:  Vec<u8>,
+    /// The parameter in the JSON format of `concordium-client`.
+    json:       serde_json::Value,
+}
+
+impl AdminTransaction {
+    fn new(entrypoint: &'static str, parameter: &impl Serial, json: serde_json::Value) -> Self {
+        Self {
+            entrypoint,
+            parameter: to_bytes(parameter),
+            json,
+        }
+    }
+
+    /// The `updateStateMachine` transaction adding or removing a transition
+    /// edge.
+    fn transition_edge(edge: TransitionEdgeArgs, update: contract::Update) -> Self {
+        Self::new(
+            "updateStateMachine",
+            &contract::UpdateStateMachineParams {
+                address: edge.address,
+                from_status: edge.from_status.into(),
+                to_status: edge.to_status.into(),
+                update,
+            },
+            json!({
+                "address": edge.address.to_string(),
+                "from_status": variant_json(contract::Status::from(edge.from_status)),
+                "to_status": variant_json(contract::Status::from(edge.to_status)),
+                "update": variant_json(update),
+            }),
+        )
+    }
+
+    /// Simulate the transaction and, unless `dry_run` is set, sign it with the
+    /// `admin_key`, send it with the given `nonce`, and wait until it is
+    /// finalized.
+    async fn run(
+        self,
+        mut contract_client: ContractClient<TrackAndTraceContract>,
+        admin_key: &WalletAccount,
+        nonce: Nonce,
+        dry_run: bool,
+    ) -> anyhow::Result<()> {
+        let builder = contract_client
+            .dry_run_update_raw::<ViewError>(
+                self.entrypoint,
+                Amount::zero(),
+                admin_key.address,
+                self.parameter
+                    .clone()
+                    .try_into()
+                    .context("The parameter is too large.")?,
+            )
+            .await
+            .with_context(|| format!("The simulation of `{}` failed.", self.entrypoint))?
+            .nonce(nonce);
+
+        println!("Entrypoint: {}", self.entrypoint);
+        println!(
+            "Parameter (JSON): {}",
+            serde_json::to_string_pretty(&self.json)?
+        );
+        println!("Parameter (hex): {}", hex::encode(&self.parameter));
+        println!("Sender: {}", admin_key.address);
+        println!("Nonce: {nonce}");
+        println!(
+            "The maximum amount of NRG allowed for the transaction is {}.",
+            builder.current_energy()
+        );
+
+        if dry_run {
+            println!("Dry run: the transaction was not sent.");
+            return Ok(());
+        }
+
+        let handle = builder.send(admin_key).await?;
+        println!("Transaction {handle} submitted. Waiting for finalization.");
+
+        if let Err(err) = handle.wait_for_finalization().await {
+            anyhow::bail!("The transaction `{}` failed: {err:#?}", self.entrypoint);
+        }
+        println!("Transaction finalized successfully.");
+
+        Ok(())
+    }
+}
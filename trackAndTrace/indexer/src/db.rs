@@ -1,21 +1,45 @@
 use anyhow::Context;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use concordium_rust_sdk::{
-    cis2::MetadataUrl,
-    smart_contracts::common::from_bytes,
+    cis2::{MetadataUrl, TokenAmount, TokenId},
+    smart_contracts::common::{
+        from_bytes, to_bytes, AccountAddress, Address, Cursor, Deserial, OwnedEntrypointName,
+    },
     types::{
-        hashes::{BlockHash, TransactionHash},
+        hashes::{BlockHash, Hash, TransactionHash},
+        smart_contracts::ModuleReference,
         AbsoluteBlockHeight, ContractAddress,
     },
+    web3id::did::Network,
 };
 use deadpool_postgres::{GenericClient, Object};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
 use tokio_postgres::{
     types::{Json, ToSql},
     NoTls,
 };
 use track_and_trace::{Status, *};
 
+/// The migrations embedded from the `../resources/migrations` directory. The
+/// migrations are run in order of their version and each migration is only
+/// applied once. The applied migrations are recorded by `refinery` in the
+/// `refinery_schema_history` table.
+mod embedded {
+    refinery::embed_migrations!("./resources/migrations");
+}
+
+/// The version of the latest migration embedded into the binaries, i.e. the
+/// newest schema version of the database the binaries support.
+pub fn supported_schema_version() -> u64 {
+    embedded::migrations::runner()
+        .get_migrations()
+        .iter()
+        .map(|migration| migration.version() as u64)
+        .max()
+        .unwrap_or_default()
+}
+
 /// Represents possible errors returned from [`Database`] or [`DatabasePool`]
 /// functions
 #[derive(thiserror::Error, Debug)]
@@ -29,13 +53,46 @@ pub enum DatabaseError {
     /// Failed to configure database
     #[error("Could not configure database: {0}")]
     Configuration(#[from] anyhow::Error),
+    /// The schema of the database is not supported by the binary.
+    #[error("{0}")]
+    IncompatibleSchema(String),
 }
 
 /// Alias for returning results with [`DatabaseError`]s as the `Err` variant.
 type DatabaseResult<T> = Result<T, DatabaseError>;
 
+/// The number of blocks whose events are stored in one partition of the event
+/// tables (see [`Database::ensure_partitions`]).
+pub const PARTITION_SIZE: u64 = 1_000_000;
+
+/// The statements converting the event tables into TimescaleDB hypertables
+/// (see [`Database::create_hypertables`]).
+const HYPERTABLES_SQL: &str = include_str!("../resources/timescale/hypertables.sql");
+
+/// The maximum size in bytes of the payload of a `NOTIFY`. PostgreSQL rejects
+/// payloads of 8000 bytes or more in its default configuration.
+const MAX_NOTIFY_PAYLOAD_SIZE: usize = 7999;
+
+/// The genesis block hash of mainnet.
+const MAINNET_GENESIS_BLOCK_HASH: &str =
+    "9dd9ca4d19e9393877d2c44b70f89acbfc0883c2243e5eeaecc0d1cd0503f478";
+
+/// The genesis block hash of testnet.
+const TESTNET_GENESIS_BLOCK_HASH: &str =
+    "4221332d34e1694168c2a0c0b3fd0f273809612cb13d000d5c2e00e85f50f796";
+
+/// The network with the given genesis block hash. Returns `None` for other
+/// chains, e.g. a local chain.
+pub fn network_of_genesis(genesis_block_hash: &BlockHash) -> Option<Network> {
+    match genesis_block_hash.to_string().as_str() {
+        MAINNET_GENESIS_BLOCK_HASH => Some(Network::Mainnet),
+        TESTNET_GENESIS_BLOCK_HASH => Some(Network::Testnet),
+        _ => None,
+    }
+}
+
 /// The database configuration stored in the database.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct StoredConfiguration {
     /// The genesis block hash of the network monitored.
     pub genesis_block_hash:            BlockHash,
@@ -73,23 +130,634 @@ impl TryFrom<tokio_postgres::Row> for StoredConfiguration {
     }
 }
 
-/// A `StoredItemStatusChanged` event stored in the database.
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
-pub struct StoredItemStatusChangedEvent {
+/// The number of events stored in the database per event table.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+pub struct StoredEventCounts {
+    /// The number of rows in the `item_created_events` table.
+    pub item_created_events:        u64,
+    /// The number of rows in the `item_status_changed_events` table.
+    pub item_status_changed_events: u64,
+}
+
+impl TryFrom<tokio_postgres::Row> for StoredEventCounts {
+    type Error = DatabaseError;
+
+    fn try_from(value: tokio_postgres::Row) -> DatabaseResult<Self> {
+        let raw_item_created_events: i64 = value.try_get("item_created_events")?;
+        let raw_item_status_changed_events: i64 = value.try_get("item_status_changed_events")?;
+
+        Ok(Self {
+            item_created_events:        raw_item_created_events as u64,
+            item_status_changed_events: raw_item_status_changed_events as u64,
+        })
+    }
+}
+
+/// The number of items whose current status is `status`.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+pub struct StoredStatusCount {
+    /// The current status of the items.
+    pub status: Status,
+    /// The number of items with the status.
+    pub count:  u64,
+}
+
+impl TryFrom<tokio_postgres::Row> for StoredStatusCount {
+    type Error = DatabaseError;
+
+    fn try_from(value: tokio_postgres::Row) -> DatabaseResult<Self> {
+        let Json(status): Json<Status> = value.try_get("status")?;
+        let raw_count: i64 = value.try_get("count")?;
+
+        Ok(Self {
+            status,
+            count: raw_count as u64,
+        })
+    }
+}
+
+/// The number of items that moved from `from_status` to `to_status` on `day`
+/// as counted by the `daily_status_transitions` materialized view.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+pub struct StoredStatusTransitionCount {
+    /// The day (UTC) the blocks with the status changes were produced on.
+    pub day:         NaiveDate,
+    /// The status of the items before the status change.
+    pub from_status: Status,
+    /// The status of the items after the status change.
+    pub to_status:   Status,
+    /// The number of status changes from `from_status` to `to_status`.
+    pub transitions: u64,
+}
+
+impl TryFrom<tokio_postgres::Row> for StoredStatusTransitionCount {
+    type Error = DatabaseError;
+
+    fn try_from(value: tokio_postgres::Row) -> DatabaseResult<Self> {
+        let Json(from_status): Json<Status> = value.try_get("from_status")?;
+        let Json(to_status): Json<Status> = value.try_get("to_status")?;
+        let raw_transitions: i64 = value.try_get("transitions")?;
+
+        Ok(Self {
+            day: value.try_get("day")?,
+            from_status,
+            to_status,
+            transitions: raw_transitions as u64,
+        })
+    }
+}
+
+/// A recently created item with its current status.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+pub struct StoredRecentItem {
+    /// The id of the item.
+    pub item_id:    u64,
+    /// The current status of the item.
+    pub status:     Status,
+    /// The timestamp of the block the item was created in.
+    pub created_at: DateTime<Utc>,
+    /// The timestamp of the block the status of the item last changed in, or
+    /// the time it was created if its status never changed.
+    pub updated_at: DateTime<Utc>,
+}
+
+impl TryFrom<tokio_postgres::Row> for StoredRecentItem {
+    type Error = DatabaseError;
+
+    fn try_from(value: tokio_postgres::Row) -> DatabaseResult<Self> {
+        let raw_item_id: i64 = value.try_get("item_id")?;
+        let Json(status): Json<Status> = value.try_get("status")?;
+
+        Ok(Self {
+            item_id: raw_item_id as u64,
+            status,
+            created_at: value.try_get("created_at")?,
+            updated_at: value.try_get("updated_at")?,
+        })
+    }
+}
+
+/// The number of events included in blocks of a single day (UTC) per event
+/// table.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+pub struct StoredDailyEventCounts {
+    /// The day (UTC) the blocks were produced on.
+    pub day:    NaiveDate,
+    /// The number of events per event table.
+    pub counts: StoredEventCounts,
+}
+
+impl TryFrom<tokio_postgres::Row> for StoredDailyEventCounts {
+    type Error = DatabaseError;
+
+    fn try_from(value: tokio_postgres::Row) -> DatabaseResult<Self> {
+        let day = value.try_get("day")?;
+        Ok(Self {
+            day,
+            counts: value.try_into()?,
+        })
+    }
+}
+
+/// The tables of the database storing events.
+#[derive(Debug, Clone, Copy)]
+pub enum EventTable {
+    /// The `item_created_events` table.
+    ItemCreatedEvents,
+    /// The `item_status_changed_events` table.
+    ItemStatusChangedEvents,
+    /// The `item_summary_events` table.
+    ItemSummaryEvents,
+    /// The `token_transfer_events` table.
+    TokenTransferEvents,
+    /// The `nonce_events` table.
+    NonceEvents,
+}
+
+impl EventTable {
+    /// The name of the table in the database.
+    pub fn name(self) -> &'static str {
+        match self {
+            EventTable::ItemCreatedEvents => "item_created_events",
+            EventTable::ItemStatusChangedEvents => "item_status_changed_events",
+            EventTable::ItemSummaryEvents => "item_summary_events",
+            EventTable::TokenTransferEvents => "token_transfer_events",
+            EventTable::NonceEvents => "nonce_events",
+        }
+    }
+}
+
+/// How the additional data of status changed events is removed after its
+/// retention period. In both cases the SHA-256 hash of the additional data is
+/// kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum AdditionalDataRedaction {
+    /// Overwrite the bytes of the additional data with zeros, keeping its
+    /// length.
+    Redact,
+    /// Replace the additional data with empty bytes.
+    Drop,
+}
+
+/// The location of an event on chain.
+#[derive(Debug, Clone)]
+pub struct EventLocation {
+    /// The height of the block the event was included in.
+    pub block_height:     AbsoluteBlockHeight,
     /// The timestamp of the block the event was included in.
     pub block_time:       DateTime<Utc>,
     /// The transaction hash that the event was recorded in.
     pub transaction_hash: TransactionHash,
     /// The index from the array of logged events in a transaction.
     pub event_index:      u64,
+    /// The account that sent the transaction the event was recorded in.
+    pub sender:           AccountAddress,
+    /// The entrypoint of the contract whose invocation logged the event, e.g.
+    /// `changeItemStatus` for a direct call or `permit` for a sponsored
+    /// transaction.
+    pub entrypoint:       OwnedEntrypointName,
+}
+
+/// An event of the contract that is indexed into its own table. Each row of
+/// the table stores the location of the event on chain (see [`EventLocation`])
+/// followed by the event specific [`IndexableEvent::COLUMNS`]. Indexing a new
+/// event type only requires a migration creating its table, an implementation
+/// of this trait, and a handler (see [`crate::handler::StoreEvent`]) routed to
+/// by the dispatcher of the `indexer`.
+pub trait IndexableEvent: Sync {
+    /// The table the event is stored in.
+    const TABLE: EventTable;
+    /// The names of the event specific columns in the order of the values
+    /// returned by [`IndexableEvent::params`].
+    const COLUMNS: &'static [&'static str];
+
+    /// The values of the event specific columns.
+    fn params(&self) -> Vec<Box<dyn ToSql + Sync + Send + '_>>;
+}
+
+impl IndexableEvent for ItemCreatedEvent {
+    const COLUMNS: &'static [&'static str] = &["item_id", "metadata_url", "initial_status"];
+    const TABLE: EventTable = EventTable::ItemCreatedEvents;
+
+    fn params(&self) -> Vec<Box<dyn ToSql + Sync + Send + '_>> {
+        vec![
+            Box::new(self.item_id.0 as i64),
+            Box::new(to_bytes(&self.metadata_url)),
+            Box::new(Json(&self.initial_status)),
+        ]
+    }
+}
+
+impl IndexableEvent for ItemStatusChangedEvent<AdditionalData> {
+    const COLUMNS: &'static [&'static str] = &[
+        "item_id",
+        "new_status",
+        "additional_data",
+        "additional_data_hash",
+    ];
+    const TABLE: EventTable = EventTable::ItemStatusChangedEvents;
+
+    fn params(&self) -> Vec<Box<dyn ToSql + Sync + Send + '_>> {
+        vec![
+            Box::new(self.item_id.0 as i64),
+            Box::new(Json(&self.new_status)),
+            Box::new(to_bytes(&self.additional_data)),
+            // The additional data is stored in full, so an event overwritten by
+            // the `backfill` subcommand is redacted again after its retention
+            // period.
+            Box::new(None::<Vec<u8>>),
+        ]
+    }
+}
+
+impl IndexableEvent for ItemSummaryEvent {
+    const COLUMNS: &'static [&'static str] = &["item_id", "version", "status", "metadata_hash"];
+    const TABLE: EventTable = EventTable::ItemSummaryEvents;
+
+    fn params(&self) -> Vec<Box<dyn ToSql + Sync + Send + '_>> {
+        vec![
+            Box::new(self.item_id.0 as i64),
+            Box::new(self.version as i16),
+            Box::new(Json(&self.status)),
+            Box::new(self.metadata_hash.map(|hash| hash.0.to_vec())),
+        ]
+    }
+}
+
+impl IndexableEvent for NonceEvent {
+    const COLUMNS: &'static [&'static str] = &["account", "nonce"];
+    const TABLE: EventTable = EventTable::NonceEvents;
+
+    fn params(&self) -> Vec<Box<dyn ToSql + Sync + Send + '_>> {
+        vec![
+            Box::new(self.account.0.as_ref()),
+            Box::new(self.nonce as i64),
+        ]
+    }
+}
+
+/// A CIS-2 transfer event logged by the token contract minting a token per
+/// item.
+#[derive(Debug, Clone)]
+pub struct TokenTransferEvent {
+    /// The token contract that logged the event.
+    pub token_contract: ContractAddress,
+    /// The token id as logged in the event.
+    pub token_id:       TokenId,
+    /// The amount of tokens transferred.
+    pub amount:         TokenAmount,
+    /// The address the tokens were transferred from.
+    pub from:           Address,
+    /// The address the tokens were transferred to.
+    pub to:             Address,
+}
+
+impl TokenTransferEvent {
+    /// The item the token was minted for. The item ids of the track and trace
+    /// contract are `TokenIdU64`s, so a token id of eight bytes is read as a
+    /// little endian item id. Returns `None` for other token ids.
+    pub fn item_id(&self) -> Option<u64> {
+        <[u8; 8]>::try_from(self.token_id.as_ref())
+            .ok()
+            .map(u64::from_le_bytes)
+    }
+}
+
+impl IndexableEvent for TokenTransferEvent {
+    const COLUMNS: &'static [&'static str] = &[
+        "token_contract_index",
+        "token_contract_subindex",
+        "token_id",
+        "item_id",
+        "amount",
+        "from_address",
+        "to_address",
+    ];
+    const TABLE: EventTable = EventTable::TokenTransferEvents;
+
+    fn params(&self) -> Vec<Box<dyn ToSql + Sync + Send + '_>> {
+        vec![
+            Box::new(self.token_contract.index as i64),
+            Box::new(self.token_contract.subindex as i64),
+            Box::new(self.token_id.as_ref()),
+            Box::new(self.item_id().map(|item_id| item_id as i64)),
+            Box::new(self.amount.to_string()),
+            Box::new(Json(&self.from)),
+            Box::new(Json(&self.to)),
+        ]
+    }
+}
+
+/// The outcome of inserting an event with [`insert_event`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertOutcome {
+    /// The event was inserted.
+    Inserted,
+    /// The event was skipped because an event with the same block height,
+    /// transaction hash, and event index is already stored.
+    Duplicate,
+}
+
+/// Insert an event found at the given location on chain into its table using
+/// the `client`, which can be a connection or a database transaction. The
+/// event gets the next `id` of the table and the network recorded in the
+/// settings. An event that is already stored at
+/// the same location is not inserted again and [`InsertOutcome::Duplicate`] is
+/// returned. The conflict target is left out, since the unique index of the
+/// location also includes the `block_time` if the event tables are
+/// hypertables (see [`Database::create_hypertables`]).
+pub async fn insert_event<C: GenericClient, E: IndexableEvent>(
+    client: &C,
+    location: &EventLocation,
+    event: &E,
+) -> DatabaseResult<InsertOutcome> {
+    let placeholders = (7..7 + E::COLUMNS.len())
+        .map(|index| format!("${index}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let query = format!(
+        "INSERT INTO {table} (id, block_time, block_height, transaction_hash, event_index, \
+         sender, entrypoint, network, {columns}) SELECT COALESCE(MAX(id) + 1, 0), $1, $2, $3, $4, \
+         $5, $6, (SELECT network FROM settings), {placeholders} FROM {table} ON CONFLICT DO \
+         NOTHING",
+        table = E::TABLE.name(),
+        columns = E::COLUMNS.join(", "),
+    );
+    let insert_event = client.prepare_cached(&query).await?;
+
+    let event_params = event.params();
+    let location_params: [&(dyn ToSql + Sync); 6] = [
+        &location.block_time,
+        &(location.block_height.height as i64),
+        &location.transaction_hash.as_ref(),
+        &(location.event_index as i64),
+        &location.sender.0.as_ref(),
+        &location.entrypoint.to_string(),
+    ];
+    let params: Vec<&(dyn ToSql + Sync)> = location_params
+        .into_iter()
+        .chain(
+            event_params
+                .iter()
+                .map(|param| param.as_ref() as &(dyn ToSql + Sync)),
+        )
+        .collect();
+    let inserted = client.execute(&insert_event, &params).await?;
+    if inserted == 0 {
+        Ok(InsertOutcome::Duplicate)
+    } else {
+        Ok(InsertOutcome::Inserted)
+    }
+}
+
+/// Overwrite the event stored for the transaction hash and event index of the
+/// `location` with the given event using the `client`, which can be a
+/// connection or a database transaction. The `id` of the row is kept, while the
+/// row gets a new `change_id` so that clients syncing the changes receive the
+/// overwritten event again. Returns the number of updated rows.
+pub async fn update_event<C: GenericClient, E: IndexableEvent>(
+    client: &C,
+    location: &EventLocation,
+    event: &E,
+) -> DatabaseResult<u64> {
+    let assignments = E::COLUMNS
+        .iter()
+        .enumerate()
+        .map(|(index, column)| format!("{column} = ${}", index + 7))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let query = format!(
+        "UPDATE {table} SET block_time = $1, block_height = $2, sender = $5, entrypoint = $6, \
+         change_id = nextval('change_id_seq'), {assignments} WHERE transaction_hash = $3 AND \
+         event_index = $4",
+        table = E::TABLE.name(),
+    );
+    let update_event = client.prepare_cached(&query).await?;
+
+    let event_params = event.params();
+    let location_params: [&(dyn ToSql + Sync); 6] = [
+        &location.block_time,
+        &(location.block_height.height as i64),
+        &location.transaction_hash.as_ref(),
+        &(location.event_index as i64),
+        &location.sender.0.as_ref(),
+        &location.entrypoint.to_string(),
+    ];
+    let params: Vec<&(dyn ToSql + Sync)> = location_params
+        .into_iter()
+        .chain(
+            event_params
+                .iter()
+                .map(|param| param.as_ref() as &(dyn ToSql + Sync)),
+        )
+        .collect();
+    Ok(client.execute(&update_event, &params).await?)
+}
+
+/// Insert an upgrade of the contract from the module `old_module_ref` to the
+/// module `new_module_ref` into the `upgrades` table using the `client`, which
+/// can be a connection or a database transaction. The upgrade gets the network
+/// recorded in the settings. An upgrade that is already stored is not inserted
+/// again and [`InsertOutcome::Duplicate`] is returned.
+pub async fn insert_upgrade<C: GenericClient>(
+    client: &C,
+    location: &EventLocation,
+    old_module_ref: &ModuleReference,
+    new_module_ref: &ModuleReference,
+) -> DatabaseResult<InsertOutcome> {
+    let insert_upgrade = client
+        .prepare_cached(
+            "INSERT INTO upgrades (block_height, block_time, transaction_hash, old_module_ref, \
+             new_module_ref, network) VALUES ($1, $2, $3, $4, $5, (SELECT network FROM settings)) \
+             ON CONFLICT (transaction_hash, old_module_ref, new_module_ref) DO NOTHING",
+        )
+        .await?;
+    let params: [&(dyn ToSql + Sync); 5] = [
+        &(location.block_height.height as i64),
+        &location.block_time,
+        &location.transaction_hash.as_ref(),
+        &old_module_ref.as_ref(),
+        &new_module_ref.as_ref(),
+    ];
+    let inserted = client.execute(&insert_upgrade, &params).await?;
+    if inserted == 0 {
+        Ok(InsertOutcome::Duplicate)
+    } else {
+        Ok(InsertOutcome::Inserted)
+    }
+}
+
+/// Delete the events from blocks above the block `height` from the event
+/// `table` using the `client`, which can be a connection or a database
+/// transaction. Returns the number of deleted events.
+pub async fn delete_events_above<C: GenericClient>(
+    client: &C,
+    table: EventTable,
+    height: AbsoluteBlockHeight,
+) -> DatabaseResult<u64> {
+    let delete_events = client
+        .prepare_cached(&format!(
+            "DELETE FROM {} WHERE block_height > $1",
+            table.name()
+        ))
+        .await?;
+    let params: [&(dyn ToSql + Sync); 1] = [&(height.height as i64)];
+    Ok(client.execute(&delete_events, &params).await?)
+}
+
+/// Set the `latest_processed_block_height` checkpoint in the settings using
+/// the `client`, which can be a connection or a database transaction. With
+/// `None`, the indexer starts from the block the contract was created in.
+pub async fn set_latest_processed_block_height<C: GenericClient>(
+    client: &C,
+    height: Option<AbsoluteBlockHeight>,
+) -> DatabaseResult<()> {
+    let set_checkpoint = client
+        .prepare_cached("UPDATE settings SET latest_processed_block_height = $1 WHERE id = true")
+        .await?;
+    let params: [&(dyn ToSql + Sync); 1] = [&height.map(|height| height.height as i64)];
+    client.execute(&set_checkpoint, &params).await?;
+    Ok(())
+}
+
+/// Send every event of `events` as a JSON payload with `NOTIFY` on the
+/// `channel` using the `client`. If the client is the database transaction
+/// storing the events, the notifications are delivered to the listeners when
+/// the transaction commits and are dropped if it is rolled back. An event
+/// whose payload exceeds the size limit of PostgreSQL is sent without its
+/// `additional_data` and `metadata_url` and with `"truncated": true`, so that
+/// listeners can read the full event from the database.
+pub async fn notify_events<C: GenericClient, E: Serialize>(
+    client: &C,
+    channel: &str,
+    events: &[E],
+) -> DatabaseResult<()> {
+    let notify = client.prepare_cached("SELECT pg_notify($1, $2)").await?;
+    for event in events {
+        let mut payload = serde_json::to_value(event)
+            .map_err(|e| DatabaseError::TypeConversion(e.to_string()))?;
+        if payload.to_string().len() > MAX_NOTIFY_PAYLOAD_SIZE {
+            if let serde_json::Value::Object(fields) = &mut payload {
+                fields.remove("additional_data");
+                fields.remove("metadata_url");
+                fields.insert("truncated".to_string(), serde_json::Value::Bool(true));
+            }
+        }
+        let params: [&(dyn ToSql + Sync); 2] = [&channel, &payload.to_string()];
+        client.execute(&notify, &params).await?;
+    }
+    Ok(())
+}
+
+/// Get the latest nonce of the `account` stored in the `nonce_events` table
+/// using the `client`, which can be a connection or a database transaction.
+/// Returns `None` if no nonce event of the account is stored.
+pub async fn get_latest_nonce<C: GenericClient>(
+    client: &C,
+    account: &AccountAddress,
+) -> DatabaseResult<Option<u64>> {
+    let get_latest_nonce = client
+        .prepare_cached("SELECT MAX(nonce) AS nonce FROM nonce_events WHERE account = $1")
+        .await?;
+    let params: [&(dyn ToSql + Sync); 1] = [&account.0.as_ref()];
+    let row = client.query_one(&get_latest_nonce, &params).await?;
+    let nonce: Option<i64> = row.try_get("nonce")?;
+    Ok(nonce.map(|nonce| nonce as u64))
+}
+
+/// Get the sender of the transaction an event was recorded in from the postgres
+/// row of the event. The sender is `None` for events indexed before the sender
+/// was recorded.
+fn sender_from_row(row: &tokio_postgres::Row) -> DatabaseResult<Option<AccountAddress>> {
+    let raw_sender: Option<&[u8]> = row.try_get("sender")?;
+    raw_sender
+        .map(|raw_sender| <[u8; 32]>::try_from(raw_sender).map(AccountAddress))
+        .transpose()
+        .map_err(|_| DatabaseError::TypeConversion("sender".to_string()))
+}
+
+/// Get the entrypoint whose invocation logged an event from the postgres row of
+/// the event. The entrypoint is `None` for events indexed before the entrypoint
+/// was recorded.
+fn entrypoint_from_row(row: &tokio_postgres::Row) -> DatabaseResult<Option<OwnedEntrypointName>> {
+    let raw_entrypoint: Option<String> = row.try_get("entrypoint")?;
+    raw_entrypoint
+        .map(OwnedEntrypointName::new)
+        .transpose()
+        .map_err(|_| DatabaseError::TypeConversion("entrypoint".to_string()))
+}
+
+/// Get the hash of the redacted or dropped additional data from the postgres
+/// row of a status changed event. The hash is `None` if the additional data is
+/// kept.
+fn additional_data_hash_from_row(row: &tokio_postgres::Row) -> DatabaseResult<Option<Hash>> {
+    let raw_hash: Option<&[u8]> = row.try_get("additional_data_hash")?;
+    raw_hash
+        .map(Hash::try_from)
+        .transpose()
+        .map_err(|_| DatabaseError::TypeConversion("additional_data_hash".to_string()))
+}
+
+/// Get the additional data from the postgres row of a status changed event.
+/// Redacted or dropped additional data can no longer be decoded and is returned
+/// as `Raw` bytes.
+fn additional_data_from_row(
+    row: &tokio_postgres::Row,
+    additional_data_hash: Option<Hash>,
+) -> DatabaseResult<AdditionalData> {
+    let raw_additional_data: &[u8] = row.try_get("additional_data")?;
+    Ok(decode_additional_data(
+        raw_additional_data,
+        additional_data_hash.is_some(),
+    ))
+}
+
+/// Decode the stored bytes of the additional data of a status changed event.
+/// Rows indexed before the additional data was structured hold the payload
+/// bytes as is. Such payloads (and redacted payloads) do not decode to exactly
+/// one `AdditionalData` and are returned as `Raw` bytes.
+fn decode_additional_data(raw_additional_data: &[u8], redacted: bool) -> AdditionalData {
+    if !redacted {
+        let mut cursor = Cursor::new(raw_additional_data);
+        if let Ok(additional_data) = AdditionalData::deserial(&mut cursor) {
+            if cursor.offset == raw_additional_data.len() {
+                return additional_data;
+            }
+        }
+    }
+    AdditionalData::from_bytes(raw_additional_data.into())
+}
+
+/// A `StoredItemStatusChanged` event stored in the database.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct StoredItemStatusChangedEvent {
+    /// The timestamp of the block the event was included in.
+    pub block_time:           DateTime<Utc>,
+    /// The transaction hash that the event was recorded in.
+    pub transaction_hash:     TransactionHash,
+    /// The index from the array of logged events in a transaction.
+    pub event_index:          u64,
+    /// The account that sent the transaction the event was recorded in.
+    /// `None` for events indexed before the sender was recorded.
+    pub sender:               Option<AccountAddress>,
+    /// The entrypoint whose invocation logged the event, e.g.
+    /// `changeItemStatus` for a direct call or `permit` for a sponsored
+    /// transaction. `None` for events indexed before the entrypoint was
+    /// recorded.
+    pub entrypoint:           Option<OwnedEntrypointName>,
     /// The item's id as logged in the event.
-    pub item_id:          u64,
+    pub item_id:              u64,
     /// The item's new status as logged in the event.
-    pub new_status:       Status,
-    /// Any additional data encoded as generic bytes as logged in the event.
-    /// Usecase-specific data can be included here such as temperature,
-    /// longitude, latitude, ... .
-    pub additional_data:  AdditionalData,
+    pub new_status:           Status,
+    /// The additional data as logged in the event, e.g. a location or a sensor
+    /// reading. The data is redacted or dropped once it is older than the
+    /// retention period of the additional data and then returned as `Raw`
+    /// bytes.
+    pub additional_data:      AdditionalData,
+    /// The SHA-256 hash of the additional data as logged in the event if the
+    /// additional data was redacted or dropped. `None` if the additional data
+    /// is kept.
+    pub additional_data_hash: Option<Hash>,
 }
 
 impl TryFrom<tokio_postgres::Row> for StoredItemStatusChangedEvent {
@@ -100,8 +768,8 @@ impl TryFrom<tokio_postgres::Row> for StoredItemStatusChangedEvent {
         let raw_transaction_hash: &[u8] = value.try_get("transaction_hash")?;
         let raw_item_id: i64 = value.try_get("item_id")?;
         let raw_event_index: i64 = value.try_get("event_index")?;
-        let raw_additional_data: &[u8] = value.try_get("additional_data")?;
         let Json(new_status): Json<Status> = value.try_get("new_status")?;
+        let additional_data_hash = additional_data_hash_from_row(&value)?;
 
         let events = Self {
             block_time: value.try_get("block_time")?,
@@ -109,9 +777,12 @@ impl TryFrom<tokio_postgres::Row> for StoredItemStatusChangedEvent {
                 .try_into()
                 .map_err(|_| DatabaseError::TypeConversion("transaction_hash".to_string()))?,
             event_index: raw_event_index as u64,
+            sender: sender_from_row(&value)?,
+            entrypoint: entrypoint_from_row(&value)?,
             new_status,
             item_id: raw_item_id as u64,
-            additional_data: AdditionalData::from_bytes(raw_additional_data.into()),
+            additional_data: additional_data_from_row(&value, additional_data_hash)?,
+            additional_data_hash,
         };
         Ok(events)
     }
@@ -126,6 +797,13 @@ pub struct StoredItemCreatedEvent {
     pub transaction_hash: TransactionHash,
     /// The index from the array of logged events in a transaction.
     pub event_index:      u64,
+    /// The account that sent the transaction the event was recorded in.
+    /// `None` for events indexed before the sender was recorded.
+    pub sender:           Option<AccountAddress>,
+    /// The entrypoint whose invocation logged the event, e.g. `createItem`
+    /// for a direct call or `permit` for a sponsored transaction. `None` for
+    /// events indexed before the entrypoint was recorded.
+    pub entrypoint:       Option<OwnedEntrypointName>,
     /// The item's id as logged in the event.
     pub item_id:          u64,
     /// The item's metadata_url as logged in the event.
@@ -150,6 +828,8 @@ impl TryFrom<tokio_postgres::Row> for StoredItemCreatedEvent {
                 .try_into()
                 .map_err(|_| DatabaseError::TypeConversion("transaction_hash".to_string()))?,
             event_index: raw_event_index as u64,
+            sender: sender_from_row(&value)?,
+            entrypoint: entrypoint_from_row(&value)?,
             item_id: raw_item_id as u64,
             metadata_url: from_bytes(value.try_get("metadata_url")?)
                 .map_err(|_| DatabaseError::TypeConversion("metadata_url".to_string()))?,
@@ -159,6 +839,213 @@ impl TryFrom<tokio_postgres::Row> for StoredItemCreatedEvent {
     }
 }
 
+/// An event of an item in the timeline of the item as returned by
+/// [`Database::get_item_timeline`].
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type")]
+pub enum StoredItemTimelineEvent {
+    /// The creation of the item.
+    ItemCreated(StoredItemCreatedEvent),
+    /// A status change of the item.
+    ItemStatusChanged(StoredItemStatusChangedEvent),
+}
+
+impl TryFrom<tokio_postgres::Row> for StoredItemTimelineEvent {
+    type Error = DatabaseError;
+
+    // Conversion from a postgres row of the `item_timeline` view to the
+    // `StoredItemTimelineEvent` type.
+    fn try_from(value: tokio_postgres::Row) -> DatabaseResult<Self> {
+        let event_type: &str = value.try_get("event_type")?;
+        let raw_transaction_hash: &[u8] = value.try_get("transaction_hash")?;
+        let raw_item_id: i64 = value.try_get("item_id")?;
+        let raw_event_index: i64 = value.try_get("event_index")?;
+        let Json(status): Json<Status> = value.try_get("status")?;
+
+        let block_time = value.try_get("block_time")?;
+        let sender = sender_from_row(&value)?;
+        let entrypoint = entrypoint_from_row(&value)?;
+        let transaction_hash = raw_transaction_hash
+            .try_into()
+            .map_err(|_| DatabaseError::TypeConversion("transaction_hash".to_string()))?;
+
+        let event = match event_type {
+            "ItemCreated" => Self::ItemCreated(StoredItemCreatedEvent {
+                block_time,
+                transaction_hash,
+                event_index: raw_event_index as u64,
+                sender,
+                entrypoint,
+                item_id: raw_item_id as u64,
+                metadata_url: from_bytes(value.try_get("metadata_url")?)
+                    .map_err(|_| DatabaseError::TypeConversion("metadata_url".to_string()))?,
+                initial_status: status,
+            }),
+            "ItemStatusChanged" => {
+                let additional_data_hash = additional_data_hash_from_row(&value)?;
+                Self::ItemStatusChanged(StoredItemStatusChangedEvent {
+                    block_time,
+                    transaction_hash,
+                    event_index: raw_event_index as u64,
+                    sender,
+                    entrypoint,
+                    item_id: raw_item_id as u64,
+                    new_status: status,
+                    additional_data: additional_data_from_row(&value, additional_data_hash)?,
+                    additional_data_hash,
+                })
+            }
+            _ => return Err(DatabaseError::TypeConversion("event_type".to_string())),
+        };
+        Ok(event)
+    }
+}
+
+/// An event of an item together with its `change_id` as returned by
+/// [`Database::get_changes`]. The `change_id` is shared by both event tables
+/// and increases with every inserted or overwritten event.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct StoredChange {
+    /// The cursor of the change.
+    pub change_id: u64,
+    /// The event that was inserted or overwritten.
+    pub event:     StoredItemTimelineEvent,
+}
+
+impl TryFrom<tokio_postgres::Row> for StoredChange {
+    type Error = DatabaseError;
+
+    // Conversion from a postgres row of the `item_timeline` view to the
+    // `StoredChange` type.
+    fn try_from(value: tokio_postgres::Row) -> DatabaseResult<Self> {
+        let raw_change_id: i64 = value.try_get("change_id")?;
+        Ok(Self {
+            change_id: raw_change_id as u64,
+            event:     StoredItemTimelineEvent::try_from(value)?,
+        })
+    }
+}
+
+/// A status of an item together with the time it was set. Used for the public
+/// provenance summary which must not expose any other data of the events.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct StoredStatusUpdate {
+    /// The timestamp of the block the status was set in.
+    pub block_time: DateTime<Utc>,
+    /// The status of the item.
+    pub status:     Status,
+}
+
+impl TryFrom<tokio_postgres::Row> for StoredStatusUpdate {
+    type Error = DatabaseError;
+
+    // Conversion from the postgres row to the `StoredStatusUpdate` type.
+    fn try_from(value: tokio_postgres::Row) -> DatabaseResult<Self> {
+        let Json(status): Json<Status> = value.try_get("status")?;
+        Ok(Self {
+            block_time: value.try_get("block_time")?,
+            status,
+        })
+    }
+}
+
+/// A transfer of the token minted for an item stored in the database.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct StoredTokenTransfer {
+    /// The height of the block the transfer was included in.
+    pub block_height:     AbsoluteBlockHeight,
+    /// The timestamp of the block the transfer was included in.
+    pub block_time:       DateTime<Utc>,
+    /// The transaction hash that the transfer was recorded in.
+    pub transaction_hash: TransactionHash,
+    /// The index from the array of logged events in a transaction.
+    pub event_index:      u64,
+    /// The token contract that logged the transfer.
+    pub token_contract:   ContractAddress,
+    /// The item the token was minted for.
+    pub item_id:          u64,
+    /// The amount of tokens transferred as a decimal string.
+    pub amount:           String,
+    /// The address the tokens were transferred from.
+    pub from:             Address,
+    /// The address the tokens were transferred to.
+    pub to:               Address,
+}
+
+impl TryFrom<tokio_postgres::Row> for StoredTokenTransfer {
+    type Error = DatabaseError;
+
+    // Conversion from the postgres row to the `StoredTokenTransfer` type.
+    fn try_from(value: tokio_postgres::Row) -> DatabaseResult<Self> {
+        let raw_block_height: i64 = value.try_get("block_height")?;
+        let raw_transaction_hash: &[u8] = value.try_get("transaction_hash")?;
+        let raw_event_index: i64 = value.try_get("event_index")?;
+        let raw_token_contract_index: i64 = value.try_get("token_contract_index")?;
+        let raw_token_contract_subindex: i64 = value.try_get("token_contract_subindex")?;
+        let raw_item_id: i64 = value.try_get("item_id")?;
+        let Json(from): Json<Address> = value.try_get("from_address")?;
+        let Json(to): Json<Address> = value.try_get("to_address")?;
+
+        Ok(Self {
+            block_height: AbsoluteBlockHeight::from(raw_block_height as u64),
+            block_time: value.try_get("block_time")?,
+            transaction_hash: raw_transaction_hash
+                .try_into()
+                .map_err(|_| DatabaseError::TypeConversion("transaction_hash".to_string()))?,
+            event_index: raw_event_index as u64,
+            token_contract: ContractAddress::new(
+                raw_token_contract_index as u64,
+                raw_token_contract_subindex as u64,
+            ),
+            item_id: raw_item_id as u64,
+            amount: value.try_get("amount")?,
+            from,
+            to,
+        })
+    }
+}
+
+/// An upgrade of the contract stored in the database.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct StoredUpgrade {
+    /// The height of the block the upgrade was included in.
+    pub block_height:     AbsoluteBlockHeight,
+    /// The timestamp of the block the upgrade was included in.
+    pub block_time:       DateTime<Utc>,
+    /// The transaction hash that the upgrade was recorded in.
+    pub transaction_hash: TransactionHash,
+    /// The module the contract was an instance of before the upgrade.
+    pub old_module_ref:   ModuleReference,
+    /// The module the contract is an instance of after the upgrade.
+    pub new_module_ref:   ModuleReference,
+}
+
+impl TryFrom<tokio_postgres::Row> for StoredUpgrade {
+    type Error = DatabaseError;
+
+    // Conversion from the postgres row to the `StoredUpgrade` type.
+    fn try_from(value: tokio_postgres::Row) -> DatabaseResult<Self> {
+        let raw_block_height: i64 = value.try_get("block_height")?;
+        let raw_transaction_hash: &[u8] = value.try_get("transaction_hash")?;
+        let raw_old_module_ref: &[u8] = value.try_get("old_module_ref")?;
+        let raw_new_module_ref: &[u8] = value.try_get("new_module_ref")?;
+
+        Ok(Self {
+            block_height:     AbsoluteBlockHeight::from(raw_block_height as u64),
+            block_time:       value.try_get("block_time")?,
+            transaction_hash: raw_transaction_hash
+                .try_into()
+                .map_err(|_| DatabaseError::TypeConversion("transaction_hash".to_string()))?,
+            old_module_ref:   raw_old_module_ref
+                .try_into()
+                .map_err(|_| DatabaseError::TypeConversion("old_module_ref".to_string()))?,
+            new_module_ref:   raw_new_module_ref
+                .try_into()
+                .map_err(|_| DatabaseError::TypeConversion("new_module_ref".to_string()))?,
+        })
+    }
+}
+
 /// Database client wrapper
 pub struct Database {
     /// The database client
@@ -184,14 +1071,18 @@ impl Database {
         let init_settings = self
             .client
             .prepare_cached(
-                "INSERT INTO settings (genesis_block_hash, contract_index, contract_subindex) \
-                 VALUES ($1, $2, $3) ON CONFLICT DO NOTHING",
+                "INSERT INTO settings (genesis_block_hash, contract_index, contract_subindex, \
+                 network, schema_version, indexer_version) VALUES ($1, $2, $3, $4, (SELECT \
+                 MAX(version) FROM refinery_schema_history), $5) ON CONFLICT DO NOTHING",
             )
             .await?;
-        let params: [&(dyn ToSql + Sync); 3] = [
+        let network = network_of_genesis(genesis_block_hash).map(|network| network.to_string());
+        let params: [&(dyn ToSql + Sync); 5] = [
             &genesis_block_hash.as_ref(),
             &(contract_address.index as i64),
             &(contract_address.subindex as i64),
+            &network,
+            &env!("CARGO_PKG_VERSION"),
         ];
         self.client.execute(&init_settings, &params).await?;
         Ok(())
@@ -209,8 +1100,419 @@ impl Database {
         self.client.query_one(&get_settings, &[]).await?.try_into()
     }
 
+    /// Get the version of the latest migration applied to the database as
+    /// recorded in the settings. Returns `None` if the settings have not been
+    /// initialized yet.
+    pub async fn get_schema_version(&self) -> DatabaseResult<Option<u64>> {
+        let get_schema_version = self
+            .client
+            .prepare_cached("SELECT schema_version FROM settings")
+            .await?;
+        let opt_row = self.client.query_opt(&get_schema_version, &[]).await?;
+        let raw_schema_version: Option<i64> = match opt_row {
+            Some(row) => row.try_get("schema_version")?,
+            None => None,
+        };
+        Ok(raw_schema_version.map(|version| version as u64))
+    }
+
+    /// Get the settings recorded in the database or `None` if the settings
+    /// have not been initialized yet.
+    pub async fn get_settings_opt(&self) -> DatabaseResult<Option<StoredConfiguration>> {
+        let get_settings = self
+            .client
+            .prepare_cached(
+                "SELECT genesis_block_hash, contract_index, contract_subindex, \
+                 latest_processed_block_height FROM settings",
+            )
+            .await?;
+        let opt_row = self.client.query_opt(&get_settings, &[]).await?;
+
+        opt_row.map(StoredConfiguration::try_from).transpose()
+    }
+
+    /// Restores the settings row from previously exported settings. Any
+    /// existing settings row is overwritten.
+    pub async fn restore_settings(&self, settings: &StoredConfiguration) -> DatabaseResult<()> {
+        let restore_settings = self
+            .client
+            .prepare_cached(
+                "INSERT INTO settings (genesis_block_hash, contract_index, contract_subindex, \
+                 latest_processed_block_height, network) VALUES ($1, $2, $3, $4, $5) ON CONFLICT \
+                 (id) DO UPDATE SET genesis_block_hash = EXCLUDED.genesis_block_hash, \
+                 contract_index = EXCLUDED.contract_index, contract_subindex = \
+                 EXCLUDED.contract_subindex, latest_processed_block_height = \
+                 EXCLUDED.latest_processed_block_height, network = EXCLUDED.network",
+            )
+            .await?;
+        let network =
+            network_of_genesis(&settings.genesis_block_hash).map(|network| network.to_string());
+        let params: [&(dyn ToSql + Sync); 5] = [
+            &settings.genesis_block_hash.as_ref(),
+            &(settings.contract_address.index as i64),
+            &(settings.contract_address.subindex as i64),
+            &settings
+                .latest_processed_block_height
+                .map(|height| height.height as i64),
+            &network,
+        ];
+        self.client.execute(&restore_settings, &params).await?;
+        Ok(())
+    }
+
+    /// Delete the events from blocks with a slot time before `cutoff`. Item
+    /// created events are only deleted once no status changed events of the
+    /// item remain, so that the creation of an item is kept as long as any of
+    /// its history is. The item summary and token transfer events are deleted
+    /// as well. Returns the number of deleted item created and status changed
+    /// events.
+    pub async fn prune_events(&self, cutoff: DateTime<Utc>) -> DatabaseResult<StoredEventCounts> {
+        let prune_item_summary_events = self
+            .client
+            .prepare_cached("DELETE FROM item_summary_events WHERE block_time < $1")
+            .await?;
+        let prune_token_transfer_events = self
+            .client
+            .prepare_cached("DELETE FROM token_transfer_events WHERE block_time < $1")
+            .await?;
+        let prune_item_status_changed_events = self
+            .client
+            .prepare_cached("DELETE FROM item_status_changed_events WHERE block_time < $1")
+            .await?;
+        let prune_item_created_events = self
+            .client
+            .prepare_cached(
+                "DELETE FROM item_created_events WHERE block_time < $1 AND NOT EXISTS (SELECT 1 \
+                 FROM item_status_changed_events WHERE item_status_changed_events.item_id = \
+                 item_created_events.item_id)",
+            )
+            .await?;
+        let params: [&(dyn ToSql + Sync); 1] = [&cutoff];
+
+        self.client
+            .execute(&prune_item_summary_events, &params)
+            .await?;
+        self.client
+            .execute(&prune_token_transfer_events, &params)
+            .await?;
+        let item_status_changed_events = self
+            .client
+            .execute(&prune_item_status_changed_events, &params)
+            .await?;
+        let item_created_events = self
+            .client
+            .execute(&prune_item_created_events, &params)
+            .await?;
+
+        Ok(StoredEventCounts {
+            item_created_events,
+            item_status_changed_events,
+        })
+    }
+
+    /// Remove the additional data of the status changed events from blocks
+    /// with a slot time before `cutoff` as given by the `redaction`. The
+    /// SHA-256 hash of the additional data is stored in the
+    /// `additional_data_hash` column, and the events get a new `change_id`
+    /// so that clients syncing the changes receive the redacted events.
+    /// Events whose additional data was already removed are skipped. Returns
+    /// the number of redacted events.
+    pub async fn redact_additional_data(
+        &self,
+        cutoff: DateTime<Utc>,
+        redaction: AdditionalDataRedaction,
+    ) -> DatabaseResult<u64> {
+        let redacted_data = match redaction {
+            AdditionalDataRedaction::Redact => {
+                "decode(repeat('00', length(additional_data)), 'hex')"
+            }
+            AdditionalDataRedaction::Drop => "''::BYTEA",
+        };
+        let redact_additional_data = self
+            .client
+            .prepare_cached(&format!(
+                "UPDATE item_status_changed_events SET additional_data_hash = \
+                 sha256(additional_data), additional_data = {redacted_data}, change_id = \
+                 nextval('change_id_seq') WHERE block_time < $1 AND additional_data_hash IS NULL"
+            ))
+            .await?;
+        let params: [&(dyn ToSql + Sync); 1] = [&cutoff];
+        Ok(self
+            .client
+            .execute(&redact_additional_data, &params)
+            .await?)
+    }
+
+    /// Get the number of events stored in the database per event table.
+    pub async fn get_event_counts(&self) -> DatabaseResult<StoredEventCounts> {
+        let get_event_counts = self
+            .client
+            .prepare_cached(
+                "SELECT (SELECT COUNT(*) FROM item_created_events) AS item_created_events, \
+                 (SELECT COUNT(*) FROM item_status_changed_events) AS item_status_changed_events",
+            )
+            .await?;
+        self.client
+            .query_one(&get_event_counts, &[])
+            .await?
+            .try_into()
+    }
+
+    /// Get the number of events per event table included in blocks with a
+    /// slot time of at least `since`.
+    pub async fn get_event_counts_since(
+        &self,
+        since: DateTime<Utc>,
+    ) -> DatabaseResult<StoredEventCounts> {
+        let get_event_counts = self
+            .client
+            .prepare_cached(
+                "SELECT (SELECT COUNT(*) FROM item_created_events WHERE block_time >= $1) AS \
+                 item_created_events, (SELECT COUNT(*) FROM item_status_changed_events WHERE \
+                 block_time >= $1) AS item_status_changed_events",
+            )
+            .await?;
+        let params: [&(dyn ToSql + Sync); 1] = [&since];
+        self.client
+            .query_one(&get_event_counts, &params)
+            .await?
+            .try_into()
+    }
+
+    /// Get the `limit` most recently created items with their current status,
+    /// starting with the most recent one.
+    pub async fn get_recent_items(&self, limit: u32) -> DatabaseResult<Vec<StoredRecentItem>> {
+        let get_recent_items = self
+            .client
+            .prepare_cached(
+                "SELECT item_created_events.item_id, COALESCE(latest.new_status, \
+                 item_created_events.initial_status) AS status, item_created_events.block_time AS \
+                 created_at, COALESCE(latest.block_time, item_created_events.block_time) AS \
+                 updated_at FROM item_created_events LEFT JOIN LATERAL (SELECT new_status, \
+                 block_time FROM item_status_changed_events WHERE \
+                 item_status_changed_events.item_id = item_created_events.item_id ORDER BY id \
+                 DESC LIMIT 1) AS latest ON true ORDER BY item_created_events.id DESC LIMIT $1",
+            )
+            .await?;
+        let params: [&(dyn ToSql + Sync); 1] = [&i64::from(limit)];
+
+        let rows = self.client.query(&get_recent_items, &params).await?;
+
+        rows.into_iter()
+            .map(StoredRecentItem::try_from)
+            .collect::<Result<Vec<_>, _>>()
+    }
+
+    /// Get the block height and event index of the latest event in the event
+    /// `table`, i.e., the event with the largest `(block_height, event_index)`.
+    /// Events indexed before the block height was recorded are ignored.
+    /// Returns `None` if the table holds no events with a block height.
+    pub async fn get_latest_event_position(
+        &self,
+        table: EventTable,
+    ) -> DatabaseResult<Option<(AbsoluteBlockHeight, u64)>> {
+        let get_latest_event = self
+            .client
+            .prepare_cached(&format!(
+                "SELECT block_height, event_index FROM {} WHERE block_height IS NOT NULL ORDER BY \
+                 block_height DESC, event_index DESC LIMIT 1",
+                table.name()
+            ))
+            .await?;
+        let opt_row = self.client.query_opt(&get_latest_event, &[]).await?;
+
+        opt_row
+            .map(|row| {
+                let raw_block_height: i64 = row.try_get("block_height")?;
+                let raw_event_index: i64 = row.try_get("event_index")?;
+                Ok((
+                    AbsoluteBlockHeight::from(raw_block_height as u64),
+                    raw_event_index as u64,
+                ))
+            })
+            .transpose()
+    }
+
+    /// Get the number of items per current status. The current status of an
+    /// item is the new status of its latest status changed event or its
+    /// initial status if its status never changed. Statuses without items are
+    /// not returned.
+    pub async fn get_status_counts(&self) -> DatabaseResult<Vec<StoredStatusCount>> {
+        let get_status_counts = self
+            .client
+            .prepare_cached(
+                "SELECT status, COUNT(*) AS count FROM (SELECT DISTINCT ON \
+                 (item_created_events.item_id) COALESCE(item_status_changed_events.new_status, \
+                 item_created_events.initial_status) AS status FROM item_created_events LEFT JOIN \
+                 item_status_changed_events ON item_status_changed_events.item_id = \
+                 item_created_events.item_id ORDER BY item_created_events.item_id, \
+                 item_status_changed_events.id DESC NULLS LAST) AS current_statuses GROUP BY \
+                 status ORDER BY count DESC",
+            )
+            .await?;
+
+        let rows = self.client.query(&get_status_counts, &[]).await?;
+
+        rows.into_iter()
+            .map(StoredStatusCount::try_from)
+            .collect::<Result<Vec<_>, _>>()
+    }
+
+    /// Get the number of events per event table included in blocks of each day
+    /// (UTC), starting with the oldest day. Days without events are not
+    /// returned.
+    pub async fn get_events_per_day(&self) -> DatabaseResult<Vec<StoredDailyEventCounts>> {
+        let get_events_per_day = self
+            .client
+            .prepare_cached(
+                "SELECT (block_time AT TIME ZONE 'UTC')::DATE AS day, COUNT(*) FILTER (WHERE \
+                 event_type = 'ItemCreated') AS item_created_events, COUNT(*) FILTER (WHERE \
+                 event_type = 'ItemStatusChanged') AS item_status_changed_events FROM \
+                 item_timeline GROUP BY day ORDER BY day",
+            )
+            .await?;
+
+        let rows = self.client.query(&get_events_per_day, &[]).await?;
+
+        rows.into_iter()
+            .map(StoredDailyEventCounts::try_from)
+            .collect::<Result<Vec<_>, _>>()
+    }
+
+    /// Get the number of status transitions per day (UTC) and pair of statuses
+    /// (e.g. `Produced` to `InTransit`), starting with the oldest day. The
+    /// counts are read from the `daily_status_transitions` materialized view
+    /// and only include the events indexed before the last
+    /// [`Database::refresh_status_transitions`].
+    pub async fn get_status_transitions_per_day(
+        &self,
+    ) -> DatabaseResult<Vec<StoredStatusTransitionCount>> {
+        let get_status_transitions = self
+            .client
+            .prepare_cached(
+                "SELECT day, from_status, to_status, transitions FROM daily_status_transitions \
+                 ORDER BY day, from_status, to_status",
+            )
+            .await?;
+
+        let rows = self.client.query(&get_status_transitions, &[]).await?;
+
+        rows.into_iter()
+            .map(StoredStatusTransitionCount::try_from)
+            .collect::<Result<Vec<_>, _>>()
+    }
+
+    /// Recompute the `daily_status_transitions` materialized view from the
+    /// event tables. The view is refreshed concurrently, so it can be read
+    /// during the refresh.
+    pub async fn refresh_status_transitions(&self) -> DatabaseResult<()> {
+        self.client
+            .execute(
+                "REFRESH MATERIALIZED VIEW CONCURRENTLY daily_status_transitions",
+                &[],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Get a page of at most `limit` rows from the event `table` ordered by
+    /// `id`, starting after the row with id `after_id`. Rows are only
+    /// returned if their `block_height` lies between `from_height` and
+    /// `to_height` (both inclusive) when the respective bound is given.
+    pub async fn get_events_page(
+        &self,
+        table: EventTable,
+        after_id: Option<i64>,
+        from_height: Option<AbsoluteBlockHeight>,
+        to_height: Option<AbsoluteBlockHeight>,
+        limit: u32,
+    ) -> DatabaseResult<Vec<tokio_postgres::Row>> {
+        let get_events_page = self
+            .client
+            .prepare_cached(&format!(
+                "SELECT * FROM {} WHERE ($1::INT8 IS NULL OR id > $1) AND ($2::INT8 IS NULL OR \
+                 block_height >= $2) AND ($3::INT8 IS NULL OR block_height <= $3) ORDER BY id \
+                 LIMIT $4",
+                table.name()
+            ))
+            .await?;
+        let params: [&(dyn ToSql + Sync); 4] = [
+            &after_id,
+            &from_height.map(|height| height.height as i64),
+            &to_height.map(|height| height.height as i64),
+            &(limit as i64),
+        ];
+
+        Ok(self.client.query(&get_events_page, &params).await?)
+    }
+
+    /// Get the raw row of the event identified by the `transaction_hash` and
+    /// `event_index` from the event `table`.
+    pub async fn get_event_row(
+        &self,
+        table: EventTable,
+        transaction_hash: &TransactionHash,
+        event_index: u64,
+    ) -> DatabaseResult<Option<tokio_postgres::Row>> {
+        let get_event_row = self
+            .client
+            .prepare_cached(&format!(
+                "SELECT * FROM {} WHERE transaction_hash = $1 AND event_index = $2",
+                table.name()
+            ))
+            .await?;
+        let params: [&(dyn ToSql + Sync); 2] = [&transaction_hash.as_ref(), &(event_index as i64)];
+        Ok(self.client.query_opt(&get_event_row, &params).await?)
+    }
+
+    /// Get the `transaction_hash` and `event_index` of all events in the event
+    /// `table` from blocks with a height between `from_height` and
+    /// `to_height` (both inclusive).
+    pub async fn get_event_keys_in_range(
+        &self,
+        table: EventTable,
+        from_height: AbsoluteBlockHeight,
+        to_height: AbsoluteBlockHeight,
+    ) -> DatabaseResult<Vec<(TransactionHash, u64)>> {
+        let get_event_keys = self
+            .client
+            .prepare_cached(&format!(
+                "SELECT transaction_hash, event_index FROM {} WHERE block_height >= $1 AND \
+                 block_height <= $2",
+                table.name()
+            ))
+            .await?;
+        let params: [&(dyn ToSql + Sync); 2] =
+            [&(from_height.height as i64), &(to_height.height as i64)];
+        let rows = self.client.query(&get_event_keys, &params).await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let raw_transaction_hash: &[u8] = row.try_get("transaction_hash")?;
+                let transaction_hash = raw_transaction_hash
+                    .try_into()
+                    .map_err(|_| DatabaseError::TypeConversion("transaction_hash".to_string()))?;
+                let event_index: i64 = row.try_get("event_index")?;
+                Ok((transaction_hash, event_index as u64))
+            })
+            .collect()
+    }
+
+    /// Insert an event found at the given location on chain into its table
+    /// unless it is already stored.
+    pub async fn insert_event<E: IndexableEvent>(
+        &self,
+        location: &EventLocation,
+        event: &E,
+    ) -> DatabaseResult<InsertOutcome> {
+        insert_event(&*self.client, location, event).await
+    }
+
     /// Get all [`StoredItemStatusChangedEvents`] by item id.
     /// The query enforces pagination with the `limit` and `offset` parameter.
+    /// If a `network` is given, events indexed from other networks are
+    /// skipped. Events of chains other than mainnet and testnet are never
+    /// skipped.
     /// Note: This function will be used by the http server and the
     /// `#[allow(dead_code)]` is only temporary until the http server is
     /// developed.
@@ -220,17 +1522,24 @@ impl Database {
         item_id: u64,
         limit: u32,
         offset: u32,
+        network: Option<Network>,
     ) -> DatabaseResult<Vec<StoredItemStatusChangedEvent>> {
         let get_item_status_changed_event_submissions = self
             .client
             .prepare_cached(
-                "SELECT block_time, transaction_hash, event_index, item_id, new_status, \
-                 additional_data from item_status_changed_events WHERE item_id = $1 LIMIT $2 \
-                 OFFSET $3",
+                "SELECT block_time, transaction_hash, event_index, sender, entrypoint, item_id, \
+                 new_status, additional_data, additional_data_hash from \
+                 item_status_changed_events WHERE item_id = $1 AND ($4::TEXT IS NULL OR network \
+                 IS NULL OR network = $4) LIMIT $2 OFFSET $3",
             )
             .await?;
-        let params: [&(dyn ToSql + Sync); 3] =
-            [&(item_id as i64), &(limit as i64), &(offset as i64)];
+        let network = network.map(|network| network.to_string());
+        let params: [&(dyn ToSql + Sync); 4] = [
+            &(item_id as i64),
+            &(limit as i64),
+            &(offset as i64),
+            &network,
+        ];
 
         let rows = self
             .client
@@ -250,19 +1559,25 @@ impl Database {
     /// matching the query. Note: This function will be used by the http
     /// server and the `#[allow(dead_code)]` is only temporary until the
     /// http server is developed.
+    /// If a `network` is given, events indexed from other networks are
+    /// skipped. Events of chains other than mainnet and testnet are never
+    /// skipped.
     #[allow(dead_code)]
     pub async fn get_item_created_event_submission(
         &self,
         item_id: u64,
+        network: Option<Network>,
     ) -> DatabaseResult<Option<StoredItemCreatedEvent>> {
         let get_item_created_event_submissions = self
             .client
             .prepare_cached(
-                "SELECT block_time, transaction_hash, event_index, item_id, metadata_url, \
-                 initial_status from item_created_events WHERE item_id = $1",
+                "SELECT block_time, transaction_hash, event_index, sender, entrypoint, item_id, \
+                 metadata_url, initial_status from item_created_events WHERE item_id = $1 AND \
+                 ($2::TEXT IS NULL OR network IS NULL OR network = $2)",
             )
             .await?;
-        let params: [&(dyn ToSql + Sync); 1] = [&(item_id as i64)];
+        let network = network.map(|network| network.to_string());
+        let params: [&(dyn ToSql + Sync); 2] = [&(item_id as i64), &network];
 
         let opt_row = self
             .client
@@ -271,58 +1586,620 @@ impl Database {
 
         opt_row.map(StoredItemCreatedEvent::try_from).transpose()
     }
+
+    /// Get the timeline of an item, i.e. the created event followed by all
+    /// status changed events of the item, ordered by the block height they
+    /// were included in. Events indexed before the `block_height` column was
+    /// introduced come first and are ordered by their block time. Events in
+    /// the same block are ordered with the created event first and then in
+    /// the order they were indexed.
+    /// If a `network` is given, events indexed from other networks are
+    /// skipped. Events of chains other than mainnet and testnet are never
+    /// skipped.
+    pub async fn get_item_timeline(
+        &self,
+        item_id: u64,
+        network: Option<Network>,
+    ) -> DatabaseResult<Vec<StoredItemTimelineEvent>> {
+        let get_item_timeline = self
+            .client
+            .prepare_cached(
+                "SELECT event_type, block_time, transaction_hash, event_index, sender, \
+                 entrypoint, item_id, status, metadata_url, additional_data, additional_data_hash \
+                 FROM item_timeline WHERE item_id = $1 AND ($2::TEXT IS NULL OR network IS NULL \
+                 OR network = $2) ORDER BY block_height ASC NULLS FIRST, block_time, event_type, \
+                 id",
+            )
+            .await?;
+        let network = network.map(|network| network.to_string());
+        let params: [&(dyn ToSql + Sync); 2] = [&(item_id as i64), &network];
+
+        let rows = self.client.query(&get_item_timeline, &params).await?;
+
+        rows.into_iter()
+            .map(StoredItemTimelineEvent::try_from)
+            .collect::<Result<Vec<_>, _>>()
+    }
+
+    /// Get the events recorded in transactions sent by the `sender` account
+    /// across all items, starting with the latest. The query enforces
+    /// pagination with the `limit` and `offset` parameter. Events indexed
+    /// before the sender was recorded are never returned.
+    /// If a `network` is given, events indexed from other networks are
+    /// skipped. Events of chains other than mainnet and testnet are never
+    /// skipped.
+    pub async fn get_events_by_sender(
+        &self,
+        sender: &AccountAddress,
+        limit: u32,
+        offset: u32,
+        network: Option<Network>,
+    ) -> DatabaseResult<Vec<StoredItemTimelineEvent>> {
+        let get_events_by_sender = self
+            .client
+            .prepare_cached(
+                "SELECT event_type, block_time, transaction_hash, event_index, sender, \
+                 entrypoint, item_id, status, metadata_url, additional_data, additional_data_hash \
+                 FROM item_timeline WHERE sender = $1 AND ($4::TEXT IS NULL OR network IS NULL OR \
+                 network = $4) ORDER BY block_height DESC NULLS LAST, block_time DESC, event_type \
+                 DESC, id DESC LIMIT $2 OFFSET $3",
+            )
+            .await?;
+        let network = network.map(|network| network.to_string());
+        let params: [&(dyn ToSql + Sync); 4] = [
+            &sender.0.as_ref(),
+            &(limit as i64),
+            &(offset as i64),
+            &network,
+        ];
+
+        let rows = self.client.query(&get_events_by_sender, &params).await?;
+
+        rows.into_iter()
+            .map(StoredItemTimelineEvent::try_from)
+            .collect::<Result<Vec<_>, _>>()
+    }
+
+    /// Get the latest `limit` status updates of an item ordered by the time
+    /// they were indexed, starting with the oldest.
+    /// If a `network` is given, events indexed from other networks are
+    /// skipped. Events of chains other than mainnet and testnet are never
+    /// skipped.
+    pub async fn get_item_status_history(
+        &self,
+        item_id: u64,
+        limit: u32,
+        network: Option<Network>,
+    ) -> DatabaseResult<Vec<StoredStatusUpdate>> {
+        let get_item_status_history = self
+            .client
+            .prepare_cached(
+                "SELECT block_time, status FROM (SELECT id, block_time, new_status AS status FROM \
+                 item_status_changed_events WHERE item_id = $1 AND ($3::TEXT IS NULL OR network \
+                 IS NULL OR network = $3) ORDER BY id DESC LIMIT $2) AS latest ORDER BY id",
+            )
+            .await?;
+        let network = network.map(|network| network.to_string());
+        let params: [&(dyn ToSql + Sync); 3] = [&(item_id as i64), &(limit as i64), &network];
+
+        let rows = self.client.query(&get_item_status_history, &params).await?;
+
+        rows.into_iter()
+            .map(StoredStatusUpdate::try_from)
+            .collect::<Result<Vec<_>, _>>()
+    }
+
+    /// Get at most `limit` events of all items that were inserted or
+    /// overwritten after the change with the `change_id` given as the `cursor`,
+    /// ordered by their `change_id`. If no `cursor` is given, the changes are
+    /// returned from the start.
+    /// If a `network` is given, events indexed from other networks are
+    /// skipped. Events of chains other than mainnet and testnet are never
+    /// skipped.
+    pub async fn get_changes(
+        &self,
+        cursor: Option<u64>,
+        limit: u32,
+        network: Option<Network>,
+    ) -> DatabaseResult<Vec<StoredChange>> {
+        let get_changes = self
+            .client
+            .prepare_cached(
+                "SELECT change_id, event_type, block_time, transaction_hash, event_index, sender, \
+                 entrypoint, item_id, status, metadata_url, additional_data, additional_data_hash \
+                 FROM item_timeline WHERE ($1::INT8 IS NULL OR change_id > $1) AND ($3::TEXT IS \
+                 NULL OR network IS NULL OR network = $3) ORDER BY change_id LIMIT $2",
+            )
+            .await?;
+        let cursor = cursor.map(|cursor| cursor as i64);
+        let network = network.map(|network| network.to_string());
+        let params: [&(dyn ToSql + Sync); 3] = [&cursor, &(limit as i64), &network];
+
+        let rows = self.client.query(&get_changes, &params).await?;
+
+        rows.into_iter()
+            .map(StoredChange::try_from)
+            .collect::<Result<Vec<_>, _>>()
+    }
+
+    /// Get the transfers of the token minted for the item with the given
+    /// `item_id`, ordered by the block height they were included in. Together
+    /// with [`Database::get_item_timeline`] this gives the ownership and status
+    /// history of the item.
+    /// If a `network` is given, events indexed from other networks are
+    /// skipped. Events of chains other than mainnet and testnet are never
+    /// skipped.
+    pub async fn get_item_transfers(
+        &self,
+        item_id: u64,
+        network: Option<Network>,
+    ) -> DatabaseResult<Vec<StoredTokenTransfer>> {
+        let get_item_transfers = self
+            .client
+            .prepare_cached(
+                "SELECT block_height, block_time, transaction_hash, event_index, \
+                 token_contract_index, token_contract_subindex, item_id, amount, from_address, \
+                 to_address FROM token_transfer_events WHERE item_id = $1 AND ($2::TEXT IS NULL \
+                 OR network IS NULL OR network = $2) ORDER BY block_height, event_index, id",
+            )
+            .await?;
+        let network = network.map(|network| network.to_string());
+        let params: [&(dyn ToSql + Sync); 2] = [&(item_id as i64), &network];
+
+        let rows = self.client.query(&get_item_transfers, &params).await?;
+
+        rows.into_iter()
+            .map(StoredTokenTransfer::try_from)
+            .collect::<Result<Vec<_>, _>>()
+    }
+
+    /// Create the partitions of the event tables for the range of
+    /// [`PARTITION_SIZE`] blocks containing the block `height`, if they do not
+    /// exist yet. The partition of a table is not created if its default
+    /// partition already holds events of the range (e.g. events stored before
+    /// the tables were partitioned), in which case the further events of the
+    /// range are stored in the default partition as well.
+    pub async fn ensure_partitions(&self, height: AbsoluteBlockHeight) -> DatabaseResult<()> {
+        let from = height.height / PARTITION_SIZE * PARTITION_SIZE;
+        let to = from + PARTITION_SIZE;
+        for table in [
+            EventTable::ItemCreatedEvents,
+            EventTable::ItemStatusChangedEvents,
+            EventTable::ItemSummaryEvents,
+            EventTable::TokenTransferEvents,
+            EventTable::NonceEvents,
+        ] {
+            let table = table.name();
+            let default_holds_range = self
+                .client
+                .prepare_cached(&format!(
+                    "SELECT EXISTS (SELECT 1 FROM {table}_default WHERE block_height >= $1 AND \
+                     block_height < $2)"
+                ))
+                .await?;
+            let params: [&(dyn ToSql + Sync); 2] = [&(from as i64), &(to as i64)];
+            let row = self.client.query_one(&default_holds_range, &params).await?;
+            if row.try_get::<_, bool>(0)? {
+                tracing::debug!(
+                    "Events of blocks {from} to {to} are kept in the default partition of {table}."
+                );
+                continue;
+            }
+            // Creating a partition of a table with a default partition scans the default
+            // partition and locks the table, so it is only done once per range.
+            self.client
+                .batch_execute(&format!(
+                    "CREATE TABLE IF NOT EXISTS {table}_{from}_{to} PARTITION OF {table} FOR \
+                     VALUES FROM ({from}) TO ({to})"
+                ))
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Whether the event tables are TimescaleDB hypertables. Their chunks are
+    /// created by TimescaleDB, so the partitions of the event tables must not
+    /// be created with [`Database::ensure_partitions`].
+    pub async fn uses_hypertables(&self) -> DatabaseResult<bool> {
+        // The information schema only exists if the extension is installed.
+        let timescale_installed: bool = self
+            .client
+            .query_one(
+                "SELECT to_regclass('timescaledb_information.hypertables') IS NOT NULL",
+                &[],
+            )
+            .await?
+            .try_get(0)?;
+        if !timescale_installed {
+            return Ok(false);
+        }
+        let is_hypertable = self
+            .client
+            .prepare_cached(
+                "SELECT EXISTS (SELECT 1 FROM timescaledb_information.hypertables WHERE \
+                 hypertable_schema = current_schema() AND hypertable_name = $1)",
+            )
+            .await?;
+        let params: [&(dyn ToSql + Sync); 1] = [&EventTable::ItemCreatedEvents.name()];
+        Ok(self
+            .client
+            .query_one(&is_hypertable, &params)
+            .await?
+            .try_get(0)?)
+    }
+
+    /// Convert the event tables into TimescaleDB hypertables partitioned into
+    /// chunks by `block_time`, unless they are hypertables already. The
+    /// `timescaledb` extension is created if it is not installed yet. All
+    /// tables are converted in one database transaction, which copies the
+    /// stored events and locks the event tables until it is committed. Returns
+    /// whether the tables were converted.
+    pub async fn create_hypertables(&mut self) -> DatabaseResult<bool> {
+        if self.uses_hypertables().await? {
+            return Ok(false);
+        }
+        let db_transaction = self.client.transaction().await?;
+        db_transaction.batch_execute(HYPERTABLES_SQL).await?;
+        db_transaction.commit().await?;
+        Ok(true)
+    }
+
+    /// Get the next nonce of the `account` for signing a sponsored transaction,
+    /// i.e. the nonce following the latest nonce stored in the `nonce_events`
+    /// table. Returns `0` if no nonce event of the account is stored.
+    pub async fn get_next_nonce(&self, account: &AccountAddress) -> DatabaseResult<u64> {
+        let latest_nonce = get_latest_nonce(&*self.client, account).await?;
+        Ok(latest_nonce.map_or(0, |nonce| nonce + 1))
+    }
+
+    /// Get the upgrades of the contract ordered by block height, starting with
+    /// the oldest.
+    pub async fn get_upgrades(&self) -> DatabaseResult<Vec<StoredUpgrade>> {
+        let get_upgrades = self
+            .client
+            .prepare_cached(
+                "SELECT block_height, block_time, transaction_hash, old_module_ref, \
+                 new_module_ref FROM upgrades ORDER BY block_height, id",
+            )
+            .await?;
+
+        let rows = self.client.query(&get_upgrades, &[]).await?;
+
+        rows.into_iter()
+            .map(StoredUpgrade::try_from)
+            .collect::<Result<Vec<_>, _>>()
+    }
+}
+
+/// The name of the postgres schema storing the tables of the
+/// `contract_address` in the multi-tenant mode.
+pub fn contract_schema(contract_address: &ContractAddress) -> String {
+    format!(
+        "contract_{}_{}",
+        contract_address.index, contract_address.subindex
+    )
 }
 
-/// Representation of a database pool
+/// Configure the `db_config` to store the tables of the `contract_address` in
+/// its own schema (multi-tenant mode). The schema is created if it does not
+/// exist and the mapping from the contract to the schema is recorded in the
+/// `contract_schemas` table of the `public` schema. All connections created
+/// from the returned configuration use the schema as their `search_path`, so
+/// the migrations and queries only apply to the tables of the contract.
+pub async fn use_contract_schema(
+    mut db_config: tokio_postgres::Config,
+    contract_address: &ContractAddress,
+) -> DatabaseResult<tokio_postgres::Config> {
+    let schema = contract_schema(contract_address);
+
+    let (client, connection) = db_config.connect(NoTls).await?;
+    let connection = tokio::spawn(connection);
+
+    client
+        .batch_execute(&format!(
+            "CREATE SCHEMA IF NOT EXISTS {schema};
+             CREATE TABLE IF NOT EXISTS public.contract_schemas (
+               contract_index INT8 NOT NULL,
+               contract_subindex INT8 NOT NULL,
+               schema_name TEXT NOT NULL UNIQUE,
+               created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+               PRIMARY KEY (contract_index, contract_subindex)
+             );"
+        ))
+        .await?;
+    let params: [&(dyn ToSql + Sync); 3] = [
+        &(contract_address.index as i64),
+        &(contract_address.subindex as i64),
+        &schema,
+    ];
+    client
+        .execute(
+            "INSERT INTO public.contract_schemas (contract_index, contract_subindex, schema_name) \
+             VALUES ($1, $2, $3) ON CONFLICT DO NOTHING",
+            &params,
+        )
+        .await?;
+
+    drop(client);
+    connection
+        .await
+        .context("The database connection task panicked")??;
+
+    let options = format!(
+        "{} -c search_path={schema}",
+        db_config.get_options().unwrap_or_default()
+    );
+    db_config.options(options.trim_start());
+    Ok(db_config)
+}
+
+/// Read the connection string of the database from the file at `path`, e.g.
+/// a secret mounted by the deployment that is updated when the password is
+/// rotated.
+pub fn read_db_connection_file(path: &std::path::Path) -> DatabaseResult<tokio_postgres::Config> {
+    let connection = std::fs::read_to_string(path).with_context(|| {
+        format!(
+            "Could not read the database connection file {}",
+            path.display()
+        )
+    })?;
+    Ok(connection.trim().parse()?)
+}
+
+/// Rebuild the connections of the `db_pool` with the connection string read
+/// from the file at `path` whenever the process receives a SIGHUP signal, so
+/// that the database credentials can be rotated without a restart. If a
+/// `contract_address` is given, the connections use the schema of the contract
+/// (see [`use_contract_schema`]). If the new configuration cannot be read or
+/// does not allow connecting, the error is logged and the previous connections
+/// are kept.
+/// Signals are only supported on unix, on other platforms the database
+/// connection is never reloaded.
+pub async fn reload_on_hangup(
+    db_pool: DatabasePool,
+    path: std::path::PathBuf,
+    contract_address: Option<ContractAddress>,
+) -> anyhow::Result<()> {
+    #[cfg(unix)]
+    let mut hangup_stream = {
+        use tokio::signal::unix as unix_signal;
+        unix_signal::signal(unix_signal::SignalKind::hangup())?
+    };
+    #[cfg(not(unix))]
+    let mut hangup_stream = {
+        tracing::warn!("Reloading the database connection is only supported on unix.");
+        tokio::sync::mpsc::channel::<()>(1).1
+    };
+
+    while hangup_stream.recv().await.is_some() {
+        tracing::info!(
+            "SIGHUP received. Reloading the database connection from {}.",
+            path.display()
+        );
+        let reload = async {
+            let db_config = read_db_connection_file(&path)?;
+            let db_config = match &contract_address {
+                Some(contract_address) => use_contract_schema(db_config, contract_address).await?,
+                None => db_config,
+            };
+            db_pool.reload(db_config).await
+        };
+        match reload.await {
+            Ok(()) => tracing::info!("Reloaded the database connection."),
+            Err(error) => tracing::error!(
+                "Could not reload the database connection, keeping the previous one: {error}"
+            ),
+        }
+    }
+    Ok(())
+}
+
+/// Representation of a database pool. Clones of the pool share the
+/// connections, which are replaced for all clones by
+/// [`DatabasePool::reload`].
 #[derive(Debug, Clone)]
 pub struct DatabasePool {
     /// The inner pool value.
-    pool: deadpool_postgres::Pool,
+    pool: Arc<RwLock<deadpool_postgres::Pool>>,
 }
 
 impl DatabasePool {
     /// Create a new [`DatabasePool`] from [`tokio_postgres::Config`] of size
-    /// `pool_size`. If `try_create_tables` is true, database tables are
-    /// created using `../resources/schema.sql`.
+    /// `pool_size`. If `run_migrations` is true, the pending migrations from
+    /// `../resources/migrations` are applied to the database and the
+    /// `schema_version` and `indexer_version` in the settings are updated.
+    ///
+    /// Fails with [`DatabaseError::IncompatibleSchema`] if the schema of the
+    /// database is newer than the [`supported_schema_version`], or if it is
+    /// older and `run_migrations` is false.
     pub async fn create(
         db_config: tokio_postgres::Config,
         pool_size: usize,
-        try_create_tables: bool,
+        run_migrations: bool,
     ) -> DatabaseResult<Self> {
-        let manager_config = deadpool_postgres::ManagerConfig {
-            recycling_method: deadpool_postgres::RecyclingMethod::Verified,
-        };
+        let pool = build_pool(db_config, pool_size)?;
+
+        let mut client = pool
+            .get()
+            .await
+            .context("Could not get database connection from pool")?;
+        let applied_version = applied_schema_version(&client).await?;
+        let supported_version = supported_schema_version();
+        match applied_version {
+            Some(applied_version) if applied_version > supported_version => {
+                return Err(DatabaseError::IncompatibleSchema(format!(
+                    "The database schema version {applied_version} is newer than the schema \
+                     version {supported_version} supported by indexer {}. The database was \
+                     migrated by a newer release (recorded in the `indexer_version` of the \
+                     `settings` table), so upgrade the `indexer` and `server` to that release.",
+                    env!("CARGO_PKG_VERSION")
+                )));
+            }
+            Some(applied_version) if applied_version == supported_version => (),
+            _ if !run_migrations => {
+                return Err(DatabaseError::IncompatibleSchema(format!(
+                    "The database schema version {} is older than the schema version \
+                     {supported_version} supported by indexer {}. Run `indexer migrate` to apply \
+                     the pending migrations.",
+                    applied_version.map_or("(none)".to_string(), |version| version.to_string()),
+                    env!("CARGO_PKG_VERSION")
+                )));
+            }
+            _ => (),
+        }
 
-        let manager = deadpool_postgres::Manager::from_config(db_config, NoTls, manager_config);
-        let pool = deadpool_postgres::Pool::builder(manager)
-            .create_timeout(Some(std::time::Duration::from_secs(5)))
-            .recycle_timeout(Some(std::time::Duration::from_secs(5)))
-            .wait_timeout(Some(std::time::Duration::from_secs(5)))
-            .max_size(pool_size)
-            .runtime(deadpool_postgres::Runtime::Tokio1)
-            .build()
-            .context("Failed to build database pool")?;
-
-        if try_create_tables {
-            let client = pool
-                .get()
+        if run_migrations {
+            let report = embedded::migrations::runner()
+                .run_async(&mut **client)
                 .await
-                .context("Could not get database connection from pool")?;
+                .context("Failed to run database migrations")?;
+            for migration in report.applied_migrations() {
+                tracing::info!("Applied database migration {}.", migration);
+            }
             client
-                .batch_execute(include_str!("../resources/schema.sql"))
+                .execute(
+                    "UPDATE settings SET schema_version = (SELECT MAX(version) FROM \
+                     refinery_schema_history), indexer_version = $1",
+                    &[&env!("CARGO_PKG_VERSION")],
+                )
                 .await
-                .context("Failed to execute create statements")?;
+                .context("Failed to update the schema version")?;
         }
-        Ok(Self { pool })
+        Ok(Self {
+            pool: Arc::new(RwLock::new(pool)),
+        })
     }
 
     /// Get a [`Database`] connection from the pool.
     pub async fn get(&self) -> DatabaseResult<Database> {
-        let client = self
-            .pool
+        let pool = self.current();
+        let client = pool
             .get()
             .await
             .context("Failed to get connection from pool")?;
         Ok(client.into())
     }
+
+    /// Replace the connections of the pool with connections created from the
+    /// `db_config`, e.g. after the database password was rotated. The pool
+    /// keeps its size and the migrations are not run again. The new
+    /// configuration is only used if a connection can be established with it.
+    /// Connections of the previous configuration that are in use are closed
+    /// when they are returned to the pool.
+    pub async fn reload(&self, db_config: tokio_postgres::Config) -> DatabaseResult<()> {
+        let current = self.current();
+        let pool = build_pool(db_config, current.status().max_size)?;
+        drop(
+            pool.get()
+                .await
+                .context("Could not connect with the new database configuration")?,
+        );
+
+        let previous = std::mem::replace(
+            &mut *self
+                .pool
+                .write()
+                .expect("The database pool lock is poisoned"),
+            pool,
+        );
+        previous.close();
+        Ok(())
+    }
+
+    /// The current inner pool.
+    fn current(&self) -> deadpool_postgres::Pool {
+        self.pool
+            .read()
+            .expect("The database pool lock is poisoned")
+            .clone()
+    }
+}
+
+/// Get the version of the latest migration applied to the database as recorded
+/// by `refinery`. Returns `None` if no migration has been applied yet.
+async fn applied_schema_version(client: &tokio_postgres::Client) -> DatabaseResult<Option<u64>> {
+    let row = client
+        .query_one(
+            "SELECT to_regclass('refinery_schema_history') IS NOT NULL AS initialized",
+            &[],
+        )
+        .await?;
+    if !row.try_get::<_, bool>("initialized")? {
+        return Ok(None);
+    }
+    let row = client
+        .query_one(
+            "SELECT MAX(version) AS version FROM refinery_schema_history",
+            &[],
+        )
+        .await?;
+    let version: Option<i32> = row.try_get("version")?;
+    Ok(version.map(|version| version as u64))
+}
+
+/// Build a pool of at most `pool_size` connections created from the
+/// `db_config`.
+fn build_pool(
+    db_config: tokio_postgres::Config,
+    pool_size: usize,
+) -> DatabaseResult<deadpool_postgres::Pool> {
+    let manager_config = deadpool_postgres::ManagerConfig {
+        recycling_method: deadpool_postgres::RecyclingMethod::Verified,
+    };
+
+    let manager = deadpool_postgres::Manager::from_config(db_config, NoTls, manager_config);
+    let pool = deadpool_postgres::Pool::builder(manager)
+        .create_timeout(Some(std::time::Duration::from_secs(5)))
+        .recycle_timeout(Some(std::time::Duration::from_secs(5)))
+        .wait_timeout(Some(std::time::Duration::from_secs(5)))
+        .max_size(pool_size)
+        .runtime(deadpool_postgres::Runtime::Tokio1)
+        .build()
+        .context("Failed to build database pool")?;
+    Ok(pool)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that the additional data stored in the contract serialization is
+    /// decoded, and that the payload bytes of a row indexed before the
+    /// additional data was structured are returned as `Raw` bytes.
+    #[test]
+    fn test_decode_additional_data() {
+        let location = AdditionalData::Location {
+            latitude:  55676098,
+            longitude: 12568337,
+        };
+        assert_eq!(
+            decode_additional_data(&to_bytes(&location), false),
+            location
+        );
+
+        // A legacy row with a sensor payload that does not decode at all.
+        let legacy = b"{\"temperature\":21.5}".to_vec();
+        assert_eq!(
+            decode_additional_data(&legacy, false),
+            AdditionalData::from_bytes(legacy)
+        );
+
+        // A legacy row whose payload starts like a `Temperature` variant but
+        // has trailing bytes.
+        let legacy = vec![2, 0, 0, 0, 0, 42];
+        assert_eq!(
+            decode_additional_data(&legacy, false),
+            AdditionalData::from_bytes(legacy)
+        );
+
+        // Legacy rows with an empty payload.
+        assert_eq!(decode_additional_data(&[], false), AdditionalData::empty());
+
+        // Redacted additional data is never decoded.
+        let redacted = vec![0; to_bytes(&location).len()];
+        assert_eq!(
+            decode_additional_data(&redacted, true),
+            AdditionalData::from_bytes(redacted)
+        );
+    }
 }
@@ -0,0 +1,318 @@
+//! Export of the indexed event tables for offline analytics. Each event table
+//! is streamed out of the database in pages and written to its own file in
+//! either CSV or Parquet format.
+use crate::db::{DatabasePool, EventTable};
+use anyhow::Context;
+use arrow::{
+    array::{ArrayRef, Int64Builder, StringBuilder, TimestampMicrosecondBuilder},
+    datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit},
+    record_batch::RecordBatch,
+};
+use chrono::{DateTime, Utc};
+use concordium_rust_sdk::{
+    cis2::MetadataUrl,
+    smart_contracts::common::{from_bytes, AccountAddress},
+    types::{hashes::TransactionHash, AbsoluteBlockHeight},
+};
+use parquet::arrow::ArrowWriter;
+use std::{fs::File, path::Path, sync::Arc};
+use tokio_postgres::types::Json;
+use track_and_trace::Status;
+
+/// The number of rows fetched from the database and written at once.
+const PAGE_SIZE: u32 = 10_000;
+
+/// The file formats the events can be exported to.
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// Comma-separated values with a header row.
+    Csv,
+    /// Apache Parquet.
+    Parquet,
+}
+
+impl ExportFormat {
+    /// The file extension of the format.
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Parquet => "parquet",
+        }
+    }
+}
+
+/// A writer of record batches in one of the [`ExportFormat`]s.
+enum EventWriter {
+    Csv(arrow::csv::Writer<File>),
+    Parquet(ArrowWriter<File>),
+}
+
+impl EventWriter {
+    /// Create a new writer for the file at `path`.
+    fn create(format: ExportFormat, path: &Path, schema: SchemaRef) -> anyhow::Result<Self> {
+        let file =
+            File::create(path).with_context(|| format!("Could not create {}", path.display()))?;
+        let writer = match format {
+            ExportFormat::Csv => EventWriter::Csv(arrow::csv::Writer::new(file)),
+            ExportFormat::Parquet => {
+                EventWriter::Parquet(ArrowWriter::try_new(file, schema, None)?)
+            }
+        };
+        Ok(writer)
+    }
+
+    /// Write a batch of events.
+    fn write(&mut self, batch: &RecordBatch) -> anyhow::Result<()> {
+        match self {
+            EventWriter::Csv(writer) => writer.write(batch)?,
+            EventWriter::Parquet(writer) => writer.write(batch)?,
+        }
+        Ok(())
+    }
+
+    /// Flush the remaining data and finish the file.
+    fn finish(self) -> anyhow::Result<()> {
+        if let EventWriter::Parquet(writer) = self {
+            writer.close()?;
+        }
+        Ok(())
+    }
+}
+
+/// Export the event tables into the `output` directory. Only events in
+/// blocks with a height between `from_height` and `to_height` (both
+/// inclusive) are exported if the respective bound is given. Events indexed
+/// before the `block_height` column was introduced have no block height and
+/// are only exported if no bound is given.
+pub async fn export_events(
+    db_pool: &DatabasePool,
+    format: ExportFormat,
+    output: &Path,
+    from_height: Option<AbsoluteBlockHeight>,
+    to_height: Option<AbsoluteBlockHeight>,
+) -> anyhow::Result<()> {
+    std::fs::create_dir_all(output)
+        .with_context(|| format!("Could not create directory {}", output.display()))?;
+
+    let db = db_pool
+        .get()
+        .await
+        .context("Could not get database connection from pool")?;
+
+    for table in [
+        EventTable::ItemCreatedEvents,
+        EventTable::ItemStatusChangedEvents,
+        EventTable::ItemSummaryEvents,
+        EventTable::TokenTransferEvents,
+        EventTable::NonceEvents,
+    ] {
+        let schema = schema(table);
+        let path = output.join(format!("{}.{}", table.name(), format.extension()));
+        let mut writer = EventWriter::create(format, &path, schema.clone())?;
+
+        let mut exported = 0u64;
+        let mut after_id = None;
+        loop {
+            let rows = db
+                .get_events_page(table, after_id, from_height, to_height, PAGE_SIZE)
+                .await
+                .with_context(|| format!("Could not read events from {}", table.name()))?;
+            let Some(last_row) = rows.last() else {
+                break;
+            };
+            after_id = Some(last_row.try_get("id")?);
+
+            writer.write(&record_batch(table, schema.clone(), &rows)?)?;
+            exported += rows.len() as u64;
+        }
+        writer.finish()?;
+
+        tracing::info!("Exported {} events to {}.", exported, path.display());
+    }
+
+    Ok(())
+}
+
+/// The schema of the exported file for the event `table`.
+fn schema(table: EventTable) -> SchemaRef {
+    let mut fields = vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("block_height", DataType::Int64, true),
+        Field::new(
+            "block_time",
+            DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            false,
+        ),
+        Field::new("transaction_hash", DataType::Utf8, false),
+        Field::new("event_index", DataType::Int64, false),
+        Field::new("sender", DataType::Utf8, true),
+        // Tokens not minted for an item and nonces have no item id.
+        Field::new(
+            "item_id",
+            DataType::Int64,
+            matches!(
+                table,
+                EventTable::TokenTransferEvents | EventTable::NonceEvents
+            ),
+        ),
+    ];
+    match table {
+        EventTable::ItemCreatedEvents => {
+            fields.push(Field::new("metadata_url", DataType::Utf8, true));
+            fields.push(Field::new("initial_status", DataType::Utf8, false));
+        }
+        EventTable::ItemStatusChangedEvents => {
+            fields.push(Field::new("new_status", DataType::Utf8, false));
+            fields.push(Field::new("additional_data", DataType::Utf8, false));
+            fields.push(Field::new("additional_data_hash", DataType::Utf8, true));
+        }
+        EventTable::ItemSummaryEvents => {
+            fields.push(Field::new("status", DataType::Utf8, false));
+            fields.push(Field::new("metadata_hash", DataType::Utf8, true));
+        }
+        EventTable::TokenTransferEvents => {
+            fields.push(Field::new("token_id", DataType::Utf8, false));
+            fields.push(Field::new("amount", DataType::Utf8, false));
+            fields.push(Field::new("transfer", DataType::Utf8, false));
+        }
+        EventTable::NonceEvents => {
+            fields.push(Field::new("account", DataType::Utf8, false));
+            fields.push(Field::new("nonce", DataType::Utf8, false));
+        }
+    }
+    Arc::new(Schema::new(fields))
+}
+
+/// Convert the database `rows` of the event `table` into a record batch with
+/// the given `schema`.
+fn record_batch(
+    table: EventTable,
+    schema: SchemaRef,
+    rows: &[tokio_postgres::Row],
+) -> anyhow::Result<RecordBatch> {
+    let mut id = Int64Builder::new();
+    let mut block_height = Int64Builder::new();
+    let mut block_time = TimestampMicrosecondBuilder::new();
+    let mut transaction_hash = StringBuilder::new();
+    let mut event_index = Int64Builder::new();
+    let mut sender = StringBuilder::new();
+    let mut item_id = Int64Builder::new();
+    // The table specific columns. Only the status changed and token transfer
+    // events have a third column.
+    let mut first = StringBuilder::new();
+    let mut second = StringBuilder::new();
+    let mut third = StringBuilder::new();
+
+    for row in rows {
+        id.append_value(row.try_get("id")?);
+        block_height.append_option(row.try_get::<_, Option<i64>>("block_height")?);
+        block_time.append_value(
+            row.try_get::<_, DateTime<Utc>>("block_time")?
+                .timestamp_micros(),
+        );
+        let raw_transaction_hash: &[u8] = row.try_get("transaction_hash")?;
+        let hash: TransactionHash = raw_transaction_hash
+            .try_into()
+            .context("Could not convert transaction_hash")?;
+        transaction_hash.append_value(hash.to_string());
+        event_index.append_value(row.try_get("event_index")?);
+        let raw_sender: Option<&[u8]> = row.try_get("sender")?;
+        sender.append_option(
+            raw_sender
+                .map(|raw_sender| <[u8; 32]>::try_from(raw_sender).map(AccountAddress))
+                .transpose()
+                .context("Could not convert sender")?
+                .map(|sender| sender.to_string()),
+        );
+        // The `nonce_events` table has no `item_id` column.
+        if matches!(table, EventTable::NonceEvents) {
+            item_id.append_null();
+        } else {
+            item_id.append_option(row.try_get::<_, Option<i64>>("item_id")?);
+        }
+
+        match table {
+            EventTable::ItemCreatedEvents => {
+                let metadata_url: Option<MetadataUrl> = from_bytes(row.try_get("metadata_url")?)
+                    .context("Could not convert metadata_url")?;
+                first.append_option(
+                    metadata_url
+                        .map(|metadata_url| serde_json::to_string(&metadata_url))
+                        .transpose()?,
+                );
+                let Json(initial_status): Json<Status> = row.try_get("initial_status")?;
+                second.append_value(format!("{initial_status:?}"));
+            }
+            EventTable::ItemStatusChangedEvents => {
+                let Json(new_status): Json<Status> = row.try_get("new_status")?;
+                first.append_value(format!("{new_status:?}"));
+                let additional_data: &[u8] = row.try_get("additional_data")?;
+                second.append_value(
+                    additional_data
+                        .iter()
+                        .map(|byte| format!("{byte:02x}"))
+                        .collect::<String>(),
+                );
+                let additional_data_hash: Option<&[u8]> = row.try_get("additional_data_hash")?;
+                third.append_option(additional_data_hash.map(|additional_data_hash| {
+                    additional_data_hash
+                        .iter()
+                        .map(|byte| format!("{byte:02x}"))
+                        .collect::<String>()
+                }));
+            }
+            EventTable::ItemSummaryEvents => {
+                let Json(status): Json<Status> = row.try_get("status")?;
+                first.append_value(format!("{status:?}"));
+                let metadata_hash: Option<&[u8]> = row.try_get("metadata_hash")?;
+                second.append_option(metadata_hash.map(|metadata_hash| {
+                    metadata_hash
+                        .iter()
+                        .map(|byte| format!("{byte:02x}"))
+                        .collect::<String>()
+                }));
+            }
+            EventTable::TokenTransferEvents => {
+                let token_id: &[u8] = row.try_get("token_id")?;
+                first.append_value(
+                    token_id
+                        .iter()
+                        .map(|byte| format!("{byte:02x}"))
+                        .collect::<String>(),
+                );
+                second.append_value(row.try_get::<_, String>("amount")?);
+                let Json(from): Json<serde_json::Value> = row.try_get("from_address")?;
+                let Json(to): Json<serde_json::Value> = row.try_get("to_address")?;
+                third.append_value(serde_json::json!({ "from": from, "to": to }).to_string());
+            }
+            EventTable::NonceEvents => {
+                let raw_account: &[u8] = row.try_get("account")?;
+                let account = <[u8; 32]>::try_from(raw_account)
+                    .map(AccountAddress)
+                    .context("Could not convert account")?;
+                first.append_value(account.to_string());
+                second.append_value(row.try_get::<_, i64>("nonce")?.to_string());
+            }
+        }
+    }
+
+    let mut columns: Vec<ArrayRef> = vec![
+        Arc::new(id.finish()),
+        Arc::new(block_height.finish()),
+        Arc::new(block_time.finish().with_timezone("UTC")),
+        Arc::new(transaction_hash.finish()),
+        Arc::new(event_index.finish()),
+        Arc::new(sender.finish()),
+        Arc::new(item_id.finish()),
+        Arc::new(first.finish()),
+        Arc::new(second.finish()),
+    ];
+    if matches!(
+        table,
+        EventTable::ItemStatusChangedEvents | EventTable::TokenTransferEvents
+    ) {
+        columns.push(Arc::new(third.finish()));
+    }
+
+    Ok(RecordBatch::try_new(schema, columns)?)
+}
@@ -0,0 +1,186 @@
+//! Handlers storing the events parsed by the `indexer` in the database. Every
+//! kind of event is routed to its own [`EventHandler`], which stores the event
+//! within the database transaction of the batch and decides whether the event
+//! is published. The batching, retries, duplicate detection, and publishing are
+//! shared by all kinds of events, so indexing a new event type only requires
+//! an [`IndexableEvent`] implementation and a handler, usually a
+//! [`StoreEvent`].
+use crate::{
+    db::{self, insert_event, insert_upgrade, EventLocation, IndexableEvent, InsertOutcome},
+    published::PublishedEvent,
+};
+use anyhow::Context;
+use concordium_rust_sdk::{
+    indexer::async_trait,
+    types::{smart_contracts::ModuleReference, ContractAddress},
+};
+use deadpool_postgres::{GenericClient, Transaction};
+use track_and_trace as contract;
+
+/// An upgrade of a contract from the old to the new module.
+#[derive(Debug, Clone, Copy)]
+pub struct ContractUpgrade {
+    /// The module of the contract before the upgrade.
+    pub old_module_ref: ModuleReference,
+    /// The module of the contract after the upgrade.
+    pub new_module_ref: ModuleReference,
+}
+
+/// The result of handling an event with an [`EventHandler`].
+#[derive(Debug)]
+pub struct HandledEvent {
+    /// Whether the event was skipped because it is already stored.
+    pub duplicate: bool,
+    /// The event published to the sink and sent with `NOTIFY` once the event
+    /// is stored. Events that are already stored are not published again.
+    pub published: Option<PublishedEvent>,
+}
+
+/// A handler storing the events of type `E` in the database.
+#[async_trait]
+pub trait EventHandler<E: Sync>: Send + Sync {
+    /// Store the `event` logged at the `location` within the database
+    /// `transaction` of the batch the event was included in.
+    async fn handle(
+        &self,
+        transaction: &Transaction<'_>,
+        location: &EventLocation,
+        event: &E,
+    ) -> anyhow::Result<HandledEvent>;
+}
+
+/// A handler inserting the events into their event table and optionally
+/// publishing them.
+pub struct StoreEvent<E> {
+    /// The conversion of the stored events into the published events. If not
+    /// set, the events are stored but not published.
+    publish: Option<fn(&EventLocation, &E) -> PublishedEvent>,
+}
+
+impl<E> StoreEvent<E> {
+    /// A handler storing the events without publishing them.
+    pub fn unpublished() -> Self { Self { publish: None } }
+
+    /// A handler storing the events and publishing them converted with
+    /// `publish`.
+    pub fn published(publish: fn(&EventLocation, &E) -> PublishedEvent) -> Self {
+        Self {
+            publish: Some(publish),
+        }
+    }
+}
+
+#[async_trait]
+impl<E: IndexableEvent> EventHandler<E> for StoreEvent<E> {
+    async fn handle(
+        &self,
+        transaction: &Transaction<'_>,
+        location: &EventLocation,
+        event: &E,
+    ) -> anyhow::Result<HandledEvent> {
+        let outcome = insert_event(transaction, location, event)
+            .await
+            .context("Failed to insert event")?;
+        Ok(HandledEvent {
+            duplicate: outcome == InsertOutcome::Duplicate,
+            published: self.publish.map(|publish| publish(location, event)),
+        })
+    }
+}
+
+/// A handler storing the `Nonce` events of the contract. Before an event is
+/// stored, its nonce is checked against the latest nonce of the account stored
+/// in the database. The nonces are not published.
+pub struct StoreNonce;
+
+#[async_trait]
+impl EventHandler<contract::NonceEvent> for StoreNonce {
+    async fn handle(
+        &self,
+        transaction: &Transaction<'_>,
+        location: &EventLocation,
+        event: &contract::NonceEvent,
+    ) -> anyhow::Result<HandledEvent> {
+        check_nonce(transaction, location, event).await?;
+        StoreEvent::unpublished()
+            .handle(transaction, location, event)
+            .await
+    }
+}
+
+/// Check that the nonce of the `event` follows the latest nonce of the
+/// account stored in the database using the `client` and log a warning
+/// otherwise. The contract increases the nonce of an account by one with every
+/// permit, so a gap means that the nonce events of the account in between were
+/// missed, e.g. because blocks were skipped with `--follow`.
+async fn check_nonce<C: GenericClient>(
+    client: &C,
+    location: &EventLocation,
+    event: &contract::NonceEvent,
+) -> anyhow::Result<()> {
+    let latest_nonce = db::get_latest_nonce(client, &event.account)
+        .await
+        .context("Failed to get the latest nonce")?;
+    if let Some(latest_nonce) = latest_nonce {
+        if event.nonce > latest_nonce + 1 {
+            tracing::warn!(
+                block_height = %location.block_height,
+                transaction_hash = %location.transaction_hash,
+                event_index = location.event_index,
+                "Missed the nonce events of account {} from nonce {} to nonce {}. The nonce {} \
+                 is logged in block {} after the latest stored nonce {}.",
+                event.account,
+                latest_nonce + 1,
+                event.nonce - 1,
+                event.nonce,
+                location.block_height,
+                latest_nonce
+            );
+        }
+    }
+    Ok(())
+}
+
+/// A handler recording the upgrades of the `contract` in the `upgrades` table
+/// and warning when the module of the contract changes. Upgrades are not
+/// published and upgrades that are already recorded are not reported as
+/// duplicate events.
+pub struct RecordUpgrade {
+    /// The contract whose upgrades are recorded.
+    pub contract: ContractAddress,
+}
+
+#[async_trait]
+impl EventHandler<ContractUpgrade> for RecordUpgrade {
+    async fn handle(
+        &self,
+        transaction: &Transaction<'_>,
+        location: &EventLocation,
+        upgrade: &ContractUpgrade,
+    ) -> anyhow::Result<HandledEvent> {
+        let outcome = insert_upgrade(
+            transaction,
+            location,
+            &upgrade.old_module_ref,
+            &upgrade.new_module_ref,
+        )
+        .await
+        .context("Failed to insert upgrade")?;
+        if outcome == InsertOutcome::Inserted {
+            tracing::warn!(
+                block_height = %location.block_height,
+                transaction_hash = %location.transaction_hash,
+                "Contract {} was upgraded from module {} to module {} in block {}. The events of \
+                 the new module may no longer match the event schema of the indexer.",
+                self.contract,
+                upgrade.old_module_ref,
+                upgrade.new_module_ref,
+                location.block_height
+            );
+        }
+        Ok(HandledEvent {
+            duplicate: false,
+            published: None,
+        })
+    }
+}
@@ -0,0 +1,482 @@
+//! A small HTTP server reporting the health of the `indexer`. The `/health`
+//! endpoint reports the last processed block height, the time since the last
+//! block was processed, and the database connectivity. It returns `503 Service
+//! Unavailable` if the database is not reachable or the indexer has fallen
+//! behind the chain by more than a configurable threshold, which makes it
+//! suitable for Kubernetes liveness/readiness probes. The `/metrics` endpoint
+//! exposes the counters of the indexer in the Prometheus text format. If
+//! enabled, the `/pause` and `/resume` endpoints pause and resume the
+//! processing of fetched blocks, and the `/dashboard` endpoint serves a
+//! read-only HTML status page. The `/info` endpoint returns the build metadata
+//! of the indexer.
+use crate::{
+    db::{DatabasePool, DatabaseResult, StoredRecentItem},
+    info::Info,
+};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::Html,
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use concordium_rust_sdk::types::AbsoluteBlockHeight;
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+use tokio::sync::Notify;
+
+/// The number of recently created items listed on the dashboard.
+const DASHBOARD_RECENT_ITEMS: u32 = 20;
+
+/// The last block processed by the indexer.
+#[derive(Debug, Clone, Copy)]
+struct ProcessedBlock {
+    /// The height of the block.
+    height:       AbsoluteBlockHeight,
+    /// The slot time of the block.
+    block_time:   DateTime<Utc>,
+    /// The time the block was processed by the indexer.
+    processed_at: Instant,
+}
+
+/// The progress of catching up with the last finalized block of the chain.
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+pub struct CatchUpProgress {
+    /// The last finalized block height reported by the node.
+    pub last_finalized_block_height: AbsoluteBlockHeight,
+    /// The number of finalized blocks not processed yet.
+    pub remaining_blocks:            u64,
+    /// The percentage of blocks processed since the indexer started from its
+    /// start block height.
+    pub percent_complete:            f64,
+    /// The number of blocks processed per second since the previous report.
+    pub blocks_per_second:           Option<f64>,
+    /// The estimated number of seconds until the indexer has caught up.
+    pub eta_seconds:                 Option<u64>,
+}
+
+/// The progress of the indexer shared between the indexing loop, the progress
+/// reporting, and the health server.
+#[derive(Debug, Clone, Default)]
+pub struct IndexerProgress {
+    /// The last block processed or `None` if no block has been processed
+    /// since the indexer was started.
+    last_processed:   Arc<Mutex<Option<ProcessedBlock>>>,
+    /// The latest catch-up progress reported.
+    catch_up:         Arc<Mutex<Option<CatchUpProgress>>>,
+    /// The number of events skipped because they were already stored.
+    duplicate_events: Arc<AtomicU64>,
+    /// The number of fetched blocks waiting to be processed.
+    queue_depth:      Arc<AtomicU64>,
+    /// Whether the processing of fetched blocks is paused.
+    paused:           Arc<AtomicBool>,
+    /// Notifies the indexing loop when the processing is resumed.
+    resumed:          Arc<Notify>,
+}
+
+impl IndexerProgress {
+    /// Record that the block at `height` with slot time `block_time` has been
+    /// processed.
+    pub fn block_processed(&self, height: AbsoluteBlockHeight, block_time: DateTime<Utc>) {
+        let mut last_processed = self
+            .last_processed
+            .lock()
+            .expect("The lock is never poisoned");
+        *last_processed = Some(ProcessedBlock {
+            height,
+            block_time,
+            processed_at: Instant::now(),
+        });
+    }
+
+    /// Get the last block processed.
+    fn last_processed(&self) -> Option<ProcessedBlock> {
+        *self
+            .last_processed
+            .lock()
+            .expect("The lock is never poisoned")
+    }
+
+    /// Get the height of the last block processed.
+    pub fn last_processed_height(&self) -> Option<AbsoluteBlockHeight> {
+        self.last_processed().map(|block| block.height)
+    }
+
+    /// Record the latest catch-up progress.
+    pub fn set_catch_up(&self, catch_up: CatchUpProgress) {
+        *self.catch_up.lock().expect("The lock is never poisoned") = Some(catch_up);
+    }
+
+    /// Get the latest catch-up progress.
+    fn catch_up(&self) -> Option<CatchUpProgress> {
+        *self.catch_up.lock().expect("The lock is never poisoned")
+    }
+
+    /// Record that `count` events were skipped because they were already
+    /// stored.
+    pub fn duplicate_events_skipped(&self, count: u64) {
+        self.duplicate_events.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Get the number of events skipped because they were already stored since
+    /// the indexer was started.
+    pub fn duplicate_events(&self) -> u64 { self.duplicate_events.load(Ordering::Relaxed) }
+
+    /// Record the number of fetched blocks waiting to be processed.
+    pub fn set_queue_depth(&self, depth: u64) { self.queue_depth.store(depth, Ordering::Relaxed) }
+
+    /// Get the number of fetched blocks that were waiting to be processed when
+    /// the last batch was processed.
+    pub fn queue_depth(&self) -> u64 { self.queue_depth.load(Ordering::Relaxed) }
+
+    /// Pause the processing of fetched blocks. Returns whether the processing
+    /// was running before.
+    pub fn pause(&self) -> bool { !self.paused.swap(true, Ordering::SeqCst) }
+
+    /// Resume the processing of fetched blocks. Returns whether the processing
+    /// was paused before.
+    pub fn resume(&self) -> bool {
+        let was_paused = self.paused.swap(false, Ordering::SeqCst);
+        self.resumed.notify_waiters();
+        was_paused
+    }
+
+    /// Whether the processing of fetched blocks is paused.
+    pub fn is_paused(&self) -> bool { self.paused.load(Ordering::SeqCst) }
+
+    /// Wait until the processing of fetched blocks is resumed. Returns right
+    /// away if it is not paused.
+    pub async fn wait_until_resumed(&self) {
+        loop {
+            // The future is created before checking the flag, so that a resume in
+            // between is not missed.
+            let resumed = self.resumed.notified();
+            if !self.is_paused() {
+                return;
+            }
+            resumed.await;
+        }
+    }
+}
+
+/// The state of the health server.
+#[derive(Debug, Clone)]
+struct HealthServer {
+    /// The progress of the indexer.
+    progress:   IndexerProgress,
+    /// A database pool used to check the database connectivity.
+    db_pool:    DatabasePool,
+    /// The maximum time the last processed block may lag behind the current
+    /// time before the indexer is reported as unhealthy.
+    max_behind: Duration,
+    /// The build metadata returned by the `info` endpoint.
+    info:       Info,
+}
+
+/// Struct returned by the `pause` and `resume` endpoints.
+#[derive(serde::Serialize)]
+struct PauseState {
+    /// Whether the processing of fetched blocks is paused.
+    paused:  bool,
+    /// Whether the request changed the state, i.e. `false` if the processing
+    /// was already paused (resumed).
+    changed: bool,
+}
+
+/// Struct returned by the `health` endpoint.
+#[derive(serde::Serialize)]
+struct Health {
+    /// The version of the indexer.
+    version: &'static str,
+    /// Whether the indexer is healthy.
+    healthy: bool,
+    /// Whether the database is reachable.
+    database_connected: bool,
+    /// The last block height processed since the indexer was started.
+    last_processed_block_height: Option<AbsoluteBlockHeight>,
+    /// The slot time of the last processed block.
+    last_processed_block_time: Option<DateTime<Utc>>,
+    /// The number of seconds since the last block was processed.
+    seconds_since_last_processed_block: Option<u64>,
+    /// The number of seconds the slot time of the last processed block lags
+    /// behind the current time.
+    seconds_behind: Option<i64>,
+    /// The latest progress of catching up with the last finalized block.
+    catch_up: Option<CatchUpProgress>,
+    /// The number of events skipped since the indexer was started because
+    /// they were already stored.
+    duplicate_events: u64,
+    /// The number of fetched blocks waiting to be processed. A queue that
+    /// stays full means that storing the blocks is the bottleneck.
+    block_queue_depth: u64,
+    /// Whether the processing of fetched blocks is paused.
+    paused: bool,
+}
+
+/// Run the health server on `listen_address` until the process exits. The
+/// `/pause` and `/resume` endpoints are only served with `pause_control` and
+/// the `/dashboard` endpoint is only served with `dashboard`.
+pub async fn serve(
+    listen_address: SocketAddr,
+    progress: IndexerProgress,
+    db_pool: DatabasePool,
+    max_behind: Duration,
+    pause_control: bool,
+    dashboard: bool,
+    info: Info,
+) -> anyhow::Result<()> {
+    let state = HealthServer {
+        progress,
+        db_pool,
+        max_behind,
+        info,
+    };
+
+    let mut router = Router::new()
+        .route("/health", get(health))
+        .route("/metrics", get(metrics))
+        .route("/info", get(info));
+    if pause_control {
+        router = router
+            .route("/pause", post(pause))
+            .route("/resume", post(resume));
+    }
+    if dashboard {
+        router = router.route("/dashboard", get(status_dashboard));
+    }
+    let router = router.with_state(state);
+
+    tracing::info!("Health endpoint listening at {}", listen_address);
+
+    axum::Server::bind(&listen_address)
+        .serve(router.into_make_service())
+        .await?;
+
+    Ok(())
+}
+
+/// Handles the `health` endpoint, returning `503 Service Unavailable` if the
+/// database is not reachable, no block has been processed yet, or the last
+/// processed block lags behind the current time by more than `max_behind`.
+/// While the processing is paused, the indexer is reported as healthy, so that
+/// it is not re-started during database maintenance.
+async fn health(State(state): State<HealthServer>) -> (StatusCode, Json<Health>) {
+    let database_connected = match state.db_pool.get().await {
+        Ok(db) => db.client.simple_query("SELECT 1").await.is_ok(),
+        Err(_) => false,
+    };
+
+    let last_processed = state.progress.last_processed();
+    let seconds_behind = last_processed.map(|block| (Utc::now() - block.block_time).num_seconds());
+
+    let paused = state.progress.is_paused();
+    let healthy = paused
+        || (database_connected
+            && seconds_behind.map_or(false, |seconds_behind| {
+                seconds_behind <= state.max_behind.as_secs() as i64
+            }));
+
+    let status = if healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (
+        status,
+        Json(Health {
+            version: env!("CARGO_PKG_VERSION"),
+            healthy,
+            database_connected,
+            last_processed_block_height: last_processed.map(|block| block.height),
+            last_processed_block_time: last_processed.map(|block| block.block_time),
+            seconds_since_last_processed_block: last_processed
+                .map(|block| block.processed_at.elapsed().as_secs()),
+            seconds_behind,
+            catch_up: state.progress.catch_up(),
+            duplicate_events: state.progress.duplicate_events(),
+            block_queue_depth: state.progress.queue_depth(),
+            paused,
+        }),
+    )
+}
+
+/// Handles the `info` endpoint, returning the build metadata of the indexer.
+async fn info(State(state): State<HealthServer>) -> Json<Info> { Json(state.info) }
+
+/// Handles the `pause` endpoint, pausing the processing of fetched blocks.
+async fn pause(State(state): State<HealthServer>) -> Json<PauseState> {
+    let changed = state.progress.pause();
+    if changed {
+        tracing::info!("Indexing paused.");
+    }
+    Json(PauseState {
+        paused: true,
+        changed,
+    })
+}
+
+/// Handles the `resume` endpoint, resuming the processing of fetched blocks.
+async fn resume(State(state): State<HealthServer>) -> Json<PauseState> {
+    let changed = state.progress.resume();
+    if changed {
+        tracing::info!("Indexing resumed.");
+    }
+    Json(PauseState {
+        paused: false,
+        changed,
+    })
+}
+
+/// Handles the `metrics` endpoint, returning the counters of the indexer in the
+/// Prometheus text format.
+async fn metrics(State(state): State<HealthServer>) -> String {
+    let last_processed_block_height = state
+        .progress
+        .last_processed_height()
+        .map_or(0, |height| height.height);
+    [
+        (
+            "indexer_last_processed_block_height",
+            "gauge",
+            "The height of the last processed block.",
+            last_processed_block_height,
+        ),
+        (
+            "indexer_duplicate_events_total",
+            "counter",
+            "The number of events skipped because they were already stored.",
+            state.progress.duplicate_events(),
+        ),
+        (
+            "indexer_block_queue_depth",
+            "gauge",
+            "The number of fetched blocks waiting to be processed.",
+            state.progress.queue_depth(),
+        ),
+        (
+            "indexer_paused",
+            "gauge",
+            "Whether the processing of fetched blocks is paused.",
+            state.progress.is_paused().into(),
+        ),
+    ]
+    .map(|(name, kind, help, value)| {
+        format!("# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}\n")
+    })
+    .concat()
+}
+
+/// The data shown on the dashboard that is read from the database.
+struct DashboardData {
+    /// The last block height recorded as processed in the database.
+    checkpoint:                 Option<AbsoluteBlockHeight>,
+    /// The number of `ItemCreated` events indexed in the last hour.
+    item_created_events:        u64,
+    /// The number of `ItemStatusChanged` events indexed in the last hour.
+    item_status_changed_events: u64,
+    /// The most recently created items.
+    recent_items:               Vec<StoredRecentItem>,
+}
+
+/// Read the data shown on the dashboard from the database.
+async fn dashboard_data(db_pool: &DatabasePool) -> DatabaseResult<DashboardData> {
+    let db = db_pool.get().await?;
+    let settings = db.get_settings().await?;
+    let counts = db
+        .get_event_counts_since(Utc::now() - chrono::Duration::hours(1))
+        .await?;
+    let recent_items = db.get_recent_items(DASHBOARD_RECENT_ITEMS).await?;
+    Ok(DashboardData {
+        checkpoint: settings.latest_processed_block_height,
+        item_created_events: counts.item_created_events,
+        item_status_changed_events: counts.item_status_changed_events,
+        recent_items,
+    })
+}
+
+/// Handles the `dashboard` endpoint, returning a read-only HTML page with the
+/// checkpoint of the indexer, the number of events indexed in the last hour,
+/// and the most recently created items. The page reloads itself every 30
+/// seconds. Returns `503 Service Unavailable` if the database is not
+/// reachable.
+async fn status_dashboard(State(state): State<HealthServer>) -> (StatusCode, Html<String>) {
+    let data = match dashboard_data(&state.db_pool).await {
+        Ok(data) => data,
+        Err(error) => {
+            tracing::warn!("Unable to read the dashboard data: {error}");
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Html(dashboard_page(
+                    "<p>The database is not reachable.</p>".to_string(),
+                )),
+            );
+        }
+    };
+
+    let last_processed = state.progress.last_processed();
+    let summary = [
+        (
+            "Checkpoint",
+            data.checkpoint
+                .map_or("none".to_string(), |height| height.to_string()),
+        ),
+        (
+            "Last processed block",
+            last_processed.map_or("none since start".to_string(), |block| {
+                format!("{} ({})", block.height, block.block_time.to_rfc3339())
+            }),
+        ),
+        ("Paused", state.progress.is_paused().to_string()),
+        (
+            "ItemCreated events (last hour)",
+            data.item_created_events.to_string(),
+        ),
+        (
+            "ItemStatusChanged events (last hour)",
+            data.item_status_changed_events.to_string(),
+        ),
+    ]
+    .map(|(name, value)| format!("<tr><th>{name}</th><td>{value}</td></tr>"))
+    .concat();
+
+    let recent_items = data
+        .recent_items
+        .iter()
+        .map(|item| {
+            format!(
+                "<tr><td>{}</td><td>{:?}</td><td>{}</td><td>{}</td></tr>",
+                item.item_id,
+                item.status,
+                item.created_at.to_rfc3339(),
+                item.updated_at.to_rfc3339()
+            )
+        })
+        .collect::<String>();
+
+    let body = format!(
+        "<table>{summary}</table><h2>Recent \
+         items</h2><table><tr><th>Item</th><th>Status</th><th>Created</th><th>Updated</th></\
+         tr>{recent_items}</table>"
+    );
+    (StatusCode::OK, Html(dashboard_page(body)))
+}
+
+/// Wrap the `body` of the dashboard in an HTML page that reloads itself every
+/// 30 seconds.
+fn dashboard_page(body: String) -> String {
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><meta http-equiv=\"refresh\" \
+         content=\"30\"><title>Track and trace indexer</title><style>body {{ font-family: \
+         sans-serif; }} th, td {{ text-align: left; padding: 2px 12px; \
+         }}</style></head><body><h1>Track and trace indexer {}</h1>{body}</body></html>",
+        env!("CARGO_PKG_VERSION")
+    )
+}
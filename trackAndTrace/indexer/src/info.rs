@@ -0,0 +1,39 @@
+//! The build metadata returned by the `/info` endpoints of the `indexer` and
+//! the `server`, so that operators can audit exactly which build is running
+//! and which contracts it tracks. The git commit, build time, and enabled
+//! features are emitted at build time by `vergen` (see `build.rs`).
+use concordium_rust_sdk::types::ContractAddress;
+
+/// Struct returned by the `info` endpoint.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Info {
+    /// The name of the service, e.g. `indexer`.
+    pub service:    &'static str,
+    /// The version of the crate.
+    pub version:    &'static str,
+    /// The git commit the service was built from.
+    pub git_commit: &'static str,
+    /// The time the service was built (RFC 3339).
+    pub build_time: &'static str,
+    /// The cargo features the service was built with.
+    pub features:   Vec<&'static str>,
+    /// The contracts tracked by the service.
+    pub contracts:  Vec<ContractAddress>,
+}
+
+impl Info {
+    /// The build metadata of the `service` tracking the `contracts`.
+    pub fn new(service: &'static str, contracts: Vec<ContractAddress>) -> Self {
+        Self {
+            service,
+            version: env!("CARGO_PKG_VERSION"),
+            git_commit: env!("VERGEN_GIT_SHA"),
+            build_time: env!("VERGEN_BUILD_TIMESTAMP"),
+            features: env!("VERGEN_CARGO_FEATURES")
+                .split(',')
+                .filter(|feature| !feature.is_empty())
+                .collect(),
+            contracts,
+        }
+    }
+}
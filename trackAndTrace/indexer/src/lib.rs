@@ -1,2 +1,12 @@
 pub mod db;
+pub mod export;
+pub mod handler;
+pub mod health;
+pub mod info;
+pub mod logging;
+pub mod published;
+#[cfg(any(feature = "kafka", feature = "nats"))]
+pub mod sink;
+pub mod verify;
+pub mod watchdog;
 pub use crate::db::DatabasePool;
@@ -0,0 +1,32 @@
+//! Set up of the log output shared by the indexer and the server.
+use tracing_subscriber::{filter::Targets, prelude::*};
+
+/// The format of the log output.
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum LogFormat {
+    /// Human readable text.
+    Text,
+    /// One JSON object per line. The fields of a log line (e.g.
+    /// `block_height`, `transaction_hash`, and `event_index`) are top-level
+    /// keys and the fields of the enclosing spans are listed under `spans`, so
+    /// that the logs can be ingested without parsing the message.
+    Json,
+}
+
+/// Install the global tracing subscriber writing the logs that pass the
+/// `filter` in the given `format`.
+pub fn init(format: LogFormat, filter: Targets) {
+    let (text_layer, json_layer) = match format {
+        LogFormat::Text => (Some(tracing_subscriber::fmt::layer()), None),
+        LogFormat::Json => (
+            None,
+            Some(tracing_subscriber::fmt::layer().json().flatten_event(true)),
+        ),
+    };
+
+    tracing_subscriber::registry()
+        .with(text_layer)
+        .with(json_layer)
+        .with(filter)
+        .init();
+}
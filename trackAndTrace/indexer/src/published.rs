@@ -0,0 +1,90 @@
+//! The indexed events as published to the message broker with `--sink` and
+//! sent with `NOTIFY` with `--notify`. Every event is serialized as a JSON
+//! object containing the location of the event and its data.
+use crate::db::EventLocation;
+use chrono::{DateTime, Utc};
+use concordium_rust_sdk::types::{hashes::TransactionHash, AbsoluteBlockHeight, AccountAddress};
+use track_and_trace::{
+    AdditionalData, ItemCreatedEvent, ItemStatusChangedEvent, MetadataUrl, Status,
+};
+
+/// An indexed event as published to the message broker and sent with `NOTIFY`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PublishedEvent {
+    /// The height of the block the event was included in.
+    pub block_height:     AbsoluteBlockHeight,
+    /// The timestamp of the block the event was included in.
+    pub block_time:       DateTime<Utc>,
+    /// The transaction hash that the event was recorded in.
+    pub transaction_hash: TransactionHash,
+    /// The index from the array of logged events in a transaction.
+    pub event_index:      u64,
+    /// The account that sent the transaction the event was recorded in.
+    pub sender:           AccountAddress,
+    /// The event.
+    #[serde(flatten)]
+    pub event:            PublishedEventData,
+}
+
+/// The data of a [`PublishedEvent`]. The type of the event is given in the
+/// `type` field of the message.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(tag = "type")]
+pub enum PublishedEventData {
+    /// An `ItemCreatedEvent`.
+    ItemCreated {
+        /// The item's id as logged in the event.
+        item_id:        u64,
+        /// The item's metadata_url as logged in the event.
+        metadata_url:   Option<MetadataUrl>,
+        /// The item's initial status as logged in the event.
+        initial_status: Status,
+    },
+    /// An `ItemStatusChangedEvent`.
+    ItemStatusChanged {
+        /// The item's id as logged in the event.
+        item_id:         u64,
+        /// The item's new status as logged in the event.
+        new_status:      Status,
+        /// Any additional data encoded as generic bytes as logged in the
+        /// event.
+        additional_data: AdditionalData,
+    },
+}
+
+impl PublishedEvent {
+    /// The published `ItemCreatedEvent` at the `location`.
+    pub fn item_created(location: &EventLocation, event: &ItemCreatedEvent) -> Self {
+        Self::new(location, PublishedEventData::ItemCreated {
+            item_id:        event.item_id.0,
+            metadata_url:   event.metadata_url.clone(),
+            initial_status: event.initial_status,
+        })
+    }
+
+    /// The published `ItemStatusChangedEvent` at the `location`.
+    pub fn item_status_changed(
+        location: &EventLocation,
+        event: &ItemStatusChangedEvent<AdditionalData>,
+    ) -> Self {
+        Self::new(location, PublishedEventData::ItemStatusChanged {
+            item_id:         event.item_id.0,
+            new_status:      event.new_status,
+            additional_data: event.additional_data.clone(),
+        })
+    }
+
+    fn new(location: &EventLocation, event: PublishedEventData) -> Self {
+        Self {
+            block_height: location.block_height,
+            block_time: location.block_time,
+            transaction_hash: location.transaction_hash,
+            event_index: location.event_index,
+            sender: location.sender,
+            event,
+        }
+    }
+
+    /// The key uniquely identifying the event.
+    pub fn key(&self) -> String { format!("{}-{}", self.transaction_hash, self.event_index) }
+}
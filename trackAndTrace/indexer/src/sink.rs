@@ -0,0 +1,108 @@
+//! Publishing of the indexed events to a message broker so that downstream
+//! systems (e.g. an ERP or analytics pipeline) can consume the events without
+//! polling the database. Every event is published as a JSON message to a
+//! Kafka topic or a NATS subject. The module is only available with the
+//! `kafka` or the `nats` feature, and each broker only with its feature.
+//!
+//! The events of a block are published before the database transaction of the
+//! block is committed. If publishing fails, the block is not committed and is
+//! processed (and published) again. Consumers can therefore receive an event
+//! more than once and should deduplicate the events by their key
+//! `<transaction_hash>-<event_index>`.
+use crate::published::PublishedEvent;
+use anyhow::Context;
+#[cfg(feature = "kafka")]
+use rdkafka::producer::{FutureProducer, FutureRecord};
+
+/// A connection to the message broker the events are published to.
+pub enum EventSink {
+    /// A Kafka producer publishing to the `topic`.
+    #[cfg(feature = "kafka")]
+    Kafka {
+        producer: FutureProducer,
+        topic:    String,
+    },
+    /// A NATS client publishing to the `subject`.
+    #[cfg(feature = "nats")]
+    Nats {
+        client:  async_nats::Client,
+        subject: String,
+    },
+}
+
+/// The maximum time to wait until a message is acknowledged by Kafka.
+#[cfg(feature = "kafka")]
+const KAFKA_MESSAGE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+impl EventSink {
+    /// Create a Kafka producer publishing to the `topic`. The `brokers` are a
+    /// comma-separated list of broker addresses.
+    #[cfg(feature = "kafka")]
+    pub fn kafka(brokers: &str, topic: String) -> anyhow::Result<Self> {
+        let producer = rdkafka::ClientConfig::new()
+            .set("bootstrap.servers", brokers)
+            .set(
+                "message.timeout.ms",
+                KAFKA_MESSAGE_TIMEOUT.as_millis().to_string(),
+            )
+            .set("enable.idempotence", "true")
+            .create()
+            .context("Could not create Kafka producer")?;
+        Ok(Self::Kafka { producer, topic })
+    }
+
+    /// Connect to NATS publishing to the `subject`. The `brokers` are a
+    /// comma-separated list of server addresses.
+    #[cfg(feature = "nats")]
+    pub async fn nats(brokers: &str, subject: String) -> anyhow::Result<Self> {
+        let client = async_nats::connect(brokers)
+            .await
+            .context("Could not connect to NATS")?;
+        Ok(Self::Nats { client, subject })
+    }
+
+    /// Publish the `events` in the given order and wait until the broker
+    /// acknowledged them.
+    pub async fn publish(&self, events: &[PublishedEvent]) -> anyhow::Result<()> {
+        match self {
+            #[cfg(feature = "kafka")]
+            EventSink::Kafka { producer, topic } => {
+                for event in events {
+                    let key = event.key();
+                    let payload = serde_json::to_vec(event)?;
+                    producer
+                        .send(
+                            FutureRecord::to(topic).key(&key).payload(&payload),
+                            KAFKA_MESSAGE_TIMEOUT,
+                        )
+                        .await
+                        .map_err(|(error, _message)| error)
+                        .with_context(|| format!("Could not publish event {key} to Kafka"))?;
+                }
+            }
+            #[cfg(feature = "nats")]
+            EventSink::Nats { client, subject } => {
+                for event in events {
+                    let key = event.key();
+                    // The message id lets NATS JetStream streams deduplicate events that are
+                    // published again after a failure.
+                    let mut headers = async_nats::HeaderMap::new();
+                    headers.insert("Nats-Msg-Id", key.as_str());
+                    client
+                        .publish_with_headers(
+                            subject.clone(),
+                            headers,
+                            serde_json::to_vec(event)?.into(),
+                        )
+                        .await
+                        .with_context(|| format!("Could not publish event {key} to NATS"))?;
+                }
+                client
+                    .flush()
+                    .await
+                    .context("Could not flush events to NATS")?;
+            }
+        }
+        Ok(())
+    }
+}
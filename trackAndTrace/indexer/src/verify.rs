@@ -0,0 +1,324 @@
+//! Verification and repair of the indexed events. The events of the track and
+//! trace contract in a range of blocks are re-read from the node and compared
+//! with the rows stored in the database. Missing events can be re-inserted.
+use crate::db::{self, Database, EventLocation, EventTable, IndexableEvent};
+use anyhow::Context;
+use concordium_rust_sdk::{
+    indexer::{AffectedContractIndexer, TraverseConfig},
+    smart_contracts::common::to_bytes,
+    types::{hashes::TransactionHash, AbsoluteBlockHeight, ContractAddress},
+    v2 as sdk,
+};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashSet};
+use tokio::sync::mpsc;
+use tokio_postgres::types::Json;
+use track_and_trace::{
+    AdditionalData, Event, ItemCreatedEvent, ItemStatusChangedEvent, ItemSummaryEvent, Status,
+};
+
+/// An event of the contract that is indexed in the database.
+#[derive(Debug, Clone)]
+pub enum IndexedEvent {
+    /// An event stored in the `item_created_events` table.
+    ItemCreated(ItemCreatedEvent),
+    /// An event stored in the `item_status_changed_events` table.
+    ItemStatusChanged(ItemStatusChangedEvent<AdditionalData>),
+    /// An event stored in the `item_summary_events` table.
+    ItemSummary(ItemSummaryEvent),
+}
+
+impl IndexedEvent {
+    /// The table the event is stored in.
+    fn table(&self) -> EventTable {
+        match self {
+            IndexedEvent::ItemCreated(_) => ItemCreatedEvent::TABLE,
+            IndexedEvent::ItemStatusChanged(_) => ItemStatusChangedEvent::<AdditionalData>::TABLE,
+            IndexedEvent::ItemSummary(_) => ItemSummaryEvent::TABLE,
+        }
+    }
+
+    /// Check whether the database `row` stores this event at the `location`.
+    /// The block height, the sender, and the entrypoint are only compared if
+    /// they are stored in the row.
+    fn matches(&self, location: &EventLocation, row: &tokio_postgres::Row) -> anyhow::Result<bool> {
+        let block_height: Option<i64> = row.try_get("block_height")?;
+        if block_height.map_or(false, |height| {
+            height as u64 != location.block_height.height
+        }) {
+            return Ok(false);
+        }
+        let sender: Option<&[u8]> = row.try_get("sender")?;
+        if sender.map_or(false, |sender| sender != location.sender.0.as_slice()) {
+            return Ok(false);
+        }
+        let entrypoint: Option<&str> = row.try_get("entrypoint")?;
+        if entrypoint.map_or(false, |entrypoint| {
+            entrypoint != location.entrypoint.to_string()
+        }) {
+            return Ok(false);
+        }
+        let item_id: i64 = row.try_get("item_id")?;
+        let matches = match self {
+            IndexedEvent::ItemCreated(event) => {
+                let metadata_url: &[u8] = row.try_get("metadata_url")?;
+                let Json(initial_status): Json<Status> = row.try_get("initial_status")?;
+                item_id as u64 == event.item_id.0
+                    && metadata_url == to_bytes(&event.metadata_url).as_slice()
+                    && initial_status == event.initial_status
+            }
+            IndexedEvent::ItemStatusChanged(event) => {
+                let Json(new_status): Json<Status> = row.try_get("new_status")?;
+                let additional_data: &[u8] = row.try_get("additional_data")?;
+                let additional_data_hash: Option<&[u8]> = row.try_get("additional_data_hash")?;
+                // Only the hash of additional data removed after its retention
+                // period is compared.
+                let event_additional_data = to_bytes(&event.additional_data);
+                let additional_data_matches = match additional_data_hash {
+                    Some(hash) => hash == Sha256::digest(&event_additional_data).as_slice(),
+                    None => additional_data == event_additional_data.as_slice(),
+                };
+                item_id as u64 == event.item_id.0
+                    && new_status == event.new_status
+                    && additional_data_matches
+            }
+            IndexedEvent::ItemSummary(event) => {
+                let version: i16 = row.try_get("version")?;
+                let Json(status): Json<Status> = row.try_get("status")?;
+                let metadata_hash: Option<&[u8]> = row.try_get("metadata_hash")?;
+                item_id as u64 == event.item_id.0
+                    && version as u8 == event.version
+                    && status == event.status
+                    && metadata_hash == event.metadata_hash.as_ref().map(|hash| hash.0.as_slice())
+            }
+        };
+        Ok(matches)
+    }
+}
+
+/// An indexed event of the contract together with its location on chain.
+#[derive(Debug, Clone)]
+pub struct ChainEvent {
+    /// The location of the event on chain.
+    pub location: EventLocation,
+    /// The event.
+    pub event:    IndexedEvent,
+}
+
+/// The outcome of comparing the events of a block range on chain with the
+/// rows in the database.
+#[derive(Debug, Default)]
+pub struct VerificationReport {
+    /// The number of events that are stored correctly.
+    pub matching:   u64,
+    /// The events that are not stored in the database.
+    pub missing:    Vec<ChainEvent>,
+    /// The events that are stored in the database with different values.
+    pub mismatched: Vec<ChainEvent>,
+    /// The events stored in the database within the block range that do not
+    /// exist on chain, identified by transaction hash and event index.
+    pub unexpected: Vec<(EventTable, TransactionHash, u64)>,
+}
+
+impl VerificationReport {
+    /// Whether the database matches the chain.
+    pub fn is_consistent(&self) -> bool {
+        self.missing.is_empty() && self.mismatched.is_empty() && self.unexpected.is_empty()
+    }
+}
+
+/// Read the events of the contract from the blocks with a height between
+/// `from_height` and `to_height` (both inclusive) from the node. The blocks
+/// must be finalized. The events are returned in the order they were logged.
+pub async fn chain_events(
+    endpoint: sdk::Endpoint,
+    contract_address: ContractAddress,
+    from_height: AbsoluteBlockHeight,
+    to_height: AbsoluteBlockHeight,
+) -> anyhow::Result<Vec<ChainEvent>> {
+    let traverse_config = TraverseConfig::new_single(endpoint, from_height);
+    let (sender, mut receiver) = mpsc::channel(20);
+    let traverser = tokio::spawn(traverse_config.traverse(
+        AffectedContractIndexer {
+            addresses: BTreeSet::from([contract_address]),
+            all:       true,
+        },
+        sender,
+    ));
+
+    let mut chain_events = Vec::new();
+    while let Some((block_info, contract_update_info)) = receiver.recv().await {
+        for single_contract_update_info in contract_update_info {
+            // The events are enumerated the same way as when they are indexed.
+            for (_contract_invoked, entry_point_name, events) in
+                single_contract_update_info.0.execution_tree.events()
+            {
+                for (event_index, event) in events.iter().enumerate() {
+                    let event = match event.parse::<Event<AdditionalData>>()? {
+                        Event::ItemCreated(event) => IndexedEvent::ItemCreated(event),
+                        Event::ItemStatusChanged(event) => IndexedEvent::ItemStatusChanged(event),
+                        Event::ItemSummary(event) => IndexedEvent::ItemSummary(event),
+                        _ => continue,
+                    };
+                    chain_events.push(ChainEvent {
+                        location: EventLocation {
+                            block_height:     block_info.block_height,
+                            block_time:       block_info.block_slot_time,
+                            transaction_hash: single_contract_update_info.0.transaction_hash,
+                            event_index:      event_index as u64,
+                            sender:           single_contract_update_info.0.sender,
+                            entrypoint:       entry_point_name.to_owned(),
+                        },
+                        event,
+                    });
+                }
+            }
+        }
+        if block_info.block_height >= to_height {
+            traverser.abort();
+            return Ok(chain_events);
+        }
+    }
+
+    traverser
+        .await
+        .context("The traversal task panicked")?
+        .context("Failed to traverse the chain")?;
+    anyhow::bail!("The traversal stopped before reaching block height {to_height}.")
+}
+
+/// Compare the `chain_events` of the blocks with a height between
+/// `from_height` and `to_height` (both inclusive) with the rows stored in the
+/// database.
+pub async fn verify(
+    db: &Database,
+    chain_events: Vec<ChainEvent>,
+    from_height: AbsoluteBlockHeight,
+    to_height: AbsoluteBlockHeight,
+) -> anyhow::Result<VerificationReport> {
+    let mut report = VerificationReport::default();
+    let mut expected_keys = HashSet::new();
+
+    for chain_event in chain_events {
+        let table = chain_event.event.table();
+        expected_keys.insert((
+            table.name(),
+            chain_event.location.transaction_hash,
+            chain_event.location.event_index,
+        ));
+        let row = db
+            .get_event_row(
+                table,
+                &chain_event.location.transaction_hash,
+                chain_event.location.event_index,
+            )
+            .await
+            .with_context(|| format!("Could not read event from {}", table.name()))?;
+        match row {
+            None => report.missing.push(chain_event),
+            Some(row) if chain_event.event.matches(&chain_event.location, &row)? => {
+                report.matching += 1
+            }
+            Some(_) => report.mismatched.push(chain_event),
+        }
+    }
+
+    for table in [
+        EventTable::ItemCreatedEvents,
+        EventTable::ItemStatusChangedEvents,
+        EventTable::ItemSummaryEvents,
+    ] {
+        let keys = db
+            .get_event_keys_in_range(table, from_height, to_height)
+            .await
+            .with_context(|| format!("Could not read events from {}", table.name()))?;
+        report.unexpected.extend(
+            keys.into_iter()
+                .filter(|(transaction_hash, event_index)| {
+                    !expected_keys.contains(&(table.name(), *transaction_hash, *event_index))
+                })
+                .map(|(transaction_hash, event_index)| (table, transaction_hash, event_index)),
+        );
+    }
+
+    Ok(report)
+}
+
+/// Insert the `missing` events into the database in the order they were
+/// logged on chain.
+pub async fn repair(db: &Database, missing: &[ChainEvent]) -> anyhow::Result<()> {
+    for chain_event in missing {
+        match &chain_event.event {
+            IndexedEvent::ItemCreated(event) => db.insert_event(&chain_event.location, event).await,
+            IndexedEvent::ItemStatusChanged(event) => {
+                db.insert_event(&chain_event.location, event).await
+            }
+            IndexedEvent::ItemSummary(event) => db.insert_event(&chain_event.location, event).await,
+        }
+        .with_context(|| {
+            format!(
+                "Could not insert event from transaction {} with event index {}",
+                chain_event.location.transaction_hash, chain_event.location.event_index
+            )
+        })?;
+    }
+    Ok(())
+}
+
+/// Insert the `missing` events and overwrite the rows of the `mismatched`
+/// events of a [`VerificationReport`] with the events on chain. All changes
+/// are applied in one database transaction. Rows that do not exist on chain
+/// are left untouched.
+pub async fn backfill(db: &mut Database, report: &VerificationReport) -> anyhow::Result<()> {
+    let db_transaction = db
+        .client
+        .transaction()
+        .await
+        .context("Failed to build database transaction")?;
+
+    for chain_event in &report.missing {
+        match &chain_event.event {
+            IndexedEvent::ItemCreated(event) => {
+                db::insert_event(&db_transaction, &chain_event.location, event).await
+            }
+            IndexedEvent::ItemStatusChanged(event) => {
+                db::insert_event(&db_transaction, &chain_event.location, event).await
+            }
+            IndexedEvent::ItemSummary(event) => {
+                db::insert_event(&db_transaction, &chain_event.location, event).await
+            }
+        }
+        .with_context(|| {
+            format!(
+                "Could not insert event from transaction {} with event index {}",
+                chain_event.location.transaction_hash, chain_event.location.event_index
+            )
+        })?;
+    }
+
+    for chain_event in &report.mismatched {
+        match &chain_event.event {
+            IndexedEvent::ItemCreated(event) => {
+                db::update_event(&db_transaction, &chain_event.location, event).await
+            }
+            IndexedEvent::ItemStatusChanged(event) => {
+                db::update_event(&db_transaction, &chain_event.location, event).await
+            }
+            IndexedEvent::ItemSummary(event) => {
+                db::update_event(&db_transaction, &chain_event.location, event).await
+            }
+        }
+        .with_context(|| {
+            format!(
+                "Could not update event from transaction {} with event index {}",
+                chain_event.location.transaction_hash, chain_event.location.event_index
+            )
+        })?;
+    }
+
+    db_transaction
+        .commit()
+        .await
+        .context("Failed to commit backfill transaction")?;
+    Ok(())
+}
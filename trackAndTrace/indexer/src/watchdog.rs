@@ -0,0 +1,144 @@
+//! A watchdog detecting that the indexing has stalled. The traversal of the
+//! chain or the channel between the traversal and the processing of the blocks
+//! can get stuck without failing, in which case the `indexer` keeps running but
+//! stops storing blocks. The watchdog periodically compares the last processed
+//! block with the last finalized block of the node and raises an alert if no
+//! block has been processed for a configurable time while the node keeps
+//! finalizing blocks. The alert is logged as an error and, if configured,
+//! posted as JSON to a webhook.
+use crate::health::IndexerProgress;
+use concordium_rust_sdk::{types::AbsoluteBlockHeight, v2::Client};
+use std::time::{Duration, Instant};
+
+/// The maximum time between two checks of the watchdog.
+const MAX_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The alert posted to the webhook when the indexing has stalled.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct StallAlert {
+    /// The last block height processed since the indexer was started.
+    pub last_processed_block_height:        Option<AbsoluteBlockHeight>,
+    /// The last finalized block height reported by the node.
+    pub last_finalized_block_height:        AbsoluteBlockHeight,
+    /// The number of seconds since the last block was processed (or since the
+    /// indexer was started if no block has been processed yet).
+    pub seconds_since_last_processed_block: u64,
+}
+
+/// The watchdog raising an alert if no block has been processed for
+/// `stall_after` while the last finalized block of the node advances.
+#[derive(Debug)]
+pub struct Watchdog {
+    /// The client querying the last finalized block of the node.
+    client:      Client,
+    /// The progress of the indexer.
+    progress:    IndexerProgress,
+    /// The time without a processed block after which an alert is raised.
+    stall_after: Duration,
+    /// The webhook the alerts are posted to. If not set, alerts are only
+    /// logged.
+    webhook:     Option<reqwest::Url>,
+    /// The client posting the alerts to the webhook.
+    http_client: reqwest::Client,
+}
+
+impl Watchdog {
+    /// Create a watchdog raising an alert if no block has been processed for
+    /// `stall_after` and posting the alerts to the `webhook`, if given.
+    pub fn new(
+        client: Client,
+        progress: IndexerProgress,
+        stall_after: Duration,
+        webhook: Option<reqwest::Url>,
+    ) -> Self {
+        Self {
+            client,
+            progress,
+            stall_after,
+            webhook,
+            http_client: reqwest::Client::new(),
+        }
+    }
+
+    /// Watch the progress of the indexer until the process exits. An alert is
+    /// raised once per stall. The indexing is not considered stalled while it
+    /// is paused or if the node has not finalized a block that has not been
+    /// processed yet.
+    pub async fn run(mut self) {
+        let mut interval = tokio::time::interval(self.stall_after.min(MAX_CHECK_INTERVAL));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        // The last processed block height and the time it was first observed.
+        let mut last_progress = (self.progress.last_processed_height(), Instant::now());
+        // Whether an alert has been raised for the current stall.
+        let mut alerted = false;
+
+        loop {
+            interval.tick().await;
+
+            let processed = self.progress.last_processed_height();
+            if processed != last_progress.0 || self.progress.is_paused() {
+                if alerted {
+                    tracing::info!(
+                        "Indexing resumed after a stall at block {:?}.",
+                        last_progress.0
+                    );
+                }
+                last_progress = (processed, Instant::now());
+                alerted = false;
+                continue;
+            }
+
+            let stalled_for = last_progress.1.elapsed();
+            if alerted || stalled_for < self.stall_after {
+                continue;
+            }
+
+            let last_finalized = match self.client.get_consensus_info().await {
+                Ok(consensus_info) => consensus_info.last_finalized_block_height,
+                Err(error) => {
+                    tracing::warn!("Could not query the last finalized block height: {error}");
+                    continue;
+                }
+            };
+            // If the node does not finalize blocks either, the indexer has
+            // nothing to process.
+            if processed.map_or(false, |processed| processed >= last_finalized) {
+                continue;
+            }
+
+            self.fire(StallAlert {
+                last_processed_block_height:        processed,
+                last_finalized_block_height:        last_finalized,
+                seconds_since_last_processed_block: stalled_for.as_secs(),
+            })
+            .await;
+            alerted = true;
+        }
+    }
+
+    /// Log the `alert` and post it to the webhook.
+    async fn fire(&self, alert: StallAlert) {
+        tracing::error!(
+            "Indexing stalled: no block processed for {} seconds (last processed block {:?}) \
+             while the node finalized block {}.",
+            alert.seconds_since_last_processed_block,
+            alert.last_processed_block_height,
+            alert.last_finalized_block_height
+        );
+        let Some(webhook) = &self.webhook else {
+            return;
+        };
+        let response = self
+            .http_client
+            .post(webhook.clone())
+            .timeout(Duration::from_secs(10))
+            .json(&alert)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status);
+        if let Err(error) = response {
+            tracing::error!("Unable to post the stall alert to the webhook: {error}");
+        }
+    }
+}
@@ -29,16 +29,19 @@
 //!             from:               Status::Produced,
 //!             to:                 vec![Status::InTransit],
 //!             authorized_account: PRODUCER,
+//!             max_duration:       None,
 //!         },
 //!         TransitionEdges {
 //!             from:               Status::InTransit,
 //!             to:                 vec![Status::InStore],
 //!             authorized_account: TRANSPORTER,
+//!             max_duration:       None,
 //!         },
 //!         TransitionEdges {
 //!             from:               Status::InStore,
 //!             to:                 vec![Status::Sold],
 //!             authorized_account: SELLER,
+//!             max_duration:       None,
 //!         },
 //!     ];
 //! ```
@@ -49,8 +52,8 @@
 //! the contract runs out of item ids.
 #![cfg_attr(not(feature = "std"), no_std)]
 use concordium_cis2::{
-    StandardIdentifier, SupportResult, SupportsQueryParams, SupportsQueryResponse, TokenIdU64,
-    CIS0_STANDARD_IDENTIFIER,
+    Receiver, StandardIdentifier, SupportResult, SupportsQueryParams, SupportsQueryResponse,
+    TokenAmountU8, TokenIdU64, Transfer, TransferParams, CIS0_STANDARD_IDENTIFIER,
 };
 use concordium_std::*;
 // Re-export type.
@@ -60,13 +63,39 @@ pub use concordium_std::MetadataUrl;
 pub const CIS6_STANDARD_IDENTIFIER: StandardIdentifier<'static> =
     StandardIdentifier::new_unchecked("CIS-6");
 
+/// The standard identifier for the CIS-3 standard (sponsored transactions).
+pub const CIS3_STANDARD_IDENTIFIER: StandardIdentifier<'static> =
+    StandardIdentifier::new_unchecked("CIS-3");
+
 /// List of supported standards by this contract address.
-const SUPPORTS_STANDARDS: [StandardIdentifier<'static>; 2] =
-    [CIS0_STANDARD_IDENTIFIER, CIS6_STANDARD_IDENTIFIER];
+const SUPPORTS_STANDARDS: [StandardIdentifier<'static>; 3] = [
+    CIS0_STANDARD_IDENTIFIER,
+    CIS3_STANDARD_IDENTIFIER,
+    CIS6_STANDARD_IDENTIFIER,
+];
+
+/// The entrypoint of the CIS-2 contract invoked to mint the twin token of an
+/// item.
+const TWIN_TOKEN_MINT_ENTRYPOINT: EntrypointName = EntrypointName::new_unchecked("mint");
+
+/// The entrypoint of the CIS-2 contract invoked to transfer the twin token of
+/// an item.
+const TWIN_TOKEN_TRANSFER_ENTRYPOINT: EntrypointName = EntrypointName::new_unchecked("transfer");
+
+/// The entrypoint of the CIS-2 contract invoked to burn the twin token of an
+/// archived item.
+const TWIN_TOKEN_BURN_ENTRYPOINT: EntrypointName = EntrypointName::new_unchecked("burn");
+
+/// The entrypoint of the CIS-2 contract invoked to update the metadata URL of
+/// the twin token of an item.
+const TWIN_TOKEN_UPDATE_METADATA_ENTRYPOINT: EntrypointName =
+    EntrypointName::new_unchecked("updateMetadata");
 
 /// List of supported entrypoints by the `permit` function.
-const SUPPORTS_PERMIT_ENTRYPOINTS: [EntrypointName; 1] =
-    [EntrypointName::new_unchecked("changeItemStatus")];
+const SUPPORTS_PERMIT_ENTRYPOINTS: [EntrypointName; 2] = [
+    EntrypointName::new_unchecked("changeItemStatus"),
+    EntrypointName::new_unchecked("createItem"),
+];
 
 /// The CIS-6 standard defines the item id to be a variable-length ASCII string
 /// up to 255 characters. To encode all possible item ids, 255 bytes would be
@@ -97,6 +126,45 @@ pub enum Event<A: Serial> {
     /// whenever the `permit` function is invoked.
     #[concordium(tag = 250)]
     Nonce(NonceEvent),
+    /// The event tracks when the metadata of an item is frozen.
+    #[concordium(tag = 4)]
+    ItemMetadataFrozen(ItemMetadataFrozenEvent),
+    /// The event summarizes the state of an item after every mutation of the
+    /// item.
+    #[concordium(tag = 5)]
+    ItemSummary(ItemSummaryEvent),
+    /// The event tracks when the ownership of an item is transferred.
+    #[concordium(tag = 6)]
+    ItemOwnerChanged(ItemOwnerChangedEvent),
+    /// The event tracks when the metadata of an item is updated.
+    #[concordium(tag = 7)]
+    ItemMetadataUpdated(ItemMetadataUpdatedEvent),
+    /// The event tracks when a custom status is registered.
+    #[concordium(tag = 8)]
+    CustomStatusAdded(CustomStatusAddedEvent),
+    /// The event tracks when an item is archived.
+    #[concordium(tag = 9)]
+    ItemArchived(ItemArchivedEvent),
+    /// The event tracks when an address is proposed as a new Admin.
+    #[concordium(tag = 10)]
+    AdminProposed(AdminProposedEvent),
+    /// The event tracks when a proposed address accepts the Admin role.
+    #[concordium(tag = 11)]
+    AdminAccepted(AdminAcceptedEvent),
+    /// The event tracks when an item is marked `Expired` because it overstayed
+    /// the maximum duration of its status.
+    #[concordium(tag = 12)]
+    ItemExpired(ItemExpiredEvent),
+    /// The event tracks when a transition edge of the state machine is added
+    /// or removed.
+    #[concordium(tag = 13)]
+    TransitionEdgeUpdated(TransitionEdgeUpdatedEvent),
+    /// The event tracks when the maximum duration of a status is changed.
+    #[concordium(tag = 14)]
+    MaxDurationUpdated(MaxDurationUpdatedEvent),
+    /// The event tracks when the roles allowed to create items are changed.
+    #[concordium(tag = 15)]
+    CreateItemRolesUpdated(CreateItemRolesUpdatedEvent),
 }
 
 /// The [`ItemCreatedEvent`] is logged when an item is created.
@@ -118,8 +186,8 @@ pub struct ItemStatusChangedEvent<A: Serial> {
     pub item_id:         ItemID,
     /// The item's new status.
     pub new_status:      Status,
-    /// Any additional data encoded as generic bytes. Usecase-specific data can
-    /// be included here such as temperature, longitude, latitude, ... .
+    /// Any additional data of the status change such as a location, a sensor
+    /// reading, or a document hash (see [`AdditionalData`]).
     pub additional_data: A,
 }
 
@@ -151,6 +219,156 @@ pub struct NonceEvent {
     pub nonce:   u64,
 }
 
+/// The [`ItemMetadataFrozenEvent`] is logged when the metadata of an item is
+/// permanently locked.
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq, Clone)]
+pub struct ItemMetadataFrozenEvent {
+    /// The item's id.
+    pub item_id: ItemID,
+}
+
+/// The [`ItemMetadataUpdatedEvent`] is logged when the metadata_url of an item
+/// is replaced.
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq, Clone)]
+pub struct ItemMetadataUpdatedEvent {
+    /// The item's id.
+    pub item_id:      ItemID,
+    /// The item's new metadata_url.
+    pub metadata_url: Option<MetadataUrl>,
+}
+
+/// The [`AdminProposedEvent`] is logged when an Admin proposes an address as a
+/// new Admin.
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq, Clone)]
+pub struct AdminProposedEvent {
+    /// The proposed address.
+    pub address:     Address,
+    /// The Admin that proposed the address.
+    pub proposed_by: Address,
+}
+
+/// The [`AdminAcceptedEvent`] is logged when a proposed address accepts the
+/// Admin role. It is followed by a [`GrantRoleEvent`].
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq, Clone)]
+pub struct AdminAcceptedEvent {
+    /// The new Admin.
+    pub address: Address,
+}
+
+/// The [`ItemExpiredEvent`] is logged when an item is marked `Expired` because
+/// it stayed in its status longer than the maximum duration of the status.
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq, Clone)]
+pub struct ItemExpiredEvent {
+    /// The item's id.
+    pub item_id:         ItemID,
+    /// The status the item overstayed.
+    pub previous_status: Status,
+}
+
+/// The [`TransitionEdgeUpdatedEvent`] is logged for every transition edge of
+/// the state machine when the contract is initialized and whenever a
+/// transition edge is added or removed with `updateStateMachine`, so that the
+/// state machine can be reconstructed from the events.
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq, Clone)]
+pub struct TransitionEdgeUpdatedEvent {
+    /// The account that is allowed to execute the state transition.
+    pub address:     AccountAddress,
+    /// The from state of the state transition.
+    pub from_status: Status,
+    /// The to state of the state transition.
+    pub to_status:   Status,
+    /// Whether the transition edge was added or removed.
+    pub update:      Update,
+}
+
+/// The [`MaxDurationUpdatedEvent`] is logged for every status with a maximum
+/// duration when the contract is initialized and whenever the maximum duration
+/// of a status is changed with `setMaxDuration`.
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq, Clone)]
+pub struct MaxDurationUpdatedEvent {
+    /// The status whose maximum duration changed.
+    pub status:       Status,
+    /// The new maximum duration of the status. `None` if items may stay in
+    /// the status indefinitely.
+    pub max_duration: Option<Duration>,
+}
+
+/// The [`CreateItemRolesUpdatedEvent`] is logged when the contract is
+/// initialized and whenever the roles allowed to create items are changed with
+/// `setCreateItemRoles`.
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq, Clone)]
+pub struct CreateItemRolesUpdatedEvent {
+    /// The roles that are allowed to create items.
+    pub roles: Vec<Roles>,
+}
+
+/// The [`ItemArchivedEvent`] is logged when an item is archived and its state
+/// is removed from the contract.
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq, Clone)]
+pub struct ItemArchivedEvent {
+    /// The item's id.
+    pub item_id: ItemID,
+}
+
+/// The [`CustomStatusAddedEvent`] is logged when a custom status is
+/// registered, either when the contract is initialized or by the Admin.
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq, Clone)]
+pub struct CustomStatusAddedEvent {
+    /// The registered status.
+    pub status: Status,
+    /// The name of the registered status.
+    pub name:   String,
+}
+
+/// The [`ItemOwnerChangedEvent`] is logged when the ownership of an item (and
+/// its twin token) is transferred.
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq, Clone)]
+pub struct ItemOwnerChangedEvent {
+    /// The item's id.
+    pub item_id: ItemID,
+    /// The previous owner of the item.
+    pub from:    Address,
+    /// The new owner of the item.
+    pub to:      Address,
+}
+
+/// The version of the [`ItemSummaryEvent`] logged by this contract. The
+/// version is increased whenever fields are added to the event.
+pub const ITEM_SUMMARY_EVENT_VERSION: u8 = 1;
+
+/// The [`ItemSummaryEvent`] is logged after the [`ItemCreatedEvent`],
+/// [`ItemStatusChangedEvent`], [`ItemMetadataFrozenEvent`],
+/// [`ItemMetadataUpdatedEvent`], and [`ItemExpiredEvent`]. It contains
+/// the state of the item after the mutation, so that light wallets can follow
+/// an item with a single event filter instead of decoding all event variants.
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq, Clone)]
+pub struct ItemSummaryEvent {
+    /// The version of the event (see [`ITEM_SUMMARY_EVENT_VERSION`]).
+    pub version:       u8,
+    /// The item's id.
+    pub item_id:       ItemID,
+    /// The item's current status.
+    pub status:        Status,
+    /// The hash of the item's metadata, if the item has a metadata_url with a
+    /// hash.
+    pub metadata_hash: Option<HashSha2256>,
+}
+
+impl ItemSummaryEvent {
+    /// The summary of the item with the given id and state.
+    pub fn new(item_id: ItemID, item: &ItemState) -> Self {
+        ItemSummaryEvent {
+            version: ITEM_SUMMARY_EVENT_VERSION,
+            item_id,
+            status: item.status,
+            metadata_hash: item
+                .metadata_url
+                .as_ref()
+                .and_then(|metadata_url| metadata_url.hash),
+        }
+    }
+}
+
 /// A struct containing a set of roles granted to an address.
 #[derive(Serial, DeserialWithState, Deletable)]
 #[concordium(state_parameter = "S")]
@@ -162,9 +380,13 @@ struct AddressRoleState<S> {
 /// Enum of available roles in this contract. Several addresses can have the
 /// same role and an address can have several roles.
 #[derive(Serialize, PartialEq, Eq, Reject, SchemaType, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub enum Roles {
     /// Admin role.
     Admin,
+    /// Producer role. Producers can create items if the role is one of the
+    /// roles allowed to create items (see `setCreateItemRoles`).
+    Producer,
 }
 
 /// Enum of the statuses that an item can have.
@@ -179,15 +401,50 @@ pub enum Status {
     InStore,
     /// Item is sold.
     Sold,
+    /// A custom status registered by the deployment, e.g. `CustomsCleared` or
+    /// `Recalled`. The value is the index of the name of the status in the
+    /// custom statuses returned by `getCustomStatuses`.
+    Custom(u8),
+    /// Item overstayed the maximum duration of its previous status (see
+    /// `expireItem`).
+    Expired,
 }
 
 /// A struct containing a state of one item.
 #[derive(Debug, Serialize, SchemaType, Clone, PartialEq, Eq)]
 pub struct ItemState {
     /// The status of the item.
-    pub status:       Status,
+    pub status:          Status,
     /// The metadata_url of the item.
-    pub metadata_url: Option<MetadataUrl>,
+    pub metadata_url:    Option<MetadataUrl>,
+    /// Whether the metadata of the item is permanently locked. Once frozen,
+    /// the metadata of the item can no longer be updated.
+    pub metadata_frozen: bool,
+    /// The address that created the item. Besides the Admin, only the creator
+    /// can update the metadata of the item.
+    pub creator:         Address,
+    /// The slot time of the block the item was created in.
+    pub created_at:      Timestamp,
+    /// The slot time of the block the status of the item was last changed in.
+    /// Equal to `created_at` until the status is changed for the first time.
+    pub updated_at:      Timestamp,
+}
+
+/// The maximum number of entries stored in the history of an item. Once the
+/// history is full, the oldest entry is dropped for every new entry.
+pub const MAX_ITEM_HISTORY_LENGTH: usize = 32;
+
+/// An entry in the history of an item returned by the `getItemHistory` view.
+#[derive(Debug, Serialize, SchemaType, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct ItemHistoryEntry {
+    /// The status of the item after the item was created or updated.
+    pub status:    Status,
+    /// The slot time of the block in which the item was created or updated.
+    pub timestamp: Timestamp,
+    /// The address that created or updated the item. For a status change with
+    /// a sponsored transaction, this is the signer of the `PermitMessage`.
+    pub actor:     Address,
 }
 
 /// The state of the smart contract.
@@ -201,6 +458,12 @@ struct State<S = StateApi> {
     next_item_id:    u64,
     /// A map containing all roles granted to addresses.
     roles:           StateMap<Address, AddressRoleState<S>, S>,
+    /// A map containing all addresses that were ever granted a role by their
+    /// index, so that the addresses can be looked up in order of their index.
+    role_addresses:  StateMap<u64, Address, S>,
+    /// The next index that will be assigned to an address when it is granted
+    /// its first role. This value is sequentially increased by 1.
+    next_role_index: u64,
     /// A map containing all items with their states.
     items:           StateMap<ItemID, ItemState, S>,
     /// A map containing all allowed transitions of the state machine.
@@ -215,6 +478,40 @@ struct State<S = StateApi> {
     /// mapping keeps track of the next nonce that needs to be used by the
     /// account to generate a signature.
     nonces_registry: StateMap<AccountAddress, u64, S>,
+    /// The roles that are allowed to create items. An address can create items
+    /// if it has at least one of these roles.
+    creator_roles:   Vec<Roles>,
+    /// The hook that is invoked on every status change of an item, if
+    /// configured by the Admin.
+    status_hook:     Option<StatusHook>,
+    /// The CIS-2 contract minting a twin token for every item, if configured
+    /// when the contract was initialized.
+    twin_token:      Option<ContractAddress>,
+    /// A map containing the owners of the items (and their twin tokens, if
+    /// minted), initially the creators of the items.
+    item_owners:     StateMap<ItemID, Address, S>,
+    /// Whether the contract is paused by the Admin. While paused, no items can
+    /// be created, changed, transferred, or archived.
+    paused:          bool,
+    /// The names of the custom statuses registered in addition to the built-in
+    /// statuses. The custom status `Status::Custom(i)` is named by the `i`-th
+    /// entry.
+    custom_statuses: Vec<String>,
+    /// A map containing the last [`MAX_ITEM_HISTORY_LENGTH`] statuses of the
+    /// items, oldest first.
+    item_history:    StateMap<ItemID, Vec<ItemHistoryEntry>, S>,
+    /// The time after which an item that is `Sold` can be archived by anyone,
+    /// if configured by the Admin. The Admin can archive items at any time.
+    archive_after:   Option<Duration>,
+    /// The number of addresses with the Admin role. The last Admin can not be
+    /// revoked, so that the contract can always be administered.
+    admin_count:     u32,
+    /// The addresses proposed as new Admins that have not accepted the Admin
+    /// role yet.
+    pending_admins:  StateSet<Address, S>,
+    /// A map containing the maximum durations items may stay in a status
+    /// before they can be marked `Expired`.
+    max_durations:   StateMap<Status, Duration, S>,
 }
 
 /// The different errors the contract can produce.
@@ -257,6 +554,50 @@ pub enum CustomContractError {
     Expired, // -15
     /// Update of state machine was unsuccessful.
     Unsuccessful, // -16
+    /// The metadata of the item is frozen and can no longer be updated.
+    MetadataFrozen, // -17
+    /// The status hook failed and its failure policy rejects the status
+    /// change.
+    HookFailed, // -18
+    /// The contract mints no twin tokens, so items have no owners.
+    TwinTokenNotConfigured, // -19
+    /// Minting or transferring the twin token of an item in the CIS-2
+    /// contract failed.
+    TwinTokenFailed, // -20
+    /// The contract is paused and does not accept state changes.
+    Paused, // -21
+    /// Upgrade failed because the new module does not exist.
+    FailedUpgradeMissingModule, // -22
+    /// Upgrade failed because the new module does not contain a contract with
+    /// a matching name.
+    FailedUpgradeMissingContract, // -23
+    /// Upgrade failed because the smart contract version of the module is not
+    /// supported.
+    FailedUpgradeUnsupportedModuleVersion, // -24
+    /// The migration function invoked after the upgrade failed.
+    FailedUpgradeMigration, // -25
+    /// The status is a custom status that is not registered.
+    UnknownStatus, // -26
+    /// A custom status with the same name is already registered.
+    CustomStatusAlreadyExists, // -27
+    /// The maximum number of custom statuses (256) is registered.
+    TooManyCustomStatuses, // -28
+    /// The item is not sold or the archive period has not passed since the
+    /// item was sold.
+    NotArchivable, // -29
+    /// The Admin role can only be granted by proposing the new Admin with
+    /// `proposeAdmin` and accepting it with `acceptAdmin`.
+    AdminRequiresAcceptance, // -30
+    /// The last Admin can not be revoked.
+    LastAdmin, // -31
+    /// The sender was not proposed as a new Admin.
+    AdminNotProposed, // -32
+    /// The status of the item has no maximum duration or the maximum duration
+    /// has not passed since the item entered the status.
+    NotExpired, // -33
+    /// The handover message does not start with the `CUSTODY_HANDOVER_TAG` or
+    /// hands over the item to its current owner.
+    InvalidHandover, // -34
 }
 
 /// Mapping account signature error to CustomContractError
@@ -269,6 +610,17 @@ impl From<CheckAccountSignatureError> for CustomContractError {
     }
 }
 
+/// Mapping the upgrade errors to CustomContractError.
+impl From<UpgradeError> for CustomContractError {
+    fn from(ue: UpgradeError) -> Self {
+        match ue {
+            UpgradeError::MissingModule => Self::FailedUpgradeMissingModule,
+            UpgradeError::MissingContract => Self::FailedUpgradeMissingContract,
+            UpgradeError::UnsupportedModuleVersion => Self::FailedUpgradeUnsupportedModuleVersion,
+        }
+    }
+}
+
 /// Mapping the logging errors to CustomContractError.
 impl From<LogError> for CustomContractError {
     fn from(le: LogError) -> Self {
@@ -369,17 +721,47 @@ impl<S: HasStateApi> State<S> {
         Ok((item, transitions))
     }
 
-    /// Create the state and state machine from a vector of transition edges.
-    pub fn from_iter(state_builder: &mut StateBuilder<S>, i: Vec<TransitionEdges>) -> Self {
+    /// Create the state and state machine from a vector of transition edges,
+    /// the roles that are allowed to create items, and the custom statuses.
+    /// Fails if the custom statuses can not be registered or a transition edge
+    /// uses a custom status that is not registered.
+    pub fn from_iter(
+        state_builder: &mut StateBuilder<S>,
+        params: InitParams,
+    ) -> ContractResult<Self> {
         let mut r = Self {
             next_item_id:    0u64,
             roles:           state_builder.new_map(),
+            role_addresses:  state_builder.new_map(),
+            next_role_index: 0,
             items:           state_builder.new_map(),
             transitions:     state_builder.new_map(),
             nonces_registry: state_builder.new_map(),
+            creator_roles:   params.creator_roles,
+            status_hook:     None,
+            twin_token:      params.twin_token,
+            item_owners:     state_builder.new_map(),
+            paused:          false,
+            custom_statuses: Vec::new(),
+            item_history:    state_builder.new_map(),
+            archive_after:   None,
+            admin_count:     0,
+            pending_admins:  state_builder.new_set(),
+            max_durations:   state_builder.new_map(),
         };
-        for transition_edge in i {
+        for name in params.custom_statuses {
+            r.add_custom_status(name)?;
+        }
+        for transition_edge in params.transitions {
+            ensure!(
+                r.is_known_status(&transition_edge.from),
+                CustomContractError::UnknownStatus
+            );
+            if let Some(max_duration) = transition_edge.max_duration {
+                r.limit_duration(transition_edge.from, max_duration);
+            }
             for to in transition_edge.to {
+                ensure!(r.is_known_status(&to), CustomContractError::UnknownStatus);
                 r.add(
                     state_builder,
                     transition_edge.from,
@@ -388,11 +770,95 @@ impl<S: HasStateApi> State<S> {
                 );
             }
         }
-        r
+        Ok(r)
+    }
+
+    /// Limit the time items may stay in the `status` to `max_duration`. If the
+    /// status already has a maximum duration, the shorter one applies.
+    fn limit_duration(&mut self, status: Status, max_duration: Duration) {
+        self.max_durations
+            .entry(status)
+            .and_modify(|current| *current = (*current).min(max_duration))
+            .or_insert(max_duration);
+    }
+
+    /// Get the time at which an item overstays its current status, i.e. the
+    /// time it entered the status plus the maximum duration of the status.
+    /// Returns `None` if the status has no maximum duration or the item is
+    /// already `Expired`.
+    fn expires_at(&self, item_id: &ItemID) -> ContractResult<Option<Timestamp>> {
+        let item = self
+            .items
+            .get(item_id)
+            .ok_or(CustomContractError::ItemDoesNotExist)?;
+        if item.status == Status::Expired {
+            return Ok(None);
+        }
+        Ok(self
+            .max_durations
+            .get(&item.status)
+            .and_then(|max_duration| item.updated_at.checked_add(*max_duration)))
+    }
+
+    /// Register a custom status with the given name and return the status.
+    fn add_custom_status(&mut self, name: String) -> ContractResult<Status> {
+        ensure!(
+            !self.custom_statuses.contains(&name),
+            CustomContractError::CustomStatusAlreadyExists
+        );
+        let index = u8::try_from(self.custom_statuses.len())
+            .map_err(|_| CustomContractError::TooManyCustomStatuses)?;
+        self.custom_statuses.push(name);
+        Ok(Status::Custom(index))
+    }
+
+    /// Append an entry to the history of an item and drop the oldest entry if
+    /// the history is full.
+    fn record_history(&mut self, item_id: ItemID, entry: ItemHistoryEntry) {
+        let mut history = self.item_history.entry(item_id).or_insert_with(Vec::new);
+        if history.len() >= MAX_ITEM_HISTORY_LENGTH {
+            history.remove(0);
+        }
+        history.push(entry);
+    }
+
+    /// Check if an item that is `Sold` can be archived at `now` because the
+    /// archive period has passed since the item was sold.
+    fn is_archivable(&self, item_id: &ItemID, now: Timestamp) -> ContractResult<bool> {
+        let item = self
+            .items
+            .get(item_id)
+            .ok_or(CustomContractError::ItemDoesNotExist)?;
+        let Some(archive_after) = self.archive_after else {
+            return Ok(false);
+        };
+        if item.status != Status::Sold {
+            return Ok(false);
+        }
+        // The last status change of a sold item is the status change to `Sold`.
+        Ok(item
+            .updated_at
+            .checked_add(archive_after)
+            .map_or(false, |archivable_at| now >= archivable_at))
+    }
+
+    /// Check if a status is a built-in status or a registered custom status.
+    fn is_known_status(&self, status: &Status) -> bool {
+        match status {
+            Status::Custom(index) => usize::from(*index) < self.custom_statuses.len(),
+            _ => true,
+        }
     }
 
     /// Grant role to an address.
     fn grant_role(&mut self, account: &Address, role: Roles, state_builder: &mut StateBuilder<S>) {
+        if role == Roles::Admin && !self.has_role(account, Roles::Admin) {
+            self.admin_count += 1;
+        }
+        if self.roles.get(account).is_none() {
+            self.role_addresses.insert(self.next_role_index, *account);
+            self.next_role_index += 1;
+        }
         self.roles
             .entry(*account)
             .or_insert_with(|| AddressRoleState {
@@ -406,6 +872,9 @@ impl<S: HasStateApi> State<S> {
 
     /// Revoke role from an address.
     fn revoke_role(&mut self, account: &Address, role: Roles) {
+        if role == Roles::Admin && self.has_role(account, Roles::Admin) {
+            self.admin_count -= 1;
+        }
         self.roles.entry(*account).and_modify(|entry| {
             entry.roles.remove(&role);
         });
@@ -418,11 +887,20 @@ impl<S: HasStateApi> State<S> {
             Some(roles) => roles.roles.contains(&role),
         };
     }
+
+    /// Check if an address has one of the roles that are allowed to create
+    /// items.
+    fn can_create_item(&self, account: &Address) -> bool {
+        self.creator_roles
+            .iter()
+            .any(|role| self.has_role(account, *role))
+    }
 }
 
 /// The parameter type for the contract function `init` which
-/// initilizes a new instance of the contract.
-#[derive(Serialize, SchemaType)]
+/// initilizes a new instance of the contract. It is also the return type of
+/// the `getStateMachine` view.
+#[derive(Debug, Serialize, SchemaType, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct TransitionEdges {
     /// The status of the `from` node of the transition edges.
@@ -432,12 +910,40 @@ pub struct TransitionEdges {
     /// The account that is allowed to execute the state transitions described
     /// above.
     pub authorized_account: AccountAddress,
+    /// The maximum duration an item may stay in the `from` status. Once it has
+    /// passed, anyone can mark the item `Expired` with `expireItem`. If several
+    /// edges from the same status have a maximum duration, the shortest one
+    /// applies.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_duration:       Option<Duration>,
+}
+
+/// The parameter type for the contract function `init`.
+#[derive(Debug, Serialize, SchemaType, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct InitParams {
+    /// The transition edges of the state machine.
+    pub transitions:     Vec<TransitionEdges>,
+    /// The roles that are allowed to create items, e.g. `[Admin]` if only the
+    /// Admin creates items or `[Admin, Producer]` if producers register their
+    /// items themselves.
+    pub creator_roles:   Vec<Roles>,
+    /// The CIS-2 contract minting a twin token for every item, so that wallets
+    /// display the items owned by an account as tokens. If `None`, no twin
+    /// tokens are minted.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub twin_token:      Option<ContractAddress>,
+    /// The names of the custom statuses registered in addition to the built-in
+    /// statuses, e.g. `["CustomsCleared", "Recalled"]`. The `i`-th name is
+    /// registered as `Status::Custom(i)` and can be used in the `transitions`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub custom_statuses: Vec<String>,
 }
 
 /// Init function that creates a new contract.
 #[init(
     contract = "track_and_trace",
-    parameter = "Vec<TransitionEdges>",
+    parameter = "InitParams",
     event = "Event<AdditionalData>",
     enable_logger
 )]
@@ -447,9 +953,9 @@ fn init(
     logger: &mut impl HasLogger,
 ) -> InitResult<State> {
     // Parse the parameter.
-    let iter: Vec<TransitionEdges> = ctx.parameter_cursor().get()?;
+    let params: InitParams = ctx.parameter_cursor().get()?;
 
-    let mut state = State::from_iter(state_builder, iter);
+    let mut state = State::from_iter(state_builder, params)?;
 
     // Get the instantiater of this contract instance.
     let invoker = Address::Account(ctx.init_origin());
@@ -461,9 +967,73 @@ fn init(
         role:    Roles::Admin,
     }))?;
 
+    // Log the registered custom statuses.
+    for (index, name) in state.custom_statuses.iter().enumerate() {
+        logger.log(&Event::<AdditionalData>::CustomStatusAdded(
+            CustomStatusAddedEvent {
+                status: Status::Custom(index as u8),
+                name:   name.clone(),
+            },
+        ))?;
+    }
+
+    // Log the transition edges and maximum durations of the state machine.
+    for (from_status, status_transitions) in state.transitions.iter() {
+        for (address, targets) in status_transitions.transitions.iter() {
+            for to_status in targets.iter() {
+                logger.log(&Event::<AdditionalData>::TransitionEdgeUpdated(
+                    TransitionEdgeUpdatedEvent {
+                        address:     *address,
+                        from_status: *from_status,
+                        to_status:   *to_status,
+                        update:      Update::Add,
+                    },
+                ))?;
+            }
+        }
+    }
+    for (status, max_duration) in state.max_durations.iter() {
+        logger.log(&Event::<AdditionalData>::MaxDurationUpdated(
+            MaxDurationUpdatedEvent {
+                status:       *status,
+                max_duration: Some(*max_duration),
+            },
+        ))?;
+    }
+
+    // Log the roles allowed to create items.
+    logger.log(&Event::<AdditionalData>::CreateItemRolesUpdated(
+        CreateItemRolesUpdatedEvent {
+            roles: state.creator_roles.clone(),
+        },
+    ))?;
+
     Ok(state)
 }
 
+/// View the history of an item, oldest first. Only the last
+/// [`MAX_ITEM_HISTORY_LENGTH`] entries are kept.
+#[receive(
+    contract = "track_and_trace",
+    name = "getItemHistory",
+    parameter = "ItemID",
+    return_value = "Vec<ItemHistoryEntry>",
+    error = "CustomContractError"
+)]
+fn contract_get_item_history(
+    ctx: &ReceiveContext,
+    host: &Host<State>,
+) -> ContractResult<Vec<ItemHistoryEntry>> {
+    // Parse the parameter.
+    let item_id: ItemID = ctx.parameter_cursor().get()?;
+
+    host.state()
+        .item_history
+        .get(&item_id)
+        .map(|history| (*history).clone())
+        .ok_or(CustomContractError::ItemDoesNotExist)
+}
+
 /// View the next item id from the state.
 #[receive(
     contract = "track_and_trace",
@@ -503,6 +1073,86 @@ fn contract_get_roles(ctx: &ReceiveContext, host: &Host<State>) -> ReceiveResult
     Ok(roles)
 }
 
+/// The maximum number of addresses returned by the `getAddressesWithRole`
+/// view.
+pub const MAX_ADDRESS_PAGE_SIZE: u32 = 100;
+
+/// The maximum number of address indices looked up by one call of
+/// `getAddressesWithRole`, which bounds the energy of a call when many
+/// consecutive addresses do not have the role.
+pub const MAX_ROLE_ADDRESSES_SCANNED: u64 = 1000;
+
+/// The parameter type for the contract function `getAddressesWithRole` which
+/// returns a page of the addresses that have a role.
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq, Clone)]
+pub struct GetAddressesWithRoleParams {
+    /// The role the addresses have.
+    pub role:        Roles,
+    /// The index of the first address of the page.
+    pub start_index: u64,
+    /// The maximum number of addresses in the page. At most
+    /// [`MAX_ADDRESS_PAGE_SIZE`] addresses are returned.
+    pub limit:       u32,
+}
+
+/// The return value of the contract function `getAddressesWithRole`.
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq, Clone)]
+pub struct AddressesWithRolePage {
+    /// The addresses with the role ordered by their index.
+    pub addresses:        Vec<Address>,
+    /// The `start_index` of the next page or `None` if there are no more
+    /// addresses.
+    pub next_start_index: Option<u64>,
+}
+
+/// View a page of the addresses that have a role, e.g. all Admins for an admin
+/// dashboard. Every address is assigned a sequential index when it is granted
+/// its first role, and the addresses are returned in the order of their index
+/// starting at the `start_index`. At most [`MAX_ROLE_ADDRESSES_SCANNED`]
+/// indices are looked up per call, so a page can contain fewer addresses than
+/// the `limit` (or none at all) after a long run of addresses without the role.
+/// The next page starts at the returned `next_start_index`, which is `None`
+/// once there are no more addresses.
+#[receive(
+    contract = "track_and_trace",
+    name = "getAddressesWithRole",
+    parameter = "GetAddressesWithRoleParams",
+    return_value = "AddressesWithRolePage"
+)]
+fn contract_get_addresses_with_role(
+    ctx: &ReceiveContext,
+    host: &Host<State>,
+) -> ReceiveResult<AddressesWithRolePage> {
+    // Parse the parameter.
+    let params: GetAddressesWithRoleParams = ctx.parameter_cursor().get()?;
+
+    let state = host.state();
+    let limit = params.limit.min(MAX_ADDRESS_PAGE_SIZE) as usize;
+    let mut addresses = Vec::with_capacity(limit);
+
+    // The addresses are looked up by their index instead of iterating the
+    // roles map, so that a page does not walk the addresses of previous pages.
+    let end_index = state.next_role_index.min(
+        params
+            .start_index
+            .saturating_add(MAX_ROLE_ADDRESSES_SCANNED),
+    );
+    let mut next_index = params.start_index;
+    while next_index < end_index && addresses.len() < limit {
+        if let Some(address) = state.role_addresses.get(&next_index) {
+            if state.has_role(&address, params.role) {
+                addresses.push(*address);
+            }
+        }
+        next_index += 1;
+    }
+
+    Ok(AddressesWithRolePage {
+        addresses,
+        next_start_index: (next_index < state.next_role_index).then_some(next_index),
+    })
+}
+
 /// View the state of an item.
 #[receive(
     contract = "track_and_trace",
@@ -521,13 +1171,87 @@ fn contract_get_item_state(ctx: &ReceiveContext, host: &Host<State>) -> ReceiveR
         .ok_or(CustomContractError::ItemDoesNotExist.into())
 }
 
-/// Receive function for the Admin to create a new item.
+/// The maximum number of items returned by the `getItemStatesPaged` view.
+pub const MAX_ITEM_PAGE_SIZE: u32 = 100;
+
+/// The maximum number of item ids looked up by one call of
+/// `getItemStatesPaged`, which bounds the energy of a call when many
+/// consecutive items are archived.
+pub const MAX_ITEM_IDS_SCANNED: u64 = 1000;
+
+/// The parameter type for the contract function `getItemStatesPaged` which
+/// returns a page of the items.
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq, Clone)]
+pub struct GetItemStatesPagedParams {
+    /// The id of the first item of the page.
+    pub start_id: ItemID,
+    /// The maximum number of items in the page. At most
+    /// [`MAX_ITEM_PAGE_SIZE`] items are returned.
+    pub limit:    u32,
+}
+
+/// The return value of the contract function `getItemStatesPaged`.
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq, Clone)]
+pub struct ItemStatesPage {
+    /// The items of the page with their states ordered by the item id.
+    pub items:         Vec<(ItemID, ItemState)>,
+    /// The `start_id` of the next page or `None` if there are no more items.
+    pub next_start_id: Option<ItemID>,
+}
+
+/// View a page of the items with their states ordered by the item id, starting
+/// at the `start_id`. Archived items are skipped, so the page can contain gaps
+/// in the item ids. At most [`MAX_ITEM_IDS_SCANNED`] item ids are looked up per
+/// call, so a page can contain fewer items than the `limit` (or none at all)
+/// after a long run of archived items. The next page starts at the returned
+/// `next_start_id`, which is `None` once there are no more items.
+#[receive(
+    contract = "track_and_trace",
+    name = "getItemStatesPaged",
+    parameter = "GetItemStatesPagedParams",
+    return_value = "ItemStatesPage"
+)]
+fn contract_get_item_states_paged(
+    ctx: &ReceiveContext,
+    host: &Host<State>,
+) -> ReceiveResult<ItemStatesPage> {
+    // Parse the parameter.
+    let params: GetItemStatesPagedParams = ctx.parameter_cursor().get()?;
+
+    let state = host.state();
+    let limit = params.limit.min(MAX_ITEM_PAGE_SIZE) as usize;
+    let mut items = Vec::with_capacity(limit);
+
+    // The item ids are assigned sequentially, so the items are looked up by id
+    // instead of iterating the map, which is not ordered by the item id.
+    let end_id = state
+        .next_item_id
+        .min(params.start_id.0.saturating_add(MAX_ITEM_IDS_SCANNED));
+    let mut next_id = params.start_id.0;
+    while next_id < end_id && items.len() < limit {
+        let item_id = ItemID::from(next_id);
+        if let Some(item) = state.items.get(&item_id) {
+            items.push((item_id, item.clone()));
+        }
+        next_id += 1;
+    }
+
+    Ok(ItemStatesPage {
+        items,
+        next_start_id: (next_id < state.next_item_id).then(|| ItemID::from(next_id)),
+    })
+}
+
+/// Receive function to create a new item. Only addresses with one of the roles
+/// allowed to create items (see `setCreateItemRoles`) can create items.
 ///
 /// It rejects if:
 /// - It fails to parse the parameter.
-/// - The sender is not the Admin of the contract instance.
+/// - The contract is paused.
+/// - The sender has none of the roles that are allowed to create items.
 /// - The item already exists in the state which should technically not happen.
-/// - It fails to log the `ItemCreatedEvent`.
+/// - It fails to log the `ItemCreatedEvent` or the `ItemSummaryEvent`.
+/// - Twin tokens are minted and minting the twin token of the item fails.
 #[receive(
     contract = "track_and_trace",
     name = "createItem",
@@ -536,7 +1260,7 @@ fn contract_get_item_state(ctx: &ReceiveContext, host: &Host<State>) -> ReceiveR
     mutable,
     enable_logger
 )]
-fn create_item(
+fn contract_create_item(
     ctx: &ReceiveContext,
     host: &mut Host<State>,
     logger: &mut impl HasLogger,
@@ -544,9 +1268,30 @@ fn create_item(
     // Parse the parameter.
     let metadata_url: Option<MetadataUrl> = ctx.parameter_cursor().get()?;
 
-    // Check that only the Admin is authorized to create a new item.
+    // Check that the contract is not paused.
+    ensure!(!host.state().paused, CustomContractError::Paused);
+
+    create_item(
+        metadata_url,
+        ctx.sender(),
+        ctx.metadata().slot_time(),
+        host,
+        logger,
+    )
+}
+
+/// Helper function to create an item by the `creator` at the `timestamp`.
+fn create_item(
+    metadata_url: Option<MetadataUrl>,
+    creator: Address,
+    timestamp: Timestamp,
+    host: &mut Host<State>,
+    logger: &mut impl HasLogger,
+) -> ContractResult<()> {
+    // Check that only the addresses with a role that is allowed to create items
+    // are authorized to create a new item.
     ensure!(
-        host.state().has_role(&ctx.sender(), Roles::Admin),
+        host.state().can_create_item(&creator),
         CustomContractError::Unauthorized
     );
 
@@ -557,114 +1302,1066 @@ fn create_item(
 
     let item_id = ItemID::from(next_item_id);
 
-    // Create the item in state.
-    let previous_item = host.state_mut().items.insert(item_id, ItemState {
+    let item = ItemState {
         metadata_url: metadata_url.clone(),
-        status:       Status::Produced,
-    });
+        status: Status::Produced,
+        metadata_frozen: false,
+        creator,
+        created_at: timestamp,
+        updated_at: timestamp,
+    };
+    let summary = ItemSummaryEvent::new(item_id, &item);
+
+    // Create the item in state.
+    let previous_item = host.state_mut().items.insert(item_id, item);
 
     ensure_eq!(previous_item, None, CustomContractError::ItemAlreadyExists);
 
+    host.state_mut().record_history(item_id, ItemHistoryEntry {
+        status: Status::Produced,
+        timestamp,
+        actor: creator,
+    });
+
     // Log an ItemCreatedEvent.
     logger.log(&Event::<AdditionalData>::ItemCreated(ItemCreatedEvent {
         item_id,
-        metadata_url,
+        metadata_url: metadata_url.clone(),
         initial_status: Status::Produced,
     }))?;
 
-    Ok(())
-}
-
-/// Partial parameter type for the contract function
-/// `changeItemStatus`.
-#[derive(Serialize, SchemaType, Debug, PartialEq, Eq, Clone)]
-#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
-pub struct AdditionalData {
-    /// Any additional data encoded as generic bytes. Usecase-specific data can
-    /// be included here such as temperature, longitude, latitude, ... .
-    pub bytes: Vec<u8>,
-}
-
-impl AdditionalData {
-    pub fn empty() -> Self { AdditionalData { bytes: vec![] } }
+    // Log an ItemSummaryEvent.
+    logger.log(&Event::<AdditionalData>::ItemSummary(summary))?;
 
-    pub fn from_bytes(bytes: Vec<u8>) -> Self { AdditionalData { bytes } }
-}
+    // Record the creator as the first owner of the item.
+    host.state_mut().item_owners.insert(item_id, creator);
 
-/// The parameter type for the contract function `changeItemStatus` which
-/// updates the status of an item.
-#[derive(Serialize, SchemaType)]
-pub struct ChangeItemStatusParams<A> {
-    /// The item's id.
-    pub item_id:         ItemID,
-    /// The item's new status.
-    pub new_status:      Status,
-    /// Any additional data encoded as generic bytes. Usecase-specific data can
-    /// be included here such as temperature, longitude, latitude, ... .
-    pub additional_data: A,
+    mint_twin_token(host, item_id, creator, metadata_url)
 }
 
-/// Receive function to update the item's
-/// status based on the rules of the state machine.
+/// Receive function for the Admin to permanently lock the metadata of an item.
+/// Once frozen, the metadata of the item can no longer be updated which makes
+/// the record provably immutable (e.g. after the goods have been shipped).
 ///
 /// It rejects if:
 /// - It fails to parse the parameter.
-/// - Sender is not an authorized role to update the item to the next state.
+/// - The contract is paused.
+/// - The sender is not the Admin of the contract instance.
 /// - The item does not exist in the state.
-/// - A contract is invoking the function.
-/// - It fails to log the `ItemStatusChangedEvent`.
+/// - The metadata of the item is already frozen.
+/// - It fails to log the `ItemMetadataFrozenEvent` or the `ItemSummaryEvent`.
 #[receive(
     contract = "track_and_trace",
-    name = "changeItemStatus",
-    parameter = "ChangeItemStatusParams<AdditionalData>",
+    name = "freezeItemMetadata",
+    parameter = "ItemID",
     error = "CustomContractError",
     mutable,
     enable_logger
 )]
-fn contract_change_item_status(
+fn contract_freeze_item_metadata(
     ctx: &ReceiveContext,
     host: &mut Host<State>,
     logger: &mut impl HasLogger,
 ) -> ContractResult<()> {
     // Parse the parameter.
-    let param: ChangeItemStatusParams<AdditionalData> = ctx.parameter_cursor().get()?;
+    let item_id: ItemID = ctx.parameter_cursor().get()?;
 
-    let account = match ctx.sender() {
-        Address::Account(account) => account,
-        Address::Contract(_) => bail!(CustomContractError::NoContract),
-    };
+    // Check that the contract is not paused.
+    ensure!(!host.state().paused, CustomContractError::Paused);
 
-    change_item_status(param, account, host, logger)
-}
+    // Check that only the Admin is authorized to freeze the metadata of an item.
+    ensure!(
+        host.state().has_role(&ctx.sender(), Roles::Admin),
+        CustomContractError::Unauthorized
+    );
 
-/// Helper function to update the item's status based on the rules of the state.
-fn change_item_status(
-    param: ChangeItemStatusParams<AdditionalData>,
-    account: AccountAddress,
-    host: &mut Host<State>,
-    logger: &mut impl HasLogger,
-) -> ContractResult<()> {
-    let (mut item, allowed_transitions) =
-        host.state_mut().get_item_and_transitions(&param.item_id)?;
+    let mut item = host
+        .state_mut()
+        .items
+        .get_mut(&item_id)
+        .ok_or(CustomContractError::ItemDoesNotExist)?;
 
-    let verify = allowed_transitions.check(&account, &param.new_status);
+    ensure!(!item.metadata_frozen, CustomContractError::MetadataFrozen);
 
-    // Check that transition adheres to the state machine rules.
-    ensure!(verify, CustomContractError::Unauthorized);
+    item.metadata_frozen = true;
+    let summary = ItemSummaryEvent::new(item_id, &item);
+    drop(item);
 
-    // Update the state of the item.
-    item.status = param.new_status;
+    // Log an ItemMetadataFrozenEvent.
+    logger.log(&Event::<AdditionalData>::ItemMetadataFrozen(
+        ItemMetadataFrozenEvent { item_id },
+    ))?;
 
-    // Log an ItemStatusChangedEvent.
-    logger.log(&Event::ItemStatusChanged(ItemStatusChangedEvent {
-        item_id:         param.item_id,
-        new_status:      param.new_status,
-        additional_data: param.additional_data,
+    // Log an ItemSummaryEvent.
+    logger.log(&Event::<AdditionalData>::ItemSummary(summary))?;
+
+    Ok(())
+}
+
+/// The parameter type for the contract function `updateItemMetadata` which
+/// replaces the metadata_url of an item.
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct UpdateItemMetadataParams {
+    /// The item's id.
+    pub item_id:      ItemID,
+    /// The item's new metadata_url.
+    pub metadata_url: Option<MetadataUrl>,
+}
+
+/// Receive function for the Admin or the creator of an item to replace the
+/// metadata_url of the item, e.g. to correct a typo or to point to a new
+/// location of the metadata. The metadata URL of the twin token of the item is
+/// updated along, so that wallets show the new metadata.
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+/// - The contract is paused.
+/// - The item does not exist in the state.
+/// - The sender is neither the Admin of the contract instance nor the address
+///   that created the item.
+/// - The metadata of the item is frozen.
+/// - It fails to log the `ItemMetadataUpdatedEvent` or the `ItemSummaryEvent`.
+/// - Twin tokens are minted and updating the metadata URL of the twin token of
+///   the item fails.
+#[receive(
+    contract = "track_and_trace",
+    name = "updateItemMetadata",
+    parameter = "UpdateItemMetadataParams",
+    error = "CustomContractError",
+    mutable,
+    enable_logger
+)]
+fn contract_update_item_metadata(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+    logger: &mut impl HasLogger,
+) -> ContractResult<()> {
+    // Parse the parameter.
+    let param: UpdateItemMetadataParams = ctx.parameter_cursor().get()?;
+
+    // Check that the contract is not paused.
+    ensure!(!host.state().paused, CustomContractError::Paused);
+
+    let sender = ctx.sender();
+    let is_admin = host.state().has_role(&sender, Roles::Admin);
+
+    let mut item = host
+        .state_mut()
+        .items
+        .get_mut(&param.item_id)
+        .ok_or(CustomContractError::ItemDoesNotExist)?;
+
+    // Check that only the Admin or the creator of the item is authorized to
+    // update the metadata of the item.
+    ensure!(
+        is_admin || item.creator == sender,
+        CustomContractError::Unauthorized
+    );
+
+    ensure!(!item.metadata_frozen, CustomContractError::MetadataFrozen);
+
+    item.metadata_url = param.metadata_url.clone();
+    let summary = ItemSummaryEvent::new(param.item_id, &item);
+    drop(item);
+
+    // Log an ItemMetadataUpdatedEvent.
+    logger.log(&Event::<AdditionalData>::ItemMetadataUpdated(
+        ItemMetadataUpdatedEvent {
+            item_id:      param.item_id,
+            metadata_url: param.metadata_url.clone(),
+        },
+    ))?;
+
+    // Log an ItemSummaryEvent.
+    logger.log(&Event::<AdditionalData>::ItemSummary(summary))?;
+
+    update_twin_token_metadata(host, param.item_id, param.metadata_url)
+}
+
+/// View the time after which an item that is `Sold` can be archived by anyone.
+#[receive(
+    contract = "track_and_trace",
+    name = "getArchivePeriod",
+    return_value = "Option<Duration>"
+)]
+fn contract_get_archive_period(
+    _ctx: &ReceiveContext,
+    host: &Host<State>,
+) -> ReceiveResult<Option<Duration>> {
+    Ok(host.state().archive_after)
+}
+
+/// Set the time after which an item that is `Sold` can be archived by anyone.
+/// If `None`, only the Admin can archive items.
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+/// - The sender is not the Admin of the contract instance.
+#[receive(
+    contract = "track_and_trace",
+    name = "setArchivePeriod",
+    parameter = "Option<Duration>",
+    error = "CustomContractError",
+    mutable
+)]
+fn contract_set_archive_period(ctx: &ReceiveContext, host: &mut Host<State>) -> ContractResult<()> {
+    // Parse the parameter.
+    let archive_after: Option<Duration> = ctx.parameter_cursor().get()?;
+
+    // Check that only the Admin is authorized to configure the archive period.
+    ensure!(
+        host.state().has_role(&ctx.sender(), Roles::Admin),
+        CustomContractError::Unauthorized
+    );
+
+    host.state_mut().archive_after = archive_after;
+    Ok(())
+}
+
+/// Receive function to archive an item. The state (including the creator),
+/// history, and owner of the item are removed from the contract to keep the
+/// contract state bounded, while the events of the item remain available to
+/// indexers. The twin token of the item is burned, so that wallets no longer
+/// show a token for the archived item. The Admin can archive any item. Anyone
+/// else can archive an item once it has been `Sold` for the archive period (see
+/// `setArchivePeriod`).
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+/// - The contract is paused.
+/// - The item does not exist in the state.
+/// - The sender is not the Admin and the item is not archivable, because the
+///   item is not sold, no archive period is configured, or the archive period
+///   has not passed since the item was sold.
+/// - It fails to log the `ItemArchivedEvent`.
+/// - Twin tokens are minted and burning the twin token of the item fails.
+#[receive(
+    contract = "track_and_trace",
+    name = "archiveItem",
+    parameter = "ItemID",
+    error = "CustomContractError",
+    mutable,
+    enable_logger
+)]
+fn contract_archive_item(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+    logger: &mut impl HasLogger,
+) -> ContractResult<()> {
+    // Parse the parameter.
+    let item_id: ItemID = ctx.parameter_cursor().get()?;
+
+    // Check that the contract is not paused.
+    ensure!(!host.state().paused, CustomContractError::Paused);
+
+    let is_archivable = host
+        .state()
+        .is_archivable(&item_id, ctx.metadata().slot_time())?;
+
+    // Check that the Admin can archive any item and anyone else only items that
+    // are archivable.
+    ensure!(
+        is_archivable || host.state().has_role(&ctx.sender(), Roles::Admin),
+        CustomContractError::NotArchivable
+    );
+
+    let state = host.state_mut();
+    state.items.remove(&item_id);
+    state.item_history.remove(&item_id);
+    let owner = state.item_owners.remove_and_get(&item_id);
+
+    // Log an ItemArchivedEvent.
+    logger.log(&Event::<AdditionalData>::ItemArchived(ItemArchivedEvent {
+        item_id,
+    }))?;
+
+    match owner {
+        Some(owner) => burn_twin_token(host, item_id, owner),
+        None => Ok(()),
+    }
+}
+
+/// Receive function to mark an item `Expired` because it stayed in its status
+/// longer than the maximum duration of the status (see the `max_duration` of
+/// the transition edges). Anyone can expire an item, e.g. a service monitoring
+/// perishable goods. The status hook is invoked with the `Expired` status.
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+/// - The contract is paused.
+/// - The item does not exist in the state.
+/// - The status of the item has no maximum duration or the maximum duration has
+///   not passed since the item entered the status.
+/// - It fails to log the `ItemExpiredEvent` or the `ItemSummaryEvent`.
+/// - The status hook fails and its failure policy rejects the status change.
+#[receive(
+    contract = "track_and_trace",
+    name = "expireItem",
+    parameter = "ItemID",
+    error = "CustomContractError",
+    mutable,
+    enable_logger
+)]
+fn contract_expire_item(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+    logger: &mut impl HasLogger,
+) -> ContractResult<()> {
+    // Parse the parameter.
+    let item_id: ItemID = ctx.parameter_cursor().get()?;
+
+    // Check that the contract is not paused.
+    ensure!(!host.state().paused, CustomContractError::Paused);
+
+    // Check that the item overstayed its status.
+    let now = ctx.metadata().slot_time();
+    let expires_at = host.state().expires_at(&item_id)?;
+    ensure!(
+        expires_at.map_or(false, |expires_at| now >= expires_at),
+        CustomContractError::NotExpired
+    );
+
+    let mut item = host
+        .state_mut()
+        .items
+        .get_mut(&item_id)
+        .ok_or(CustomContractError::ItemDoesNotExist)?;
+    let previous_status = item.status;
+
+    // Update the state of the item.
+    item.status = Status::Expired;
+    item.updated_at = now;
+    let summary = ItemSummaryEvent::new(item_id, &item);
+    drop(item);
+
+    host.state_mut().record_history(item_id, ItemHistoryEntry {
+        status:    Status::Expired,
+        timestamp: now,
+        actor:     ctx.sender(),
+    });
+
+    // Log an ItemExpiredEvent.
+    logger.log(&Event::<AdditionalData>::ItemExpired(ItemExpiredEvent {
+        item_id,
+        previous_status,
+    }))?;
+
+    // Log an ItemSummaryEvent.
+    logger.log(&Event::<AdditionalData>::ItemSummary(summary))?;
+
+    invoke_status_hook(host, item_id, Status::Expired)
+}
+
+/// Partial parameter type for the contract function
+/// `changeItemStatus`. The additional data is part of the contract schema, so
+/// that indexers and frontends can decode the payload of a status change
+/// without knowing the conventions of the deployment.
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum AdditionalData {
+    /// Any additional data encoded as generic bytes. Usecase-specific data
+    /// without a dedicated variant can be included here.
+    Raw { bytes: Vec<u8> },
+    /// The GPS coordinates of the item at the time of the status change in
+    /// microdegrees (WGS 84).
+    Location { latitude: i32, longitude: i32 },
+    /// A temperature reading of a sensor attached to the item in millidegrees
+    /// Celsius.
+    Temperature { millidegrees_celsius: i32 },
+    /// The SHA-256 hash of a document related to the status change, e.g. a
+    /// delivery note or a certificate.
+    DocumentHash { hash: [u8; 32] },
+}
+
+impl AdditionalData {
+    pub fn empty() -> Self { AdditionalData::Raw { bytes: vec![] } }
+
+    pub fn from_bytes(bytes: Vec<u8>) -> Self { AdditionalData::Raw { bytes } }
+}
+
+/// The parameter type for the contract function `changeItemStatus` which
+/// updates the status of an item.
+#[derive(Serialize, SchemaType)]
+pub struct ChangeItemStatusParams<A> {
+    /// The item's id.
+    pub item_id:         ItemID,
+    /// The item's new status.
+    pub new_status:      Status,
+    /// Any additional data of the status change such as a location, a sensor
+    /// reading, or a document hash (see [`AdditionalData`]).
+    pub additional_data: A,
+}
+
+/// Receive function to update the item's
+/// status based on the rules of the state machine.
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+/// - The contract is paused.
+/// - Sender is not an authorized role to update the item to the next state.
+/// - The item does not exist in the state.
+/// - A contract is invoking the function.
+/// - It fails to log the `ItemStatusChangedEvent` or the `ItemSummaryEvent`.
+/// - The status hook fails and its failure policy is `Reject`.
+#[receive(
+    contract = "track_and_trace",
+    name = "changeItemStatus",
+    parameter = "ChangeItemStatusParams<AdditionalData>",
+    error = "CustomContractError",
+    mutable,
+    enable_logger
+)]
+fn contract_change_item_status(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+    logger: &mut impl HasLogger,
+) -> ContractResult<()> {
+    // Parse the parameter.
+    let param: ChangeItemStatusParams<AdditionalData> = ctx.parameter_cursor().get()?;
+
+    // Check that the contract is not paused.
+    ensure!(!host.state().paused, CustomContractError::Paused);
+
+    let account = match ctx.sender() {
+        Address::Account(account) => account,
+        Address::Contract(_) => bail!(CustomContractError::NoContract),
+    };
+
+    change_item_status(param, account, ctx.metadata().slot_time(), host, logger)
+}
+
+/// Receive function to update the statuses of several items in one
+/// transaction, e.g. when a transporter scans a truckload of items. Every
+/// status change is checked and logged as if `changeItemStatus` was invoked for
+/// each item in order. The status changes are applied all or nothing.
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+/// - The contract is paused.
+/// - A contract is invoking the function.
+/// - Sender is not an authorized role to update any of the items to its next
+///   state.
+/// - Any of the items does not exist in the state.
+/// - It fails to log the `ItemStatusChangedEvent` or the `ItemSummaryEvent`.
+/// - The status hook fails and its failure policy is `Reject`.
+#[receive(
+    contract = "track_and_trace",
+    name = "changeItemStatusBatch",
+    parameter = "Vec<ChangeItemStatusParams<AdditionalData>>",
+    error = "CustomContractError",
+    mutable,
+    enable_logger
+)]
+fn contract_change_item_status_batch(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+    logger: &mut impl HasLogger,
+) -> ContractResult<()> {
+    // Parse the parameter.
+    let params: Vec<ChangeItemStatusParams<AdditionalData>> = ctx.parameter_cursor().get()?;
+
+    // Check that the contract is not paused.
+    ensure!(!host.state().paused, CustomContractError::Paused);
+
+    let account = match ctx.sender() {
+        Address::Account(account) => account,
+        Address::Contract(_) => bail!(CustomContractError::NoContract),
+    };
+
+    for param in params {
+        change_item_status(param, account, ctx.metadata().slot_time(), host, logger)?;
+    }
+    Ok(())
+}
+
+/// Helper function to update the item's status based on the rules of the state
+/// and record the status change by the `account` at the `timestamp` in the
+/// history of the item.
+fn change_item_status(
+    param: ChangeItemStatusParams<AdditionalData>,
+    account: AccountAddress,
+    timestamp: Timestamp,
+    host: &mut Host<State>,
+    logger: &mut impl HasLogger,
+) -> ContractResult<()> {
+    let (mut item, allowed_transitions) =
+        host.state_mut().get_item_and_transitions(&param.item_id)?;
+
+    let verify = allowed_transitions.check(&account, &param.new_status);
+
+    // Check that transition adheres to the state machine rules.
+    ensure!(verify, CustomContractError::Unauthorized);
+
+    // Update the state of the item.
+    item.status = param.new_status;
+    item.updated_at = timestamp;
+    let summary = ItemSummaryEvent::new(param.item_id, &item);
+
+    // Log an ItemStatusChangedEvent.
+    logger.log(&Event::ItemStatusChanged(ItemStatusChangedEvent {
+        item_id:         param.item_id,
+        new_status:      param.new_status,
+        additional_data: param.additional_data,
+    }))?;
+
+    // Log an ItemSummaryEvent.
+    logger.log(&Event::<AdditionalData>::ItemSummary(summary))?;
+
+    // Release the borrows of the state before the hook is invoked.
+    drop(item);
+    drop(allowed_transitions);
+
+    host.state_mut()
+        .record_history(param.item_id, ItemHistoryEntry {
+            status: param.new_status,
+            timestamp,
+            actor: Address::Account(account),
+        });
+
+    invoke_status_hook(host, param.item_id, param.new_status)
+}
+
+/// The policy deciding how a failure of the status hook is handled.
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum HookFailurePolicy {
+    /// The status change is rejected if the hook fails.
+    Reject,
+    /// The failure of the hook is ignored and the status change succeeds.
+    Ignore,
+}
+
+/// A contract entrypoint that is invoked with the [`StatusHookParams`] on
+/// every status change of an item, e.g. to mint a receipt NFT when an item is
+/// sold, without modifying this contract.
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq, Clone)]
+pub struct StatusHook {
+    /// The contract that is invoked.
+    pub contract:       ContractAddress,
+    /// The entrypoint of the contract that is invoked.
+    pub entrypoint:     OwnedEntrypointName,
+    /// How a failure of the hook is handled.
+    pub failure_policy: HookFailurePolicy,
+}
+
+/// The parameter the status hook is invoked with.
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq, Clone)]
+pub struct StatusHookParams {
+    /// The item's id.
+    pub item_id:    ItemID,
+    /// The item's new status.
+    pub new_status: Status,
+}
+
+/// Helper function to invoke the status hook (if configured) with the item id
+/// and the new status of the item. The hook is invoked without CCD. A failure
+/// of the hook rejects the status change or is ignored depending on the
+/// failure policy of the hook.
+fn invoke_status_hook(
+    host: &mut Host<State>,
+    item_id: ItemID,
+    new_status: Status,
+) -> ContractResult<()> {
+    let Some(hook) = host.state().status_hook.clone() else {
+        return Ok(());
+    };
+
+    let result = host.invoke_contract(
+        &hook.contract,
+        &StatusHookParams {
+            item_id,
+            new_status,
+        },
+        hook.entrypoint.as_entrypoint_name(),
+        Amount::zero(),
+    );
+
+    match (result, hook.failure_policy) {
+        (Ok(_), _) | (Err(_), HookFailurePolicy::Ignore) => Ok(()),
+        (Err(_), HookFailurePolicy::Reject) => Err(CustomContractError::HookFailed),
+    }
+}
+
+/// View the status hook that is invoked on every status change of an item.
+#[receive(
+    contract = "track_and_trace",
+    name = "getStatusHook",
+    return_value = "Option<StatusHook>"
+)]
+fn contract_get_status_hook(
+    _ctx: &ReceiveContext,
+    host: &Host<State>,
+) -> ReceiveResult<Option<StatusHook>> {
+    Ok(host.state().status_hook.clone())
+}
+
+/// Set the hook that is invoked on every status change of an item. The
+/// previous hook is replaced. With `None`, no hook is invoked.
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+/// - The sender is not the Admin of the contract instance.
+#[receive(
+    contract = "track_and_trace",
+    name = "setStatusHook",
+    parameter = "Option<StatusHook>",
+    error = "CustomContractError",
+    mutable
+)]
+fn contract_set_status_hook(ctx: &ReceiveContext, host: &mut Host<State>) -> ContractResult<()> {
+    // Parse the parameter.
+    let hook: Option<StatusHook> = ctx.parameter_cursor().get()?;
+
+    // Check that only the Admin is authorized to configure the status hook.
+    ensure!(
+        host.state().has_role(&ctx.sender(), Roles::Admin),
+        CustomContractError::Unauthorized
+    );
+
+    host.state_mut().status_hook = hook;
+    Ok(())
+}
+
+/// The parameter the CIS-2 contract is invoked with at its `mint` entrypoint
+/// to mint the twin token of an item.
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq, Clone)]
+pub struct TwinTokenMintParams {
+    /// The address receiving the twin token, which is the creator of the item.
+    pub owner:        Address,
+    /// The id of the twin token, which is the item's id.
+    pub token_id:     ItemID,
+    /// The item's metadata_url.
+    pub metadata_url: Option<MetadataUrl>,
+}
+
+/// Helper function to mint the twin token of a newly created item to the
+/// `owner` (if twin tokens are minted). The CIS-2 contract has to accept mints
+/// from this contract.
+fn mint_twin_token(
+    host: &mut Host<State>,
+    item_id: ItemID,
+    owner: Address,
+    metadata_url: Option<MetadataUrl>,
+) -> ContractResult<()> {
+    let Some(twin_token) = host.state().twin_token else {
+        return Ok(());
+    };
+
+    host.invoke_contract(
+        &twin_token,
+        &TwinTokenMintParams {
+            owner,
+            token_id: item_id,
+            metadata_url,
+        },
+        TWIN_TOKEN_MINT_ENTRYPOINT,
+        Amount::zero(),
+    )
+    .map_err(|_| CustomContractError::TwinTokenFailed)?;
+
+    Ok(())
+}
+
+/// The parameter the CIS-2 contract is invoked with at its `burn` entrypoint
+/// to burn the twin token of an archived item.
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq, Clone)]
+pub struct TwinTokenBurnParams {
+    /// The owner of the twin token.
+    pub owner:    Address,
+    /// The id of the twin token, which is the item's id.
+    pub token_id: ItemID,
+}
+
+/// Helper function to burn the twin token of an archived item owned by the
+/// `owner` (if twin tokens are minted). The CIS-2 contract has to accept burns
+/// from this contract.
+fn burn_twin_token(host: &mut Host<State>, item_id: ItemID, owner: Address) -> ContractResult<()> {
+    let Some(twin_token) = host.state().twin_token else {
+        return Ok(());
+    };
+
+    host.invoke_contract(
+        &twin_token,
+        &TwinTokenBurnParams {
+            owner,
+            token_id: item_id,
+        },
+        TWIN_TOKEN_BURN_ENTRYPOINT,
+        Amount::zero(),
+    )
+    .map_err(|_| CustomContractError::TwinTokenFailed)?;
+
+    Ok(())
+}
+
+/// The parameter the CIS-2 contract is invoked with at its `updateMetadata`
+/// entrypoint to update the metadata URL of the twin token of an item.
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq, Clone)]
+pub struct TwinTokenUpdateMetadataParams {
+    /// The id of the twin token, which is the item's id.
+    pub token_id:     ItemID,
+    /// The item's new metadata_url.
+    pub metadata_url: Option<MetadataUrl>,
+}
+
+/// Helper function to update the metadata URL of the twin token of an item to
+/// the `metadata_url` of the item (if twin tokens are minted). The CIS-2
+/// contract has to accept metadata updates from this contract.
+fn update_twin_token_metadata(
+    host: &mut Host<State>,
+    item_id: ItemID,
+    metadata_url: Option<MetadataUrl>,
+) -> ContractResult<()> {
+    let Some(twin_token) = host.state().twin_token else {
+        return Ok(());
+    };
+
+    host.invoke_contract(
+        &twin_token,
+        &TwinTokenUpdateMetadataParams {
+            token_id: item_id,
+            metadata_url,
+        },
+        TWIN_TOKEN_UPDATE_METADATA_ENTRYPOINT,
+        Amount::zero(),
+    )
+    .map_err(|_| CustomContractError::TwinTokenFailed)?;
+
+    Ok(())
+}
+
+/// The parameter type for the contract function `transferItem` which
+/// transfers the ownership of an item.
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq, Clone)]
+pub struct TransferItemParams {
+    /// The item's id.
+    pub item_id: ItemID,
+    /// The account that becomes the new owner of the item.
+    pub to:      AccountAddress,
+}
+
+/// Receive function to transfer the ownership of an item, e.g. when the goods
+/// are handed over to the next party of the supply chain. The twin token of the
+/// item is transferred along in the CIS-2 contract, which has to accept
+/// transfers of its tokens by this contract (e.g. by having this contract as an
+/// operator of all owners).
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+/// - The contract is paused.
+/// - The contract mints no twin tokens.
+/// - The item does not exist in the state.
+/// - The sender is neither the owner of the item nor the Admin.
+/// - It fails to log the `ItemOwnerChangedEvent`.
+/// - Transferring the twin token of the item fails.
+#[receive(
+    contract = "track_and_trace",
+    name = "transferItem",
+    parameter = "TransferItemParams",
+    error = "CustomContractError",
+    mutable,
+    enable_logger
+)]
+fn contract_transfer_item(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+    logger: &mut impl HasLogger,
+) -> ContractResult<()> {
+    // Parse the parameter.
+    let param: TransferItemParams = ctx.parameter_cursor().get()?;
+
+    // Check that the contract is not paused.
+    ensure!(!host.state().paused, CustomContractError::Paused);
+
+    // Check that the contract mints twin tokens.
+    ensure!(
+        host.state().twin_token.is_some(),
+        CustomContractError::TwinTokenNotConfigured
+    );
+
+    let from = host
+        .state()
+        .item_owners
+        .get(&param.item_id)
+        .map(|owner| *owner)
+        .ok_or(CustomContractError::ItemDoesNotExist)?;
+
+    // Check that only the owner of the item or the Admin is authorized to
+    // transfer the item.
+    let sender = ctx.sender();
+    ensure!(
+        sender == from || host.state().has_role(&sender, Roles::Admin),
+        CustomContractError::Unauthorized
+    );
+
+    transfer_item(host, logger, param.item_id, from, param.to)
+}
+
+/// Helper function to transfer the ownership of the item with the `item_id`
+/// and its twin token (if twin tokens are minted) from the owner `from` to the
+/// account `to`.
+fn transfer_item(
+    host: &mut Host<State>,
+    logger: &mut impl HasLogger,
+    item_id: ItemID,
+    from: Address,
+    to: AccountAddress,
+) -> ContractResult<()> {
+    host.state_mut()
+        .item_owners
+        .insert(item_id, Address::Account(to));
+
+    // Log an ItemOwnerChangedEvent.
+    logger.log(&Event::<AdditionalData>::ItemOwnerChanged(
+        ItemOwnerChangedEvent {
+            item_id,
+            from,
+            to: Address::Account(to),
+        },
+    ))?;
+
+    let Some(twin_token) = host.state().twin_token else {
+        return Ok(());
+    };
+
+    let transfer = Transfer {
+        token_id: item_id,
+        amount: TokenAmountU8(1),
+        from,
+        to: Receiver::Account(to),
+        data: concordium_cis2::AdditionalData::empty(),
+    };
+    host.invoke_contract(
+        &twin_token,
+        &TransferParams(vec![transfer]),
+        TWIN_TOKEN_TRANSFER_ENTRYPOINT,
+        Amount::zero(),
+    )
+    .map_err(|_| CustomContractError::TwinTokenFailed)?;
+
+    Ok(())
+}
+
+/// View the CIS-2 contract minting the twin tokens of the items.
+#[receive(
+    contract = "track_and_trace",
+    name = "getTwinTokenContract",
+    return_value = "Option<ContractAddress>"
+)]
+fn contract_get_twin_token_contract(
+    _ctx: &ReceiveContext,
+    host: &Host<State>,
+) -> ReceiveResult<Option<ContractAddress>> {
+    Ok(host.state().twin_token)
+}
+
+/// View the owner of an item (and its twin token, if minted). Returns `None` if
+/// the item does not exist.
+#[receive(
+    contract = "track_and_trace",
+    name = "getItemOwner",
+    parameter = "ItemID",
+    return_value = "Option<Address>"
+)]
+fn contract_get_item_owner(
+    ctx: &ReceiveContext,
+    host: &Host<State>,
+) -> ReceiveResult<Option<Address>> {
+    // Parse the parameter.
+    let item_id: ItemID = ctx.parameter_cursor().get()?;
+
+    Ok(host.state().item_owners.get(&item_id).map(|owner| *owner))
+}
+
+/// The tag at the start of every `CustodyHandoverMessage`. A `PermitMessage`
+/// starts with the address of this contract instead, so a signed permit can
+/// never be read as a signed handover and vice versa.
+pub const CUSTODY_HANDOVER_TAG: [u8; 16] = *b"CUSTODY_HANDOVER";
+
+/// The message signed by both the current and the next owner of an item to
+/// hand over the item.
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq, Clone)]
+pub struct CustodyHandoverMessage {
+    /// The [`CUSTODY_HANDOVER_TAG`] separating handovers from permits.
+    pub tag:              [u8; 16],
+    /// The contract_address that the signatures are intended for.
+    pub contract_address: ContractAddress,
+    /// The item's id.
+    pub item_id:          ItemID,
+    /// The current owner handing over the item.
+    pub from:             AccountAddress,
+    /// The next owner taking over the item.
+    pub to:               AccountAddress,
+    /// The nonce of the `from` account (see `nonceOf`), to prevent replay
+    /// attacks.
+    pub nonce:            u64,
+    /// A timestamp to make the signatures expire.
+    pub timestamp:        Timestamp,
+}
+
+/// The parameter type for the contract function `transferItemCustody`. Takes
+/// the handover message and the signatures of both owners.
+#[derive(Serialize, SchemaType)]
+pub struct TransferItemCustodyParams {
+    /// The signature/s of the current owner (`from`).
+    pub from_signature: AccountSignatures,
+    /// The signature/s of the next owner (`to`).
+    pub to_signature:   AccountSignatures,
+    /// Message that was signed by both owners.
+    pub message:        CustodyHandoverMessage,
+}
+
+/// The parameter type for the contract function `viewHandoverMessageHash`.
+#[derive(Serialize, SchemaType)]
+pub struct ViewHandoverMessageHashParams {
+    /// The owner signing the message.
+    pub signer:  AccountAddress,
+    /// Message that is signed.
+    pub message: CustodyHandoverMessage,
+}
+
+/// Receive function to hand over an item from its current owner to the next
+/// owner, transferring the ownership and the twin token (if minted) of the item
+/// like `transferItem`, but also if the contract mints no twin tokens. Both
+/// owners sign the same `CustodyHandoverMessage` and the signatures are
+/// verified on-chain, which proves that the item was physically handed over and
+/// taken over. Anyone can submit the handover, e.g. a sponsor paying for the
+/// transaction. The nonce of the `from` account is increased like for a
+/// `permit`.
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+/// - The contract is paused.
+/// - The message does not start with the `CUSTODY_HANDOVER_TAG`.
+/// - The `from` and the `to` account of the message are the same.
+/// - The signatures were intended for a different contract.
+/// - The signatures are expired.
+/// - The item does not exist in the state.
+/// - The `from` account of the message is not the owner of the item.
+/// - A different nonce of the `from` account is expected.
+/// - One of the signatures can not be validated.
+/// - It fails to log the `ItemOwnerChangedEvent` or the `NonceEvent`.
+/// - Transferring the twin token of the item fails.
+#[receive(
+    contract = "track_and_trace",
+    name = "transferItemCustody",
+    parameter = "TransferItemCustodyParams",
+    error = "CustomContractError",
+    crypto_primitives,
+    enable_logger,
+    mutable
+)]
+fn contract_transfer_item_custody(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+    logger: &mut impl HasLogger,
+    crypto_primitives: &impl HasCryptoPrimitives,
+) -> ContractResult<()> {
+    // Parse the parameter.
+    let param: TransferItemCustodyParams = ctx.parameter_cursor().get()?;
+    let message = param.message;
+
+    // Check that the contract is not paused.
+    ensure!(!host.state().paused, CustomContractError::Paused);
+
+    // Check that the message is a handover and not handed over to the owner.
+    ensure_eq!(
+        message.tag,
+        CUSTODY_HANDOVER_TAG,
+        CustomContractError::InvalidHandover
+    );
+    ensure!(
+        message.from != message.to,
+        CustomContractError::InvalidHandover
+    );
+
+    // Check that the signatures were intended for this contract.
+    ensure_eq!(
+        message.contract_address,
+        ctx.self_address(),
+        CustomContractError::WrongContract
+    );
+
+    // Check signatures are not expired.
+    ensure!(
+        message.timestamp > ctx.metadata().slot_time(),
+        CustomContractError::Expired
+    );
+
+    let owner = host
+        .state()
+        .item_owners
+        .get(&message.item_id)
+        .map(|owner| *owner)
+        .ok_or(CustomContractError::ItemDoesNotExist)?;
+
+    // Check that only the current owner can hand over the item.
+    ensure_eq!(
+        owner,
+        Address::Account(message.from),
+        CustomContractError::Unauthorized
+    );
+
+    // Update the nonce of the current owner.
+    let mut entry = host
+        .state_mut()
+        .nonces_registry
+        .entry(message.from)
+        .or_insert_with(|| 0);
+    let nonce = *entry;
+    *entry += 1;
+    drop(entry);
+
+    // Check the nonce to prevent replay attacks.
+    ensure_eq!(message.nonce, nonce, CustomContractError::NonceMismatch);
+
+    // Check the signatures of both owners.
+    let message_bytes = to_bytes(&message);
+    for (signer, signature) in [
+        (message.from, &param.from_signature),
+        (message.to, &param.to_signature),
+    ] {
+        let message_hash = calculate_message_hash(signer, &message_bytes, crypto_primitives);
+        let valid_signature = host.check_account_signature(signer, signature, &message_hash)?;
+        ensure!(valid_signature, CustomContractError::WrongSignature);
+    }
+
+    transfer_item(host, logger, message.item_id, owner, message.to)?;
+
+    // Log the nonce event.
+    logger.log(&Event::<AdditionalData>::Nonce(NonceEvent {
+        account: message.from,
+        nonce,
     }))?;
 
     Ok(())
 }
 
+/// Helper function to calculate the hash of the `CustodyHandoverMessage` that
+/// is signed by the `signer`.
+#[receive(
+    contract = "track_and_trace",
+    name = "viewHandoverMessageHash",
+    parameter = "ViewHandoverMessageHashParams",
+    return_value = "[u8;32]",
+    error = "CustomContractError",
+    crypto_primitives
+)]
+fn contract_view_handover_message_hash(
+    ctx: &ReceiveContext,
+    _host: &Host<State>,
+    crypto_primitives: &impl HasCryptoPrimitives,
+) -> ContractResult<[u8; 32]> {
+    // Parse the parameter.
+    let param: ViewHandoverMessageHashParams = ctx.parameter_cursor().get()?;
+
+    Ok(calculate_message_hash(
+        param.signer,
+        &to_bytes(&param.message),
+        crypto_primitives,
+    ))
+}
+
 /// The update of a state transition.
 #[derive(Debug, Serialize, Clone, Copy, SchemaType, PartialEq, Eq)]
 pub enum Update {
@@ -693,17 +2390,21 @@ pub struct UpdateStateMachineParams {
 /// It rejects if:
 /// - It fails to parse the parameter.
 /// - The sender is not the Admin of the contract instance.
+/// - A status of the transition is a custom status that is not registered.
 /// - The state machine cannot be updated with the given state transition.
+/// - It fails to log the `TransitionEdgeUpdatedEvent`.
 #[receive(
     contract = "track_and_trace",
     name = "updateStateMachine",
     parameter = "UpdateStateMachineParams",
     error = "CustomContractError",
-    mutable
+    mutable,
+    enable_logger
 )]
 fn contract_update_state_machine(
     ctx: &ReceiveContext,
     host: &mut Host<State>,
+    logger: &mut impl HasLogger,
 ) -> ContractResult<()> {
     // Parse the parameter.
     let params: UpdateStateMachineParams = ctx.parameter_cursor().get()?;
@@ -718,6 +2419,12 @@ fn contract_update_state_machine(
         CustomContractError::Unauthorized
     );
 
+    // Check that custom statuses are registered.
+    ensure!(
+        state.is_known_status(&params.from_status) && state.is_known_status(&params.to_status),
+        CustomContractError::UnknownStatus
+    );
+
     match params.update {
         Update::Add => {
             let success = state.add(
@@ -740,6 +2447,86 @@ fn contract_update_state_machine(
         }
     };
 
+    // Log a TransitionEdgeUpdatedEvent.
+    logger.log(&Event::<AdditionalData>::TransitionEdgeUpdated(
+        TransitionEdgeUpdatedEvent {
+            address:     params.address,
+            from_status: params.from_status,
+            to_status:   params.to_status,
+            update:      params.update,
+        },
+    ))?;
+
+    Ok(())
+}
+
+/// The parameter for the contract function `setMaxDuration` which sets the
+/// maximum duration items may stay in a status.
+#[derive(Serialize, SchemaType)]
+pub struct SetMaxDurationParams {
+    /// The status whose maximum duration is set.
+    pub status:       Status,
+    /// The maximum duration items may stay in the status. If `None`, items
+    /// may stay in the status indefinitely.
+    pub max_duration: Option<Duration>,
+}
+
+/// Set the maximum duration items may stay in a status before anyone can mark
+/// them `Expired` with `expireItem`. The maximum duration applies to items that
+/// are already in the status, measured from the time they entered the status.
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+/// - The sender is not the Admin of the contract instance.
+/// - The status is a custom status that is not registered.
+/// - It fails to log the `MaxDurationUpdatedEvent`.
+#[receive(
+    contract = "track_and_trace",
+    name = "setMaxDuration",
+    parameter = "SetMaxDurationParams",
+    error = "CustomContractError",
+    mutable,
+    enable_logger
+)]
+fn contract_set_max_duration(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+    logger: &mut impl HasLogger,
+) -> ContractResult<()> {
+    // Parse the parameter.
+    let params: SetMaxDurationParams = ctx.parameter_cursor().get()?;
+
+    let state = host.state_mut();
+
+    // Check that only the Admin is authorized to set maximum durations.
+    ensure!(
+        state.has_role(&ctx.sender(), Roles::Admin),
+        CustomContractError::Unauthorized
+    );
+
+    // Check that custom statuses are registered.
+    ensure!(
+        state.is_known_status(&params.status),
+        CustomContractError::UnknownStatus
+    );
+
+    match params.max_duration {
+        Some(max_duration) => {
+            state.max_durations.insert(params.status, max_duration);
+        }
+        None => {
+            state.max_durations.remove(&params.status);
+        }
+    }
+
+    // Log a MaxDurationUpdatedEvent.
+    logger.log(&Event::<AdditionalData>::MaxDurationUpdated(
+        MaxDurationUpdatedEvent {
+            status:       params.status,
+            max_duration: params.max_duration,
+        },
+    ))?;
+
     Ok(())
 }
 
@@ -753,11 +2540,13 @@ pub struct GrantRoleParams {
     pub role:    Roles,
 }
 
-/// Add role to an address.
+/// Add role to an address. The Admin role can not be granted directly, new
+/// Admins are added with `proposeAdmin` and `acceptAdmin`.
 ///
 /// It rejects if:
 /// - It fails to parse the parameter.
 /// - The sender is not the Admin of the contract instance.
+/// - The role is the Admin role.
 #[receive(
     contract = "track_and_trace",
     name = "grantRole",
@@ -784,6 +2573,12 @@ fn contract_grant_role(
         CustomContractError::Unauthorized
     );
 
+    // Check that the Admin role is accepted by the new Admin.
+    ensure!(
+        params.role != Roles::Admin,
+        CustomContractError::AdminRequiresAcceptance
+    );
+
     // Grant role.
     state.grant_role(&params.address, params.role, state_builder);
 
@@ -810,6 +2605,7 @@ pub struct RevokeRoleParams {
 /// It rejects if:
 /// - It fails to parse the parameter.
 /// - The sender is not the Admin of the contract instance.
+/// - The Admin role is revoked from the last Admin.
 #[receive(
     contract = "track_and_trace",
     name = "revokeRole",
@@ -836,6 +2632,14 @@ fn contract_revoke_role(
         CustomContractError::Unauthorized
     );
 
+    // Check that the last Admin is not revoked.
+    ensure!(
+        params.role != Roles::Admin
+            || state.admin_count > 1
+            || !state.has_role(&params.address, Roles::Admin),
+        CustomContractError::LastAdmin
+    );
+
     // Revoke role.
     state.revoke_role(&params.address, params.role);
     // Log a RevokeRoleEvent.
@@ -846,6 +2650,313 @@ fn contract_revoke_role(
     Ok(())
 }
 
+/// Propose an address as a new Admin. The address becomes an Admin once it
+/// accepts the Admin role with `acceptAdmin`, so that the Admin role is not
+/// granted to an address whose keys are not controlled by anyone. Several
+/// Admins can be proposed and added, so that losing the keys of one Admin does
+/// not lock the contract.
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+/// - The sender is not the Admin of the contract instance.
+/// - It fails to log the `AdminProposedEvent`.
+#[receive(
+    contract = "track_and_trace",
+    name = "proposeAdmin",
+    parameter = "Address",
+    error = "CustomContractError",
+    enable_logger,
+    mutable
+)]
+fn contract_propose_admin(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+    logger: &mut impl HasLogger,
+) -> ContractResult<()> {
+    // Parse the parameter.
+    let address: Address = ctx.parameter_cursor().get()?;
+
+    // Get the sender who invoked this contract function.
+    let sender = ctx.sender();
+    // Check that only the Admin is authorized to propose new Admins.
+    ensure!(
+        host.state().has_role(&sender, Roles::Admin),
+        CustomContractError::Unauthorized
+    );
+
+    host.state_mut().pending_admins.insert(address);
+
+    // Log an AdminProposedEvent.
+    logger.log(&Event::<AdditionalData>::AdminProposed(
+        AdminProposedEvent {
+            address,
+            proposed_by: sender,
+        },
+    ))?;
+    Ok(())
+}
+
+/// Accept the Admin role after the sender was proposed as a new Admin with
+/// `proposeAdmin`.
+///
+/// It rejects if:
+/// - The sender was not proposed as a new Admin.
+/// - It fails to log the `AdminAcceptedEvent` or the `GrantRoleEvent`.
+#[receive(
+    contract = "track_and_trace",
+    name = "acceptAdmin",
+    error = "CustomContractError",
+    enable_logger,
+    mutable
+)]
+fn contract_accept_admin(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+    logger: &mut impl HasLogger,
+) -> ContractResult<()> {
+    let (state, state_builder) = host.state_and_builder();
+
+    // Get the sender who invoked this contract function.
+    let sender = ctx.sender();
+    // Check that the sender was proposed as a new Admin.
+    ensure!(
+        state.pending_admins.remove(&sender),
+        CustomContractError::AdminNotProposed
+    );
+
+    // Grant the Admin role.
+    state.grant_role(&sender, Roles::Admin, state_builder);
+
+    // Log an AdminAcceptedEvent and a GrantRoleEvent.
+    logger.log(&Event::<AdditionalData>::AdminAccepted(
+        AdminAcceptedEvent { address: sender },
+    ))?;
+    logger.log(&Event::<AdditionalData>::GrantRole(GrantRoleEvent {
+        address: sender,
+        role:    Roles::Admin,
+    }))?;
+    Ok(())
+}
+
+/// View the roles that are allowed to create items.
+#[receive(
+    contract = "track_and_trace",
+    name = "getCreateItemRoles",
+    return_value = "Vec<Roles>"
+)]
+fn contract_get_create_item_roles(
+    _ctx: &ReceiveContext,
+    host: &Host<State>,
+) -> ReceiveResult<Vec<Roles>> {
+    Ok(host.state().creator_roles.clone())
+}
+
+/// Set the roles that are allowed to create items. The previously allowed
+/// roles are replaced.
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+/// - The sender is not the Admin of the contract instance.
+/// - It fails to log the `CreateItemRolesUpdatedEvent`.
+#[receive(
+    contract = "track_and_trace",
+    name = "setCreateItemRoles",
+    parameter = "Vec<Roles>",
+    error = "CustomContractError",
+    mutable,
+    enable_logger
+)]
+fn contract_set_create_item_roles(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+    logger: &mut impl HasLogger,
+) -> ContractResult<()> {
+    // Parse the parameter.
+    let roles: Vec<Roles> = ctx.parameter_cursor().get()?;
+
+    // Check that only the Admin is authorized to configure the roles that are
+    // allowed to create items.
+    ensure!(
+        host.state().has_role(&ctx.sender(), Roles::Admin),
+        CustomContractError::Unauthorized
+    );
+
+    host.state_mut().creator_roles = roles.clone();
+
+    // Log a CreateItemRolesUpdatedEvent.
+    logger.log(&Event::<AdditionalData>::CreateItemRolesUpdated(
+        CreateItemRolesUpdatedEvent { roles },
+    ))?;
+    Ok(())
+}
+
+/// View the names of the custom statuses. The `i`-th name is the name of the
+/// status `Status::Custom(i)`.
+#[receive(
+    contract = "track_and_trace",
+    name = "getCustomStatuses",
+    return_value = "Vec<String>"
+)]
+fn contract_get_custom_statuses(
+    _ctx: &ReceiveContext,
+    host: &Host<State>,
+) -> ReceiveResult<Vec<String>> {
+    Ok(host.state().custom_statuses.clone())
+}
+
+/// Receive function for the Admin to register a custom status with the given
+/// name. The status can then be used in the transition edges of the state
+/// machine (see `updateStateMachine`). Custom statuses can not be removed.
+///
+/// It rejects if:
+/// - It fails to parse the parameter.
+/// - The sender is not the Admin of the contract instance.
+/// - A custom status with the same name is already registered.
+/// - 256 custom statuses are already registered.
+/// - It fails to log the `CustomStatusAddedEvent`.
+#[receive(
+    contract = "track_and_trace",
+    name = "addCustomStatus",
+    parameter = "String",
+    error = "CustomContractError",
+    mutable,
+    enable_logger
+)]
+fn contract_add_custom_status(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+    logger: &mut impl HasLogger,
+) -> ContractResult<()> {
+    // Parse the parameter.
+    let name: String = ctx.parameter_cursor().get()?;
+
+    // Check that only the Admin is authorized to register custom statuses.
+    ensure!(
+        host.state().has_role(&ctx.sender(), Roles::Admin),
+        CustomContractError::Unauthorized
+    );
+
+    let status = host.state_mut().add_custom_status(name.clone())?;
+
+    // Log a CustomStatusAddedEvent.
+    logger.log(&Event::<AdditionalData>::CustomStatusAdded(
+        CustomStatusAddedEvent { status, name },
+    ))?;
+
+    Ok(())
+}
+
+/// The parameter type for the contract function `upgrade`.
+/// Takes the new module and optionally an entrypoint to call in the new module
+/// after the upgrade, e.g. to migrate the state.
+#[derive(Serialize, SchemaType)]
+pub struct UpgradeParams {
+    /// The new module reference.
+    pub module:  ModuleReference,
+    /// Optional entrypoint to call in the new module after the upgrade.
+    pub migrate: Option<(OwnedEntrypointName, OwnedParameter)>,
+}
+
+/// Receive function for the Admin to upgrade the contract instance to a new
+/// module and optionally call a migration function in the new module after
+/// the upgrade. The state of the contract instance, including all items, is
+/// kept.
+///
+/// It rejects if:
+/// - It fails to parse the parameter or the state.
+/// - The sender is not the Admin of the contract instance.
+/// - The new module does not exist, does not contain a `track_and_trace`
+///   contract, or has an unsupported smart contract version.
+/// - The migration function fails.
+#[receive(
+    contract = "track_and_trace",
+    name = "upgrade",
+    parameter = "UpgradeParams",
+    error = "CustomContractError",
+    low_level
+)]
+fn contract_upgrade(ctx: &ReceiveContext, host: &mut LowLevelHost) -> ContractResult<()> {
+    // Read the top-level contract state.
+    let state: State = host.state().read_root()?;
+
+    // Check that only the Admin is authorized to upgrade the contract.
+    ensure!(
+        state.has_role(&ctx.sender(), Roles::Admin),
+        CustomContractError::Unauthorized
+    );
+
+    // Parse the parameter.
+    let param: UpgradeParams = ctx.parameter_cursor().get()?;
+
+    // Trigger the upgrade.
+    host.upgrade(param.module)?;
+
+    // Call the migration function if provided.
+    if let Some((func, parameter)) = param.migrate {
+        host.invoke_contract_raw(
+            &ctx.self_address(),
+            parameter.as_parameter(),
+            func.as_entrypoint_name(),
+            Amount::zero(),
+        )
+        .map_err(|_| CustomContractError::FailedUpgradeMigration)?;
+    }
+
+    Ok(())
+}
+
+/// View whether the contract is paused.
+#[receive(contract = "track_and_trace", name = "isPaused", return_value = "bool")]
+fn contract_is_paused(_ctx: &ReceiveContext, host: &Host<State>) -> ReceiveResult<bool> {
+    Ok(host.state().paused)
+}
+
+/// Receive function for the Admin to pause the contract, e.g. during incident
+/// response. While paused, all entrypoints changing items reject with `Paused`:
+/// `createItem`, `updateItemMetadata`, `freezeItemMetadata`, `archiveItem`,
+/// `expireItem`, `changeItemStatus`, `changeItemStatusBatch`, `transferItem`,
+/// `transferItemCustody`, and `permit`. The Admin entrypoints configuring the
+/// contract (e.g. the roles, the state machine, or `upgrade`) remain available
+/// to resolve the incident.
+///
+/// It rejects if:
+/// - The sender is not the Admin of the contract instance.
+#[receive(
+    contract = "track_and_trace",
+    name = "pause",
+    error = "CustomContractError",
+    mutable
+)]
+fn contract_pause(ctx: &ReceiveContext, host: &mut Host<State>) -> ContractResult<()> {
+    set_paused(ctx, host, true)
+}
+
+/// Receive function for the Admin to unpause the contract.
+///
+/// It rejects if:
+/// - The sender is not the Admin of the contract instance.
+#[receive(
+    contract = "track_and_trace",
+    name = "unpause",
+    error = "CustomContractError",
+    mutable
+)]
+fn contract_unpause(ctx: &ReceiveContext, host: &mut Host<State>) -> ContractResult<()> {
+    set_paused(ctx, host, false)
+}
+
+/// Set whether the contract is paused if the sender is the Admin.
+fn set_paused(ctx: &ReceiveContext, host: &mut Host<State>, paused: bool) -> ContractResult<()> {
+    // Check that only the Admin is authorized to pause or unpause the contract.
+    ensure!(
+        host.state().has_role(&ctx.sender(), Roles::Admin),
+        CustomContractError::Unauthorized
+    );
+
+    host.state_mut().paused = paused;
+    Ok(())
+}
+
 /// Part of the parameter type for the contract function `permit`.
 /// Specifies the message that is signed.
 #[derive(SchemaType, Serialize)]
@@ -885,11 +2996,13 @@ pub struct PermitParamPartial {
     signer:    AccountAddress,
 }
 
-/// Verify an ed25519 signature and allows calling the `changeItemStatus`
-/// function.
+/// Verify an ed25519 signature and allows calling the `changeItemStatus` or
+/// the `createItem` function, so that the signer does not need CCD to pay for
+/// the transaction.
 ///
 /// It rejects if:
 /// - It fails to parse the parameter.
+/// - The contract is paused.
 /// - A different nonce is expected.
 /// - The signature was intended for a different contract.
 /// - The signature was intended for a different `entry_point`.
@@ -898,6 +3011,9 @@ pub struct PermitParamPartial {
 /// - Fails to log event.
 /// - Signer is not an authorized role to update the item to the next state.
 /// - The item does not exist in the state.
+/// - The status hook fails and its failure policy is `Reject`.
+/// - Signer has none of the roles that are allowed to create items.
+/// - Twin tokens are minted and minting the twin token of the item fails.
 #[receive(
     contract = "track_and_trace",
     name = "permit",
@@ -916,6 +3032,9 @@ fn contract_permit(
     // Parse the parameter.
     let param: PermitParam = ctx.parameter_cursor().get()?;
 
+    // Check that the contract is not paused.
+    ensure!(!host.state().paused, CustomContractError::Paused);
+
     // Update the nonce.
     let mut entry = host
         .state_mut()
@@ -954,11 +3073,26 @@ fn contract_permit(
         host.check_account_signature(param.signer, &param.signature, &message_hash)?;
     ensure!(valid_signature, CustomContractError::WrongSignature);
 
-    if message.entry_point.as_entrypoint_name() == EntrypointName::new_unchecked("changeItemStatus")
-    {
+    let entry_point = message.entry_point.as_entrypoint_name();
+    if entry_point == EntrypointName::new_unchecked("changeItemStatus") {
         let change_item_status_param: ChangeItemStatusParams<AdditionalData> =
             from_bytes(&message.payload)?;
-        change_item_status(change_item_status_param, param.signer, host, logger)?;
+        change_item_status(
+            change_item_status_param,
+            param.signer,
+            ctx.metadata().slot_time(),
+            host,
+            logger,
+        )?;
+    } else if entry_point == EntrypointName::new_unchecked("createItem") {
+        let metadata_url: Option<MetadataUrl> = from_bytes(&message.payload)?;
+        create_item(
+            metadata_url,
+            Address::Account(param.signer),
+            ctx.metadata().slot_time(),
+            host,
+            logger,
+        )?;
     } else {
         bail!(CustomContractError::WrongEntryPoint)
     }
@@ -1002,6 +3136,20 @@ fn contract_view_message_hash(
 
     cursor.read_exact(&mut message_bytes)?;
 
+    Ok(calculate_message_hash(
+        param.signer,
+        &message_bytes,
+        crypto_primitives,
+    ))
+}
+
+/// Helper function to calculate the hash of the `message_bytes` signed by the
+/// `signer`.
+fn calculate_message_hash(
+    signer: AccountAddress,
+    message_bytes: &[u8],
+    crypto_primitives: &impl HasCryptoPrimitives,
+) -> [u8; 32] {
     // The message signed in the Concordium browser wallet is prepended with the
     // `account` address and 8 zero bytes. Accounts in the Concordium browser wallet
     // can either sign a regular transaction (in that case the prepend is
@@ -1011,19 +3159,17 @@ fn contract_view_message_hash(
     // sign a transaction. The account nonce is of type u64 (8 bytes).
     let mut msg_prepend = [0; 32 + 8];
     // Prepend the `account` address of the signer.
-    msg_prepend[0..32].copy_from_slice(param.signer.as_ref());
+    msg_prepend[0..32].copy_from_slice(signer.as_ref());
     // Prepend 8 zero bytes.
     msg_prepend[32..40].copy_from_slice(&[0u8; 8]);
     // Calculate the message hash.
-    let message_hash = crypto_primitives
-        .hash_sha2_256(&[&msg_prepend[0..40], &message_bytes].concat())
-        .0;
-
-    Ok(message_hash)
+    crypto_primitives
+        .hash_sha2_256(&[&msg_prepend[0..40], message_bytes].concat())
+        .0
 }
 
 /// Query if standards are supported given a list of
-/// standard identifiers.
+/// standard identifiers. The contract supports CIS-0, CIS-3, and CIS-6.
 ///
 /// It rejects if:
 /// - It fails to parse the parameter.
@@ -1214,3 +3360,42 @@ fn is_transition_edge(ctx: &ReceiveContext, host: &Host<State>) -> ContractResul
     };
     Ok(transitions.check(&params.account, &params.to_status))
 }
+
+/// View all edges of the state machine. Each returned edge contains the `from`
+/// status, the `to` statuses, and the account authorized to execute these
+/// transitions. This allows frontends to render the allowed next actions for
+/// the current user dynamically. Accounts that have no transitions left from
+/// a status (e.g. after removing them with `updateStateMachine`) are omitted.
+#[receive(
+    contract = "track_and_trace",
+    name = "getStateMachine",
+    return_value = "Vec<TransitionEdges>",
+    error = "CustomContractError"
+)]
+fn get_state_machine(
+    _ctx: &ReceiveContext,
+    host: &Host<State>,
+) -> ContractResult<Vec<TransitionEdges>> {
+    let mut edges = Vec::new();
+
+    for (from, status_transitions) in host.state().transitions.iter() {
+        let max_duration = host
+            .state()
+            .max_durations
+            .get(&from)
+            .map(|max_duration| *max_duration);
+        for (authorized_account, targets) in status_transitions.transitions.iter() {
+            let to: Vec<Status> = targets.iter().map(|status| *status).collect();
+            if !to.is_empty() {
+                edges.push(TransitionEdges {
+                    from: *from,
+                    to,
+                    authorized_account: *authorized_account,
+                    max_duration,
+                });
+            }
+        }
+    }
+
+    Ok(edges)
+}
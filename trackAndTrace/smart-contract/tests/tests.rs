@@ -29,6 +29,7 @@ const DUMMY_SIGNATURE: SignatureEd25519 = SignatureEd25519([
 struct AccountKeypairs {
     admin:    AccountKeys,
     producer: AccountKeys,
+    seller:   AccountKeys,
 }
 
 /// Test that the `hasRole` view function works.
@@ -62,6 +63,337 @@ fn test_has_role() {
     assert_eq!(has_role, true, "Admin should have role");
 }
 
+// 1. Test that the Admin role can NOT be granted without being accepted.
+// 2. Test that only the ADMIN can propose a new Admin and only the proposed
+//    address can accept the Admin role.
+// 3. Test that the last Admin can NOT be revoked.
+#[test]
+fn test_propose_and_accept_admin() {
+    let (mut chain, _, track_and_trace_contract_address) = initialize_chain_and_contract();
+
+    let update = |chain: &mut Chain, sender: AccountAddress, entrypoint: &str, message| {
+        chain.contract_update(
+            SIGNER,
+            sender,
+            Address::Account(sender),
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount: Amount::from_ccd(0),
+                address: track_and_trace_contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(format!(
+                    "track_and_trace.{entrypoint}"
+                )),
+                message,
+            },
+        )
+    };
+
+    let parse_error = |error: ContractInvokeError| -> CustomContractError {
+        error
+            .parse_return_value()
+            .expect("CustomContractError return value")
+    };
+
+    let grant_admin = OwnedParameter::from_serial(&GrantRoleParams {
+        address: SELLER_ADDR,
+        role:    Roles::Admin,
+    })
+    .expect("Serialize parameter");
+    let revoke_admin = |address: Address| {
+        OwnedParameter::from_serial(&RevokeRoleParams {
+            address,
+            role: Roles::Admin,
+        })
+        .expect("Serialize parameter")
+    };
+    let propose_seller = OwnedParameter::from_serial(&SELLER_ADDR).expect("Serialize parameter");
+
+    // Check the Admin role can NOT be granted directly.
+    let error = update(&mut chain, ADMIN, "grantRole", grant_admin).expect_err("Should fail");
+    assert_eq!(
+        parse_error(error),
+        CustomContractError::AdminRequiresAcceptance
+    );
+
+    // Check the last Admin can NOT be revoked.
+    let error =
+        update(&mut chain, ADMIN, "revokeRole", revoke_admin(ADMIN_ADDR)).expect_err("Should fail");
+    assert_eq!(parse_error(error), CustomContractError::LastAdmin);
+
+    // Check the PRODUCER can NOT propose a new Admin.
+    let error = update(&mut chain, PRODUCER, "proposeAdmin", propose_seller.clone())
+        .expect_err("Should fail");
+    assert_eq!(parse_error(error), CustomContractError::Unauthorized);
+
+    // Check the SELLER can NOT accept the Admin role without being proposed.
+    let error = update(&mut chain, SELLER, "acceptAdmin", OwnedParameter::empty())
+        .expect_err("Should fail");
+    assert_eq!(parse_error(error), CustomContractError::AdminNotProposed);
+
+    // Check the ADMIN can propose the SELLER as a new Admin.
+    let proposal = update(&mut chain, ADMIN, "proposeAdmin", propose_seller)
+        .expect("Should be able to propose a new Admin");
+    let events = proposal
+        .events()
+        .flat_map(|(_addr, events)| events.iter().map(|e| e.parse().expect("Deserialize event")))
+        .collect::<Vec<Event<AdditionalData>>>();
+    assert_eq!(events, [Event::AdminProposed(AdminProposedEvent {
+        address:     SELLER_ADDR,
+        proposed_by: ADMIN_ADDR,
+    })]);
+
+    // Check the PRODUCER can NOT accept the Admin role proposed to the SELLER.
+    let error = update(&mut chain, PRODUCER, "acceptAdmin", OwnedParameter::empty())
+        .expect_err("Should fail");
+    assert_eq!(parse_error(error), CustomContractError::AdminNotProposed);
+
+    // Check the SELLER can accept the Admin role.
+    let acceptance = update(&mut chain, SELLER, "acceptAdmin", OwnedParameter::empty())
+        .expect("Should be able to accept the Admin role");
+    let events = acceptance
+        .events()
+        .flat_map(|(_addr, events)| events.iter().map(|e| e.parse().expect("Deserialize event")))
+        .collect::<Vec<Event<AdditionalData>>>();
+    assert_eq!(events, [
+        Event::AdminAccepted(AdminAcceptedEvent {
+            address: SELLER_ADDR,
+        }),
+        Event::GrantRole(GrantRoleEvent {
+            address: SELLER_ADDR,
+            role:    Roles::Admin,
+        }),
+    ]);
+
+    // Check the new Admin can revoke the Admin role of the ADMIN but not its own
+    // Admin role, since it is the last Admin.
+    update(&mut chain, SELLER, "revokeRole", revoke_admin(ADMIN_ADDR))
+        .expect("Should be able to revoke the Admin role");
+    let error = update(&mut chain, SELLER, "revokeRole", revoke_admin(SELLER_ADDR))
+        .expect_err("Should fail");
+    assert_eq!(parse_error(error), CustomContractError::LastAdmin);
+}
+
+// 1. Test that the addresses with a role are returned page by page in the order
+//    their roles were granted.
+// 2. Test that addresses whose role was revoked are not returned.
+// 3. Test that a page stops after a long run of addresses without the role and
+//    returns where the next page starts.
+#[test]
+fn test_get_addresses_with_role() {
+    let (mut chain, _, track_and_trace_contract_address) = initialize_chain_and_contract();
+
+    let update_role = |chain: &mut Chain, entrypoint: &str, message: OwnedParameter| {
+        chain
+            .contract_update(
+                SIGNER,
+                ADMIN,
+                ADMIN_ADDR,
+                Energy::from(10000),
+                UpdateContractPayload {
+                    amount: Amount::from_ccd(0),
+                    address: track_and_trace_contract_address,
+                    receive_name: OwnedReceiveName::new_unchecked(format!(
+                        "track_and_trace.{entrypoint}"
+                    )),
+                    message,
+                },
+            )
+            .expect("Should be able to update role");
+    };
+    let grant_producer_role = |chain: &mut Chain, address: Address| {
+        let parameter = GrantRoleParams {
+            address,
+            role: Roles::Producer,
+        };
+        update_role(
+            chain,
+            "grantRole",
+            OwnedParameter::from_serial(&parameter).expect("Serialize parameter"),
+        );
+    };
+
+    let get_addresses_with_role =
+        |chain: &Chain, role: Roles, start_index: u64, limit: u32| -> (Vec<Address>, Option<u64>) {
+            let page: AddressesWithRolePage = chain
+                .contract_invoke(
+                    ADMIN,
+                    ADMIN_ADDR,
+                    Energy::from(1000000),
+                    UpdateContractPayload {
+                        amount:       Amount::zero(),
+                        receive_name: OwnedReceiveName::new_unchecked(
+                            "track_and_trace.getAddressesWithRole".to_string(),
+                        ),
+                        address:      track_and_trace_contract_address,
+                        message:      OwnedParameter::from_serial(&GetAddressesWithRoleParams {
+                            role,
+                            start_index,
+                            limit,
+                        })
+                        .expect("Serialize parameter"),
+                    },
+                )
+                .expect("Invoke getAddressesWithRole")
+                .parse_return_value()
+                .expect("getAddressesWithRole return value");
+            (page.addresses, page.next_start_index)
+        };
+
+    // Grant the Producer role to the PRODUCER and the SELLER.
+    for address in [PRODUCER_ADDR, SELLER_ADDR] {
+        grant_producer_role(&mut chain, address);
+    }
+
+    assert_eq!(
+        get_addresses_with_role(&chain, Roles::Admin, 0, 10),
+        (vec![ADMIN_ADDR], None)
+    );
+
+    // Check that the producers are returned one per page.
+    assert_eq!(
+        get_addresses_with_role(&chain, Roles::Producer, 0, 1),
+        (vec![PRODUCER_ADDR], Some(2))
+    );
+    assert_eq!(
+        get_addresses_with_role(&chain, Roles::Producer, 2, 1),
+        (vec![SELLER_ADDR], None)
+    );
+
+    // Check that the SELLER is no longer returned once the role is revoked.
+    let parameter = RevokeRoleParams {
+        address: SELLER_ADDR,
+        role:    Roles::Producer,
+    };
+    update_role(
+        &mut chain,
+        "revokeRole",
+        OwnedParameter::from_serial(&parameter).expect("Serialize parameter"),
+    );
+    assert_eq!(
+        get_addresses_with_role(&chain, Roles::Producer, 0, 10),
+        (vec![PRODUCER_ADDR], None)
+    );
+
+    // Check that a page of the Admins stops after the maximum number of scanned
+    // addresses without the Admin role and the next page continues after them.
+    for index in 0..MAX_ROLE_ADDRESSES_SCANNED {
+        grant_producer_role(
+            &mut chain,
+            Address::Contract(ContractAddress::new(1000 + index, 0)),
+        );
+    }
+    assert_eq!(
+        get_addresses_with_role(&chain, Roles::Admin, 1, 10),
+        (vec![], Some(1 + MAX_ROLE_ADDRESSES_SCANNED))
+    );
+    assert_eq!(
+        get_addresses_with_role(&chain, Roles::Admin, 1 + MAX_ROLE_ADDRESSES_SCANNED, 10),
+        (vec![], None)
+    );
+}
+
+/// Test that the `getStateMachine` view function returns all transition edges.
+#[test]
+fn test_get_state_machine() {
+    let (mut chain, _, track_and_trace_contract_address) = initialize_chain_and_contract();
+
+    let get_state_machine = |chain: &Chain| -> Vec<TransitionEdges> {
+        chain
+            .contract_invoke(
+                ADMIN,
+                ADMIN_ADDR,
+                Energy::from(10000),
+                UpdateContractPayload {
+                    amount:       Amount::zero(),
+                    receive_name: OwnedReceiveName::new_unchecked(
+                        "track_and_trace.getStateMachine".to_string(),
+                    ),
+                    address:      track_and_trace_contract_address,
+                    message:      OwnedParameter::empty(),
+                },
+            )
+            .expect("Invoke getStateMachine")
+            .parse_return_value()
+            .expect("getStateMachine return value")
+    };
+
+    // The edges are ordered by the `from` status and the authorized account.
+    let mut expected = vec![
+        TransitionEdges {
+            from:               Status::Produced,
+            to:                 vec![Status::InTransit, Status::InStore, Status::Sold],
+            authorized_account: ADMIN,
+            max_duration:       None,
+        },
+        TransitionEdges {
+            from:               Status::Produced,
+            to:                 vec![Status::InTransit],
+            authorized_account: PRODUCER,
+            max_duration:       None,
+        },
+        TransitionEdges {
+            from:               Status::InTransit,
+            to:                 vec![Status::Produced, Status::InStore, Status::Sold],
+            authorized_account: ADMIN,
+            max_duration:       None,
+        },
+        TransitionEdges {
+            from:               Status::InTransit,
+            to:                 vec![Status::InStore],
+            authorized_account: TRANSPORTER,
+            max_duration:       None,
+        },
+        TransitionEdges {
+            from:               Status::InStore,
+            to:                 vec![Status::Produced, Status::InTransit, Status::Sold],
+            authorized_account: ADMIN,
+            max_duration:       None,
+        },
+        TransitionEdges {
+            from:               Status::InStore,
+            to:                 vec![Status::Sold],
+            authorized_account: SELLER,
+            max_duration:       None,
+        },
+        TransitionEdges {
+            from:               Status::Sold,
+            to:                 vec![Status::Produced, Status::InTransit, Status::InStore],
+            authorized_account: ADMIN,
+            max_duration:       None,
+        },
+    ];
+
+    assert_eq!(get_state_machine(&chain), expected);
+
+    // Remove the only transition of the PRODUCER.
+    chain
+        .contract_update(
+            SIGNER,
+            ADMIN,
+            ADMIN_ADDR,
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::zero(),
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.updateStateMachine".to_string(),
+                ),
+                address:      track_and_trace_contract_address,
+                message:      OwnedParameter::from_serial(&UpdateStateMachineParams {
+                    address:     PRODUCER,
+                    from_status: Status::Produced,
+                    to_status:   Status::InTransit,
+                    update:      Update::Remove,
+                })
+                .expect("Serialize parameter"),
+            },
+        )
+        .expect("Should be able to remove the transition edge");
+
+    // The PRODUCER has no transitions left and is omitted.
+    expected.remove(1);
+    assert_eq!(get_state_machine(&chain), expected);
+}
+
 /// Test adding and removing state transition edges.
 #[test]
 fn test_add_and_remove_of_state_transition_edges() {
@@ -111,7 +443,7 @@ fn test_add_and_remove_of_state_transition_edges() {
     };
 
     // Check the ADMIN can update the state machine (add transition).
-    let _update = chain
+    let update = chain
         .contract_update(
             SIGNER,
             ADMIN,
@@ -129,6 +461,21 @@ fn test_add_and_remove_of_state_transition_edges() {
         )
         .expect("Should be able to update the state machine");
 
+    // Check that the event is logged.
+    let events = update
+        .events()
+        .flat_map(|(_addr, events)| events.iter().map(|e| e.parse().expect("Deserialize event")))
+        .collect::<Vec<Event<AdditionalData>>>();
+
+    assert_eq!(events, [Event::TransitionEdgeUpdated(
+        TransitionEdgeUpdatedEvent {
+            address: NEW_ADDR,
+            from_status,
+            to_status,
+            update: update_transition_edge.update,
+        }
+    )]);
+
     let invoke = chain
         .contract_invoke(
             ADMIN,
@@ -155,7 +502,7 @@ fn test_add_and_remove_of_state_transition_edges() {
     update_transition_edge.update = Update::Remove;
 
     // Check the ADMIN can update the state machine (remove transition).
-    let _update = chain
+    let update = chain
         .contract_update(
             SIGNER,
             ADMIN,
@@ -173,6 +520,21 @@ fn test_add_and_remove_of_state_transition_edges() {
         )
         .expect("Should be able to update the state machine");
 
+    // Check that the event is logged.
+    let events = update
+        .events()
+        .flat_map(|(_addr, events)| events.iter().map(|e| e.parse().expect("Deserialize event")))
+        .collect::<Vec<Event<AdditionalData>>>();
+
+    assert_eq!(events, [Event::TransitionEdgeUpdated(
+        TransitionEdgeUpdatedEvent {
+            address: NEW_ADDR,
+            from_status,
+            to_status,
+            update: update_transition_edge.update,
+        }
+    )]);
+
     let invoke = chain
         .contract_invoke(
             ADMIN,
@@ -241,11 +603,19 @@ fn test_create_item_and_update_item_status() {
         .flat_map(|(_addr, events)| events.iter().map(|e| e.parse().expect("Deserialize event")))
         .collect::<Vec<Event<AdditionalData>>>();
 
-    assert_eq!(events, [Event::ItemCreated(ItemCreatedEvent {
-        item_id,
-        metadata_url: metadata_url.clone(),
-        initial_status: Status::Produced,
-    })]);
+    assert_eq!(events, [
+        Event::ItemCreated(ItemCreatedEvent {
+            item_id,
+            metadata_url: metadata_url.clone(),
+            initial_status: Status::Produced,
+        }),
+        Event::ItemSummary(ItemSummaryEvent {
+            version: ITEM_SUMMARY_EVENT_VERSION,
+            item_id,
+            status: Status::Produced,
+            metadata_hash: None,
+        }),
+    ]);
 
     // Check contract state.
     check_state(
@@ -253,11 +623,12 @@ fn test_create_item_and_update_item_status() {
         track_and_trace_contract_address,
         Status::Produced,
         metadata_url.clone(),
+        false,
     );
 
     let parameter = ChangeItemStatusParams {
         item_id,
-        additional_data: AdditionalData { bytes: vec![] },
+        additional_data: AdditionalData::empty(),
         new_status: Status::InTransit,
     };
 
@@ -285,11 +656,19 @@ fn test_create_item_and_update_item_status() {
         .flat_map(|(_addr, events)| events.iter().map(|e| e.parse().expect("Deserialize event")))
         .collect::<Vec<Event<AdditionalData>>>();
 
-    assert_eq!(events, [Event::ItemStatusChanged(ItemStatusChangedEvent {
-        item_id:         parameter.item_id,
-        new_status:      Status::InTransit,
-        additional_data: parameter.additional_data,
-    })]);
+    assert_eq!(events, [
+        Event::ItemStatusChanged(ItemStatusChangedEvent {
+            item_id:         parameter.item_id,
+            new_status:      Status::InTransit,
+            additional_data: parameter.additional_data,
+        }),
+        Event::ItemSummary(ItemSummaryEvent {
+            version:       ITEM_SUMMARY_EVENT_VERSION,
+            item_id:       parameter.item_id,
+            status:        Status::InTransit,
+            metadata_hash: None,
+        }),
+    ]);
 
     // Check contract state.
     check_state(
@@ -297,11 +676,12 @@ fn test_create_item_and_update_item_status() {
         track_and_trace_contract_address,
         Status::InTransit,
         metadata_url.clone(),
+        false,
     );
 
     let parameter = ChangeItemStatusParams {
         item_id,
-        additional_data: AdditionalData { bytes: vec![] },
+        additional_data: AdditionalData::empty(),
         new_status: Status::Sold,
     };
 
@@ -333,7 +713,7 @@ fn test_create_item_and_update_item_status() {
     let parameter = ChangeItemStatusParams {
         item_id,
         new_status: Status::Sold,
-        additional_data: AdditionalData { bytes: vec![] },
+        additional_data: AdditionalData::empty(),
     };
 
     // Check the ADMIN can update the item to any state.
@@ -360,11 +740,19 @@ fn test_create_item_and_update_item_status() {
         .flat_map(|(_addr, events)| events.iter().map(|e| e.parse().expect("Deserialize event")))
         .collect::<Vec<Event<AdditionalData>>>();
 
-    assert_eq!(events, [Event::ItemStatusChanged(ItemStatusChangedEvent {
-        item_id:         parameter.item_id,
-        new_status:      parameter.new_status,
-        additional_data: parameter.additional_data,
-    })]);
+    assert_eq!(events, [
+        Event::ItemStatusChanged(ItemStatusChangedEvent {
+            item_id:         parameter.item_id,
+            new_status:      parameter.new_status,
+            additional_data: parameter.additional_data,
+        }),
+        Event::ItemSummary(ItemSummaryEvent {
+            version:       ITEM_SUMMARY_EVENT_VERSION,
+            item_id:       parameter.item_id,
+            status:        parameter.new_status,
+            metadata_hash: None,
+        }),
+    ]);
 
     // Check contract state.
     check_state(
@@ -372,64 +760,253 @@ fn test_create_item_and_update_item_status() {
         track_and_trace_contract_address,
         parameter.new_status,
         metadata_url,
+        false,
     );
 }
 
-// Invoke the several getter functions and check that the contract state is as
-// expected. Exactly one item is expected to be in the state.
-fn check_state(
-    chain: &Chain,
-    track_and_trace_contract_address: ContractAddress,
-    status: Status,
-    metadata_url: Option<MetadataUrl>,
-) {
-    let invoke = chain
-        .contract_invoke(
-            ADMIN,
-            ADMIN_ADDR,
-            Energy::from(10000),
+// 1. Test that the PRODUCER can update the statuses of several items at once.
+// 2. Test that no status is updated if the sender is not authorized to update
+//    one of the items.
+#[test]
+fn test_change_item_status_batch() {
+    let (mut chain, _, track_and_trace_contract_address) = initialize_chain_and_contract();
+
+    // Create three items.
+    for _ in 0..3 {
+        create_item(&mut chain, track_and_trace_contract_address, ADMIN)
+            .expect("Should be able to create item");
+    }
+
+    let change_item_status_batch = |chain: &mut Chain,
+                                    sender: AccountAddress,
+                                    params: Vec<(u64, Status)>| {
+        let params: Vec<ChangeItemStatusParams<AdditionalData>> = params
+            .into_iter()
+            .map(|(item_id, new_status)| ChangeItemStatusParams {
+                item_id: ItemID::from(item_id),
+                new_status,
+                additional_data: AdditionalData::empty(),
+            })
+            .collect();
+        chain.contract_update(
+            SIGNER,
+            sender,
+            Address::Account(sender),
+            Energy::from(30000),
             UpdateContractPayload {
-                amount:       Amount::zero(),
+                amount:       Amount::from_ccd(0),
+                address:      track_and_trace_contract_address,
                 receive_name: OwnedReceiveName::new_unchecked(
-                    "track_and_trace.getRoles".to_string(),
+                    "track_and_trace.changeItemStatusBatch".to_string(),
                 ),
-                address:      track_and_trace_contract_address,
-                message:      OwnedParameter::from_serial(&ADMIN_ADDR)
-                    .expect("Serialize parameter"),
+                message:      OwnedParameter::from_serial(&params).expect("Serialize parameter"),
             },
         )
-        .expect("Invoke view");
+    };
 
-    let return_value: Vec<Roles> = invoke.parse_return_value().expect("ViewState return value");
+    // Check the PRODUCER can update the statuses of two items at once.
+    let params = vec![(0, Status::InTransit), (1, Status::InTransit)];
+    let update = change_item_status_batch(&mut chain, PRODUCER, params)
+        .expect("Should be able to update the statuses of the items");
 
-    assert_eq!(return_value, vec![Roles::Admin]);
+    // Check that the events are logged for every item in order.
+    let events = update
+        .events()
+        .flat_map(|(_addr, events)| events.iter().map(|e| e.parse().expect("Deserialize event")))
+        .collect::<Vec<Event<AdditionalData>>>();
 
-    let item_id = ItemID::from(0u64);
+    let expected_events = |item_id: ItemID| {
+        [
+            Event::ItemStatusChanged(ItemStatusChangedEvent {
+                item_id,
+                new_status: Status::InTransit,
+                additional_data: AdditionalData::empty(),
+            }),
+            Event::ItemSummary(ItemSummaryEvent {
+                version: ITEM_SUMMARY_EVENT_VERSION,
+                item_id,
+                status: Status::InTransit,
+                metadata_hash: None,
+            }),
+        ]
+    };
+    assert_eq!(
+        events,
+        [
+            expected_events(ItemID::from(0u64)),
+            expected_events(ItemID::from(1u64))
+        ]
+        .concat()
+    );
 
-    let invoke = chain
-        .contract_invoke(
+    // Check that no status is updated if the TRANSPORTER is not authorized to
+    // update one of the items (the third item is still `Produced`).
+    let params = vec![(0, Status::InStore), (2, Status::InStore)];
+    let update =
+        change_item_status_batch(&mut chain, TRANSPORTER, params).expect_err("Should expect error");
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::Unauthorized);
+
+    // Check contract state.
+    check_state(
+        &chain,
+        track_and_trace_contract_address,
+        Status::InTransit,
+        None,
+        false,
+    );
+}
+
+// 1. Test that a location, a temperature reading, and a document hash can be
+//    attached to status changes and are logged with the status changed events.
+#[test]
+fn test_typed_additional_data() {
+    let (mut chain, _, track_and_trace_contract_address) = initialize_chain_and_contract();
+
+    create_item(&mut chain, track_and_trace_contract_address, ADMIN)
+        .expect("Should be able to create item");
+
+    let status_changes = [
+        (PRODUCER, Status::InTransit, AdditionalData::Location {
+            latitude:  55_676_098,
+            longitude: 12_568_337,
+        }),
+        (TRANSPORTER, Status::InStore, AdditionalData::Temperature {
+            millidegrees_celsius: -18_500,
+        }),
+        (SELLER, Status::Sold, AdditionalData::DocumentHash {
+            hash: [7u8; 32],
+        }),
+    ];
+
+    for (sender, new_status, additional_data) in status_changes {
+        let parameter = ChangeItemStatusParams {
+            item_id: ItemID::from(0u64),
+            new_status,
+            additional_data,
+        };
+
+        let update = chain
+            .contract_update(
+                SIGNER,
+                sender,
+                Address::Account(sender),
+                Energy::from(10000),
+                UpdateContractPayload {
+                    amount:       Amount::from_ccd(0),
+                    address:      track_and_trace_contract_address,
+                    receive_name: OwnedReceiveName::new_unchecked(
+                        "track_and_trace.changeItemStatus".to_string(),
+                    ),
+                    message:      OwnedParameter::from_serial(&parameter)
+                        .expect("Serialize parameter"),
+                },
+            )
+            .expect("Should be able update the state of the item");
+
+        // Check that the additional data is logged with the status change.
+        let events = update
+            .events()
+            .flat_map(|(_addr, events)| {
+                events.iter().map(|e| e.parse().expect("Deserialize event"))
+            })
+            .collect::<Vec<Event<AdditionalData>>>();
+
+        assert_eq!(
+            events[0],
+            Event::ItemStatusChanged(ItemStatusChangedEvent {
+                item_id:         parameter.item_id,
+                new_status:      parameter.new_status,
+                additional_data: parameter.additional_data,
+            })
+        );
+    }
+
+    // Check contract state.
+    check_state(
+        &chain,
+        track_and_trace_contract_address,
+        Status::Sold,
+        None,
+        false,
+    );
+}
+
+// 1. Test that the PRODUCER can NOT register a custom status.
+// 2. Test that the ADMIN can register a custom status but not twice.
+// 3. Test that transition edges can NOT use unregistered custom statuses.
+// 4. Test that an item can be updated to a registered custom status.
+#[test]
+fn test_custom_statuses() {
+    let (mut chain, _, track_and_trace_contract_address) = initialize_chain_and_contract();
+
+    let add_custom_status = |chain: &mut Chain, sender: AccountAddress, name: &str| {
+        chain.contract_update(
+            SIGNER,
+            sender,
+            Address::Account(sender),
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::from_ccd(0),
+                address:      track_and_trace_contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.addCustomStatus".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(&name.to_string())
+                    .expect("Serialize parameter"),
+            },
+        )
+    };
+
+    let add_transition_edge = |chain: &mut Chain, to_status: Status| {
+        chain.contract_update(
+            SIGNER,
             ADMIN,
             ADMIN_ADDR,
             Energy::from(10000),
             UpdateContractPayload {
-                amount:       Amount::zero(),
+                amount:       Amount::from_ccd(0),
+                address:      track_and_trace_contract_address,
                 receive_name: OwnedReceiveName::new_unchecked(
-                    "track_and_trace.getItemState".to_string(),
+                    "track_and_trace.updateStateMachine".to_string(),
                 ),
-                address:      track_and_trace_contract_address,
-                message:      OwnedParameter::from_serial(&item_id).expect("Serialize parameter"),
+                message:      OwnedParameter::from_serial(&UpdateStateMachineParams {
+                    address: PRODUCER,
+                    from_status: Status::Produced,
+                    to_status,
+                    update: Update::Add,
+                })
+                .expect("Serialize parameter"),
             },
         )
-        .expect("Invoke view");
+    };
 
-    let return_value: ItemState = invoke.parse_return_value().expect("ViewState return value");
+    // Check the PRODUCER can NOT register a custom status.
+    let update =
+        add_custom_status(&mut chain, PRODUCER, "Recalled").expect_err("Should expect error");
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::Unauthorized);
 
-    assert_eq!(return_value, ItemState {
-        status,
-        metadata_url
-    });
+    // Check the ADMIN can register a custom status.
+    let update = add_custom_status(&mut chain, ADMIN, "Recalled")
+        .expect("Should be able to register a custom status");
 
-    let invoke = chain
+    // Check that the event is logged.
+    let events = update
+        .events()
+        .flat_map(|(_addr, events)| events.iter().map(|e| e.parse().expect("Deserialize event")))
+        .collect::<Vec<Event<AdditionalData>>>();
+
+    assert_eq!(events, [Event::CustomStatusAdded(CustomStatusAddedEvent {
+        status: Status::Custom(0),
+        name:   "Recalled".to_string(),
+    })]);
+
+    let custom_statuses: Vec<String> = chain
         .contract_invoke(
             ADMIN,
             ADMIN_ADDR,
@@ -437,25 +1014,2069 @@ fn check_state(
             UpdateContractPayload {
                 amount:       Amount::zero(),
                 receive_name: OwnedReceiveName::new_unchecked(
-                    "track_and_trace.getNextItemId".to_string(),
+                    "track_and_trace.getCustomStatuses".to_string(),
                 ),
                 address:      track_and_trace_contract_address,
                 message:      OwnedParameter::empty(),
             },
         )
-        .expect("Invoke view");
+        .expect("Invoke getCustomStatuses")
+        .parse_return_value()
+        .expect("getCustomStatuses return value");
+    assert_eq!(custom_statuses, vec!["Recalled".to_string()]);
 
-    let return_value: u64 = invoke.parse_return_value().expect("ViewState return value");
+    // Check the ADMIN can NOT register the same custom status twice.
+    let update = add_custom_status(&mut chain, ADMIN, "Recalled").expect_err("Should expect error");
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::CustomStatusAlreadyExists);
 
-    assert_eq!(return_value, 1u64);
-}
+    // Check that a transition edge can NOT use an unregistered custom status.
+    let update =
+        add_transition_edge(&mut chain, Status::Custom(1)).expect_err("Should expect error");
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::UnknownStatus);
 
-/// Setup chain and contract. Returns the chain, keys of the ADMIN and PRODUCER,
-/// and the contract address.
-fn initialize_chain_and_contract() -> (Chain, AccountKeypairs, ContractAddress) {
-    let mut chain = Chain::builder()
-        .build()
-        .expect("Should be able to build chain");
+    // Check that the PRODUCER can update an item to the custom status.
+    add_transition_edge(&mut chain, Status::Custom(0))
+        .expect("Should be able to add the transition edge");
+    create_item(&mut chain, track_and_trace_contract_address, ADMIN)
+        .expect("Should be able to create item");
+
+    let _update = chain
+        .contract_update(
+            SIGNER,
+            PRODUCER,
+            PRODUCER_ADDR,
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::from_ccd(0),
+                address:      track_and_trace_contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.changeItemStatus".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(&ChangeItemStatusParams {
+                    item_id:         ItemID::from(0u64),
+                    new_status:      Status::Custom(0),
+                    additional_data: AdditionalData::empty(),
+                })
+                .expect("Serialize parameter"),
+            },
+        )
+        .expect("Should be able to update the state of the item");
+
+    // Check contract state.
+    check_state(
+        &chain,
+        track_and_trace_contract_address,
+        Status::Custom(0),
+        None,
+        false,
+    );
+}
+
+// 1. Test that the history of an item records the creation and every status
+//    change in order.
+// 2. Test that only the last `MAX_ITEM_HISTORY_LENGTH` entries are kept.
+#[test]
+fn test_item_history() {
+    let (mut chain, _, track_and_trace_contract_address) = initialize_chain_and_contract();
+
+    let item_id = ItemID::from(0u64);
+
+    let change_item_status = |chain: &mut Chain, sender: AccountAddress, new_status: Status| {
+        chain.contract_update(
+            SIGNER,
+            sender,
+            Address::Account(sender),
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::from_ccd(0),
+                address:      track_and_trace_contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.changeItemStatus".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(&ChangeItemStatusParams {
+                    item_id,
+                    new_status,
+                    additional_data: AdditionalData::empty(),
+                })
+                .expect("Serialize parameter"),
+            },
+        )
+    };
+
+    let get_item_history = |chain: &Chain| {
+        chain.contract_invoke(
+            ADMIN,
+            ADMIN_ADDR,
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::zero(),
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.getItemHistory".to_string(),
+                ),
+                address:      track_and_trace_contract_address,
+                message:      OwnedParameter::from_serial(&item_id).expect("Serialize parameter"),
+            },
+        )
+    };
+
+    // Check that the history of an item that does not exist can NOT be viewed.
+    let invoke = get_item_history(&chain).expect_err("Should expect error");
+    let error: CustomContractError = invoke
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::ItemDoesNotExist);
+
+    // Create the item and update its status twice with one second in between.
+    let created_at = chain.block_time();
+    create_item(&mut chain, track_and_trace_contract_address, ADMIN)
+        .expect("Should be able to create item");
+    chain
+        .tick_block_time(Duration::from_seconds(1))
+        .expect("Should be able to advance the block time");
+    change_item_status(&mut chain, PRODUCER, Status::InTransit)
+        .expect("Should be able to update the state of the item");
+    chain
+        .tick_block_time(Duration::from_seconds(1))
+        .expect("Should be able to advance the block time");
+    change_item_status(&mut chain, TRANSPORTER, Status::InStore)
+        .expect("Should be able to update the state of the item");
+
+    // Check that the history is ordered from the oldest to the newest entry.
+    let history: Vec<ItemHistoryEntry> = get_item_history(&chain)
+        .expect("Invoke getItemHistory")
+        .parse_return_value()
+        .expect("getItemHistory return value");
+    assert_eq!(history, vec![
+        ItemHistoryEntry {
+            status:    Status::Produced,
+            timestamp: created_at,
+            actor:     ADMIN_ADDR,
+        },
+        ItemHistoryEntry {
+            status:    Status::InTransit,
+            timestamp: created_at
+                .checked_add(Duration::from_seconds(1))
+                .expect("Valid timestamp"),
+            actor:     PRODUCER_ADDR,
+        },
+        ItemHistoryEntry {
+            status:    Status::InStore,
+            timestamp: created_at
+                .checked_add(Duration::from_seconds(2))
+                .expect("Valid timestamp"),
+            actor:     Address::Account(TRANSPORTER),
+        },
+    ]);
+
+    // Fill the history by having the ADMIN move the item back and forth.
+    for i in 0..MAX_ITEM_HISTORY_LENGTH {
+        let new_status = if i % 2 == 0 {
+            Status::InTransit
+        } else {
+            Status::InStore
+        };
+        change_item_status(&mut chain, ADMIN, new_status)
+            .expect("Should be able to update the state of the item");
+    }
+
+    // Check that only the last entries are kept and the oldest are dropped.
+    let history: Vec<ItemHistoryEntry> = get_item_history(&chain)
+        .expect("Invoke getItemHistory")
+        .parse_return_value()
+        .expect("getItemHistory return value");
+    assert_eq!(history.len(), MAX_ITEM_HISTORY_LENGTH);
+    assert!(
+        history.iter().all(|entry| entry.actor == ADMIN_ADDR),
+        "The entries before the last status changes should be dropped"
+    );
+    assert_eq!(
+        history.last().map(|entry| entry.status),
+        Some(Status::InStore)
+    );
+}
+
+// 1. Test that the creation time of an item is recorded as its creation and
+//    last update time.
+// 2. Test that a status change updates the last update time only.
+#[test]
+fn test_item_timestamps() {
+    let (mut chain, _, track_and_trace_contract_address) = initialize_chain_and_contract();
+
+    let item_id = ItemID::from(0u64);
+
+    let get_item_state = |chain: &Chain| -> ItemState {
+        chain
+            .contract_invoke(
+                ADMIN,
+                ADMIN_ADDR,
+                Energy::from(10000),
+                UpdateContractPayload {
+                    amount:       Amount::zero(),
+                    receive_name: OwnedReceiveName::new_unchecked(
+                        "track_and_trace.getItemState".to_string(),
+                    ),
+                    address:      track_and_trace_contract_address,
+                    message:      OwnedParameter::from_serial(&item_id)
+                        .expect("Serialize parameter"),
+                },
+            )
+            .expect("Invoke getItemState")
+            .parse_return_value()
+            .expect("getItemState return value")
+    };
+
+    // Check that the creation time is recorded as the creation and last update
+    // time.
+    let created_at = chain.block_time();
+    create_item(&mut chain, track_and_trace_contract_address, ADMIN)
+        .expect("Should be able to create item");
+    let item = get_item_state(&chain);
+    assert_eq!(item.created_at, created_at);
+    assert_eq!(item.updated_at, created_at);
+
+    // Check that a status change one second later only updates the last update
+    // time.
+    chain
+        .tick_block_time(Duration::from_seconds(1))
+        .expect("Should be able to advance the block time");
+    chain
+        .contract_update(
+            SIGNER,
+            PRODUCER,
+            PRODUCER_ADDR,
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::from_ccd(0),
+                address:      track_and_trace_contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.changeItemStatus".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(&ChangeItemStatusParams {
+                    item_id,
+                    new_status: Status::InTransit,
+                    additional_data: AdditionalData::empty(),
+                })
+                .expect("Serialize parameter"),
+            },
+        )
+        .expect("Should be able to update the state of the item");
+
+    let item = get_item_state(&chain);
+    assert_eq!(item.created_at, created_at);
+    assert_eq!(
+        item.updated_at,
+        created_at
+            .checked_add(Duration::from_seconds(1))
+            .expect("Valid timestamp")
+    );
+}
+
+// 1. Test that the items are returned in pages ordered by the item id.
+// 2. Test that archived items are skipped.
+// 3. Test that the page size is limited to `MAX_ITEM_PAGE_SIZE`.
+// 4. Test that at most `MAX_ITEM_IDS_SCANNED` item ids are looked up per page,
+//    so that the pages continue after a long run of archived items.
+#[test]
+fn test_get_item_states_paged() {
+    let (mut chain, _, track_and_trace_contract_address) = initialize_chain_and_contract();
+
+    let get_item_states_paged = |chain: &Chain, start_id: u64, limit: u32| {
+        let page: ItemStatesPage = chain
+            .contract_invoke(
+                ADMIN,
+                ADMIN_ADDR,
+                Energy::from(1000000),
+                UpdateContractPayload {
+                    amount:       Amount::zero(),
+                    receive_name: OwnedReceiveName::new_unchecked(
+                        "track_and_trace.getItemStatesPaged".to_string(),
+                    ),
+                    address:      track_and_trace_contract_address,
+                    message:      OwnedParameter::from_serial(&GetItemStatesPagedParams {
+                        start_id: ItemID::from(start_id),
+                        limit,
+                    })
+                    .expect("Serialize parameter"),
+                },
+            )
+            .expect("Invoke getItemStatesPaged")
+            .parse_return_value()
+            .expect("getItemStatesPaged return value");
+        let item_ids = page
+            .items
+            .into_iter()
+            .map(|(item_id, item)| {
+                assert_eq!(item.status, Status::Produced);
+                item_id.0
+            })
+            .collect::<Vec<_>>();
+        (item_ids, page.next_start_id.map(|item_id| item_id.0))
+    };
+
+    let archive_item = |chain: &mut Chain, item_id: u64| {
+        chain
+            .contract_update(
+                SIGNER,
+                ADMIN,
+                ADMIN_ADDR,
+                Energy::from(10000),
+                UpdateContractPayload {
+                    amount:       Amount::from_ccd(0),
+                    address:      track_and_trace_contract_address,
+                    receive_name: OwnedReceiveName::new_unchecked(
+                        "track_and_trace.archiveItem".to_string(),
+                    ),
+                    message:      OwnedParameter::from_serial(&ItemID::from(item_id))
+                        .expect("Serialize parameter"),
+                },
+            )
+            .expect("Should be able to archive item");
+    };
+
+    // Create four items and archive the second one.
+    for _ in 0..4 {
+        create_item(&mut chain, track_and_trace_contract_address, ADMIN)
+            .expect("Should be able to create item");
+    }
+    archive_item(&mut chain, 1);
+
+    // Check that the pages skip the archived item.
+    assert_eq!(get_item_states_paged(&chain, 0, 2), (vec![0, 2], Some(3)));
+    assert_eq!(get_item_states_paged(&chain, 3, 2), (vec![3], None));
+    assert_eq!(get_item_states_paged(&chain, 4, 2), (vec![], None));
+
+    // Check that the page size is limited.
+    for _ in 0..MAX_ITEM_PAGE_SIZE {
+        create_item(&mut chain, track_and_trace_contract_address, ADMIN)
+            .expect("Should be able to create item");
+    }
+    let (item_ids, next_start_id) = get_item_states_paged(&chain, 0, u32::MAX);
+    assert_eq!(item_ids.len(), MAX_ITEM_PAGE_SIZE as usize);
+    assert_eq!(next_start_id, Some(101));
+
+    // Archive a run of `MAX_ITEM_IDS_SCANNED` items starting at id 4, so that
+    // the next item after id 3 is the item with id `4 + MAX_ITEM_IDS_SCANNED`.
+    for _ in 0..MAX_ITEM_IDS_SCANNED {
+        create_item(&mut chain, track_and_trace_contract_address, ADMIN)
+            .expect("Should be able to create item");
+    }
+    for item_id in 4..4 + MAX_ITEM_IDS_SCANNED {
+        archive_item(&mut chain, item_id);
+    }
+
+    // Check that the page stops after scanning `MAX_ITEM_IDS_SCANNED` ids and
+    // the next page continues after the archived items.
+    let next_item = 4 + MAX_ITEM_IDS_SCANNED;
+    assert_eq!(
+        get_item_states_paged(&chain, 3, 2),
+        (vec![3], Some(3 + MAX_ITEM_IDS_SCANNED))
+    );
+    assert_eq!(
+        get_item_states_paged(&chain, 3 + MAX_ITEM_IDS_SCANNED, 2),
+        (vec![next_item, next_item + 1], Some(next_item + 2))
+    );
+}
+
+// 1. Test that the ADMIN can archive any item.
+// 2. Test that only the ADMIN can configure the archive period.
+// 3. Test that anyone can archive an item once it has been sold for the archive
+//    period.
+#[test]
+fn test_archive_item() {
+    let (mut chain, _, track_and_trace_contract_address) = initialize_chain_and_contract();
+
+    let archive_item = |chain: &mut Chain, sender: AccountAddress, item_id: u64| {
+        chain.contract_update(
+            SIGNER,
+            sender,
+            Address::Account(sender),
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::from_ccd(0),
+                address:      track_and_trace_contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.archiveItem".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(&ItemID::from(item_id))
+                    .expect("Serialize parameter"),
+            },
+        )
+    };
+
+    let set_archive_period = |chain: &mut Chain, sender: AccountAddress, period: Duration| {
+        chain.contract_update(
+            SIGNER,
+            sender,
+            Address::Account(sender),
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::from_ccd(0),
+                address:      track_and_trace_contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.setArchivePeriod".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(&Some(period))
+                    .expect("Serialize parameter"),
+            },
+        )
+    };
+
+    let assert_archived = |chain: &Chain, item_id: u64| {
+        let invoke = chain
+            .contract_invoke(
+                ADMIN,
+                ADMIN_ADDR,
+                Energy::from(10000),
+                UpdateContractPayload {
+                    amount:       Amount::zero(),
+                    receive_name: OwnedReceiveName::new_unchecked(
+                        "track_and_trace.getItemHistory".to_string(),
+                    ),
+                    address:      track_and_trace_contract_address,
+                    message:      OwnedParameter::from_serial(&ItemID::from(item_id))
+                        .expect("Serialize parameter"),
+                },
+            )
+            .expect_err("Should expect error");
+        let error: CustomContractError = invoke
+            .parse_return_value()
+            .expect("CustomContractError return value");
+        assert_eq!(error, CustomContractError::ItemDoesNotExist);
+    };
+
+    for _ in 0..2 {
+        create_item(&mut chain, track_and_trace_contract_address, ADMIN)
+            .expect("Should be able to create item");
+    }
+
+    // Check the PRODUCER can NOT archive an item that is not sold.
+    let update = archive_item(&mut chain, PRODUCER, 0).expect_err("Should expect error");
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::NotArchivable);
+
+    // Check the ADMIN can archive any item.
+    let update = archive_item(&mut chain, ADMIN, 0).expect("Should be able to archive item");
+
+    // Check that the event is logged.
+    let events = update
+        .events()
+        .flat_map(|(_addr, events)| events.iter().map(|e| e.parse().expect("Deserialize event")))
+        .collect::<Vec<Event<AdditionalData>>>();
+
+    assert_eq!(events, [Event::ItemArchived(ItemArchivedEvent {
+        item_id: ItemID::from(0u64),
+    })]);
+    assert_archived(&chain, 0);
+
+    // Check the PRODUCER can NOT configure the archive period.
+    let update = set_archive_period(&mut chain, PRODUCER, Duration::from_days(1))
+        .expect_err("Should expect error");
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::Unauthorized);
+
+    set_archive_period(&mut chain, ADMIN, Duration::from_days(1))
+        .expect("Should be able to set the archive period");
+
+    // Sell the second item.
+    chain
+        .contract_update(
+            SIGNER,
+            ADMIN,
+            ADMIN_ADDR,
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::from_ccd(0),
+                address:      track_and_trace_contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.changeItemStatus".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(&ChangeItemStatusParams {
+                    item_id:         ItemID::from(1u64),
+                    new_status:      Status::Sold,
+                    additional_data: AdditionalData::empty(),
+                })
+                .expect("Serialize parameter"),
+            },
+        )
+        .expect("Should be able to update the state of the item");
+
+    // Check the PRODUCER can NOT archive the item before the archive period
+    // has passed.
+    let update = archive_item(&mut chain, PRODUCER, 1).expect_err("Should expect error");
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::NotArchivable);
+
+    // Check the PRODUCER can archive the item once the archive period has
+    // passed.
+    chain
+        .tick_block_time(Duration::from_days(1))
+        .expect("Should be able to advance the block time");
+    archive_item(&mut chain, PRODUCER, 1).expect("Should be able to archive item");
+    assert_archived(&chain, 1);
+}
+
+// 1. Test that the PRODUCER can NOT set the maximum duration of a status.
+// 2. Test that an item can NOT be expired before the maximum duration of its
+//    status has passed.
+// 3. Test that anyone can expire an item once the maximum duration of its
+//    status has passed.
+// 4. Test that an expired item can NOT be expired again.
+#[test]
+fn test_expire_item() {
+    let (mut chain, _, track_and_trace_contract_address) = initialize_chain_and_contract();
+
+    let item_id = ItemID::from(0u64);
+
+    let expire_item = |chain: &mut Chain, sender: AccountAddress| {
+        chain.contract_update(
+            SIGNER,
+            sender,
+            Address::Account(sender),
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::from_ccd(0),
+                address:      track_and_trace_contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.expireItem".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(&item_id).expect("Serialize parameter"),
+            },
+        )
+    };
+
+    let set_max_duration = |chain: &mut Chain, sender: AccountAddress| {
+        chain.contract_update(
+            SIGNER,
+            sender,
+            Address::Account(sender),
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::from_ccd(0),
+                address:      track_and_trace_contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.setMaxDuration".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(&SetMaxDurationParams {
+                    status:       Status::Produced,
+                    max_duration: Some(Duration::from_hours(1)),
+                })
+                .expect("Serialize parameter"),
+            },
+        )
+    };
+
+    create_item(&mut chain, track_and_trace_contract_address, ADMIN)
+        .expect("Should be able to create item");
+
+    // Check the PRODUCER can NOT set the maximum duration of a status.
+    let update = set_max_duration(&mut chain, PRODUCER).expect_err("Should expect error");
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::Unauthorized);
+
+    // Check that an item without a maximum duration can NOT be expired.
+    let update = expire_item(&mut chain, SELLER).expect_err("Should expect error");
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::NotExpired);
+
+    let update =
+        set_max_duration(&mut chain, ADMIN).expect("Should be able to set the maximum duration");
+
+    // Check that the event is logged.
+    let events = update
+        .events()
+        .flat_map(|(_addr, events)| events.iter().map(|e| e.parse().expect("Deserialize event")))
+        .collect::<Vec<Event<AdditionalData>>>();
+
+    assert_eq!(events, [Event::MaxDurationUpdated(
+        MaxDurationUpdatedEvent {
+            status:       Status::Produced,
+            max_duration: Some(Duration::from_hours(1)),
+        }
+    )]);
+
+    // Check that the item can NOT be expired before the maximum duration has
+    // passed.
+    let update = expire_item(&mut chain, SELLER).expect_err("Should expect error");
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::NotExpired);
+
+    // Check that the SELLER can expire the item once the maximum duration has
+    // passed.
+    chain
+        .tick_block_time(Duration::from_hours(1))
+        .expect("Should be able to advance the block time");
+    let update = expire_item(&mut chain, SELLER).expect("Should be able to expire item");
+
+    // Check that the events are logged.
+    let events = update
+        .events()
+        .flat_map(|(_addr, events)| events.iter().map(|e| e.parse().expect("Deserialize event")))
+        .collect::<Vec<Event<AdditionalData>>>();
+
+    assert_eq!(events, [
+        Event::ItemExpired(ItemExpiredEvent {
+            item_id,
+            previous_status: Status::Produced,
+        }),
+        Event::ItemSummary(ItemSummaryEvent {
+            version: ITEM_SUMMARY_EVENT_VERSION,
+            item_id,
+            status: Status::Expired,
+            metadata_hash: None,
+        }),
+    ]);
+
+    // Check contract state.
+    check_state(
+        &chain,
+        track_and_trace_contract_address,
+        Status::Expired,
+        None,
+        false,
+    );
+
+    // Check that the expired item can NOT be expired again.
+    let update = expire_item(&mut chain, SELLER).expect_err("Should expect error");
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::NotExpired);
+}
+
+// 1. Test that the PRODUCER can NOT freeze the metadata of an item.
+// 2. Test that the ADMIN can freeze the metadata of an item.
+// 3. Test that the ADMIN can NOT freeze the metadata of an item twice.
+#[test]
+fn test_freeze_item_metadata() {
+    let (mut chain, _, track_and_trace_contract_address) = initialize_chain_and_contract();
+
+    // Create the Parameter.
+    let metadata_url = Some(MetadataUrl {
+        url:  "https://some.example/".to_string(),
+        hash: None,
+    });
+
+    let item_id = ItemID::from(0u64);
+
+    // Create a new item.
+    let _update = chain
+        .contract_update(
+            SIGNER,
+            ADMIN,
+            ADMIN_ADDR,
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::from_ccd(0),
+                address:      track_and_trace_contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.createItem".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(&metadata_url)
+                    .expect("Serialize parameter"),
+            },
+        )
+        .expect("Should be able to create item");
+
+    // Check the PRODUCER can NOT freeze the metadata of the item.
+    let update = chain
+        .contract_update(
+            SIGNER,
+            PRODUCER,
+            PRODUCER_ADDR,
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::from_ccd(0),
+                address:      track_and_trace_contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.freezeItemMetadata".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(&item_id).expect("Serialize parameter"),
+            },
+        )
+        .expect_err("Should expect error");
+
+    // Check that the correct error is returned.
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::Unauthorized);
+
+    // Check the ADMIN can freeze the metadata of the item.
+    let update = chain
+        .contract_update(
+            SIGNER,
+            ADMIN,
+            ADMIN_ADDR,
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::from_ccd(0),
+                address:      track_and_trace_contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.freezeItemMetadata".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(&item_id).expect("Serialize parameter"),
+            },
+        )
+        .expect("Should be able to freeze the metadata of the item");
+
+    // Check that the events are logged.
+    let events = update
+        .events()
+        .flat_map(|(_addr, events)| events.iter().map(|e| e.parse().expect("Deserialize event")))
+        .collect::<Vec<Event<AdditionalData>>>();
+
+    assert_eq!(events, [
+        Event::ItemMetadataFrozen(ItemMetadataFrozenEvent { item_id }),
+        Event::ItemSummary(ItemSummaryEvent {
+            version: ITEM_SUMMARY_EVENT_VERSION,
+            item_id,
+            status: Status::Produced,
+            metadata_hash: None,
+        }),
+    ]);
+
+    // Check contract state.
+    check_state(
+        &chain,
+        track_and_trace_contract_address,
+        Status::Produced,
+        metadata_url,
+        true,
+    );
+
+    // Check the ADMIN can NOT freeze the metadata of the item twice.
+    let update = chain
+        .contract_update(
+            SIGNER,
+            ADMIN,
+            ADMIN_ADDR,
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::from_ccd(0),
+                address:      track_and_trace_contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.freezeItemMetadata".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(&item_id).expect("Serialize parameter"),
+            },
+        )
+        .expect_err("Should expect error");
+
+    // Check that the correct error is returned.
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::MetadataFrozen);
+}
+
+// 1. Test that the creator of an item is recorded in the state of the item.
+// 2. Test that a producer that did not create the item (SELLER) can NOT update
+//    the metadata of the item.
+// 3. Test that the creator of an item can update the metadata of the item.
+// 4. Test that the ADMIN can update the metadata of an item.
+// 5. Test that the metadata of an item can NOT be updated once it is frozen.
+#[test]
+fn test_update_item_metadata() {
+    let (mut chain, _, track_and_trace_contract_address) = initialize_chain_and_contract();
+
+    let item_id = ItemID::from(0u64);
+
+    // Grant the Producer role to the PRODUCER and the SELLER.
+    for address in [PRODUCER_ADDR, SELLER_ADDR] {
+        chain
+            .contract_update(
+                SIGNER,
+                ADMIN,
+                ADMIN_ADDR,
+                Energy::from(10000),
+                UpdateContractPayload {
+                    amount:       Amount::from_ccd(0),
+                    address:      track_and_trace_contract_address,
+                    receive_name: OwnedReceiveName::new_unchecked(
+                        "track_and_trace.grantRole".to_string(),
+                    ),
+                    message:      OwnedParameter::from_serial(&GrantRoleParams {
+                        address,
+                        role: Roles::Producer,
+                    })
+                    .expect("Serialize parameter"),
+                },
+            )
+            .expect("Should be able to grant role");
+    }
+
+    // Allow producers to create items.
+    chain
+        .contract_update(
+            SIGNER,
+            ADMIN,
+            ADMIN_ADDR,
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::from_ccd(0),
+                address:      track_and_trace_contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.setCreateItemRoles".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(&vec![Roles::Admin, Roles::Producer])
+                    .expect("Serialize parameter"),
+            },
+        )
+        .expect("Should be able to set the roles allowed to create items");
+
+    // Create a new item as the PRODUCER.
+    let _update = chain
+        .contract_update(
+            SIGNER,
+            PRODUCER,
+            PRODUCER_ADDR,
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::from_ccd(0),
+                address:      track_and_trace_contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.createItem".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(&None::<MetadataUrl>)
+                    .expect("Serialize parameter"),
+            },
+        )
+        .expect("Should be able to create item");
+
+    // Check that the PRODUCER is recorded as the creator of the item.
+    let item: ItemState = chain
+        .contract_invoke(
+            ADMIN,
+            ADMIN_ADDR,
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::zero(),
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.getItemState".to_string(),
+                ),
+                address:      track_and_trace_contract_address,
+                message:      OwnedParameter::from_serial(&item_id).expect("Serialize parameter"),
+            },
+        )
+        .expect("Invoke view")
+        .parse_return_value()
+        .expect("ItemState return value");
+    assert_eq!(item.creator, PRODUCER_ADDR);
+
+    let update_item_metadata =
+        |chain: &mut Chain, sender: AccountAddress, metadata_url: Option<MetadataUrl>| {
+            chain.contract_update(
+                SIGNER,
+                sender,
+                Address::Account(sender),
+                Energy::from(10000),
+                UpdateContractPayload {
+                    amount:       Amount::from_ccd(0),
+                    address:      track_and_trace_contract_address,
+                    receive_name: OwnedReceiveName::new_unchecked(
+                        "track_and_trace.updateItemMetadata".to_string(),
+                    ),
+                    message:      OwnedParameter::from_serial(&UpdateItemMetadataParams {
+                        item_id,
+                        metadata_url,
+                    })
+                    .expect("Serialize parameter"),
+                },
+            )
+        };
+
+    // Check the SELLER can NOT update the metadata of the item, although the
+    // SELLER is a producer as well.
+    let update = update_item_metadata(&mut chain, SELLER, None).expect_err("Should expect error");
+
+    // Check that the correct error is returned.
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::Unauthorized);
+
+    // Check the creator of the item can update the metadata of the item.
+    let metadata_hash = HashSha2256([1; 32]);
+    let metadata_url = Some(MetadataUrl {
+        url:  "https://some.example/1".to_string(),
+        hash: Some(metadata_hash),
+    });
+    let update = update_item_metadata(&mut chain, PRODUCER, metadata_url.clone())
+        .expect("Should be able to update the metadata of the item");
+
+    // Check that the events are logged.
+    let events = update
+        .events()
+        .flat_map(|(_addr, events)| events.iter().map(|e| e.parse().expect("Deserialize event")))
+        .collect::<Vec<Event<AdditionalData>>>();
+
+    assert_eq!(events, [
+        Event::ItemMetadataUpdated(ItemMetadataUpdatedEvent {
+            item_id,
+            metadata_url: metadata_url.clone(),
+        }),
+        Event::ItemSummary(ItemSummaryEvent {
+            version: ITEM_SUMMARY_EVENT_VERSION,
+            item_id,
+            status: Status::Produced,
+            metadata_hash: Some(metadata_hash),
+        }),
+    ]);
+
+    // Check contract state.
+    check_state(
+        &chain,
+        track_and_trace_contract_address,
+        Status::Produced,
+        metadata_url,
+        false,
+    );
+
+    // Check the ADMIN can update the metadata of the item.
+    let metadata_url = Some(MetadataUrl {
+        url:  "https://some.example/2".to_string(),
+        hash: None,
+    });
+    let _update = update_item_metadata(&mut chain, ADMIN, metadata_url.clone())
+        .expect("Should be able to update the metadata of the item");
+
+    // Check contract state.
+    check_state(
+        &chain,
+        track_and_trace_contract_address,
+        Status::Produced,
+        metadata_url.clone(),
+        false,
+    );
+
+    // Freeze the metadata of the item.
+    let _update = chain
+        .contract_update(
+            SIGNER,
+            ADMIN,
+            ADMIN_ADDR,
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::from_ccd(0),
+                address:      track_and_trace_contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.freezeItemMetadata".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(&item_id).expect("Serialize parameter"),
+            },
+        )
+        .expect("Should be able to freeze the metadata of the item");
+
+    // Check the metadata of the item can NOT be updated once it is frozen.
+    let update = update_item_metadata(&mut chain, ADMIN, None).expect_err("Should expect error");
+
+    // Check that the correct error is returned.
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::MetadataFrozen);
+
+    // Check contract state.
+    check_state(
+        &chain,
+        track_and_trace_contract_address,
+        Status::Produced,
+        metadata_url,
+        true,
+    );
+}
+
+// 1. Test that the PRODUCER can NOT pause the contract.
+// 2. Test that items can NOT be created or updated while the contract is
+//    paused, neither directly nor with a sponsored transaction.
+// 3. Test that items can be created again once the contract is unpaused.
+#[test]
+fn test_pause_and_unpause() {
+    let (mut chain, account_keypairs, track_and_trace_contract_address) =
+        initialize_chain_and_contract();
+
+    let set_paused = |chain: &mut Chain, sender: AccountAddress, entrypoint: &str| {
+        chain.contract_update(
+            SIGNER,
+            sender,
+            Address::Account(sender),
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::from_ccd(0),
+                address:      track_and_trace_contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(format!(
+                    "track_and_trace.{entrypoint}"
+                )),
+                message:      OwnedParameter::empty(),
+            },
+        )
+    };
+
+    let is_paused = |chain: &Chain| -> bool {
+        chain
+            .contract_invoke(
+                ADMIN,
+                ADMIN_ADDR,
+                Energy::from(10000),
+                UpdateContractPayload {
+                    amount:       Amount::zero(),
+                    receive_name: OwnedReceiveName::new_unchecked(
+                        "track_and_trace.isPaused".to_string(),
+                    ),
+                    address:      track_and_trace_contract_address,
+                    message:      OwnedParameter::empty(),
+                },
+            )
+            .expect("Invoke isPaused")
+            .parse_return_value()
+            .expect("isPaused return value")
+    };
+
+    create_item(&mut chain, track_and_trace_contract_address, ADMIN)
+        .expect("Should be able to create item");
+
+    // Check the PRODUCER can NOT pause the contract.
+    let update = set_paused(&mut chain, PRODUCER, "pause").expect_err("Should expect error");
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::Unauthorized);
+    assert!(!is_paused(&chain), "Contract should not be paused");
+
+    // Check the ADMIN can pause the contract.
+    set_paused(&mut chain, ADMIN, "pause").expect("Should be able to pause the contract");
+    assert!(is_paused(&chain), "Contract should be paused");
+
+    // Check that no item can be created while the contract is paused.
+    let update = create_item(&mut chain, track_and_trace_contract_address, ADMIN)
+        .expect_err("Should expect error");
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::Paused);
+
+    // Check that the status of an item can NOT be changed while the contract is
+    // paused.
+    let payload = ChangeItemStatusParams {
+        item_id:         ItemID::from(0u64),
+        new_status:      Status::InTransit,
+        additional_data: AdditionalData::empty(),
+    };
+    let update = chain
+        .contract_update(
+            SIGNER,
+            ADMIN,
+            ADMIN_ADDR,
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::from_ccd(0),
+                address:      track_and_trace_contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.changeItemStatus".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(&payload).expect("Serialize parameter"),
+            },
+        )
+        .expect_err("Should expect error");
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::Paused);
+
+    // Check that the status of an item can NOT be changed with a sponsored
+    // transaction while the contract is paused.
+    let update = permit(
+        &mut chain,
+        track_and_trace_contract_address,
+        to_bytes(&payload),
+        "changeItemStatus".to_string(),
+        SELLER,
+        account_keypairs.admin,
+    )
+    .expect_err("Should expect error");
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::Paused);
+
+    // Check that the item can NOT be updated, frozen, archived, or transferred
+    // while the contract is paused.
+    let item_id = ItemID::from(0u64);
+    for (entrypoint, message) in [
+        (
+            "updateItemMetadata",
+            OwnedParameter::from_serial(&UpdateItemMetadataParams {
+                item_id,
+                metadata_url: None,
+            }),
+        ),
+        ("freezeItemMetadata", OwnedParameter::from_serial(&item_id)),
+        ("archiveItem", OwnedParameter::from_serial(&item_id)),
+        (
+            "transferItem",
+            OwnedParameter::from_serial(&TransferItemParams {
+                item_id,
+                to: SELLER,
+            }),
+        ),
+    ] {
+        let update = chain
+            .contract_update(
+                SIGNER,
+                ADMIN,
+                ADMIN_ADDR,
+                Energy::from(10000),
+                UpdateContractPayload {
+                    amount:       Amount::from_ccd(0),
+                    address:      track_and_trace_contract_address,
+                    receive_name: OwnedReceiveName::new_unchecked(format!(
+                        "track_and_trace.{entrypoint}"
+                    )),
+                    message:      message.expect("Serialize parameter"),
+                },
+            )
+            .expect_err("Should expect error");
+        let error: CustomContractError = update
+            .parse_return_value()
+            .expect("CustomContractError return value");
+        assert_eq!(error, CustomContractError::Paused, "{entrypoint}");
+    }
+
+    // Check that the PRODUCER can NOT unpause the contract.
+    let update = set_paused(&mut chain, PRODUCER, "unpause").expect_err("Should expect error");
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::Unauthorized);
+
+    // Check that items can be created again once the ADMIN unpaused the
+    // contract.
+    set_paused(&mut chain, ADMIN, "unpause").expect("Should be able to unpause the contract");
+    assert!(!is_paused(&chain), "Contract should not be paused");
+
+    create_item(&mut chain, track_and_trace_contract_address, ADMIN)
+        .expect("Should be able to create item");
+}
+
+// 1. Test that the PRODUCER can NOT upgrade the contract.
+// 2. Test that the upgrade fails if the new module does not exist.
+// 3. Test that the upgrade fails if the migration function fails.
+// 4. Test that the ADMIN can upgrade the contract and the items are kept.
+#[test]
+fn test_upgrade() {
+    let (mut chain, _, track_and_trace_contract_address) = initialize_chain_and_contract();
+
+    create_item(&mut chain, track_and_trace_contract_address, ADMIN)
+        .expect("Should be able to create item");
+
+    let upgrade = |chain: &mut Chain, sender: AccountAddress, param: &UpgradeParams| {
+        chain.contract_update(
+            SIGNER,
+            sender,
+            Address::Account(sender),
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::from_ccd(0),
+                address:      track_and_trace_contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.upgrade".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(param).expect("Serialize parameter"),
+            },
+        )
+    };
+
+    let module_reference = module_load_v1("./concordium-out/module.wasm.v1")
+        .expect("Module exists")
+        .get_module_ref();
+
+    // Check the PRODUCER can NOT upgrade the contract.
+    let param = UpgradeParams {
+        module:  module_reference,
+        migrate: None,
+    };
+    let update = upgrade(&mut chain, PRODUCER, &param).expect_err("Should expect error");
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::Unauthorized);
+
+    // Check the upgrade fails if the new module does not exist.
+    let missing_module = UpgradeParams {
+        module:  ModuleReference::from([0u8; 32]),
+        migrate: None,
+    };
+    let update = upgrade(&mut chain, ADMIN, &missing_module).expect_err("Should expect error");
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::FailedUpgradeMissingModule);
+
+    // Check the upgrade fails if the migration function fails. The contract
+    // itself is not the Admin and can therefore not unpause the contract.
+    let failing_migration = UpgradeParams {
+        module:  module_reference,
+        migrate: Some((
+            OwnedEntrypointName::new_unchecked("unpause".to_string()),
+            OwnedParameter::empty(),
+        )),
+    };
+    let update = upgrade(&mut chain, ADMIN, &failing_migration).expect_err("Should expect error");
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::FailedUpgradeMigration);
+
+    // Check the ADMIN can upgrade the contract.
+    upgrade(&mut chain, ADMIN, &param).expect("Should be able to upgrade the contract");
+
+    // Check that the item is kept.
+    check_state(
+        &chain,
+        track_and_trace_contract_address,
+        Status::Produced,
+        None,
+        false,
+    );
+}
+
+/// Test that the `ItemSummaryEvent` contains the hash of the item's metadata.
+#[test]
+fn test_item_summary_metadata_hash() {
+    let (mut chain, _, track_and_trace_contract_address) = initialize_chain_and_contract();
+
+    let metadata_hash = HashSha2256([1; 32]);
+    let metadata_url = Some(MetadataUrl {
+        url:  "https://some.example/".to_string(),
+        hash: Some(metadata_hash),
+    });
+
+    let update = chain
+        .contract_update(
+            SIGNER,
+            ADMIN,
+            ADMIN_ADDR,
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::from_ccd(0),
+                address:      track_and_trace_contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.createItem".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(&metadata_url)
+                    .expect("Serialize parameter"),
+            },
+        )
+        .expect("Should be able to create item");
+
+    let summaries = update
+        .events()
+        .flat_map(|(_addr, events)| events.iter().map(|e| e.parse().expect("Deserialize event")))
+        .filter_map(|event: Event<AdditionalData>| match event {
+            Event::ItemSummary(summary) => Some(summary),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(summaries, [ItemSummaryEvent {
+        version:       ITEM_SUMMARY_EVENT_VERSION,
+        item_id:       ItemID::from(0u64),
+        status:        Status::Produced,
+        metadata_hash: Some(metadata_hash),
+    }]);
+}
+
+/// Test that only addresses with one of the configured roles can create items
+/// and that only the ADMIN can configure these roles.
+#[test]
+fn test_create_item_roles() {
+    let (mut chain, _, track_and_trace_contract_address) = initialize_chain_and_contract();
+
+    let create_item = |chain: &mut Chain, sender: AccountAddress| {
+        chain.contract_update(
+            SIGNER,
+            sender,
+            Address::Account(sender),
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::from_ccd(0),
+                address:      track_and_trace_contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.createItem".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(&None::<MetadataUrl>)
+                    .expect("Serialize parameter"),
+            },
+        )
+    };
+
+    let set_create_item_roles = |chain: &mut Chain, sender: AccountAddress, roles: Vec<Roles>| {
+        chain.contract_update(
+            SIGNER,
+            sender,
+            Address::Account(sender),
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::from_ccd(0),
+                address:      track_and_trace_contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.setCreateItemRoles".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(&roles).expect("Serialize parameter"),
+            },
+        )
+    };
+
+    let get_create_item_roles = |chain: &Chain| -> Vec<Roles> {
+        chain
+            .contract_invoke(
+                ADMIN,
+                ADMIN_ADDR,
+                Energy::from(10000),
+                UpdateContractPayload {
+                    amount:       Amount::zero(),
+                    receive_name: OwnedReceiveName::new_unchecked(
+                        "track_and_trace.getCreateItemRoles".to_string(),
+                    ),
+                    address:      track_and_trace_contract_address,
+                    message:      OwnedParameter::empty(),
+                },
+            )
+            .expect("Invoke getCreateItemRoles")
+            .parse_return_value()
+            .expect("getCreateItemRoles return value")
+    };
+
+    assert_eq!(get_create_item_roles(&chain), vec![Roles::Admin]);
+
+    // Grant the Producer role to the PRODUCER.
+    chain
+        .contract_update(
+            SIGNER,
+            ADMIN,
+            ADMIN_ADDR,
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::from_ccd(0),
+                address:      track_and_trace_contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.grantRole".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(&GrantRoleParams {
+                    address: PRODUCER_ADDR,
+                    role:    Roles::Producer,
+                })
+                .expect("Serialize parameter"),
+            },
+        )
+        .expect("Should be able to grant role");
+
+    // Check the PRODUCER can NOT create an item while only the Admin role is
+    // allowed to create items.
+    let update = create_item(&mut chain, PRODUCER).expect_err("Should expect error");
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::Unauthorized);
+
+    // Check the PRODUCER can NOT configure the roles allowed to create items.
+    let update = set_create_item_roles(&mut chain, PRODUCER, vec![Roles::Producer])
+        .expect_err("Should expect error");
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::Unauthorized);
+
+    // Allow producers to create items.
+    let update = set_create_item_roles(&mut chain, ADMIN, vec![Roles::Admin, Roles::Producer])
+        .expect("Should be able to set the roles allowed to create items");
+
+    // Check that the event is logged.
+    let events = update
+        .events()
+        .flat_map(|(_addr, events)| events.iter().map(|e| e.parse().expect("Deserialize event")))
+        .collect::<Vec<Event<AdditionalData>>>();
+
+    assert_eq!(events, [Event::CreateItemRolesUpdated(
+        CreateItemRolesUpdatedEvent {
+            roles: vec![Roles::Admin, Roles::Producer],
+        }
+    )]);
+    assert_eq!(get_create_item_roles(&chain), vec![
+        Roles::Admin,
+        Roles::Producer
+    ]);
+
+    let update = create_item(&mut chain, PRODUCER).expect("Should be able to create item");
+    assert_eq!(update.events().count(), 2, "Two events should be logged");
+
+    // Only allow producers to create items. The ADMIN can still configure the
+    // roles but can NOT create items anymore.
+    set_create_item_roles(&mut chain, ADMIN, vec![Roles::Producer])
+        .expect("Should be able to set the roles allowed to create items");
+
+    let update = create_item(&mut chain, ADMIN).expect_err("Should expect error");
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::Unauthorized);
+
+    create_item(&mut chain, PRODUCER).expect("Should be able to create item");
+}
+
+/// Test that the status hook configured by the ADMIN is invoked on every
+/// status change and that its failure policy is applied if the hook fails.
+#[test]
+fn test_status_hook() {
+    let (mut chain, _, track_and_trace_contract_address) = initialize_chain_and_contract();
+
+    let set_status_hook = |chain: &mut Chain, sender: AccountAddress, hook: Option<StatusHook>| {
+        chain.contract_update(
+            SIGNER,
+            sender,
+            Address::Account(sender),
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::from_ccd(0),
+                address:      track_and_trace_contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.setStatusHook".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(&hook).expect("Serialize parameter"),
+            },
+        )
+    };
+
+    let get_status_hook = |chain: &Chain| -> Option<StatusHook> {
+        chain
+            .contract_invoke(
+                ADMIN,
+                ADMIN_ADDR,
+                Energy::from(10000),
+                UpdateContractPayload {
+                    amount:       Amount::zero(),
+                    receive_name: OwnedReceiveName::new_unchecked(
+                        "track_and_trace.getStatusHook".to_string(),
+                    ),
+                    address:      track_and_trace_contract_address,
+                    message:      OwnedParameter::empty(),
+                },
+            )
+            .expect("Invoke getStatusHook")
+            .parse_return_value()
+            .expect("getStatusHook return value")
+    };
+
+    let change_item_status = |chain: &mut Chain, sender: AccountAddress, new_status: Status| {
+        chain.contract_update(
+            SIGNER,
+            sender,
+            Address::Account(sender),
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::from_ccd(0),
+                address:      track_and_trace_contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.changeItemStatus".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(&ChangeItemStatusParams {
+                    item_id: ItemID::from(0u64),
+                    new_status,
+                    additional_data: AdditionalData::empty(),
+                })
+                .expect("Serialize parameter"),
+            },
+        )
+    };
+
+    // The hook invokes a view of the contract itself, which accepts any
+    // parameter. The hook with a missing entrypoint always fails.
+    let hook = |entrypoint: &str, failure_policy: HookFailurePolicy| StatusHook {
+        contract: track_and_trace_contract_address,
+        entrypoint: OwnedEntrypointName::new_unchecked(entrypoint.to_string()),
+        failure_policy,
+    };
+
+    let invoked_hook = |update: &ContractInvokeSuccess| {
+        update
+            .effective_trace_elements()
+            .any(|element| matches!(element, ContractTraceElement::Interrupted { .. }))
+    };
+
+    assert_eq!(get_status_hook(&chain), None);
+
+    // Check the PRODUCER can NOT configure the status hook.
+    let update = set_status_hook(
+        &mut chain,
+        PRODUCER,
+        Some(hook("getNextItemId", HookFailurePolicy::Reject)),
+    )
+    .expect_err("Should expect error");
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::Unauthorized);
+
+    // Create an item.
+    chain
+        .contract_update(
+            SIGNER,
+            ADMIN,
+            ADMIN_ADDR,
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::from_ccd(0),
+                address:      track_and_trace_contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.createItem".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(&None::<MetadataUrl>)
+                    .expect("Serialize parameter"),
+            },
+        )
+        .expect("Should be able to create item");
+
+    // Check the hook is invoked when the status of the item changes.
+    set_status_hook(
+        &mut chain,
+        ADMIN,
+        Some(hook("getNextItemId", HookFailurePolicy::Reject)),
+    )
+    .expect("Should be able to set the status hook");
+    assert_eq!(
+        get_status_hook(&chain),
+        Some(hook("getNextItemId", HookFailurePolicy::Reject))
+    );
+
+    let update = change_item_status(&mut chain, PRODUCER, Status::InTransit)
+        .expect("Should be able to update the state of the item");
+    assert!(invoked_hook(&update), "The hook should be invoked");
+
+    // Check a failing hook rejects the status change with the `Reject` policy.
+    set_status_hook(
+        &mut chain,
+        ADMIN,
+        Some(hook("missingEntrypoint", HookFailurePolicy::Reject)),
+    )
+    .expect("Should be able to set the status hook");
+
+    let update = change_item_status(&mut chain, TRANSPORTER, Status::InStore)
+        .expect_err("Should expect error");
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::HookFailed);
+
+    check_state(
+        &chain,
+        track_and_trace_contract_address,
+        Status::InTransit,
+        None,
+        false,
+    );
+
+    // Check a failing hook is ignored with the `Ignore` policy.
+    set_status_hook(
+        &mut chain,
+        ADMIN,
+        Some(hook("missingEntrypoint", HookFailurePolicy::Ignore)),
+    )
+    .expect("Should be able to set the status hook");
+
+    change_item_status(&mut chain, TRANSPORTER, Status::InStore)
+        .expect("Should be able to update the state of the item");
+
+    check_state(
+        &chain,
+        track_and_trace_contract_address,
+        Status::InStore,
+        None,
+        false,
+    );
+
+    // Check no hook is invoked after the hook is removed.
+    set_status_hook(&mut chain, ADMIN, None).expect("Should be able to remove the status hook");
+    assert_eq!(get_status_hook(&chain), None);
+
+    let update = change_item_status(&mut chain, SELLER, Status::Sold)
+        .expect("Should be able to update the state of the item");
+    assert!(!invoked_hook(&update), "No hook should be invoked");
+}
+
+/// Test that a twin token is minted in the CIS-2 contract to the creator of
+/// every item, that its metadata URL is updated along with the metadata of the
+/// item, that it is transferred along with the ownership of the item, and that
+/// it is burned when the item is archived.
+#[test]
+fn test_twin_token() {
+    let (mut chain, _, track_and_trace_contract_address) =
+        initialize_chain_and_contract_with(|chain| Some(deploy_cis2_mock(chain)));
+
+    let cis2_mock = get_twin_token_contract(&chain, track_and_trace_contract_address)
+        .expect("Twin token contract should be configured");
+
+    let invoked_cis2_mock =
+        |update: &ContractInvokeSuccess, entrypoint: &str, message: OwnedParameter| {
+            let receive_name = OwnedReceiveName::new_unchecked(format!("cis2_mock.{entrypoint}"));
+            update.effective_trace_elements().any(|element| {
+                matches!(element, ContractTraceElement::Updated { data }
+                    if data.address == cis2_mock
+                        && data.receive_name == receive_name
+                        && data.message == message)
+            })
+        };
+
+    // Check the twin token is minted to the creator of the item.
+    let update = create_item(&mut chain, track_and_trace_contract_address, ADMIN)
+        .expect("Should be able to create item");
+    let item_id = ItemID::from(0u64);
+    let mint = OwnedParameter::from_serial(&TwinTokenMintParams {
+        owner:        ADMIN_ADDR,
+        token_id:     item_id,
+        metadata_url: None,
+    })
+    .expect("Serialize parameter");
+    assert!(
+        invoked_cis2_mock(&update, "mint", mint),
+        "The twin token should be minted"
+    );
+    assert_eq!(
+        get_item_owner(&chain, track_and_trace_contract_address, item_id),
+        Some(ADMIN_ADDR)
+    );
+
+    // Check the metadata URL of the twin token is updated along with the
+    // metadata of the item.
+    let metadata_url = Some(MetadataUrl {
+        url:  "https://example.com/items/0".to_string(),
+        hash: None,
+    });
+    let update = chain
+        .contract_update(
+            SIGNER,
+            ADMIN,
+            ADMIN_ADDR,
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::zero(),
+                address:      track_and_trace_contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.updateItemMetadata".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(&UpdateItemMetadataParams {
+                    item_id,
+                    metadata_url: metadata_url.clone(),
+                })
+                .expect("Serialize parameter"),
+            },
+        )
+        .expect("Should be able to update the metadata of the item");
+    let update_metadata = OwnedParameter::from_serial(&TwinTokenUpdateMetadataParams {
+        token_id: item_id,
+        metadata_url,
+    })
+    .expect("Serialize parameter");
+    assert!(
+        invoked_cis2_mock(&update, "updateMetadata", update_metadata),
+        "The metadata URL of the twin token should be updated"
+    );
+
+    // Check the PRODUCER can NOT transfer an item it does not own.
+    let update = transfer_item(
+        &mut chain,
+        track_and_trace_contract_address,
+        PRODUCER,
+        item_id,
+        SELLER,
+    )
+    .expect_err("Should expect error");
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::Unauthorized);
+
+    // Check the ADMIN can transfer the item and the twin token is transferred
+    // along.
+    let update = transfer_item(
+        &mut chain,
+        track_and_trace_contract_address,
+        ADMIN,
+        item_id,
+        PRODUCER,
+    )
+    .expect("Should be able to transfer the item");
+
+    let events = update
+        .events()
+        .flat_map(|(_addr, events)| events.iter().map(|e| e.parse().expect("Deserialize event")))
+        .collect::<Vec<Event<AdditionalData>>>();
+    assert_eq!(events, [Event::ItemOwnerChanged(ItemOwnerChangedEvent {
+        item_id,
+        from: ADMIN_ADDR,
+        to: PRODUCER_ADDR,
+    })]);
+
+    let transfer = OwnedParameter::from_serial(&concordium_cis2::TransferParams(vec![
+        concordium_cis2::Transfer {
+            token_id: item_id,
+            amount:   concordium_cis2::TokenAmountU8(1),
+            from:     ADMIN_ADDR,
+            to:       concordium_cis2::Receiver::Account(PRODUCER),
+            data:     concordium_cis2::AdditionalData::empty(),
+        },
+    ]))
+    .expect("Serialize parameter");
+    assert!(
+        invoked_cis2_mock(&update, "transfer", transfer),
+        "The twin token should be transferred"
+    );
+    assert_eq!(
+        get_item_owner(&chain, track_and_trace_contract_address, item_id),
+        Some(PRODUCER_ADDR)
+    );
+
+    // Check the new owner can transfer the item.
+    transfer_item(
+        &mut chain,
+        track_and_trace_contract_address,
+        PRODUCER,
+        item_id,
+        SELLER,
+    )
+    .expect("Should be able to transfer the item");
+    assert_eq!(
+        get_item_owner(&chain, track_and_trace_contract_address, item_id),
+        Some(SELLER_ADDR)
+    );
+
+    // Check an item that does not exist can NOT be transferred.
+    let update = transfer_item(
+        &mut chain,
+        track_and_trace_contract_address,
+        ADMIN,
+        ItemID::from(1u64),
+        SELLER,
+    )
+    .expect_err("Should expect error");
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::ItemDoesNotExist);
+
+    // Check the twin token of the owner is burned when the item is archived.
+    let update = chain
+        .contract_update(
+            SIGNER,
+            ADMIN,
+            ADMIN_ADDR,
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::zero(),
+                address:      track_and_trace_contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.archiveItem".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(&item_id).expect("Serialize parameter"),
+            },
+        )
+        .expect("Should be able to archive the item");
+    let burn = OwnedParameter::from_serial(&TwinTokenBurnParams {
+        owner:    SELLER_ADDR,
+        token_id: item_id,
+    })
+    .expect("Serialize parameter");
+    assert!(
+        invoked_cis2_mock(&update, "burn", burn),
+        "The twin token should be burned"
+    );
+    assert_eq!(
+        get_item_owner(&chain, track_and_trace_contract_address, item_id),
+        None
+    );
+}
+
+/// Test that items can NOT be transferred without twin tokens and that items
+/// can NOT be created if minting their twin token fails.
+#[test]
+fn test_twin_token_errors() {
+    let (mut chain, _, track_and_trace_contract_address) = initialize_chain_and_contract();
+
+    assert_eq!(
+        get_twin_token_contract(&chain, track_and_trace_contract_address),
+        None
+    );
+
+    create_item(&mut chain, track_and_trace_contract_address, ADMIN)
+        .expect("Should be able to create item");
+    let item_id = ItemID::from(0u64);
+    assert_eq!(
+        get_item_owner(&chain, track_and_trace_contract_address, item_id),
+        Some(ADMIN_ADDR)
+    );
+
+    let update = transfer_item(
+        &mut chain,
+        track_and_trace_contract_address,
+        ADMIN,
+        item_id,
+        PRODUCER,
+    )
+    .expect_err("Should expect error");
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::TwinTokenNotConfigured);
+
+    // The twin token contract does not exist, so minting fails.
+    let (mut chain, _, track_and_trace_contract_address) =
+        initialize_chain_and_contract_with(|_| Some(ContractAddress::new(1000, 0)));
+
+    let update = create_item(&mut chain, track_and_trace_contract_address, ADMIN)
+        .expect_err("Should expect error");
+    let error: CustomContractError = update
+        .parse_return_value()
+        .expect("CustomContractError return value");
+    assert_eq!(error, CustomContractError::TwinTokenFailed);
+}
+
+/// A mock CIS-2 contract whose `mint`, `transfer`, `burn`, and `updateMetadata`
+/// entrypoints accept any parameter. The tests check the invocations of the
+/// mock in the trace of the updates.
+const CIS2_MOCK_WAT: &str = r#"
+(module
+  (type $entrypoint (func (param i64) (result i32)))
+  (func (export "init_cis2_mock") (type $entrypoint) (i32.const 0))
+  (func (export "cis2_mock.mint") (type $entrypoint) (i32.const 0))
+  (func (export "cis2_mock.transfer") (type $entrypoint) (i32.const 0))
+  (func (export "cis2_mock.burn") (type $entrypoint) (i32.const 0))
+  (func (export "cis2_mock.updateMetadata") (type $entrypoint) (i32.const 0)))
+"#;
+
+/// Deploy and initialize the mock CIS-2 contract. Returns the contract address.
+fn deploy_cis2_mock(chain: &mut Chain) -> ContractAddress {
+    let source = wat::parse_str(CIS2_MOCK_WAT).expect("Valid mock module");
+    let path = std::env::temp_dir().join(format!("cis2_mock_{}.wasm", std::process::id()));
+    std::fs::write(&path, source).expect("Write mock module");
+    let module = module_load_v1_raw(&path).expect("Mock module exists");
+    let deployment = chain
+        .module_deploy_v1(SIGNER, ADMIN, module)
+        .expect("Deploy valid module");
+
+    chain
+        .contract_init(SIGNER, ADMIN, Energy::from(10000), InitContractPayload {
+            amount:    Amount::zero(),
+            mod_ref:   deployment.module_reference,
+            init_name: OwnedContractName::new_unchecked("init_cis2_mock".to_string()),
+            param:     OwnedParameter::empty(),
+        })
+        .expect("Initialize mock contract")
+        .contract_address
+}
+
+/// Create an item without metadata from the `sender` account.
+fn create_item(
+    chain: &mut Chain,
+    track_and_trace_contract_address: ContractAddress,
+    sender: AccountAddress,
+) -> Result<ContractInvokeSuccess, ContractInvokeError> {
+    chain.contract_update(
+        SIGNER,
+        sender,
+        Address::Account(sender),
+        Energy::from(10000),
+        UpdateContractPayload {
+            amount:       Amount::from_ccd(0),
+            address:      track_and_trace_contract_address,
+            receive_name: OwnedReceiveName::new_unchecked("track_and_trace.createItem".to_string()),
+            message:      OwnedParameter::from_serial(&None::<MetadataUrl>)
+                .expect("Serialize parameter"),
+        },
+    )
+}
+
+/// Transfer the ownership of the item with the `item_id` to the account `to`
+/// from the `sender` account.
+fn transfer_item(
+    chain: &mut Chain,
+    track_and_trace_contract_address: ContractAddress,
+    sender: AccountAddress,
+    item_id: ItemID,
+    to: AccountAddress,
+) -> Result<ContractInvokeSuccess, ContractInvokeError> {
+    chain.contract_update(
+        SIGNER,
+        sender,
+        Address::Account(sender),
+        Energy::from(10000),
+        UpdateContractPayload {
+            amount:       Amount::from_ccd(0),
+            address:      track_and_trace_contract_address,
+            receive_name: OwnedReceiveName::new_unchecked(
+                "track_and_trace.transferItem".to_string(),
+            ),
+            message:      OwnedParameter::from_serial(&TransferItemParams { item_id, to })
+                .expect("Serialize parameter"),
+        },
+    )
+}
+
+/// Invoke the `getTwinTokenContract` view.
+fn get_twin_token_contract(
+    chain: &Chain,
+    track_and_trace_contract_address: ContractAddress,
+) -> Option<ContractAddress> {
+    chain
+        .contract_invoke(
+            ADMIN,
+            ADMIN_ADDR,
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::zero(),
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.getTwinTokenContract".to_string(),
+                ),
+                address:      track_and_trace_contract_address,
+                message:      OwnedParameter::empty(),
+            },
+        )
+        .expect("Invoke getTwinTokenContract")
+        .parse_return_value()
+        .expect("getTwinTokenContract return value")
+}
+
+/// Invoke the `getItemOwner` view for the item with the `item_id`.
+fn get_item_owner(
+    chain: &Chain,
+    track_and_trace_contract_address: ContractAddress,
+    item_id: ItemID,
+) -> Option<Address> {
+    chain
+        .contract_invoke(
+            ADMIN,
+            ADMIN_ADDR,
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::zero(),
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.getItemOwner".to_string(),
+                ),
+                address:      track_and_trace_contract_address,
+                message:      OwnedParameter::from_serial(&item_id).expect("Serialize parameter"),
+            },
+        )
+        .expect("Invoke getItemOwner")
+        .parse_return_value()
+        .expect("getItemOwner return value")
+}
+
+// Invoke the several getter functions and check that the contract state is as
+// expected. Exactly one item is expected to be in the state.
+fn check_state(
+    chain: &Chain,
+    track_and_trace_contract_address: ContractAddress,
+    status: Status,
+    metadata_url: Option<MetadataUrl>,
+    metadata_frozen: bool,
+) {
+    let invoke = chain
+        .contract_invoke(
+            ADMIN,
+            ADMIN_ADDR,
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::zero(),
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.getRoles".to_string(),
+                ),
+                address:      track_and_trace_contract_address,
+                message:      OwnedParameter::from_serial(&ADMIN_ADDR)
+                    .expect("Serialize parameter"),
+            },
+        )
+        .expect("Invoke view");
+
+    let return_value: Vec<Roles> = invoke.parse_return_value().expect("ViewState return value");
+
+    assert_eq!(return_value, vec![Roles::Admin]);
+
+    let item_id = ItemID::from(0u64);
+
+    let invoke = chain
+        .contract_invoke(
+            ADMIN,
+            ADMIN_ADDR,
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::zero(),
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.getItemState".to_string(),
+                ),
+                address:      track_and_trace_contract_address,
+                message:      OwnedParameter::from_serial(&item_id).expect("Serialize parameter"),
+            },
+        )
+        .expect("Invoke view");
+
+    let return_value: ItemState = invoke.parse_return_value().expect("ViewState return value");
+
+    assert_eq!(return_value.status, status);
+    assert_eq!(return_value.metadata_url, metadata_url);
+    assert_eq!(return_value.metadata_frozen, metadata_frozen);
+
+    let invoke = chain
+        .contract_invoke(
+            ADMIN,
+            ADMIN_ADDR,
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::zero(),
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.getNextItemId".to_string(),
+                ),
+                address:      track_and_trace_contract_address,
+                message:      OwnedParameter::empty(),
+            },
+        )
+        .expect("Invoke view");
+
+    let return_value: u64 = invoke.parse_return_value().expect("ViewState return value");
+
+    assert_eq!(return_value, 1u64);
+}
+
+/// Setup chain and contract. Returns the chain, keys of the ADMIN, PRODUCER,
+/// and SELLER, and the contract address.
+fn initialize_chain_and_contract() -> (Chain, AccountKeypairs, ContractAddress) {
+    initialize_chain_and_contract_with(|_| None)
+}
+
+/// Setup chain and contract with the twin token contract returned by
+/// `twin_token`, which is called after the accounts are created. Returns the
+/// chain, keys of the ADMIN, PRODUCER, and SELLER, and the contract address.
+fn initialize_chain_and_contract_with(
+    twin_token: impl FnOnce(&mut Chain) -> Option<ContractAddress>,
+) -> (Chain, AccountKeypairs, ContractAddress) {
+    let mut chain = Chain::builder()
+        .build()
+        .expect("Should be able to build chain");
 
     let mut rng = rand::thread_rng();
     let balance = AccountBalance {
@@ -489,6 +3110,7 @@ fn initialize_chain_and_contract() -> (Chain, AccountKeypairs, ContractAddress)
     let account_keypairs = AccountKeypairs {
         admin:    admin_keys,
         producer: producer_keys,
+        seller:   seller_keys,
     };
 
     // Load and deploy the track_and_trace module.
@@ -497,48 +3119,63 @@ fn initialize_chain_and_contract() -> (Chain, AccountKeypairs, ContractAddress)
         .module_deploy_v1(SIGNER, ADMIN, module)
         .expect("Deploy valid module");
 
-    let params: Vec<TransitionEdges> = vec![
+    let transitions: Vec<TransitionEdges> = vec![
         TransitionEdges {
             from:               Status::Produced,
             to:                 vec![Status::InTransit],
             authorized_account: PRODUCER,
+            max_duration:       None,
         },
         TransitionEdges {
             from:               Status::InTransit,
             to:                 vec![Status::InStore],
             authorized_account: TRANSPORTER,
+            max_duration:       None,
         },
         TransitionEdges {
             from:               Status::InStore,
             to:                 vec![Status::Sold],
             authorized_account: SELLER,
+            max_duration:       None,
         },
         // Admin transitions (The admin can change the status of the item to any value)
         TransitionEdges {
             from:               Status::Produced,
             to:                 vec![Status::InTransit, Status::InStore, Status::Sold],
             authorized_account: ADMIN,
+            max_duration:       None,
         },
         TransitionEdges {
             from:               Status::InTransit,
             to:                 vec![Status::Produced, Status::InStore, Status::Sold],
             authorized_account: ADMIN,
+            max_duration:       None,
         },
         TransitionEdges {
             from:               Status::InStore,
             to:                 vec![Status::InTransit, Status::Produced, Status::Sold],
             authorized_account: ADMIN,
+            max_duration:       None,
         },
         TransitionEdges {
             from:               Status::Sold,
             to:                 vec![Status::InTransit, Status::InStore, Status::Produced],
             authorized_account: ADMIN,
+            max_duration:       None,
         },
     ];
 
+    // Only the ADMIN can create items.
+    let params = InitParams {
+        transitions,
+        creator_roles: vec![Roles::Admin],
+        twin_token: twin_token(&mut chain),
+        custom_statuses: vec![],
+    };
+
     // Initialize the track_and_trace contract.
     let track_and_trace = chain
-        .contract_init(SIGNER, ADMIN, Energy::from(10000), InitContractPayload {
+        .contract_init(SIGNER, ADMIN, Energy::from(30000), InitContractPayload {
             amount:    Amount::zero(),
             mod_ref:   deployment.module_reference,
             init_name: OwnedContractName::new_unchecked("init_track_and_trace".to_string()),
@@ -584,7 +3221,7 @@ fn test_permit_change_item_status() {
     // transaction.
     let payload = ChangeItemStatusParams {
         item_id,
-        additional_data: AdditionalData { bytes: vec![] },
+        additional_data: AdditionalData::empty(),
         new_status: Status::InStore,
     };
 
@@ -617,6 +3254,7 @@ fn test_permit_change_item_status() {
         contract_address,
         Status::InStore,
         metadata_url.clone(),
+        false,
     );
 
     // Check if correct nonces are returned by the `nonceOf` function.
@@ -653,7 +3291,7 @@ fn test_permit_change_item_status() {
     // sponsored transaction.
     let payload = ChangeItemStatusParams {
         item_id,
-        additional_data: AdditionalData { bytes: vec![] },
+        additional_data: AdditionalData::empty(),
         new_status: Status::Sold,
     };
 
@@ -668,7 +3306,458 @@ fn test_permit_change_item_status() {
     .expect_err("PRODUCER should not be able to change state to Sold");
 
     // Check that the status was not updated.
-    check_state(&chain, contract_address, Status::InStore, metadata_url);
+    check_state(
+        &chain,
+        contract_address,
+        Status::InStore,
+        metadata_url,
+        false,
+    );
+}
+
+/// Test that an item can be created with a sponsored transaction and that the
+/// signer (not the sponsor) is the creator of the item.
+#[test]
+fn test_permit_create_item() {
+    let (mut chain, account_keypairs, contract_address) = initialize_chain_and_contract();
+
+    // Create the Parameter.
+    let metadata_url = Some(MetadataUrl {
+        url:  "https://some.example/".to_string(),
+        hash: None,
+    });
+
+    // Check that `createItem` is supported by the `permit` function.
+    let invoke = chain
+        .contract_invoke(
+            ADMIN,
+            ADMIN_ADDR,
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::zero(),
+                address:      contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.supportsPermit".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(&SupportsPermitQueryParams {
+                    queries: vec![OwnedEntrypointName::new_unchecked("createItem".to_string())],
+                })
+                .expect("Should be a valid inut parameter"),
+            },
+        )
+        .expect("Should be able to query supportsPermit");
+    let supports: concordium_cis2::SupportsQueryResponse =
+        from_bytes(&invoke.return_value).expect("Should return a valid result");
+    assert!(
+        matches!(supports.results[..], [
+            concordium_cis2::SupportResult::Support
+        ]),
+        "createItem should be supported by permit"
+    );
+
+    // Have the SELLER sponsor the creation of an item signed by the ADMIN.
+    let update = permit(
+        &mut chain,
+        contract_address,
+        to_bytes(&metadata_url),
+        "createItem".to_string(),
+        SELLER,
+        account_keypairs.admin,
+    )
+    .expect("Should be able to create the item");
+
+    // Check that the events are logged.
+    let events = update
+        .events()
+        .flat_map(|(_addr, events)| events.iter().map(|e| e.parse().expect("Deserialize event")))
+        .collect::<Vec<Event<AdditionalData>>>();
+
+    let item_id = ItemID::from(0u64);
+    assert_eq!(events, [
+        Event::ItemCreated(ItemCreatedEvent {
+            item_id,
+            metadata_url: metadata_url.clone(),
+            initial_status: Status::Produced,
+        }),
+        Event::ItemSummary(ItemSummaryEvent {
+            version: ITEM_SUMMARY_EVENT_VERSION,
+            item_id,
+            status: Status::Produced,
+            metadata_hash: None,
+        }),
+        Event::Nonce(NonceEvent {
+            account: ADMIN,
+            nonce:   0,
+        }),
+    ]);
+
+    // Check contract state.
+    check_state(
+        &chain,
+        contract_address,
+        Status::Produced,
+        metadata_url,
+        false,
+    );
+
+    // Check that the signer is recorded as the creator of the item.
+    let history: Vec<ItemHistoryEntry> = chain
+        .contract_invoke(
+            ADMIN,
+            ADMIN_ADDR,
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::zero(),
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.getItemHistory".to_string(),
+                ),
+                address:      contract_address,
+                message:      OwnedParameter::from_serial(&item_id).expect("Serialize parameter"),
+            },
+        )
+        .expect("Invoke getItemHistory")
+        .parse_return_value()
+        .expect("getItemHistory return value");
+    assert_eq!(history[0].actor, ADMIN_ADDR);
+}
+
+/// Test that an item is handed over to the next owner with the signatures of
+/// both owners, that the twin token is transferred along if twin tokens are
+/// minted, that the handover can be submitted by anyone, and that invalid and
+/// replayed handovers are rejected.
+#[test]
+fn test_transfer_item_custody() {
+    let (mut chain, account_keypairs, contract_address) =
+        initialize_chain_and_contract_with(|chain| Some(deploy_cis2_mock(chain)));
+    let cis2_mock =
+        get_twin_token_contract(&chain, contract_address).expect("Twin token contract exists");
+
+    create_item(&mut chain, contract_address, ADMIN).expect("Should be able to create item");
+    let item_id = ItemID::from(0u64);
+
+    // Sign the `handover` by the owners `from` and `to` with their `keys`.
+    let sign = |chain: &Chain,
+                handover: &CustodyHandoverMessage,
+                from_keys: &AccountKeys,
+                to_keys: &AccountKeys| TransferItemCustodyParams {
+        from_signature: sign_handover_message(
+            chain,
+            contract_address,
+            handover,
+            handover.from,
+            from_keys,
+        ),
+        to_signature:   sign_handover_message(
+            chain,
+            contract_address,
+            handover,
+            handover.to,
+            to_keys,
+        ),
+        message:        handover.clone(),
+    };
+    let expect_error = |chain: &mut Chain, params: &TransferItemCustodyParams| {
+        transfer_item_custody(chain, contract_address, params)
+            .expect_err("Should expect error")
+            .parse_return_value::<CustomContractError>()
+            .expect("CustomContractError return value")
+    };
+
+    let handover = CustodyHandoverMessage {
+        tag: CUSTODY_HANDOVER_TAG,
+        contract_address,
+        item_id,
+        from: ADMIN,
+        to: SELLER,
+        nonce: 0,
+        timestamp: Timestamp::from_timestamp_millis(10_000_000_000),
+    };
+
+    // Check that a message without the handover tag is rejected.
+    let message = CustodyHandoverMessage {
+        tag: [0; 16],
+        ..handover.clone()
+    };
+    let params = sign(
+        &chain,
+        &message,
+        &account_keypairs.admin,
+        &account_keypairs.seller,
+    );
+    assert_eq!(
+        expect_error(&mut chain, &params),
+        CustomContractError::InvalidHandover
+    );
+
+    // Check that the item can NOT be handed over to its owner.
+    let message = CustodyHandoverMessage {
+        to: ADMIN,
+        ..handover.clone()
+    };
+    let params = sign(
+        &chain,
+        &message,
+        &account_keypairs.admin,
+        &account_keypairs.admin,
+    );
+    assert_eq!(
+        expect_error(&mut chain, &params),
+        CustomContractError::InvalidHandover
+    );
+
+    // Check that the handover is rejected if the SELLER's signature is made
+    // with the wrong keys.
+    let params = sign(
+        &chain,
+        &handover,
+        &account_keypairs.admin,
+        &account_keypairs.producer,
+    );
+    assert_eq!(
+        expect_error(&mut chain, &params),
+        CustomContractError::WrongSignature
+    );
+
+    // Have the PRODUCER submit the handover signed by the ADMIN and the SELLER.
+    let params = sign(
+        &chain,
+        &handover,
+        &account_keypairs.admin,
+        &account_keypairs.seller,
+    );
+    let update = transfer_item_custody(&mut chain, contract_address, &params)
+        .expect("Should be able to hand over the item");
+
+    // Check that the events are logged.
+    let events = update
+        .events()
+        .flat_map(|(_addr, events)| events.iter().map(|e| e.parse().expect("Deserialize event")))
+        .collect::<Vec<Event<AdditionalData>>>();
+    assert_eq!(events, [
+        Event::ItemOwnerChanged(ItemOwnerChangedEvent {
+            item_id,
+            from: ADMIN_ADDR,
+            to: SELLER_ADDR,
+        }),
+        Event::Nonce(NonceEvent {
+            account: ADMIN,
+            nonce:   0,
+        }),
+    ]);
+
+    // Check that the SELLER owns the item and the twin token is transferred
+    // along.
+    assert_eq!(
+        get_item_owner(&chain, contract_address, item_id),
+        Some(SELLER_ADDR)
+    );
+    let transfer = OwnedParameter::from_serial(&concordium_cis2::TransferParams(vec![
+        concordium_cis2::Transfer {
+            token_id: item_id,
+            amount:   concordium_cis2::TokenAmountU8(1),
+            from:     ADMIN_ADDR,
+            to:       concordium_cis2::Receiver::Account(SELLER),
+            data:     concordium_cis2::AdditionalData::empty(),
+        },
+    ]))
+    .expect("Serialize parameter");
+    assert!(
+        update.effective_trace_elements().any(|element| {
+            matches!(element, ContractTraceElement::Updated { data }
+                if data.address == cis2_mock
+                    && data.receive_name.as_receive_name().entrypoint_name() == "transfer"
+                    && data.message == transfer)
+        }),
+        "The twin token should be transferred"
+    );
+
+    // Check that the handover can NOT be replayed, as the ADMIN no longer owns
+    // the item.
+    assert_eq!(
+        expect_error(&mut chain, &params),
+        CustomContractError::Unauthorized
+    );
+
+    // Hand the item back to the ADMIN with the nonce of the SELLER.
+    let handback = CustodyHandoverMessage {
+        from: SELLER,
+        to: ADMIN,
+        ..handover
+    };
+    let handback_params = sign(
+        &chain,
+        &handback,
+        &account_keypairs.seller,
+        &account_keypairs.admin,
+    );
+    transfer_item_custody(&mut chain, contract_address, &handback_params)
+        .expect("Should be able to hand over the item");
+
+    // Check that the first handover can NOT be replayed once the ADMIN owns
+    // the item again, as the nonce of the ADMIN was increased.
+    assert_eq!(
+        expect_error(&mut chain, &params),
+        CustomContractError::NonceMismatch
+    );
+    assert_eq!(
+        get_item_owner(&chain, contract_address, item_id),
+        Some(ADMIN_ADDR)
+    );
+
+    // Check that an item is handed over by a contract without twin tokens.
+    let (mut chain, account_keypairs, contract_address) = initialize_chain_and_contract();
+
+    create_item(&mut chain, contract_address, ADMIN).expect("Should be able to create item");
+
+    let handover = CustodyHandoverMessage {
+        contract_address,
+        ..handover
+    };
+    let params = TransferItemCustodyParams {
+        from_signature: sign_handover_message(
+            &chain,
+            contract_address,
+            &handover,
+            ADMIN,
+            &account_keypairs.admin,
+        ),
+        to_signature:   sign_handover_message(
+            &chain,
+            contract_address,
+            &handover,
+            SELLER,
+            &account_keypairs.seller,
+        ),
+        message:        handover,
+    };
+    let update = transfer_item_custody(&mut chain, contract_address, &params)
+        .expect("Should be able to hand over the item");
+
+    // Check that the events are logged and no other contract is invoked.
+    let events = update
+        .events()
+        .flat_map(|(_addr, events)| events.iter().map(|e| e.parse().expect("Deserialize event")))
+        .collect::<Vec<Event<AdditionalData>>>();
+    assert_eq!(events, [
+        Event::ItemOwnerChanged(ItemOwnerChangedEvent {
+            item_id,
+            from: ADMIN_ADDR,
+            to: SELLER_ADDR,
+        }),
+        Event::Nonce(NonceEvent {
+            account: ADMIN,
+            nonce:   0,
+        }),
+    ]);
+    assert!(
+        update.effective_trace_elements().all(|element| {
+            !matches!(element, ContractTraceElement::Updated { data }
+                if data.address != contract_address)
+        }),
+        "No twin token should be transferred"
+    );
+    assert_eq!(
+        get_item_owner(&chain, contract_address, item_id),
+        Some(SELLER_ADDR)
+    );
+}
+
+/// Test that the `supports` entrypoint reports the implemented standards CIS-0,
+/// CIS-3, and CIS-6 and no support for other standards.
+#[test]
+fn test_supports() {
+    let (chain, _, contract_address) = initialize_chain_and_contract();
+
+    let queries = ["CIS-0", "CIS-3", "CIS-6", "CIS-2"]
+        .into_iter()
+        .map(|id| concordium_cis2::StandardIdentifierOwned::new_unchecked(id.to_string()))
+        .collect();
+
+    let invoke = chain
+        .contract_invoke(
+            ADMIN,
+            ADMIN_ADDR,
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::zero(),
+                address:      contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.supports".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(&concordium_cis2::SupportsQueryParams {
+                    queries,
+                })
+                .expect("Should be a valid inut parameter"),
+            },
+        )
+        .expect("Should be able to query supports");
+    let supports: concordium_cis2::SupportsQueryResponse =
+        from_bytes(&invoke.return_value).expect("Should return a valid result");
+    assert!(
+        matches!(supports.results[..], [
+            concordium_cis2::SupportResult::Support,
+            concordium_cis2::SupportResult::Support,
+            concordium_cis2::SupportResult::Support,
+            concordium_cis2::SupportResult::NoSupport
+        ]),
+        "CIS-0, CIS-3, and CIS-6 should be supported"
+    );
+}
+
+/// Sign the custody `handover` message with the `keys` of the `signer`. The
+/// hash to be signed is calculated with the `viewHandoverMessageHash` view.
+fn sign_handover_message(
+    chain: &Chain,
+    contract_address: ContractAddress,
+    handover: &CustodyHandoverMessage,
+    signer: AccountAddress,
+    keys: &AccountKeys,
+) -> AccountSignatures {
+    let message_hash: HashSha2256 = chain
+        .contract_invoke(
+            signer,
+            Address::Account(signer),
+            Energy::from(10000),
+            UpdateContractPayload {
+                amount:       Amount::zero(),
+                address:      contract_address,
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "track_and_trace.viewHandoverMessageHash".to_string(),
+                ),
+                message:      OwnedParameter::from_serial(&ViewHandoverMessageHashParams {
+                    signer,
+                    message: handover.clone(),
+                })
+                .expect("Serialize parameter"),
+            },
+        )
+        .expect("Should be able to query viewHandoverMessageHash")
+        .parse_return_value()
+        .expect("Should return a valid result");
+
+    keys.sign_message(&to_bytes(&message_hash))
+}
+
+/// Submit the custody handover with the `params` from the PRODUCER account.
+fn transfer_item_custody(
+    chain: &mut Chain,
+    contract_address: ContractAddress,
+    params: &TransferItemCustodyParams,
+) -> Result<ContractInvokeSuccess, ContractInvokeError> {
+    chain.contract_update(
+        SIGNER,
+        PRODUCER,
+        PRODUCER_ADDR,
+        Energy::from(10000),
+        UpdateContractPayload {
+            amount:       Amount::zero(),
+            address:      contract_address,
+            receive_name: OwnedReceiveName::new_unchecked(
+                "track_and_trace.transferItemCustody".to_string(),
+            ),
+            message:      OwnedParameter::from_serial(params).expect("Serialize parameter"),
+        },
+    )
 }
 
 /// Execute a permit function invoke.
@@ -3,19 +3,19 @@ use crate::types::*;
 use anyhow::Context;
 use axum::{
     extract::{rejection::JsonRejection, State},
-    routing::post,
+    routing::{get, post},
     Json, Router,
 };
 use clap::Parser;
 use concordium_rust_sdk::{
-    contract_client::{ContractClient, InvokeContractOutcome},
-    smart_contracts::common::{
-        AccountSignatures, Amount, CredentialSignatures, OwnedEntrypointName, Signature,
-        SignatureEd25519,
-    },
+    smart_contracts::common::OwnedEntrypointName,
     types::{hashes::TransactionHash, WalletAccount},
 };
-use std::{collections::BTreeMap, path::PathBuf};
+use concordium_sponsor_relayer::{
+    AccountQuota, AllowedAccounts, AllowedContracts, Allowlist, PermitMessage, PermitParam,
+    Relayer, SponsorError,
+};
+use std::{path::PathBuf, sync::Arc};
 use tonic::transport::ClientTlsConfig;
 use tower_http::cors::{AllowOrigin, CorsLayer};
 
@@ -101,6 +101,7 @@ async fn main() -> anyhow::Result<()> {
         use tracing_subscriber::prelude::*;
         let log_filter = tracing_subscriber::filter::Targets::new()
             .with_target(module_path!(), app.log_level)
+            .with_target("concordium_sponsor_relayer", app.log_level)
             .with_target("tower_http", app.log_level);
 
         tracing_subscriber::registry()
@@ -135,7 +136,7 @@ async fn main() -> anyhow::Result<()> {
         .keep_alive_timeout(std::time::Duration::from_secs(10))
         .keep_alive_while_idle(true);
 
-    let mut node_client = concordium_rust_sdk::v2::Client::new(endpoint)
+    let node_client = concordium_rust_sdk::v2::Client::new(endpoint)
         .await
         .context("Unable to establish connection to the node.")?;
 
@@ -143,11 +144,9 @@ async fn main() -> anyhow::Result<()> {
     let keys: WalletAccount = WalletAccount::from_json_file(app.keys_path.clone())
         .context("Could not get the account keys from a file.")?;
 
-    let nonce = node_client
-        .get_next_account_sequence_number(&keys.address)
+    let relayer = Relayer::new(node_client, keys)
         .await
-        .context("Could not query the account nonce.")?
-        .nonce;
+        .context("Could not query the account nonce.")?;
 
     tracing::info!("Starting server...");
     tracing::debug!(
@@ -170,23 +169,32 @@ With the following configuration:
         app.request_timeout,
         app.rate_limit_per_account_per_hour,
         app.keys_path,
-        keys.address,
-        nonce,
+        relayer.sponsor_address(),
+        *relayer.nonce().lock().await,
         app.allowed_accounts,
         app.allowed_contracts,
     );
 
-    let state = Server::new(
-        node_client,
-        keys,
-        nonce,
+    let info = Info::new(&app.allowed_contracts);
+    let allowlist = Allowlist {
+        accounts:  app.allowed_accounts,
+        contracts: app.allowed_contracts,
+    };
+    // The rate limits are reset once per hour.
+    let quota = AccountQuota::new(
         app.rate_limit_per_account_per_hour,
-        app.allowed_accounts,
-        app.allowed_contracts,
+        Some(std::time::Duration::from_secs(60 * 60)),
     );
 
+    let state = Server {
+        relayer,
+        policy: Arc::new((allowlist, quota)),
+        info,
+    };
+
     let router = Router::new()
         .route("/api/submitTransaction", post(handle_transaction))
+        .route("/info", get(info))
         .with_state(state)
         .layer(CorsLayer::new()
                .allow_origin(AllowOrigin::mirror_request())
@@ -217,6 +225,9 @@ With the following configuration:
     Ok(())
 }
 
+/// Handle a request for the build metadata of the service.
+pub async fn info(State(state): State<Server>) -> Json<Info> { Json(state.info) }
+
 /// Handle a request for a sponsored transaction.
 pub async fn handle_transaction(
     State(state): State<Server>,
@@ -224,105 +235,26 @@ pub async fn handle_transaction(
 ) -> Result<Json<TransactionHash>, ServerError> {
     let Json(request) = request?;
 
-    if !state.allowed_accounts.allowed(&request.signer) {
-        return Err(ServerError::AccountNotAllowed {
-            account: request.signer,
-        });
-    }
-    if !state.allowed_contracts.allowed(&request.contract_address) {
-        return Err(ServerError::ContractNotAllowed {
-            contract: request.contract_address,
-        });
-    }
-
-    let message: PermitMessage = PermitMessage {
+    let message = PermitMessage {
         contract_address: request.contract_address,
         nonce:            request.nonce,
         timestamp:        request.expiry_time,
         entry_point:      OwnedEntrypointName::new_unchecked(request.entrypoint_name),
-        parameter:        request.parameter,
+        payload:          request.parameter,
     };
 
-    // Create signature map.
-    let mut signature = [0; 64];
-    hex::decode_to_slice(request.signature.clone(), &mut signature)?;
-    let mut inner_signature_map = BTreeMap::new();
-    inner_signature_map.insert(0, Signature::Ed25519(SignatureEd25519(signature)));
-    let mut signature_map = BTreeMap::new();
-    signature_map.insert(0, CredentialSignatures {
-        sigs: inner_signature_map,
-    });
-
-    let param: PermitParam = PermitParam {
-        message,
-        signature: AccountSignatures {
-            sigs: signature_map,
-        },
-        signer: request.signer,
-    };
+    let param = PermitParam::new(request.signer, &request.signature, message)
+        .map_err(SponsorError::from)?;
 
-    let mut contract_client =
-        ContractClient::<()>::create(state.node_client.clone(), request.contract_address)
-            .await
-            .map_err(ServerError::FailedToCreateContractClient)?;
-
-    let dry_run = match contract_client
-        .dry_run_update_with_reject_reason_info::<types::PermitParam, ServerError>(
-            "permit",
-            Amount::zero(),
-            state.keys.address,
-            &param,
-        )
-        .await?
-    {
-        InvokeContractOutcome::Success(dry_run) => Ok(dry_run),
-        InvokeContractOutcome::Failure(rejected_transaction) => {
-            match rejected_transaction.decoded_reason {
-                Some(decoded_reason) => Err(ServerError::TransactionSimulationRejectedTransaction(
-                    decoded_reason,
-                    rejected_transaction.reason,
-                )),
-                None => Err(ServerError::TransactionSimulationError(
-                    rejected_transaction.reason,
-                )),
-            }
-        }
-    }?;
-
-    // Get the current nonce for the backend wallet and lock it. This is necessary
-    // since it is possible that API requests come in parallel. The nonce is
-    // increased by 1 and its lock is released after the transaction is submitted to
-    // the blockchain.
-    let mut nonce = state.nonce.lock().await;
-
-    // Reset the rate limits if an hour has passed since the last reset.
-    state.reset_rate_limits_if_expired().await;
-
-    // Check the rate limits for the account.
-    //
-    // By checking and updating the rate limit *after* the dry run, we ensure that
-    // it indeed is the specified signer account that sent the request (since the
-    // signature is checked).
-    //
-    // By contrast, if we had checked and updated the rate limit *before* the dry
-    // run, then it would be easy for attackers to block other accounts from using
-    // the service by spamming requests with the victim account specified as the
-    // signer.
+    // The allowed accounts and contracts are checked before the transaction is
+    // simulated and the rate limit of the signer after the simulation. See
+    // [`concordium_sponsor_relayer::SponsorPolicy`] for why the rate limit is
+    // only updated once the signature has been checked by the contract.
     //
     // We could also add a general rate limit based on IP addresses or similar to
     // hinder DDOS attacks.
-    state.check_rate_limit(request.signer).await?;
-
-    let tx_hash = dry_run
-        .nonce(*nonce)
-        .send(&state.keys)
-        .await
-        .map_err(ServerError::SubmitSponsoredTransactionError)?
-        .hash();
-
-    tracing::debug!("Submitted transaction {} ...", tx_hash);
+    let tx_hash = state.relayer.submit(&param, state.policy.as_ref()).await?;
 
-    *nonce = nonce.next();
     Ok(tx_hash.into())
 }
 
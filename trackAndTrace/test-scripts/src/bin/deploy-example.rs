@@ -0,0 +1,318 @@
+//! A tool that sets up the track and trace example in one command. It deploys
+//! the track and trace module (if it is not deployed yet), initializes a new
+//! contract instance with the state machine of a TOML file, grants the roles
+//! listed in the file, and writes the contract address and the block the
+//! instance was created in to the env files of the indexer and the frontend.
+//! Optionally, it creates a number of demo items.
+use anyhow::Context;
+use clap::Parser as _;
+use concordium_rust_sdk::{
+    contract_client::{ContractClient, ContractInitBuilder, ModuleDeployBuilder, ViewError},
+    smart_contracts::common::{Address, Amount},
+    types::{
+        smart_contracts::WasmModule, AbsoluteBlockHeight, AccountAddress, ContractAddress,
+        WalletAccount,
+    },
+    v2::{self as sdk, BlockIdentifier},
+};
+use std::path::{Path, PathBuf};
+use track_and_trace::{MetadataUrl, *};
+
+pub enum TrackAndTraceContract {}
+
+/// Command line configuration of the application.
+#[derive(Debug, clap::Parser)]
+#[command(author, version, about)]
+struct Args {
+    #[arg(
+        long = "node",
+        short = 'n',
+        default_value = "https://grpc.testnet.concordium.com:20000",
+        help = "The endpoints are expected to point to concordium node grpc v2 API's."
+    )]
+    node_endpoint:   sdk::Endpoint,
+    #[arg(
+        long = "network",
+        default_value = "testnet",
+        help = "The network of the node, written to the env file of the frontend.",
+        value_enum
+    )]
+    network:         Network,
+    #[arg(
+        long = "module",
+        short = 'm',
+        default_value = "../smart-contract/concordium-out/module.wasm.v1",
+        help = "Source module from which to initialize the contract instance."
+    )]
+    module:          PathBuf,
+    #[arg(
+        long = "config",
+        short = 'c',
+        help = "A TOML file containing the state machine of the contract and the roles to grant."
+    )]
+    config:          PathBuf,
+    #[arg(
+        long = "admin-key-file",
+        short = 'a',
+        help = "Path to the key file of the admin account in the browser wallet export format."
+    )]
+    admin_keys_path: PathBuf,
+    #[arg(
+        long = "indexer-env",
+        default_value = "indexer.env",
+        help = "The env file written for the indexer."
+    )]
+    indexer_env:     PathBuf,
+    #[arg(
+        long = "frontend-env",
+        default_value = "frontend.env",
+        help = "The env file written for the frontend (and the docker compose setup)."
+    )]
+    frontend_env:    PathBuf,
+    #[arg(
+        long = "seed-items",
+        default_value = "0",
+        help = "The number of demo items created by the admin account after the roles are granted."
+    )]
+    seed_items:      u64,
+}
+
+/// The networks of the frontend as command line values.
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum Network {
+    Mainnet,
+    Testnet,
+}
+
+impl std::fmt::Display for Network {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Network::Mainnet => f.write_str("mainnet"),
+            Network::Testnet => f.write_str("testnet"),
+        }
+    }
+}
+
+/// The deployment read from the TOML file given with `--config`.
+#[derive(Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DeploymentConfig {
+    /// The roles that are allowed to create items. Defaults to the `Admin`
+    /// role only.
+    #[serde(default = "default_creator_roles")]
+    creator_roles:   Vec<Roles>,
+    /// The transition edges of the state machine.
+    transitions:     Vec<TransitionEdges>,
+    /// The roles granted after the contract was initialized.
+    #[serde(default)]
+    roles:           Vec<RoleGrant>,
+    /// The CIS-2 contract minting a twin token for every item. If not given,
+    /// no twin tokens are minted.
+    #[serde(default)]
+    twin_token:      Option<ContractAddress>,
+    /// The names of the custom statuses registered in addition to the built-in
+    /// statuses. The `i`-th name is used as `{ Custom = i }` in the
+    /// transitions.
+    #[serde(default)]
+    custom_statuses: Vec<String>,
+}
+
+/// A role granted to an account after the contract was initialized.
+#[derive(Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RoleGrant {
+    /// The account the role is granted to.
+    address: AccountAddress,
+    /// The granted role.
+    role:    Roles,
+}
+
+fn default_creator_roles() -> Vec<Roles> { vec![Roles::Admin] }
+
+impl DeploymentConfig {
+    /// Read the deployment from the TOML file at `path`.
+    fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let toml = std::fs::read_to_string(path).context("Unable to open the config file.")?;
+        toml::from_str(&toml).context("Unable to parse the config file.")
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+
+    let config = DeploymentConfig::from_file(&args.config)?;
+
+    let endpoint = if args
+        .node_endpoint
+        .uri()
+        .scheme()
+        .map_or(false, |x| x == &sdk::Scheme::HTTPS)
+    {
+        args.node_endpoint
+            .clone()
+            .tls_config(tonic::transport::channel::ClientTlsConfig::new())
+            .context("Unable to construct TLS configuration for the Concordium API.")?
+    } else {
+        args.node_endpoint.clone()
+    }
+    .connect_timeout(std::time::Duration::from_secs(5))
+    .timeout(std::time::Duration::from_secs(10));
+
+    let mut client = sdk::Client::new(endpoint)
+        .await
+        .context("Unable to establish connection to the node.")?;
+
+    let admin_key = WalletAccount::from_json_file(&args.admin_keys_path)
+        .context("Could not read the keys file.")?;
+
+    eprintln!(
+        "Deploying the example with admin account {}.",
+        admin_key.address
+    );
+
+    let module = WasmModule::from_file(&args.module).context("Could not read contract module.")?;
+    let mod_ref = module.get_module_ref();
+
+    // Deploy module
+    if client
+        .get_module_source(&mod_ref, BlockIdentifier::LastFinal)
+        .await
+        .is_ok()
+    {
+        eprintln!("Source module with reference {mod_ref} already exists.");
+    } else {
+        let builder =
+            ModuleDeployBuilder::dry_run_module_deploy(client.clone(), admin_key.address, module)
+                .await?;
+
+        let handle = builder.send(&admin_key.keys).await?;
+
+        eprintln!("Module deployment transaction {handle} submitted.");
+
+        let result = handle.wait_for_finalization().await?;
+
+        eprintln!("Module {} deployed.", result.module_reference);
+    }
+
+    // Initialize new instance
+    let params = InitParams {
+        transitions:     config.transitions,
+        creator_roles:   config.creator_roles,
+        twin_token:      config.twin_token,
+        custom_statuses: config.custom_statuses,
+    };
+
+    let builder = ContractInitBuilder::<TrackAndTraceContract>::dry_run_new_instance(
+        client.clone(),
+        admin_key.address,
+        mod_ref,
+        "track_and_trace",
+        Amount::zero(),
+        &params,
+    )
+    .await?;
+
+    let handle = builder.send(&admin_key.keys).await?;
+
+    eprintln!("Transaction {handle} submitted. Waiting for finalization.");
+
+    let (mut contract_client, _) = handle.wait_for_finalization().await?;
+    let contract_address = contract_client.address;
+
+    let (start_block, ..) = client
+        .find_instance_creation(.., contract_address)
+        .await
+        .context("Unable to find the block the contract instance was created in.")?;
+
+    eprintln!(
+        "Initialized a new smart contract instance at address {contract_address} in block \
+         {start_block}."
+    );
+
+    // Grant roles
+    for grant in &config.roles {
+        let param = GrantRoleParams {
+            address: Address::Account(grant.address),
+            role:    grant.role,
+        };
+        update(&mut contract_client, &admin_key, "grantRole", &param)
+            .await
+            .with_context(|| {
+                format!(
+                    "Granting the role {:?} to {} failed.",
+                    grant.role, grant.address
+                )
+            })?;
+        eprintln!("Granted the role {:?} to {}.", grant.role, grant.address);
+    }
+
+    write_env_files(&args, contract_address, start_block)?;
+
+    // Create demo items
+    for i in 0..args.seed_items {
+        let param: Option<MetadataUrl> = None;
+        update(&mut contract_client, &admin_key, "createItem", &param)
+            .await
+            .with_context(|| format!("Creating the demo item with index {i} failed."))?;
+        eprintln!("Created the demo item with index {i}.");
+    }
+
+    eprintln!("Deployment completed successfully.");
+
+    Ok(())
+}
+
+/// Send an update of the contract invoking the `entrypoint` with the `param`
+/// from the admin account and wait until it is finalized.
+async fn update<P: concordium_rust_sdk::smart_contracts::common::Serial>(
+    contract_client: &mut ContractClient<TrackAndTraceContract>,
+    admin_key: &WalletAccount,
+    entrypoint: &str,
+    param: &P,
+) -> anyhow::Result<()> {
+    let tx_dry_run = contract_client
+        .dry_run_update::<P, ViewError>(entrypoint, Amount::zero(), admin_key.address, param)
+        .await?;
+
+    let tx_hash = tx_dry_run.send(admin_key).await?;
+
+    if let Err(err) = tx_hash.wait_for_finalization().await {
+        anyhow::bail!("The transaction {tx_hash} failed: {err:#?}");
+    }
+    Ok(())
+}
+
+/// Write the env file of the indexer and the env file of the frontend. The
+/// env file of the frontend contains the variables of the docker compose
+/// setup, so it can also be passed to `docker compose --env-file`.
+fn write_env_files(
+    args: &Args,
+    contract_address: ContractAddress,
+    start_block: AbsoluteBlockHeight,
+) -> anyhow::Result<()> {
+    let header = format!(
+        "# Written by deploy-example. The contract instance was created in block {start_block}, \
+         from which the indexer starts indexing.\n"
+    );
+
+    let indexer_env = format!(
+        "{header}CCD_INDEXER_CONTRACT={contract_address}\nCCD_INDEXER_NODE={}\n",
+        args.node_endpoint.uri()
+    );
+    std::fs::write(&args.indexer_env, indexer_env)
+        .with_context(|| format!("Unable to write {}.", args.indexer_env.display()))?;
+    eprintln!("Wrote {}.", args.indexer_env.display());
+
+    let frontend_env = format!(
+        "{header}TRACK_AND_TRACE_CONTRACT_ADDRESS={contract_address}\nTRACK_AND_TRACE_NODE={}\\
+         nTRACK_AND_TRACE_NETWORK={}\n",
+        args.node_endpoint.uri(),
+        args.network
+    );
+    std::fs::write(&args.frontend_env, frontend_env)
+        .with_context(|| format!("Unable to write {}.", args.frontend_env.display()))?;
+    eprintln!("Wrote {}.", args.frontend_env.display());
+
+    Ok(())
+}
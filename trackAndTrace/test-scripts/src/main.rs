@@ -102,7 +102,7 @@ async fn main() -> anyhow::Result<()> {
     }
 
     // Initialize new instance
-    let params: Vec<TransitionEdges> = serde_json::from_reader(
+    let params: InitParams = serde_json::from_reader(
         std::fs::File::open(&args.input_parameter_json_file)
             .context("Unable to open input parameter file.")?,
     )